@@ -1,16 +1,20 @@
 use std::net::SocketAddr;
 
-use chord_rs_core::Client;
+use chord_rs_core::{Client, MaintenanceTask};
 use clap::{arg, command, Args, Parser, Subcommand, ValueEnum};
 
-use crate::commands::{lookup::Lookup, ping::Ping, CommandExecute, CommandResult, Error};
+use crate::commands::{
+    admin::Admin, health::Health, lookup::Lookup, ping::Ping, CommandExecute, CommandResult, Error,
+};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 pub(crate) struct Cli {
     /// Address of a node in the ring to connect to, format: IP[:PORT], e.g. [::1]:42000
+    ///
+    /// Required unless the subcommand is given the address of the node, like `admin`
     #[arg(long, value_name = "ADDRESS:PORT")]
-    pub(crate) ring: SocketAddr,
+    pub(crate) ring: Option<SocketAddr>,
 
     /// Set the log level
     #[arg(short('L'), long, value_name = "LEVEL", value_enum, default_value_t = LogLevel::Warn)]
@@ -28,6 +32,25 @@ pub(crate) enum Commands {
 
     /// Ping a node in the ring
     Ping(PingArgs),
+
+    /// Check the health of a node: whether it joined the ring, knows its predecessor
+    /// and reaches its successor. With `--wait-for-stable`, wait until it's part of the ring
+    Health(HealthArgs),
+
+    /// Run a maintenance task of a node right away, instead of waiting for its timer.
+    /// The node must be built with the `admin` feature
+    #[command(subcommand)]
+    Admin(AdminCommands),
+}
+
+impl Commands {
+    /// The address of the node given to the subcommand, if any, which overrides `--ring`
+    pub(crate) fn address(&self) -> Option<SocketAddr> {
+        match self {
+            Commands::Admin(command) => command.args().address,
+            _ => None,
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -45,6 +68,8 @@ impl CommandExecute for Commands {
                 let ping: Ping = Ping::try_from(args)?;
                 ping.execute(client).await
             }
+            Commands::Health(args) => Health::from(args).execute(client).await,
+            Commands::Admin(command) => Admin::from(command).execute(client).await,
         }
     }
 }
@@ -58,11 +83,71 @@ pub(crate) struct LookupArgs {
     /// if set, the key MUST be an integer
     #[arg(long, default_value_t = false)]
     pub(crate) raw: bool,
+
+    /// Print the path of the lookup, i.e. the ids of the nodes it went through
+    #[arg(long, default_value_t = false)]
+    pub(crate) trace: bool,
 }
 
 #[derive(Args)]
 pub(crate) struct PingArgs {}
 
+#[derive(Args)]
+pub(crate) struct HealthArgs {
+    /// Wait until the node joined the ring and knows its predecessor, polling its health.
+    /// Fails if the node is not stable before the timeout, so scripts bringing up a cluster
+    /// can start the next node only once the previous one is part of the ring
+    #[arg(long, default_value_t = false)]
+    pub(crate) wait_for_stable: bool,
+
+    /// How long to wait for the node to be stable, in seconds
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = 60,
+        requires = "wait_for_stable"
+    )]
+    pub(crate) timeout: u64,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum AdminCommands {
+    /// Stabilize the node: check its successor and notify it
+    Stabilize(AdminArgs),
+
+    /// Refresh the finger table of the node
+    FixFingers(AdminArgs),
+
+    /// Reconcile the successor list of the node with the one of its successor
+    Reconcile(AdminArgs),
+}
+
+impl AdminCommands {
+    /// The task run by the subcommand
+    pub(crate) fn task(&self) -> MaintenanceTask {
+        match self {
+            AdminCommands::Stabilize(_) => MaintenanceTask::Stabilize,
+            AdminCommands::FixFingers(_) => MaintenanceTask::FixFingers,
+            AdminCommands::Reconcile(_) => MaintenanceTask::ReconcileSuccessors,
+        }
+    }
+
+    pub(crate) fn args(&self) -> &AdminArgs {
+        match self {
+            AdminCommands::Stabilize(args)
+            | AdminCommands::FixFingers(args)
+            | AdminCommands::Reconcile(args) => args,
+        }
+    }
+}
+
+#[derive(Args)]
+pub(crate) struct AdminArgs {
+    /// Address of the node to run the task on, defaults to `--ring`
+    #[arg(value_name = "ADDRESS:PORT")]
+    pub(crate) address: Option<SocketAddr>,
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub(crate) enum LogLevel {
     Error,
@@ -0,0 +1,40 @@
+use chord_rs_core::{Client, MaintenanceTask};
+
+use crate::cli::AdminCommands;
+
+use super::{CommandExecute, CommandResult, Error};
+
+pub(crate) struct Admin {
+    /// The maintenance task to run on the node
+    task: MaintenanceTask,
+}
+
+#[async_trait::async_trait]
+impl CommandExecute for Admin {
+    async fn execute<C>(&self, client: C) -> Result<CommandResult, Error>
+    where
+        C: Client + Clone + Send + Sync,
+    {
+        let start = std::time::Instant::now();
+        client
+            .trigger(self.task)
+            .await
+            .map_err(|r| (*r.current_context()).clone())?;
+
+        let elapsed = start.elapsed();
+        let result = CommandResult {
+            result: format!("Completed {}", self.task),
+            execution: elapsed,
+        };
+
+        Ok(result)
+    }
+}
+
+impl From<&AdminCommands> for Admin {
+    fn from(command: &AdminCommands) -> Self {
+        Admin {
+            task: command.task(),
+        }
+    }
+}
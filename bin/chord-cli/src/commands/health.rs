@@ -0,0 +1,94 @@
+use std::time::{Duration, Instant};
+
+use chord_rs_core::{Client, HealthStatus};
+
+use crate::cli::HealthArgs;
+
+use super::{CommandExecute, CommandResult, Error};
+
+/// Delay between two health checks while waiting for the node to be stable
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub(crate) struct Health {
+    /// How long to wait for the node to be stable, if not set the health is checked once
+    wait_for_stable: Option<Duration>,
+}
+
+impl Health {
+    /// Check if the node is part of the ring: it stabilized at least once and knows its
+    /// predecessor
+    fn is_stable(health: &HealthStatus) -> bool {
+        health.joined && health.has_predecessor
+    }
+
+    /// Poll the health of the node until it's stable
+    ///
+    /// The errors are retried as well, as the node may not be serving yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The client of the node
+    /// * `timeout` - How long to wait for the node to be stable
+    async fn wait_for_stable<C>(client: &C, timeout: Duration) -> Result<HealthStatus, Error>
+    where
+        C: Client + Clone + Send + Sync,
+    {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let last = match client.health().await {
+                Ok(health) if Self::is_stable(&health) => return Ok(health),
+                Ok(health) => format!("{:?}", health),
+                Err(err) => format!("{:?}", err.current_context()),
+            };
+            log::debug!("The node is not stable yet: {}", last);
+
+            if Instant::now() + POLL_INTERVAL > deadline {
+                return Err(Error {
+                    message: format!(
+                        "The node is not stable after {:?}, last health check: {}",
+                        timeout, last
+                    ),
+                });
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl CommandExecute for Health {
+    async fn execute<C>(&self, client: C) -> Result<CommandResult, Error>
+    where
+        C: Client + Clone + Send + Sync,
+    {
+        let start = std::time::Instant::now();
+        let health = match self.wait_for_stable {
+            Some(timeout) => Self::wait_for_stable(&client, timeout).await?,
+            None => client
+                .health()
+                .await
+                .map_err(|r| (*r.current_context()).clone())?,
+        };
+
+        let elapsed = start.elapsed();
+        let result = CommandResult {
+            result: format!(
+                "Joined: {}\nHas predecessor: {}\nSuccessor reachable: {}",
+                health.joined, health.has_predecessor, health.successor_reachable
+            ),
+            execution: elapsed,
+        };
+
+        Ok(result)
+    }
+}
+
+impl From<&HealthArgs> for Health {
+    fn from(args: &HealthArgs) -> Self {
+        Health {
+            wait_for_stable: args
+                .wait_for_stable
+                .then(|| Duration::from_secs(args.timeout)),
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::num::ParseIntError;
 
-use chord_rs_core::{Client, NodeId};
+use chord_rs_core::{Client, IntoNodeId, NodeId};
 
 use crate::cli::LookupArgs;
 
@@ -8,6 +8,7 @@ use super::{CommandExecute, CommandResult, Error};
 
 pub(crate) struct Lookup {
     key: NodeId,
+    trace: bool,
 }
 
 #[async_trait::async_trait]
@@ -17,19 +18,34 @@ impl CommandExecute for Lookup {
         C: Client + Clone + Send + Sync,
     {
         let start = std::time::Instant::now();
-        let node = client
-            .find_successor(self.key.into())
-            .await
-            .map_err(|r| (*r.current_context()).clone())?;
+        let (node, path) = if self.trace {
+            client
+                .find_successor_traced(self.key)
+                .await
+                .map(|(node, path)| (node, Some(path)))
+        } else {
+            client
+                .find_successor(self.key)
+                .await
+                .map(|node| (node, None))
+        }
+        .map_err(|r| (*r.current_context()).clone())?;
 
         let elapsed = start.elapsed();
+        let mut result = format!(
+            "Id: {}\nNode:\n  Address: {}\n  Id: {}",
+            self.key,
+            node.addr(),
+            node.id()
+        );
+        if let Some(path) = path {
+            result.push_str(&format!("\nPath ({} hops):", path.len()));
+            for id in path {
+                result.push_str(&format!("\n  {}", id));
+            }
+        }
         let result = CommandResult {
-            result: format!(
-                "Id: {}\nNode:\n  Address: {}\n  Id: {}",
-                self.key,
-                node.addr(),
-                node.id()
-            ),
+            result,
             execution: elapsed,
         };
 
@@ -44,10 +60,13 @@ impl TryFrom<&LookupArgs> for Lookup {
         let key = if args.raw {
             NodeId::from(args.key.parse::<u64>()?)
         } else {
-            NodeId::from(args.key.clone())
+            args.key.as_str().into_node_id()
         };
 
-        Ok(Lookup { key })
+        Ok(Lookup {
+            key,
+            trace: args.trace,
+        })
     }
 }
 
@@ -2,6 +2,8 @@ use std::{fmt::Display, time::Duration};
 
 use chord_rs_core::{client::ClientError, Client};
 
+pub(crate) mod admin;
+pub(crate) mod health;
 pub(crate) mod lookup;
 pub(crate) mod ping;
 
@@ -15,14 +15,14 @@ impl CommandExecute for Ping {
         C: Client + Clone + Send + Sync,
     {
         let start = std::time::Instant::now();
-        client
+        let id = client
             .ping()
             .await
             .map_err(|r| (*r.current_context()).clone())?;
 
         let elapsed = start.elapsed();
         let result = CommandResult {
-            result: format!("Pong",),
+            result: format!("Pong from node {}", id),
             execution: elapsed,
         };
 
@@ -1,5 +1,7 @@
+use std::net::SocketAddr;
+
 use chord_rs_core::Client;
-use clap::Parser;
+use clap::{error::ErrorKind, CommandFactory, Parser};
 use commands::{CommandResult, Error};
 use chord_capnp::client::ChordCapnpClient;
 
@@ -11,16 +13,27 @@ mod commands;
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
+    let Some(ring) = cli.command.address().or(cli.ring) else {
+        Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "the address of a node is required, set it with `--ring`",
+            )
+            .exit();
+    };
 
-    match run(cli).await {
+    match run(ring, cli).await {
         Ok(result) => print_result(result),
-        Err(err) => println!("Failed:\n {}", err),
+        Err(err) => {
+            println!("Failed:\n {}", err);
+            std::process::exit(1);
+        }
     }
 }
 
-async fn run(cli: Cli) -> Result<CommandResult, Error> {
-    // let client = ChordGrpcClient::init(cli.ring).await;
-    let client = ChordCapnpClient::init(cli.ring).await;
+async fn run(ring: SocketAddr, cli: Cli) -> Result<CommandResult, Error> {
+    // let client = ChordGrpcClient::init(ring).await;
+    let client = ChordCapnpClient::init(ring).await;
 
     CommandExecute::execute(&cli.command, client).await
 }
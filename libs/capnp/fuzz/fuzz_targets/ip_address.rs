@@ -0,0 +1,22 @@
+//! Read arbitrary bytes as an IP address sent by a peer, the conversion may only fail with an error
+
+#![no_main]
+
+use std::net::SocketAddr;
+
+use capnp::message::ReaderOptions;
+use capnp::serialize;
+use chord_capnp::chord_capnp::chord_node::node::ip_address;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut data = data;
+    let Ok(message) = serialize::read_message_from_flat_slice(&mut data, ReaderOptions::new())
+    else {
+        return;
+    };
+
+    if let Ok(addr) = message.get_root::<ip_address::Reader>() {
+        let _ = SocketAddr::try_from(addr);
+    }
+});
@@ -0,0 +1,21 @@
+//! Read arbitrary bytes as a node sent by a peer, the conversion may only fail with an error
+
+#![no_main]
+
+use capnp::message::ReaderOptions;
+use capnp::serialize;
+use chord_capnp::chord_capnp::chord_node::node;
+use chord_rs_core::Node;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut data = data;
+    let Ok(message) = serialize::read_message_from_flat_slice(&mut data, ReaderOptions::new())
+    else {
+        return;
+    };
+
+    if let Ok(node) = message.get_root::<node::Reader>() {
+        let _ = Node::try_from(node);
+    }
+});
@@ -1,4 +1,9 @@
-use chord_rs_core::{client::ClientError, Node, NodeId};
+use std::time::SystemTime;
+
+use chord_rs_core::{
+    client::ClientError, deadline, HealthStatus, MaintenanceTask, Node, NodeId, NodeInfo,
+    RingParams,
+};
 use error_stack::{IntoReport, ResultExt};
 use futures::Future;
 
@@ -12,40 +17,72 @@ use super::CmdResult;
 
 #[derive(Debug)]
 pub(crate) enum Command {
-    FindSuccessor(NodeId, CmdResult<Node>),
+    FindSuccessor(NodeId, Option<SystemTime>, CmdResult<Node>),
+    FindSuccessorTraced(NodeId, CmdResult<(Node, Vec<NodeId>)>),
+    FindSuccessors(Vec<NodeId>, CmdResult<Vec<Option<Node>>>),
+    FindPredecessor(NodeId, CmdResult<Node>),
     Successor(CmdResult<Node>),
     SuccessorList(CmdResult<Vec<Node>>),
     Predecessor(CmdResult<Option<Node>>),
+    PredecessorList(CmdResult<Vec<Node>>),
+    NodeInfo(CmdResult<NodeInfo>),
     Notify(Node, CmdResult<()>),
-    Ping(CmdResult<()>),
+    Leave(Node, Option<Node>, CmdResult<()>),
+    Ping(CmdResult<NodeId>),
+    CanReach(Node, CmdResult<bool>),
+    Health(CmdResult<HealthStatus>),
+    Trigger(MaintenanceTask, CmdResult<()>),
+    /// Stop the thread of the spawner, the commands sent after it are dropped
+    Shutdown,
 }
 
 impl Command {
     pub(crate) fn get_error(&self) -> ClientError {
         match self {
-            Command::FindSuccessor(_, _) => ClientError::FindSuccessorFailed,
+            Command::FindSuccessor(_, _, _) => ClientError::FindSuccessorFailed,
+            Command::FindSuccessorTraced(_, _) => ClientError::FindSuccessorFailed,
+            Command::FindSuccessors(_, _) => ClientError::FindSuccessorFailed,
+            Command::FindPredecessor(_, _) => ClientError::FindPredecessorFailed,
             Command::Successor(_) => ClientError::GetSuccessorFailed,
             Command::SuccessorList(_) => ClientError::GetSuccessorListFailed,
             Command::Predecessor(_) => ClientError::GetPredecessorFailed,
+            Command::PredecessorList(_) => ClientError::GetPredecessorListFailed,
+            Command::NodeInfo(_) => ClientError::NodeInfoFailed,
             Command::Notify(_, _) => ClientError::NotifyFailed,
+            Command::Leave(_, _, _) => ClientError::LeaveFailed,
             Command::Ping(_) => ClientError::PingFailed,
+            Command::CanReach(_, _) => ClientError::CanReachFailed,
+            Command::Health(_) => ClientError::HealthFailed,
+            Command::Trigger(_, _) => ClientError::TriggerFailed,
+            Command::Shutdown => ClientError::Unexpected,
         }
     }
 
-    pub(crate) async fn ping(client: Client, sender: CmdResult<()>) {
+    pub(crate) async fn ping(client: Client, secret: &str, sender: CmdResult<NodeId>) {
         Self::handle_request(sender, ClientError::PingFailed, || async {
-            let request = client.ping_request();
+            let mut request = client.ping_request();
+            request.get().set_secret(secret);
 
-            request.send().promise.await?;
-            Ok(())
+            let reply = request.send().promise.await?;
+            Ok(reply.get()?.get_id().into())
         })
         .await
     }
 
-    pub(crate) async fn find_successor(client: Client, id: NodeId, sender: CmdResult<Node>) {
+    pub(crate) async fn find_successor(
+        client: Client,
+        secret: &str,
+        id: NodeId,
+        deadline: Option<SystemTime>,
+        sender: CmdResult<Node>,
+    ) {
         Self::handle_request(sender, ClientError::FindSuccessorFailed, || async {
             let mut request = client.find_successor_request();
             request.get().set_id(id.into());
+            request.get().set_secret(secret);
+            if let Some(deadline) = deadline {
+                request.get().set_deadline(deadline::to_millis(deadline));
+            }
 
             let reply = request.send().promise.await?;
             let node = reply.get()?.get_node()?.try_into()?;
@@ -55,9 +92,79 @@ impl Command {
         .await
     }
 
-    pub(crate) async fn get_successor(client: Client, sender: CmdResult<Node>) {
+    pub(crate) async fn find_successor_traced(
+        client: Client,
+        secret: &str,
+        id: NodeId,
+        sender: CmdResult<(Node, Vec<NodeId>)>,
+    ) {
+        Self::handle_request(sender, ClientError::FindSuccessorFailed, || async {
+            let mut request = client.find_successor_request();
+            request.get().set_id(id.into());
+            request.get().set_secret(secret);
+            request.get().set_trace(true);
+
+            let reply = request.send().promise.await?;
+            let node = reply.get()?.get_node()?.try_into()?;
+            let path = reply.get()?.get_path()?.iter().map(NodeId::from).collect();
+
+            Ok((node, path))
+        })
+        .await
+    }
+
+    pub(crate) async fn find_successors(
+        client: Client,
+        secret: &str,
+        ids: Vec<NodeId>,
+        sender: CmdResult<Vec<Option<Node>>>,
+    ) {
+        Self::handle_request(sender, ClientError::FindSuccessorFailed, || async {
+            let mut request = client.find_successors_request();
+            let mut list = request.get().init_ids(ids.len() as u32);
+            for (i, id) in ids.into_iter().enumerate() {
+                list.set(i as u32, id.into());
+            }
+            request.get().set_secret(secret);
+
+            let reply = request.send().promise.await?;
+            let mut nodes: Vec<Option<Node>> = vec![];
+            for node in reply.get()?.get_nodes()?.iter() {
+                let node = match node.which()? {
+                    chord_capnp::option::None(()) => None,
+                    chord_capnp::option::Some(reader) => Some(Node::try_from(reader?)?),
+                };
+                nodes.push(node);
+            }
+
+            Ok(nodes)
+        })
+        .await
+    }
+
+    pub(crate) async fn find_predecessor(
+        client: Client,
+        secret: &str,
+        id: NodeId,
+        sender: CmdResult<Node>,
+    ) {
+        Self::handle_request(sender, ClientError::FindPredecessorFailed, || async {
+            let mut request = client.find_predecessor_request();
+            request.get().set_id(id.into());
+            request.get().set_secret(secret);
+
+            let reply = request.send().promise.await?;
+            let node = reply.get()?.get_node()?.try_into()?;
+
+            Ok(node)
+        })
+        .await
+    }
+
+    pub(crate) async fn get_successor(client: Client, secret: &str, sender: CmdResult<Node>) {
         Self::handle_request(sender, ClientError::GetSuccessorFailed, || async {
-            let request = client.get_successor_request();
+            let mut request = client.get_successor_request();
+            request.get().set_secret(secret);
 
             let reply = request.send().promise.await?;
             let successor = reply.get()?.get_node()?.try_into()?;
@@ -66,9 +173,14 @@ impl Command {
         .await;
     }
 
-    pub(crate) async fn get_successor_list(client: Client, sender: CmdResult<Vec<Node>>) {
+    pub(crate) async fn get_successor_list(
+        client: Client,
+        secret: &str,
+        sender: CmdResult<Vec<Node>>,
+    ) {
         Self::handle_request(sender, ClientError::GetSuccessorListFailed, || async {
-            let request = client.get_successor_list_request();
+            let mut request = client.get_successor_list_request();
+            request.get().set_secret(secret);
 
             let reply = request.send().promise.await?;
             let nodes = reply.get()?.get_nodes()?;
@@ -81,9 +193,34 @@ impl Command {
         .await;
     }
 
-    pub(crate) async fn get_predecessor(client: Client, sender: CmdResult<Option<Node>>) {
+    pub(crate) async fn get_predecessor_list(
+        client: Client,
+        secret: &str,
+        sender: CmdResult<Vec<Node>>,
+    ) {
+        Self::handle_request(sender, ClientError::GetPredecessorListFailed, || async {
+            let mut request = client.get_predecessor_list_request();
+            request.get().set_secret(secret);
+
+            let reply = request.send().promise.await?;
+            let nodes = reply.get()?.get_nodes()?;
+            let predecessors: Vec<Node> = nodes
+                .iter()
+                .map(|node| node.try_into())
+                .collect::<Result<Vec<Node>, ParserError>>()?;
+            Ok(predecessors)
+        })
+        .await;
+    }
+
+    pub(crate) async fn get_predecessor(
+        client: Client,
+        secret: &str,
+        sender: CmdResult<Option<Node>>,
+    ) {
         Self::handle_request(sender, ClientError::GetPredecessorFailed, || async {
-            let request = client.get_predecessor_request();
+            let mut request = client.get_predecessor_request();
+            request.get().set_secret(secret);
 
             let reply = request.send().promise.await?;
             let node = reply.get()?.get_node()?;
@@ -101,11 +238,50 @@ impl Command {
         .await
     }
 
-    pub(crate) async fn notify(client: Client, predecessor: Node, sender: CmdResult<()>) {
+    pub(crate) async fn node_info(client: Client, secret: &str, sender: CmdResult<NodeInfo>) {
+        Self::handle_request(sender, ClientError::NodeInfoFailed, || async {
+            let mut request = client.get_node_info_request();
+            request.get().set_secret(secret);
+
+            let reply = request.send().promise.await?;
+            let reply = reply.get()?;
+            let node: Node = reply.get_node()?.try_into()?;
+            let predecessor: Option<Node> = match reply.get_predecessor()?.which()? {
+                chord_capnp::option::None(()) => None,
+                chord_capnp::option::Some(reader) => Some(reader?.try_into()?),
+            };
+            let successor_list = reply
+                .get_successors()?
+                .iter()
+                .map(|node| node.try_into())
+                .collect::<Result<Vec<Node>, ParserError>>()?;
+            let params: Option<RingParams> = match reply.get_params()?.which()? {
+                chord_capnp::option::None(()) => None,
+                chord_capnp::option::Some(reader) => Some(reader?.into()),
+            };
+
+            Ok(NodeInfo {
+                id: node.id(),
+                addr: node.addr(),
+                predecessor,
+                successor_list,
+                params,
+            })
+        })
+        .await
+    }
+
+    pub(crate) async fn notify(
+        client: Client,
+        secret: &str,
+        predecessor: Node,
+        sender: CmdResult<()>,
+    ) {
         Self::handle_request(sender, ClientError::NotifyFailed, || async {
             let mut request = client.notify_request();
             let node = request.get().init_node();
             node.insert(predecessor)?;
+            request.get().set_secret(secret);
 
             let _ = request.send().promise.await;
             Ok(())
@@ -113,6 +289,96 @@ impl Command {
         .await;
     }
 
+    pub(crate) async fn leave(
+        client: Client,
+        secret: &str,
+        node: Node,
+        predecessor: Option<Node>,
+        sender: CmdResult<()>,
+    ) {
+        Self::handle_request(sender, ClientError::LeaveFailed, || async {
+            let mut request = client.leave_request();
+            request.get().init_node().insert(node)?;
+            let mut option = request.get().init_predecessor();
+            match predecessor {
+                Some(predecessor) => option.init_some().insert(predecessor)?,
+                None => option.set_none(()),
+            }
+            request.get().set_secret(secret);
+
+            request.send().promise.await?;
+            Ok(())
+        })
+        .await;
+    }
+
+    pub(crate) async fn can_reach(
+        client: Client,
+        secret: &str,
+        target: Node,
+        sender: CmdResult<bool>,
+    ) {
+        Self::handle_request(sender, ClientError::CanReachFailed, || async {
+            let mut request = client.can_reach_request();
+            let node = request.get().init_node();
+            node.insert(target)?;
+            request.get().set_secret(secret);
+
+            let reply = request.send().promise.await?;
+            let reachable = reply.get()?.get_reachable();
+
+            Ok(reachable)
+        })
+        .await;
+    }
+
+    pub(crate) async fn health(client: Client, secret: &str, sender: CmdResult<HealthStatus>) {
+        Self::handle_request(sender, ClientError::HealthFailed, || async {
+            let mut request = client.health_request();
+            request.get().set_secret(secret);
+
+            let reply = request.send().promise.await?;
+            let reply = reply.get()?;
+
+            Ok(HealthStatus {
+                joined: reply.get_joined(),
+                has_predecessor: reply.get_has_predecessor(),
+                successor_reachable: reply.get_successor_reachable(),
+            })
+        })
+        .await;
+    }
+
+    pub(crate) async fn trigger(
+        client: Client,
+        secret: &str,
+        task: MaintenanceTask,
+        sender: CmdResult<()>,
+    ) {
+        Self::handle_request(sender, ClientError::TriggerFailed, || async {
+            match task {
+                MaintenanceTask::Stabilize => {
+                    let mut request = client.trigger_stabilize_request();
+                    request.get().set_secret(secret);
+                    request.send().promise.await?;
+                }
+                MaintenanceTask::FixFingers => {
+                    let mut request = client.trigger_fix_fingers_request();
+                    request.get().set_secret(secret);
+                    request.send().promise.await?;
+                }
+                MaintenanceTask::ReconcileSuccessors => {
+                    let mut request = client.trigger_reconcile_request();
+                    request.get().set_secret(secret);
+                    request.send().promise.await?;
+                }
+            }
+
+            Ok(())
+        })
+        .await;
+    }
+
     async fn handle_request<F, Res>(sender: CmdResult<Res>, ctx: ClientError, f: impl FnOnce() -> F)
     where
         F: Future<Output = Result<Res, CapnpClientError>>,
@@ -1,6 +1,7 @@
-use std::net::SocketAddr;
+use std::{net::SocketAddr, sync::Arc};
 
-use chord_rs_core::{client::ClientError, Client, Node, NodeId};
+use chord_rs_core::client::{ClientError, ClientFactory, Latency, LatencyStats};
+use chord_rs_core::{deadline, Client, HealthStatus, MaintenanceTask, Node, NodeId, NodeInfo};
 use error_stack::{IntoReport, Result, ResultExt};
 use thiserror::Error;
 use tokio::sync::oneshot::{self, Sender};
@@ -15,18 +16,43 @@ type CmdResult<T> = oneshot::Sender<Result<T, ClientError>>;
 #[derive(Clone)]
 pub struct ChordCapnpClient {
     spawner: LocalSpawner,
+    /// Round-trip times of the calls, shared between the clones
+    latency: Arc<Latency>,
 }
 
 #[async_trait::async_trait]
 impl Client for ChordCapnpClient {
     async fn init(addr: SocketAddr) -> Self {
-        let spawner = LocalSpawner::new(addr);
-
-        Self { spawner }
+        Self::with_secret(addr, None)
     }
 
     async fn find_successor(&self, id: NodeId) -> Result<Node, ClientError> {
-        self.handle_request(|tx| Command::FindSuccessor(id, tx))
+        self.handle_request(|tx| Command::FindSuccessor(id, deadline::current(), tx))
+            .await
+    }
+
+    async fn find_successor_traced(&self, id: NodeId) -> Result<(Node, Vec<NodeId>), ClientError> {
+        self.handle_request(|tx| Command::FindSuccessorTraced(id, tx))
+            .await
+    }
+
+    async fn find_successors(
+        &self,
+        ids: Vec<NodeId>,
+    ) -> Result<Vec<std::result::Result<Node, ClientError>>, ClientError> {
+        let nodes = self
+            .handle_request(|tx| Command::FindSuccessors(ids, tx))
+            .await?;
+
+        // The lookups failed on the other node are sent without their error
+        Ok(nodes
+            .into_iter()
+            .map(|node| node.ok_or(ClientError::FindSuccessorFailed))
+            .collect())
+    }
+
+    async fn find_predecessor(&self, id: NodeId) -> Result<Node, ClientError> {
+        self.handle_request(|tx| Command::FindPredecessor(id, tx))
             .await
     }
 
@@ -42,29 +68,101 @@ impl Client for ChordCapnpClient {
         self.handle_request(|tx| Command::Predecessor(tx)).await
     }
 
+    async fn predecessor_list(&self) -> Result<Vec<Node>, ClientError> {
+        self.handle_request(|tx| Command::PredecessorList(tx)).await
+    }
+
+    async fn node_info(&self) -> Result<NodeInfo, ClientError> {
+        self.handle_request(|tx| Command::NodeInfo(tx)).await
+    }
+
     async fn notify(&self, predecessor: Node) -> Result<(), ClientError> {
         self.handle_request(|tx| Command::Notify(predecessor, tx))
             .await
     }
 
-    async fn ping(&self) -> Result<(), ClientError> {
+    async fn leave(&self, node: Node, predecessor: Option<Node>) -> Result<(), ClientError> {
+        self.handle_request(|tx| Command::Leave(node, predecessor, tx))
+            .await
+    }
+
+    async fn ping(&self) -> Result<NodeId, ClientError> {
         self.handle_request(|tx| Command::Ping(tx)).await
     }
+
+    async fn can_reach(&self, target: Node) -> Result<bool, ClientError> {
+        self.handle_request(|tx| Command::CanReach(target, tx))
+            .await
+    }
+
+    async fn health(&self) -> Result<HealthStatus, ClientError> {
+        self.handle_request(|tx| Command::Health(tx)).await
+    }
+
+    async fn trigger(&self, task: MaintenanceTask) -> Result<(), ClientError> {
+        self.handle_request(|tx| Command::Trigger(task, tx)).await
+    }
+
+    fn latency(&self) -> Option<LatencyStats> {
+        self.latency.stats()
+    }
+
+    async fn close(&self) {
+        self.spawner.shutdown().await;
+    }
 }
 
 impl ChordCapnpClient {
+    /// Create the client of the node at the given address, sending the cluster secret with all
+    /// its requests
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the node
+    /// * `secret` - The cluster secret, if the ring requires one
+    pub fn with_secret(addr: SocketAddr, secret: Option<String>) -> Self {
+        let spawner = LocalSpawner::new(addr, secret.unwrap_or_default());
+
+        Self {
+            spawner,
+            latency: Arc::new(Latency::default()),
+        }
+    }
+
+    /// Get the factory creating the clients sending the given cluster secret, see
+    /// [`NodeService::with_client_factory`](chord_rs_core::NodeService::with_client_factory)
+    ///
+    /// # Arguments
+    ///
+    /// * `secret` - The cluster secret, if the ring requires one
+    pub fn factory(secret: Option<String>) -> ClientFactory<ChordCapnpClient> {
+        Arc::new(move |addr| {
+            let client = Self::with_secret(addr, secret.clone());
+            Box::pin(async move { client })
+        })
+    }
+
     async fn handle_request<T>(
         &self,
         request: impl FnOnce(Sender<Result<T, ClientError>>) -> Command,
     ) -> Result<T, ClientError> {
         let (tx, rx) = oneshot::channel();
-        self.spawner.spawn(request(tx)).await.unwrap()?;
-
-        let result = rx
+        self.latency
+            .measure(async {
+                self.spawner
+                    .spawn(request(tx))
+                    .await
+                    .into_report()
+                    .change_context(ClientError::Unexpected)
+                    .attach_printable("The client is closed")??;
+
+                let result = rx
+                    .await
+                    .into_report()
+                    .change_context(ClientError::Unexpected)?;
+                result
+            })
             .await
-            .into_report()
-            .change_context(ClientError::Unexpected)?;
-        result
     }
 }
 
@@ -76,4 +174,6 @@ pub(crate) enum CapnpClientError {
     ConnectionFailed(String),
     #[error("Unexpected error: {0}")]
     Unexpected(String),
+    #[error("Deadline exceeded: {0}")]
+    DeadlineExceeded(String),
 }
@@ -1,4 +1,6 @@
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::thread::JoinHandle;
 
 use capnp_rpc::{rpc_twoparty_capnp, twoparty, RpcSystem};
 use chord_rs_core::client::ClientError;
@@ -15,28 +17,46 @@ use crate::chord_capnp;
 
 use super::command::Command;
 
+/// Thread running the requests of a client, on a single-threaded runtime
+///
+/// The thread stops once it receives a [`Command::Shutdown`], or once all the clones of the
+/// spawner are dropped.
 #[derive(Clone)]
 pub(crate) struct LocalSpawner {
     sender: mpsc::UnboundedSender<(
         super::Command,
         oneshot::Sender<Result<(), Report<ClientError>>>,
     )>,
+    thread: Arc<JoinHandle<()>>,
 }
 
 impl LocalSpawner {
-    pub fn new(addr: SocketAddr) -> Self {
+    /// Start the thread of the client of the node at the given address
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the node
+    /// * `secret` - The cluster secret sent with all the requests, empty if not set
+    pub fn new(addr: SocketAddr, secret: String) -> Self {
         let (sender, mut receiver) =
             mpsc::unbounded_channel::<(Command, oneshot::Sender<Result<(), Report<ClientError>>>)>(
             );
         let rt = Builder::new_current_thread().enable_all().build().unwrap();
 
-        std::thread::spawn(move || {
+        let thread = std::thread::spawn(move || {
             let local = LocalSet::new();
 
             local.spawn_local(async move {
                 while let Some((command, result_sender)) = receiver.recv().await {
+                    if let Command::Shutdown = command {
+                        log::debug!("Shutting down the client of {}", addr);
+                        receiver.close();
+                        let _ = result_sender.send(Ok(()));
+                        break;
+                    }
+
                     let context = command.get_error();
-                    if let Err(report) = Self::run_local(addr, command).await {
+                    if let Err(report) = Self::run_local(addr, &secret, command).await {
                         match report.current_context() {
                             SpawnerError::ClientConnectionError => {
                                 log::debug!("{report:?}");
@@ -55,21 +75,32 @@ impl LocalSpawner {
             rt.block_on(local);
         });
 
-        Self { sender }
+        Self {
+            sender,
+            thread: Arc::new(thread),
+        }
     }
 
+    /// Send the command to the thread
+    ///
+    /// If the thread has stopped, the command is dropped and the returned receiver fails.
     pub(crate) fn spawn(
         &self,
         task: super::Command,
     ) -> oneshot::Receiver<Result<(), Report<ClientError>>> {
         let (tx, rx) = oneshot::channel();
-        self.sender
-            .send((task, tx))
-            .expect("Thread with LocalSet has shut down.");
+        if self.sender.send((task, tx)).is_err() {
+            log::debug!("The client is closed, dropping the request");
+        }
 
         rx
     }
 
+    /// Stop the thread, once it's done with the commands sent before
+    pub(crate) async fn shutdown(&self) {
+        let _ = self.spawn(Command::Shutdown).await;
+    }
+
     async fn rpc_system(
         addr: SocketAddr,
     ) -> Result<RpcSystem<rpc_twoparty_capnp::Side>, SpawnerError> {
@@ -89,6 +120,7 @@ impl LocalSpawner {
 
     async fn run_local(
         addr: SocketAddr,
+        secret: &str,
         command: super::Command,
     ) -> Result<(), Report<SpawnerError>> {
         let mut rpc_system = Self::rpc_system(addr)
@@ -101,22 +133,46 @@ impl LocalSpawner {
         tokio::task::spawn_local(rpc_system);
 
         match command {
-            super::command::Command::FindSuccessor(node_id, resp) => {
-                super::Command::find_successor(client, node_id, resp).await
+            super::command::Command::FindSuccessor(node_id, deadline, resp) => {
+                super::Command::find_successor(client, secret, node_id, deadline, resp).await
+            }
+            super::command::Command::FindSuccessorTraced(node_id, resp) => {
+                super::Command::find_successor_traced(client, secret, node_id, resp).await
+            }
+            super::command::Command::FindSuccessors(ids, resp) => {
+                super::Command::find_successors(client, secret, ids, resp).await
+            }
+            super::command::Command::FindPredecessor(node_id, resp) => {
+                super::Command::find_predecessor(client, secret, node_id, resp).await
             }
             super::command::Command::Predecessor(resp) => {
-                super::Command::get_predecessor(client, resp).await
+                super::Command::get_predecessor(client, secret, resp).await
             }
             super::command::Command::Notify(node, resp) => {
-                super::Command::notify(client, node, resp).await
+                super::Command::notify(client, secret, node, resp).await
+            }
+            super::command::Command::Leave(node, predecessor, resp) => {
+                super::Command::leave(client, secret, node, predecessor, resp).await
             }
             super::command::Command::Successor(resp) => {
-                super::Command::get_successor(client, resp).await
+                super::Command::get_successor(client, secret, resp).await
             }
             super::command::Command::SuccessorList(resp) => {
-                super::Command::get_successor_list(client, resp).await
+                super::Command::get_successor_list(client, secret, resp).await
+            }
+            super::command::Command::PredecessorList(resp) => {
+                super::Command::get_predecessor_list(client, secret, resp).await
+            }
+            super::Command::Ping(resp) => super::Command::ping(client, secret, resp).await,
+            super::Command::CanReach(node, resp) => {
+                super::Command::can_reach(client, secret, node, resp).await
             }
-            super::Command::Ping(resp) => super::Command::ping(client, resp).await,
+            super::Command::Health(resp) => super::Command::health(client, secret, resp).await,
+            super::Command::NodeInfo(resp) => super::Command::node_info(client, secret, resp).await,
+            super::Command::Trigger(task, resp) => {
+                super::Command::trigger(client, secret, task, resp).await
+            }
+            super::Command::Shutdown => unreachable!("The shutdown is handled by the thread"),
         }
 
         if let Err(err) = disconnector.await {
@@ -151,3 +207,50 @@ impl From<std::io::Error> for SpawnerError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    /// Wait for the thread to finish, for at most a second
+    async fn finished(thread: &JoinHandle<()>) -> bool {
+        for _ in 0..100 {
+            if thread.is_finished() {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        thread.is_finished()
+    }
+
+    #[tokio::test]
+    async fn shutdown_stops_the_thread() {
+        let spawner = LocalSpawner::new(SocketAddr::from(([127, 0, 0, 1], 42601)));
+        assert!(!spawner.thread.is_finished());
+
+        spawner.shutdown().await;
+        assert!(finished(&spawner.thread).await);
+
+        // The commands sent after the shutdown are dropped
+        let (tx, _rx) = oneshot::channel();
+        let result = spawner.spawn(Command::Ping(tx)).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn dropping_the_spawner_stops_the_thread() {
+        let spawner = LocalSpawner::new(SocketAddr::from(([127, 0, 0, 1], 42602)));
+        let thread = spawner.thread.clone();
+        let clone = spawner.clone();
+
+        drop(spawner);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!thread.is_finished());
+
+        drop(clone);
+        assert!(finished(&thread).await);
+    }
+}
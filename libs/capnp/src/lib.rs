@@ -1,10 +1,19 @@
-use std::{net::SocketAddr, sync::Arc};
+use std::{net::SocketAddr, num::NonZeroU32, sync::Arc, time::Duration};
 
 use capnp_rpc::{rpc_twoparty_capnp, twoparty, RpcSystem};
-use chord_rs_core::NodeService;
+use chord_rs_core::{
+    rate_limit::RateLimiter,
+    server::{launch_node_with, ServerConfig},
+    NodeService,
+};
 use client::ChordCapnpClient;
 use futures::AsyncReadExt;
 use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+
+#[cfg(feature = "chaos")]
+use chord_rs_core::chaos::Chaos;
 
 pub mod client;
 pub mod parser;
@@ -18,37 +27,111 @@ pub mod chord_capnp {
 pub struct Server {
     addr: SocketAddr,
     node: Arc<NodeService<ChordCapnpClient>>,
+    secret: Option<String>,
+    limiter: Option<Arc<RateLimiter>>,
+    #[cfg(feature = "chaos")]
+    chaos: Option<Arc<Chaos>>,
 }
 
 impl Server {
-    pub async fn new(addr: SocketAddr, ring: Option<SocketAddr>) -> Self {
-        const REPLICATION_FACTOR: usize = 3; // TODO: make this configurable
-        let node_service = Arc::new(NodeService::new(addr, REPLICATION_FACTOR));
-        if let Some(ring) = ring {
-            const MAX_RETRIES: u32 = 5;
-            chord_rs_core::server::join_ring(node_service.clone(), ring, MAX_RETRIES).await;
-        }
-        chord_rs_core::server::background_tasks(node_service.clone());
+    /// How long to wait for the open connections to finish on shutdown
+    pub const DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Start the node and create the server serving it, see
+    /// [`launch_node`](chord_rs_core::server::launch_node)
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the node
+    /// * `config` - The parameters of the node
+    /// * `secret` - The cluster secret, if set the requests without it are rejected, and the
+    ///   clients of the node send it with their requests
+    /// * `rate_limit` - The maximum number of requests per second accepted from every peer
+    ///   address, if not set the requests are not limited
+    pub async fn new(
+        addr: SocketAddr,
+        config: ServerConfig,
+        secret: Option<String>,
+        rate_limit: Option<NonZeroU32>,
+    ) -> Self {
+        let clients = ChordCapnpClient::factory(secret.clone());
+        let node_service = launch_node_with(addr, &config, Some(clients)).await;
 
         Self {
             addr,
             node: node_service,
+            secret,
+            limiter: rate_limit.map(|rate| Arc::new(RateLimiter::new(rate))),
+            #[cfg(feature = "chaos")]
+            chaos: None,
         }
     }
 
-    pub async fn run(&self, max_connections: usize) {
+    /// Inject faults into the requests of the ring, see [`Chaos`]
+    ///
+    /// # Arguments
+    ///
+    /// * `chaos` - The faults to inject, shared with the other servers of the process
+    #[cfg(feature = "chaos")]
+    pub fn with_chaos(mut self, chaos: Arc<Chaos>) -> Self {
+        self.chaos = Some(chaos);
+        self
+    }
+
+    /// Get the service of the node, shared with the RPC handlers
+    pub fn node(&self) -> Arc<NodeService<ChordCapnpClient>> {
+        self.node.clone()
+    }
+
+    /// Run the server until the shutdown token is cancelled
+    ///
+    /// On shutdown, the server stops accepting new connections, tells its successor that the
+    /// node is [leaving](NodeService::leave) the ring and waits up to
+    /// [`DRAIN_TIMEOUT`](Self::DRAIN_TIMEOUT) for the open connections to finish.
+    ///
+    /// Once `max_connections` connections are open, the server stops accepting new ones until
+    /// one of them is closed. The waiting connections are left in the listen backlog.
+    ///
+    /// Every connection is served by its own instance of the node's interface, which knows the
+    /// address of the peer, so the requests can be rate-limited per peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_connections` - The maximum number of concurrent connections
+    /// * `shutdown` - The token which stops the server when cancelled
+    pub async fn run(&self, max_connections: usize, shutdown: CancellationToken) {
         tokio::task::LocalSet::new()
             .run_until(async move {
-                let server = server::NodeServerImpl::new(self.node.clone());
                 let listener = tokio::net::TcpListener::bind(&self.addr).await.unwrap();
-                let chord_node_client: chord_capnp::chord_node::Client =
-                    capnp_rpc::new_client(server);
                 let sem = Arc::new(Semaphore::new(max_connections));
+                let mut connections = JoinSet::new();
 
                 loop {
-                    let (stream, _) = listener.accept().await.unwrap();
-                    let sem = sem.clone();
-                    stream.set_nodelay(true).unwrap();
+                    // Wait for a free slot before accepting, so the connections over the limit
+                    // stay in the listen backlog instead of being accepted and dropped
+                    let next = async {
+                        let permit = sem
+                            .clone()
+                            .acquire_owned()
+                            .await
+                            .expect("The semaphore is never closed");
+                        (permit, listener.accept().await)
+                    };
+                    let (permit, accepted) = tokio::select! {
+                        _ = shutdown.cancelled() => break,
+                        Some(_) = connections.join_next(), if !connections.is_empty() => continue,
+                        next = next => next,
+                    };
+                    let (stream, peer) = match accepted {
+                        Ok(accepted) => accepted,
+                        Err(err) => {
+                            log::warn!("Failed to accept connection: {}", err);
+                            continue;
+                        }
+                    };
+                    if let Err(err) = stream.set_nodelay(true) {
+                        log::warn!("Failed to set TCP_NODELAY: {}", err);
+                    }
                     let (reader, writer) =
                         tokio_util::compat::TokioAsyncReadCompatExt::compat(stream).split();
                     let network = twoparty::VatNetwork::new(
@@ -58,23 +141,125 @@ impl Server {
                         Default::default(),
                     );
 
+                    let server = server::NodeServerImpl::new(
+                        self.node.clone(),
+                        self.secret.clone(),
+                        self.limiter.clone().map(|limiter| (limiter, peer.ip())),
+                    );
+                    #[cfg(feature = "chaos")]
+                    let server = server.with_chaos(self.chaos.clone());
+                    let chord_node_client: chord_capnp::chord_node::Client =
+                        capnp_rpc::new_client(server);
                     let rpc_system =
-                        RpcSystem::new(Box::new(network), Some(chord_node_client.clone().client));
-
-                    tokio::task::spawn_local(async move {
-                        if let Ok(aq) = sem.try_acquire() {
-                            log::trace!("Semaphore acquired");
-                            if let Err(err) = rpc_system.await {
-                                log::error!("rpc system error: {}", err);
-                            }
-                            log::trace!("Semaphore released");
-                            drop(aq);
-                        } else {
-                            log::debug!("Failed to acquire semaphore")
+                        RpcSystem::new(Box::new(network), Some(chord_node_client.client));
+
+                    log::trace!(
+                        "Connection accepted, {} slots left",
+                        sem.available_permits()
+                    );
+                    connections.spawn_local(async move {
+                        if let Err(err) = rpc_system.await {
+                            log::error!("rpc system error: {}", err);
                         }
+                        drop(permit);
                     });
                 }
+
+                if let Err(err) = self.node.leave().await {
+                    log::warn!("Failed to leave the ring: {:?}", err);
+                }
+                log::info!(
+                    "Shutting down, waiting for {} open connections",
+                    connections.len()
+                );
+                let drain = async { while connections.join_next().await.is_some() {} };
+                if tokio::time::timeout(Self::DRAIN_TIMEOUT, drain)
+                    .await
+                    .is_err()
+                {
+                    log::warn!("Closing {} connections still open", connections.len());
+                    connections.shutdown().await;
+                }
+                log::info!("Server stopped");
             })
             .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpStream;
+    use tokio::task::LocalSet;
+
+    use super::*;
+    use chord_rs_core::server::JoinConfig;
+
+    async fn connect(addr: SocketAddr) -> chord_capnp::chord_node::Client {
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let (reader, writer) = tokio_util::compat::TokioAsyncReadCompatExt::compat(stream).split();
+        let network = twoparty::VatNetwork::new(
+            reader,
+            writer,
+            rpc_twoparty_capnp::Side::Client,
+            Default::default(),
+        );
+        let mut rpc_system = RpcSystem::new(Box::new(network), None);
+        let client = rpc_system.bootstrap(rpc_twoparty_capnp::Side::Server);
+        tokio::task::spawn_local(rpc_system);
+
+        client
+    }
+
+    async fn ping(client: &chord_capnp::chord_node::Client) -> Result<(), capnp::Error> {
+        client.ping_request().send().promise.await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn open_connections_survive_a_flood() {
+        const MAX_CONNECTIONS: usize = 2;
+        let addr = SocketAddr::from(([127, 0, 0, 1], 42501));
+        let config = ServerConfig {
+            join: JoinConfig {
+                bootstrap: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let server = Server::new(addr, config, None, None).await;
+        let shutdown = CancellationToken::new();
+
+        let test = async {
+            let older = connect(addr).await;
+            ping(&older).await.unwrap();
+
+            // Takes the last slot, the rest of the flood waits in the backlog
+            let mut flood = vec![];
+            for _ in 0..10 * MAX_CONNECTIONS {
+                flood.push(TcpStream::connect(addr).await.unwrap());
+            }
+
+            ping(&older).await.unwrap();
+
+            let newer = connect(addr).await;
+            let pending = tokio::time::timeout(Duration::from_millis(200), ping(&newer)).await;
+            assert!(pending.is_err(), "Connection over the limit was served");
+
+            drop(flood);
+            tokio::time::timeout(Duration::from_secs(5), ping(&newer))
+                .await
+                .expect("Connection not served after the flood closed")
+                .unwrap();
+            ping(&older).await.unwrap();
+        };
+
+        LocalSet::new()
+            .run_until(async {
+                tokio::select! {
+                    _ = server.run(MAX_CONNECTIONS, shutdown.clone()) => unreachable!(),
+                    _ = test => {}
+                }
+            })
+            .await;
+    }
+}
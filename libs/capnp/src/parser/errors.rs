@@ -1,4 +1,5 @@
 use chord_rs_core::client::ClientError;
+use chord_rs_core::error::ServiceError;
 
 use crate::client::CapnpClientError;
 
@@ -16,6 +17,7 @@ impl Into<ClientError> for CapnpClientError {
             CapnpClientError::InvalidRequest(m) => ClientError::InvalidRequest(m),
             CapnpClientError::ConnectionFailed(m) => ClientError::ConnectionFailed(m),
             CapnpClientError::Unexpected(_) => ClientError::Unexpected,
+            CapnpClientError::DeadlineExceeded(_) => ClientError::DeadlineExceeded,
         }
     }
 }
@@ -24,6 +26,14 @@ impl From<capnp::Error> for CapnpClientError {
     fn from(value: capnp::Error) -> Self {
         log::error!("capnp error: {:?}", value);
         match value.kind {
+            // The node sends the errors of the service as their message
+            capnp::ErrorKind::Failed
+                if value
+                    .description
+                    .ends_with(&ServiceError::Timeout.to_string()) =>
+            {
+                CapnpClientError::DeadlineExceeded(value.to_string())
+            }
             capnp::ErrorKind::Failed => CapnpClientError::Unexpected(value.to_string()),
             capnp::ErrorKind::Overloaded => CapnpClientError::Unexpected(value.to_string()),
             capnp::ErrorKind::Disconnected => CapnpClientError::ConnectionFailed(value.to_string()),
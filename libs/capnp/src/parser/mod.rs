@@ -22,6 +22,7 @@ pub trait ResultBuilder<T>: Sized {
 pub enum ParserError {
     InvalidNode,
     InvalidIp(String),
+    InvalidAddress(String),
 }
 
 impl Display for ParserError {
@@ -29,6 +30,7 @@ impl Display for ParserError {
         match self {
             Self::InvalidNode => write!(f, "Invalid node"),
             Self::InvalidIp(msg) => write!(f, "{}", msg),
+            Self::InvalidAddress(msg) => write!(f, "Invalid address: {}", msg),
         }
     }
 }
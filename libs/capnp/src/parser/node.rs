@@ -1,11 +1,12 @@
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 
-use chord_rs_core::Node;
+use chord_rs_core::{Node, NodeId, NodeInfo, RingParams};
 
 use crate::chord_capnp;
 use crate::chord_capnp::chord_node::node::ip_address;
 
 use crate::chord_capnp::chord_node::node;
+use crate::chord_capnp::chord_node::ring_params;
 
 use super::ResultBuilder;
 
@@ -15,7 +16,10 @@ impl TryFrom<node::Reader<'_>> for Node {
 
     fn try_from(value: node::Reader<'_>) -> Result<Self, Self::Error> {
         let id = value.get_id();
-        let addr: SocketAddr = value.get_address().unwrap().try_into()?;
+        let addr: SocketAddr = value
+            .get_address()
+            .map_err(|err| super::ParserError::InvalidAddress(err.to_string()))?
+            .try_into()?;
 
         Ok(Node::with_id(id, addr))
     }
@@ -27,7 +31,10 @@ impl TryFrom<ip_address::Reader<'_>> for SocketAddr {
 
     fn try_from(addr: ip_address::Reader<'_>) -> Result<Self, Self::Error> {
         let port = addr.get_port();
-        let address = match addr.which().unwrap() {
+        let which = addr
+            .which()
+            .map_err(|err| super::ParserError::InvalidAddress(err.to_string()))?;
+        let address = match which {
             ip_address::Which::Ipv4(Ok(ipv4)) => {
                 let mut array = [0; 4];
                 if let Some(ip) = ipv4.as_slice() {
@@ -87,6 +94,64 @@ impl ResultBuilder<Node> for chord_capnp::chord_node::FindSuccessorResults {
     }
 }
 
+/// Insert a `Node` and the path of the lookup into a `FindSuccessorResults` struct.
+impl ResultBuilder<(Node, Vec<NodeId>)> for chord_capnp::chord_node::FindSuccessorResults {
+    type Output = ();
+    #[inline]
+    fn insert(mut self, (node, path): (Node, Vec<NodeId>)) -> Result<Self::Output, capnp::Error> {
+        self.get().init_node().insert(node)?;
+
+        let mut ids = self.get().init_path(path.len() as u32);
+        for (i, id) in path.into_iter().enumerate() {
+            ids.set(i as u32, id.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Insert a `Node` into a `FindPredecessorResults` struct.
+impl ResultBuilder<Node> for chord_capnp::chord_node::FindPredecessorResults {
+    type Output = ();
+    #[inline]
+    fn insert(mut self, value: Node) -> Result<Self::Output, capnp::Error> {
+        let node = self.get().init_node();
+        node.insert(value)?;
+
+        Ok(())
+    }
+}
+
+/// Insert a `Vec<Option<Node>>` into a `FindSuccessorsResults` struct.
+impl ResultBuilder<Vec<Option<Node>>> for chord_capnp::chord_node::FindSuccessorsResults {
+    type Output = ();
+    #[inline]
+    fn insert(mut self, value: Vec<Option<Node>>) -> Result<Self::Output, capnp::Error> {
+        let mut nodes = self.get().init_nodes(value.len() as u32);
+        for (i, node) in value.into_iter().enumerate() {
+            let mut result = nodes.reborrow().get(i as u32);
+            match node {
+                Some(node) => result.init_some().insert(node)?,
+                None => result.set_none(()),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Insert a `Node` into a `GetSuccessorResults` struct.
+impl ResultBuilder<Node> for chord_capnp::chord_node::GetSuccessorResults {
+    type Output = ();
+    #[inline]
+    fn insert(mut self, value: Node) -> Result<Self::Output, capnp::Error> {
+        let node = self.get().init_node();
+        node.insert(value)?;
+
+        Ok(())
+    }
+}
+
 /// Insert a `Vec<Node>` into a `GetSuccessorListResults` struct.
 impl ResultBuilder<Vec<Node>> for chord_capnp::chord_node::GetSuccessorListResults {
     type Output = ();
@@ -99,6 +164,18 @@ impl ResultBuilder<Vec<Node>> for chord_capnp::chord_node::GetSuccessorListResul
     }
 }
 
+/// Insert a `Vec<Node>` into a `GetPredecessorListResults` struct.
+impl ResultBuilder<Vec<Node>> for chord_capnp::chord_node::GetPredecessorListResults {
+    type Output = ();
+    #[inline]
+    fn insert(mut self, value: Vec<Node>) -> Result<Self::Output, capnp::Error> {
+        let nodes = self.get().init_nodes(value.len() as u32);
+        nodes.insert(value)?;
+
+        Ok(())
+    }
+}
+
 /// Insert a `Option<Node>` into a `GetPredecessorResults` struct.
 impl ResultBuilder<Option<Node>> for chord_capnp::chord_node::GetPredecessorResults {
     type Output = ();
@@ -116,6 +193,64 @@ impl ResultBuilder<Option<Node>> for chord_capnp::chord_node::GetPredecessorResu
     }
 }
 
+/// Insert a `NodeInfo` into a `GetNodeInfoResults` struct.
+impl ResultBuilder<NodeInfo> for chord_capnp::chord_node::GetNodeInfoResults {
+    type Output = ();
+    #[inline]
+    fn insert(mut self, value: NodeInfo) -> Result<Self::Output, capnp::Error> {
+        let mut results = self.get();
+        results
+            .reborrow()
+            .init_node()
+            .insert(Node::with_id(value.id, value.addr))?;
+
+        let mut predecessor = results.reborrow().init_predecessor();
+        if let Some(node) = value.predecessor {
+            predecessor.init_some().insert(node)?;
+        } else {
+            predecessor.set_none(());
+        }
+
+        let successors = results
+            .reborrow()
+            .init_successors(value.successor_list.len() as u32);
+        successors.insert(value.successor_list)?;
+
+        let mut params = results.init_params();
+        if let Some(value) = value.params {
+            params.init_some().insert(value)?;
+        } else {
+            params.set_none(());
+        }
+
+        Ok(())
+    }
+}
+
+/// Map capnp ring params to chord_rs_core ring params
+impl From<ring_params::Reader<'_>> for RingParams {
+    fn from(value: ring_params::Reader<'_>) -> Self {
+        RingParams {
+            replication_factor: value.get_replication_factor() as usize,
+            successor_list_size: value.get_successor_list_size() as usize,
+            key_bits: value.get_key_bits(),
+        }
+    }
+}
+
+impl ResultBuilder<RingParams> for ring_params::Builder<'_> {
+    type Output = ();
+
+    #[inline]
+    fn insert(mut self, value: RingParams) -> Result<Self::Output, capnp::Error> {
+        self.set_replication_factor(value.replication_factor as u64);
+        self.set_successor_list_size(value.successor_list_size as u64);
+        self.set_key_bits(value.key_bits);
+
+        Ok(())
+    }
+}
+
 impl ResultBuilder<Node> for chord_capnp::chord_node::node::Builder<'_> {
     type Output = ();
 
@@ -284,3 +419,45 @@ mod tests {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ParserError;
+
+    #[test]
+    fn node_roundtrip() {
+        let mut message = capnp::message::Builder::new_default();
+        let node = Node::with_id(42, SocketAddr::from(([127, 0, 0, 1], 42000)));
+        message
+            .init_root::<node::Builder>()
+            .insert(node.clone())
+            .unwrap();
+
+        let reader = message.get_root_as_reader::<node::Reader>().unwrap();
+        assert_eq!(Node::try_from(reader).unwrap(), node);
+    }
+
+    #[test]
+    fn node_without_address_is_an_error() {
+        let mut message = capnp::message::Builder::new_default();
+        message.init_root::<node::Builder>().set_id(42);
+
+        let reader = message.get_root_as_reader::<node::Reader>().unwrap();
+        assert!(Node::try_from(reader).is_err());
+    }
+
+    #[test]
+    fn address_with_short_ip_is_an_error() {
+        let mut message = capnp::message::Builder::new_default();
+        let mut address = message.init_root::<node::Builder>().init_address();
+        address.set_port(42000);
+        address.init_ipv4(2);
+
+        let reader = message.get_root_as_reader::<node::Reader>().unwrap();
+        assert!(matches!(
+            Node::try_from(reader),
+            Err(ParserError::InvalidIp(_))
+        ));
+    }
+}
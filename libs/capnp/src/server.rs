@@ -1,6 +1,12 @@
-use std::{fmt::Display, sync::Arc};
+use std::{fmt::Display, future::Future, net::IpAddr, sync::Arc};
 
-use chord_rs_core::{Node, NodeService};
+#[cfg(feature = "chaos")]
+use chord_rs_core::chaos::Chaos;
+
+use chord_rs_core::{
+    auth::secret_matches, deadline, rate_limit::RateLimiter, MaintenanceTask, Node, NodeId,
+    NodeService,
+};
 
 use crate::{chord_capnp, parser::ResultBuilder};
 
@@ -9,6 +15,12 @@ use super::client::ChordCapnpClient;
 /// Implementation of the chord_node interface
 pub(crate) struct NodeServerImpl {
     node: Arc<NodeService<ChordCapnpClient>>,
+    secret: Option<String>,
+    /// The rate limiter shared by all the connections, with the peer of this connection
+    rate_limit: Option<(Arc<RateLimiter>, IpAddr)>,
+    /// The faults injected into the requests, shared by all the connections
+    #[cfg(feature = "chaos")]
+    chaos: Option<Arc<Chaos>>,
 }
 
 impl NodeServerImpl {
@@ -17,22 +29,148 @@ impl NodeServerImpl {
     /// # Arguments
     ///
     /// * `node` - The Chord node service.
-    pub fn new(node: Arc<NodeService<ChordCapnpClient>>) -> Self {
-        Self { node }
+    /// * `secret` - The cluster secret required by all the requests, if any.
+    /// * `rate_limit` - The rate limiter and the address of the peer, if the requests are limited.
+    pub fn new(
+        node: Arc<NodeService<ChordCapnpClient>>,
+        secret: Option<String>,
+        rate_limit: Option<(Arc<RateLimiter>, IpAddr)>,
+    ) -> Self {
+        Self {
+            node,
+            secret,
+            rate_limit,
+            #[cfg(feature = "chaos")]
+            chaos: None,
+        }
+    }
+
+    /// Inject faults into the requests, see [`Chaos`]
+    ///
+    /// # Arguments
+    ///
+    /// * `chaos` - The faults to inject, if any.
+    #[cfg(feature = "chaos")]
+    pub fn with_chaos(mut self, chaos: Option<Arc<Chaos>>) -> Self {
+        self.chaos = chaos;
+        self
+    }
+
+    /// Delay the request, and fail it if it's dropped, when faults are injected
+    ///
+    /// The request is delayed when the returned future is awaited, before being handled.
+    fn fault(&self) -> impl Future<Output = Result<(), capnp::Error>> + 'static {
+        #[cfg(feature = "chaos")]
+        let chaos = self.chaos.clone();
+
+        async move {
+            #[cfg(feature = "chaos")]
+            if let Some(chaos) = chaos {
+                if !chaos.inject().await {
+                    return Err(capnp::Error::disconnected(
+                        "Request dropped to inject a fault".to_string(),
+                    ));
+                }
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Check that the peer didn't exceed its rate
+    fn admit(&self) -> Result<(), capnp::Error> {
+        let Some((limiter, peer)) = &self.rate_limit else {
+            return Ok(());
+        };
+
+        if limiter.check(*peer) {
+            Ok(())
+        } else {
+            log::debug!("Rejected request from {}: rate limit exceeded", peer);
+            Err(capnp::Error::overloaded(
+                "Resource exhausted: rate limit exceeded".to_string(),
+            ))
+        }
+    }
+
+    /// Check the secret sent with the request against the cluster secret
+    ///
+    /// # Arguments
+    ///
+    /// * `secret` - The secret sent with the request.
+    fn authenticate(&self, secret: capnp::Result<&str>) -> Result<(), capnp::Error> {
+        let expected = match &self.secret {
+            Some(expected) => expected,
+            None => return Ok(()),
+        };
+
+        if secret_matches(expected, secret.unwrap_or_default()) {
+            Ok(())
+        } else {
+            log::warn!("Rejected request with an invalid cluster secret");
+            Err(capnp::Error::failed(
+                "Unauthenticated: invalid cluster secret".to_string(),
+            ))
+        }
+    }
+
+    /// Run a maintenance task on the request of an administrator
+    ///
+    /// Without the `admin` feature the request is denied, so a peer can't make the node run its
+    /// tasks.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret` - The secret sent with the request.
+    /// * `task` - The task to run.
+    fn trigger(
+        &self,
+        secret: capnp::Result<&str>,
+        task: MaintenanceTask,
+    ) -> capnp::capability::Promise<(), capnp::Error> {
+        log::trace!("Trigger {} received", task);
+        capnp::pry!(self.admit());
+        capnp::pry!(self.authenticate(secret));
+        let fault = self.fault();
+
+        let service = self.node.clone();
+
+        capnp::capability::Promise::from_future(async move {
+            fault.await?;
+            if !cfg!(feature = "admin") {
+                return Err(capnp::Error::failed(format!(
+                    "Permission denied: triggering {} requires the admin feature",
+                    task
+                )));
+            }
+            service.trigger(task).await.map_err(error_parser)?;
+
+            Ok(())
+        })
     }
 }
 
 impl chord_capnp::chord_node::Server for NodeServerImpl {
     /// Ping the node
     ///
-    /// Just responds with an empty message.
+    /// Just responds with the id of the node.
     fn ping(
         &mut self,
-        _params: chord_capnp::chord_node::PingParams,
-        mut _results: chord_capnp::chord_node::PingResults,
+        params: chord_capnp::chord_node::PingParams,
+        mut results: chord_capnp::chord_node::PingResults,
     ) -> ::capnp::capability::Promise<(), ::capnp::Error> {
         log::trace!("Ping received");
-        ::capnp::capability::Promise::ok(())
+        capnp::pry!(self.admit());
+        capnp::pry!(self.authenticate(capnp::pry!(params.get()).get_secret()));
+        let fault = self.fault();
+        let id = self.node.ping();
+
+        ::capnp::capability::Promise::from_future(async move {
+            fault.await?;
+            results.get().set_id(id.into());
+
+            Ok(())
+        })
     }
 
     /// Find the successor of a given id
@@ -47,13 +185,93 @@ impl chord_capnp::chord_node::Server for NodeServerImpl {
         results: chord_capnp::chord_node::FindSuccessorResults,
     ) -> capnp::capability::Promise<(), capnp::Error> {
         log::trace!("FindSuccessor received");
+        capnp::pry!(self.admit());
+        capnp::pry!(self.authenticate(capnp::pry!(params.get()).get_secret()));
+        let fault = self.fault();
+
+        let service = self.node.clone();
+
+        ::capnp::capability::Promise::from_future(async move {
+            fault.await?;
+            let id = params.get()?.get_id();
+            if params.get()?.get_trace() {
+                let (node, path) = service
+                    .find_successor_traced(id.into())
+                    .await
+                    .map_err(error_parser)?;
+
+                results.insert((node, path))?;
+
+                return Ok(());
+            }
+
+            let deadline = match params.get()?.get_deadline() {
+                0 => None,
+                millis => Some(deadline::from_millis(millis)),
+            };
+            let node = deadline::scope(deadline, service.find_successor(id.into()))
+                .await
+                .map_err(error_parser)?;
+
+            results.insert(node)?;
+
+            Ok(())
+        })
+    }
+
+    /// Find the successors of many ids at once.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Cap'n'proto message containing the ids to find the successors of.
+    /// * `results` - Cap'n'proto message to write the successors to.
+    fn find_successors(
+        &mut self,
+        params: chord_capnp::chord_node::FindSuccessorsParams,
+        results: chord_capnp::chord_node::FindSuccessorsResults,
+    ) -> capnp::capability::Promise<(), capnp::Error> {
+        log::trace!("FindSuccessors received");
+        capnp::pry!(self.admit());
+        capnp::pry!(self.authenticate(capnp::pry!(params.get()).get_secret()));
+        let fault = self.fault();
 
         let service = self.node.clone();
 
         ::capnp::capability::Promise::from_future(async move {
+            fault.await?;
+            let ids = params.get()?.get_ids()?.iter().map(NodeId::from).collect();
+            let nodes = service.find_successors(ids).await.map_err(error_parser)?;
+            let nodes: Vec<Option<Node>> = nodes.into_iter().map(Result::ok).collect();
+
+            results.insert(nodes)?;
+
+            Ok(())
+        })
+    }
+
+    /// Find the predecessor of the given id.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Cap'n'proto message containing the id to find the predecessor of.
+    /// * `results` - Cap'n'proto message to write the predecessor to.
+    fn find_predecessor(
+        &mut self,
+        params: chord_capnp::chord_node::FindPredecessorParams,
+        results: chord_capnp::chord_node::FindPredecessorResults,
+    ) -> capnp::capability::Promise<(), capnp::Error> {
+        log::trace!("FindPredecessor received");
+        capnp::pry!(self.admit());
+        capnp::pry!(self.authenticate(capnp::pry!(params.get()).get_secret()));
+        let fault = self.fault();
+
+        let service = self.node.clone();
+
+        ::capnp::capability::Promise::from_future(async move {
+            fault.await?;
             let id = params.get()?.get_id();
             let node = service
-                .find_successor(id.into())
+                .find_predecessor(id.into())
                 .await
                 .map_err(error_parser)?;
 
@@ -63,15 +281,54 @@ impl chord_capnp::chord_node::Server for NodeServerImpl {
         })
     }
 
+    /// Get the successor of the node
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Cap'n'proto message containing the cluster secret.
+    /// * `results` - Cap'n'proto message to write the successor to.
+    fn get_successor(
+        &mut self,
+        params: chord_capnp::chord_node::GetSuccessorParams,
+        results: chord_capnp::chord_node::GetSuccessorResults,
+    ) -> capnp::capability::Promise<(), capnp::Error> {
+        log::trace!("GetSuccessor received");
+        capnp::pry!(self.admit());
+        capnp::pry!(self.authenticate(capnp::pry!(params.get()).get_secret()));
+        let fault = self.fault();
+
+        let service = self.node.clone();
+        ::capnp::capability::Promise::from_future(async move {
+            fault.await?;
+            let node = service.get_successor().await.map_err(error_parser)?;
+
+            results.insert(node)?;
+
+            Ok(())
+        })
+    }
+
+    /// Get the successor list of the node
+    ///
+    /// Used by the other nodes to reconcile their successor lists with this one.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Cap'n'proto message containing the cluster secret.
+    /// * `results` - Cap'n'proto message to write the successor list to.
     fn get_successor_list(
         &mut self,
-        _params: chord_capnp::chord_node::GetSuccessorListParams,
+        params: chord_capnp::chord_node::GetSuccessorListParams,
         results: chord_capnp::chord_node::GetSuccessorListResults,
     ) -> capnp::capability::Promise<(), capnp::Error> {
         log::trace!("GetSuccessorList received");
+        capnp::pry!(self.admit());
+        capnp::pry!(self.authenticate(capnp::pry!(params.get()).get_secret()));
+        let fault = self.fault();
 
         let service = self.node.clone();
         ::capnp::capability::Promise::from_future(async move {
+            fault.await?;
             let node = service.get_successor_list().await.map_err(error_parser)?;
 
             results.insert(node)?;
@@ -80,22 +337,55 @@ impl chord_capnp::chord_node::Server for NodeServerImpl {
         })
     }
 
+    /// Get the predecessor list of the node
+    ///
+    /// Used by the other nodes to reconcile their predecessor lists with this one.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Cap'n'proto message containing the cluster secret.
+    /// * `results` - Cap'n'proto message to write the predecessor list to.
+    fn get_predecessor_list(
+        &mut self,
+        params: chord_capnp::chord_node::GetPredecessorListParams,
+        results: chord_capnp::chord_node::GetPredecessorListResults,
+    ) -> capnp::capability::Promise<(), capnp::Error> {
+        log::trace!("GetPredecessorList received");
+        capnp::pry!(self.admit());
+        capnp::pry!(self.authenticate(capnp::pry!(params.get()).get_secret()));
+        let fault = self.fault();
+
+        let service = self.node.clone();
+        ::capnp::capability::Promise::from_future(async move {
+            fault.await?;
+            let nodes = service.get_predecessor_list().await.map_err(error_parser)?;
+
+            results.insert(nodes)?;
+
+            Ok(())
+        })
+    }
+
     /// Get the predecessor of the node
     ///
     /// # Arguments
     ///
-    /// * `_params` - Cap'n'proto message, not used.
+    /// * `params` - Cap'n'proto message containing the cluster secret.
     /// * `results` - Cap'n'proto message to write the successor to.
     fn get_predecessor(
         &mut self,
-        _params: chord_capnp::chord_node::GetPredecessorParams,
+        params: chord_capnp::chord_node::GetPredecessorParams,
         results: chord_capnp::chord_node::GetPredecessorResults,
     ) -> capnp::capability::Promise<(), capnp::Error> {
         log::trace!("GetPredecessor received");
+        capnp::pry!(self.admit());
+        capnp::pry!(self.authenticate(capnp::pry!(params.get()).get_secret()));
+        let fault = self.fault();
 
         let service = self.node.clone();
 
         ::capnp::capability::Promise::from_future(async move {
+            fault.await?;
             let maybe_node = service.get_predecessor().await.map_err(error_parser)?;
             results.insert(maybe_node)?;
 
@@ -103,6 +393,35 @@ impl chord_capnp::chord_node::Server for NodeServerImpl {
         })
     }
 
+    /// Get the id, the predecessor and the successor list of the node
+    ///
+    /// Used by the tools crawling the ring, to get the routing state of a node in a single call.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Cap'n'proto message containing the cluster secret.
+    /// * `results` - Cap'n'proto message to write the routing state to.
+    fn get_node_info(
+        &mut self,
+        params: chord_capnp::chord_node::GetNodeInfoParams,
+        results: chord_capnp::chord_node::GetNodeInfoResults,
+    ) -> capnp::capability::Promise<(), capnp::Error> {
+        log::trace!("GetNodeInfo received");
+        capnp::pry!(self.admit());
+        capnp::pry!(self.authenticate(capnp::pry!(params.get()).get_secret()));
+        let fault = self.fault();
+
+        let service = self.node.clone();
+
+        ::capnp::capability::Promise::from_future(async move {
+            fault.await?;
+            let info = service.node_info().await.map_err(error_parser)?;
+            results.insert(info)?;
+
+            Ok(())
+        })
+    }
+
     /// Notify the node of a new predecessor
     ///
     /// # Arguments
@@ -115,17 +434,163 @@ impl chord_capnp::chord_node::Server for NodeServerImpl {
         _results: chord_capnp::chord_node::NotifyResults,
     ) -> capnp::capability::Promise<(), capnp::Error> {
         log::trace!("Notify received");
+        capnp::pry!(self.admit());
+        capnp::pry!(self.authenticate(capnp::pry!(params.get()).get_secret()));
+        let fault = self.fault();
 
         let service = self.node.clone();
 
         ::capnp::capability::Promise::from_future(async move {
+            fault.await?;
             let node = params.get()?.get_node()?;
             let node: Node = node.try_into().unwrap(); // TODO: error handling
-            service.notify(node);
+            service.notify(node).await;
+
+            Ok(())
+        })
+    }
+
+    /// Handle the predecessor or a successor leaving the ring
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Cap'n'proto message containing the leaving node and its predecessor.
+    /// * `_results` - Cap'n'proto message, not used.
+    fn leave(
+        &mut self,
+        params: chord_capnp::chord_node::LeaveParams,
+        _results: chord_capnp::chord_node::LeaveResults,
+    ) -> capnp::capability::Promise<(), capnp::Error> {
+        log::trace!("Leave received");
+        capnp::pry!(self.admit());
+        capnp::pry!(self.authenticate(capnp::pry!(params.get()).get_secret()));
+        let fault = self.fault();
+
+        let service = self.node.clone();
+
+        ::capnp::capability::Promise::from_future(async move {
+            fault.await?;
+            let node: Node = params.get()?.get_node()?.try_into().map_err(error_parser)?;
+            let predecessor = match params.get()?.get_predecessor()?.which()? {
+                chord_capnp::option::None(()) => None,
+                chord_capnp::option::Some(reader) => {
+                    Some(Node::try_from(reader?).map_err(error_parser)?)
+                }
+            };
+            service.handle_leave(node, predecessor);
 
             Ok(())
         })
     }
+
+    /// Check if the node can reach the given node
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Cap'n'proto message containing the node to check.
+    /// * `results` - Cap'n'proto message to write the result to.
+    fn can_reach(
+        &mut self,
+        params: chord_capnp::chord_node::CanReachParams,
+        mut results: chord_capnp::chord_node::CanReachResults,
+    ) -> capnp::capability::Promise<(), capnp::Error> {
+        log::trace!("CanReach received");
+        capnp::pry!(self.admit());
+        capnp::pry!(self.authenticate(capnp::pry!(params.get()).get_secret()));
+        let fault = self.fault();
+
+        let service = self.node.clone();
+
+        ::capnp::capability::Promise::from_future(async move {
+            fault.await?;
+            let node: Node = params.get()?.get_node()?.try_into().map_err(error_parser)?;
+            let reachable = service.can_reach(node).await.map_err(error_parser)?;
+            results.get().set_reachable(reachable);
+
+            Ok(())
+        })
+    }
+
+    /// Report the health of the node
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Cap'n'proto message containing the secret.
+    /// * `results` - Cap'n'proto message to write the health to.
+    fn health(
+        &mut self,
+        params: chord_capnp::chord_node::HealthParams,
+        mut results: chord_capnp::chord_node::HealthResults,
+    ) -> capnp::capability::Promise<(), capnp::Error> {
+        log::trace!("Health received");
+        capnp::pry!(self.admit());
+        capnp::pry!(self.authenticate(capnp::pry!(params.get()).get_secret()));
+        let fault = self.fault();
+
+        let service = self.node.clone();
+
+        ::capnp::capability::Promise::from_future(async move {
+            fault.await?;
+            let health = service.health().await.map_err(error_parser)?;
+            let mut results = results.get();
+            results.set_joined(health.joined);
+            results.set_has_predecessor(health.has_predecessor);
+            results.set_successor_reachable(health.successor_reachable);
+
+            Ok(())
+        })
+    }
+
+    /// Run the stabilization right away, see [`NodeServerImpl::trigger`]
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Cap'n'proto message containing the secret.
+    /// * `_results` - Cap'n'proto message, not used.
+    fn trigger_stabilize(
+        &mut self,
+        params: chord_capnp::chord_node::TriggerStabilizeParams,
+        _results: chord_capnp::chord_node::TriggerStabilizeResults,
+    ) -> capnp::capability::Promise<(), capnp::Error> {
+        self.trigger(
+            capnp::pry!(params.get()).get_secret(),
+            MaintenanceTask::Stabilize,
+        )
+    }
+
+    /// Refresh the finger table right away, see [`NodeServerImpl::trigger`]
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Cap'n'proto message containing the secret.
+    /// * `_results` - Cap'n'proto message, not used.
+    fn trigger_fix_fingers(
+        &mut self,
+        params: chord_capnp::chord_node::TriggerFixFingersParams,
+        _results: chord_capnp::chord_node::TriggerFixFingersResults,
+    ) -> capnp::capability::Promise<(), capnp::Error> {
+        self.trigger(
+            capnp::pry!(params.get()).get_secret(),
+            MaintenanceTask::FixFingers,
+        )
+    }
+
+    /// Reconcile the successor list right away, see [`NodeServerImpl::trigger`]
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Cap'n'proto message containing the secret.
+    /// * `_results` - Cap'n'proto message, not used.
+    fn trigger_reconcile(
+        &mut self,
+        params: chord_capnp::chord_node::TriggerReconcileParams,
+        _results: chord_capnp::chord_node::TriggerReconcileResults,
+    ) -> capnp::capability::Promise<(), capnp::Error> {
+        self.trigger(
+            capnp::pry!(params.get()).get_secret(),
+            MaintenanceTask::ReconcileSuccessors,
+        )
+    }
 }
 
 fn error_parser<T>(err: T) -> capnp::Error
@@ -0,0 +1,129 @@
+//! Benchmarks of the routing of the lookups
+//!
+//! The rings are simulated in memory, so the benchmarks measure the routing itself and not the
+//! network. Run them with:
+//!
+//! ```bash
+//! cargo bench -p chord-rs-core --features test-util
+//! ```
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use chord_rs_core::client::InMemoryClient;
+use chord_rs_core::simulator::Simulator;
+use chord_rs_core::{Node, NodeId, NodeService};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use futures::future::join_all;
+use tokio::runtime::Runtime;
+
+/// Sizes of the simulated rings
+const RING_SIZES: [u64; 4] = [8, 32, 128, 256];
+
+/// Number of concurrent lookups measured by the throughput benchmarks
+const CONCURRENT_LOOKUPS: u64 = 64;
+
+/// Spread the ids over the whole ring, in a shuffled but fixed order
+fn id(i: u64) -> NodeId {
+    NodeId::from(i.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
+
+/// Build a stable ring of the given size, with complete finger tables
+///
+/// # Arguments
+///
+/// * `runtime` - The runtime the nodes run on
+/// * `size` - The number of nodes
+fn ring(runtime: &Runtime, size: u64) -> Simulator {
+    runtime.block_on(async {
+        // Every ring has its own addresses, the nodes of a dropped one are unregistered
+        let base = SocketAddr::from(([127, 0, 2, 1], 45000 + size as u16 * 2));
+        let mut simulator = Simulator::new(base, 3);
+        for i in 0..size {
+            simulator.add_node(id(i)).await.unwrap();
+        }
+        simulator
+            .run_until_stable(size * 2)
+            .await
+            .expect("The ring didn't stabilize");
+        // Fix the fingers against the stable ring
+        simulator.run(2).await;
+
+        simulator
+    })
+}
+
+/// The node the lookups start from
+fn origin(simulator: &Simulator) -> Arc<NodeService<InMemoryClient>> {
+    simulator.nodes().next().unwrap().clone()
+}
+
+/// Benchmarks running against the simulated rings, which are built once for all of them
+fn routing(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let rings: Vec<(u64, Simulator)> = RING_SIZES
+        .iter()
+        .map(|&size| (size, ring(&runtime, size)))
+        .collect();
+
+    let mut group = c.benchmark_group("find_successor");
+    for (size, simulator) in &rings {
+        let node = origin(simulator);
+        let mut key = 0u64;
+        group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, _| {
+            b.to_async(&runtime).iter(|| {
+                key = key.wrapping_add(0x2545_F491_4F6C_DD1D);
+                let node = node.clone();
+                async move { node.find_successor(NodeId::from(key)).await.unwrap() }
+            })
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("find_successor_concurrent");
+    group.throughput(Throughput::Elements(CONCURRENT_LOOKUPS));
+    for (size, simulator) in &rings {
+        let node = origin(simulator);
+        group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, &size| {
+            b.to_async(&runtime).iter(|| {
+                let lookups = (0..CONCURRENT_LOOKUPS).map(|i| {
+                    let node = node.clone();
+                    async move { node.find_successor(id(i + size)).await.unwrap() }
+                });
+                join_all(lookups)
+            })
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("closest_preceding_node");
+    for (size, simulator) in &rings {
+        let node = origin(simulator);
+        let mut key = 0u64;
+        group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, _| {
+            b.iter(|| {
+                key = key.wrapping_add(0x2545_F491_4F6C_DD1D);
+                node.closest_preceding_node(black_box(NodeId::from(key)))
+            })
+        });
+    }
+    group.finish();
+}
+
+fn is_between_on_ring(c: &mut Criterion) {
+    c.bench_function("is_between_on_ring", |b| {
+        b.iter(|| {
+            Node::is_between_on_ring(black_box(10), black_box(5), black_box(15))
+                && Node::is_between_on_ring(black_box(20), black_box(15), black_box(5))
+        })
+    });
+    c.bench_function("is_between_on_ring_exclusive", |b| {
+        b.iter(|| {
+            Node::is_between_on_ring_exclusive(black_box(10), black_box(5), black_box(15))
+                && Node::is_between_on_ring_exclusive(black_box(20), black_box(15), black_box(5))
+        })
+    });
+}
+
+criterion_group!(benches, routing, is_between_on_ring);
+criterion_main!(benches);
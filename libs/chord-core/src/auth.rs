@@ -0,0 +1,37 @@
+/// Check if the secret provided by a peer matches the cluster secret.
+///
+/// The comparison takes the same time regardless of where the secrets differ,
+/// so the secret can't be guessed byte by byte by measuring response times.
+///
+/// # Arguments
+///
+/// * `expected` - The cluster secret
+/// * `provided` - The secret provided by the peer
+pub fn secret_matches(expected: &str, provided: &str) -> bool {
+    let expected = expected.as_bytes();
+    let provided = provided.as_bytes();
+    if expected.len() != provided.len() {
+        return false;
+    }
+
+    expected
+        .iter()
+        .zip(provided)
+        .fold(0, |acc, (a, b)| acc | (a ^ b))
+        == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_matches() {
+        assert!(secret_matches("secret", "secret"));
+        assert!(secret_matches("", ""));
+
+        assert!(!secret_matches("secret", "secreT"));
+        assert!(!secret_matches("secret", "secret1"));
+        assert!(!secret_matches("secret", ""));
+    }
+}
@@ -0,0 +1,188 @@
+//! Faults injected into the inbound requests, to test how the ring recovers from them
+//!
+//! > **Note**
+//! >
+//! > Only compiled with the `chaos` feature, which is meant for the test deployments. A node
+//! > built with it drops and delays the requests of its peers on purpose.
+
+use std::sync::RwLock;
+use std::time::Duration;
+
+use error_stack::{Report, Result};
+use rand::Rng;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ChaosError {
+    #[error("The drop probability {0} is not between 0 and 1")]
+    InvalidProbability(f64),
+}
+
+/// Faults injected into the inbound requests, see [`Chaos`]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ChaosConfig {
+    /// Probability of a request being dropped, between 0 and 1
+    pub drop_probability: f64,
+    /// Latency added to every request
+    pub latency: Duration,
+    /// Maximum random latency added on top of `latency`, drawn uniformly for every request
+    pub jitter: Duration,
+}
+
+impl ChaosConfig {
+    /// Check that the probability is valid
+    fn validate(&self) -> Result<(), ChaosError> {
+        if (0.0..=1.0).contains(&self.drop_probability) {
+            Ok(())
+        } else {
+            Err(Report::new(ChaosError::InvalidProbability(
+                self.drop_probability,
+            )))
+        }
+    }
+}
+
+/// Injector of the faults, shared by the servers of the transports
+///
+/// The servers call [`Chaos::inject`] before handling every request of the ring, and reply with
+/// an error instead of handling the dropped ones. The faults can be changed while the node runs,
+/// with [`Chaos::set_config`].
+#[derive(Debug)]
+pub struct Chaos {
+    config: RwLock<ChaosConfig>,
+}
+
+impl Chaos {
+    /// Create the injector of the faults
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The faults to inject
+    pub fn new(config: ChaosConfig) -> Result<Self, ChaosError> {
+        config.validate()?;
+
+        Ok(Self {
+            config: RwLock::new(config),
+        })
+    }
+
+    /// Get the faults which are injected
+    pub fn config(&self) -> ChaosConfig {
+        *self.config.read().unwrap()
+    }
+
+    /// Change the faults injected into the next requests
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The faults to inject
+    pub fn set_config(&self, config: ChaosConfig) -> Result<(), ChaosError> {
+        config.validate()?;
+        *self.config.write().unwrap() = config;
+
+        Ok(())
+    }
+
+    /// Delay the request, then decide whether it's dropped
+    ///
+    /// Returns `false` if the request is dropped, in which case it should be rejected without
+    /// being handled.
+    pub async fn inject(&self) -> bool {
+        let (delay, dropped) = self.draw();
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        if dropped {
+            log::debug!("Dropping the request to inject a fault");
+        }
+
+        !dropped
+    }
+
+    /// Draw the latency and whether the request is dropped
+    fn draw(&self) -> (Duration, bool) {
+        let config = self.config();
+        let mut rng = rand::thread_rng();
+        let jitter = if config.jitter.is_zero() {
+            Duration::ZERO
+        } else {
+            config.jitter.mul_f64(rng.gen::<f64>())
+        };
+
+        (
+            config.latency + jitter,
+            rng.gen_bool(config.drop_probability),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chaos(drop_probability: f64) -> Chaos {
+        Chaos::new(ChaosConfig {
+            drop_probability,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn requests_are_kept_or_dropped_by_the_probability() {
+        let keep = chaos(0.0);
+        let drop = chaos(1.0);
+
+        for _ in 0..100 {
+            assert!(keep.inject().await);
+            assert!(!drop.inject().await);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn latency_is_added_with_the_jitter() {
+        let chaos = Chaos::new(ChaosConfig {
+            drop_probability: 0.0,
+            latency: Duration::from_millis(100),
+            jitter: Duration::from_millis(50),
+        })
+        .unwrap();
+
+        for _ in 0..20 {
+            let start = tokio::time::Instant::now();
+            assert!(chaos.inject().await);
+
+            let elapsed = start.elapsed();
+            assert!(elapsed >= Duration::from_millis(100));
+            assert!(elapsed <= Duration::from_millis(150));
+        }
+    }
+
+    #[test]
+    fn invalid_probability_is_rejected() {
+        let config = ChaosConfig {
+            drop_probability: 1.5,
+            ..Default::default()
+        };
+        assert!(Chaos::new(config).is_err());
+
+        let chaos = chaos(0.0);
+        assert!(chaos.set_config(config).is_err());
+        assert_eq!(chaos.config().drop_probability, 0.0);
+    }
+
+    #[tokio::test]
+    async fn faults_are_changed_at_runtime() {
+        let chaos = chaos(0.0);
+        assert!(chaos.inject().await);
+
+        chaos
+            .set_config(ChaosConfig {
+                drop_probability: 1.0,
+                ..Default::default()
+            })
+            .unwrap();
+
+        assert!(!chaos.inject().await);
+    }
+}
@@ -0,0 +1,369 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use async_trait::async_trait;
+use error_stack::{Report, Result, ResultExt};
+
+use super::ClientError;
+use crate::error::ServiceError;
+use crate::{Client, HealthStatus, MaintenanceTask, Node, NodeId, NodeInfo, NodeService};
+
+type Registry = Mutex<HashMap<SocketAddr, Arc<NodeService<InMemoryClient>>>>;
+
+/// Nodes reachable by the in-memory clients, by address
+static REGISTRY: OnceLock<Registry> = OnceLock::new();
+
+fn registry() -> &'static Registry {
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// Client calling the node services of the same process directly, without any networking
+///
+/// A node is reachable once it's [registered](InMemoryClient::register), and stops being
+/// reachable once it's [unregistered](InMemoryClient::unregister), which simulates its failure.
+/// Requests to unreachable nodes fail with [`ClientError::ConnectionFailed`].
+///
+/// > **Note**
+/// >
+/// > The registry is shared by the whole process, so tests running in parallel should use
+/// > distinct addresses.
+#[derive(Debug, Clone)]
+pub struct InMemoryClient {
+    addr: SocketAddr,
+}
+
+impl InMemoryClient {
+    /// Make the node reachable by the in-memory clients
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node service to register
+    pub fn register(node: Arc<NodeService<InMemoryClient>>) {
+        registry().lock().unwrap().insert(node.addr(), node);
+    }
+
+    /// Make the node unreachable, as if it failed
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the node to unregister
+    pub fn unregister(addr: SocketAddr) {
+        registry().lock().unwrap().remove(&addr);
+    }
+
+    fn node(&self) -> Result<Arc<NodeService<InMemoryClient>>, ClientError> {
+        registry()
+            .lock()
+            .unwrap()
+            .get(&self.addr)
+            .cloned()
+            .ok_or_else(|| {
+                Report::new(ClientError::ConnectionFailed(format!(
+                    "Node {} is not reachable",
+                    self.addr
+                )))
+            })
+    }
+}
+
+#[async_trait]
+impl Client for InMemoryClient {
+    async fn init(addr: SocketAddr) -> Self {
+        Self { addr }
+    }
+
+    async fn find_successor(&self, id: NodeId) -> Result<Node, ClientError> {
+        self.node()?
+            .find_successor(id)
+            .await
+            .change_context(ClientError::FindSuccessorFailed)
+    }
+
+    async fn find_successor_traced(&self, id: NodeId) -> Result<(Node, Vec<NodeId>), ClientError> {
+        self.node()?
+            .find_successor_traced(id)
+            .await
+            .change_context(ClientError::FindSuccessorFailed)
+    }
+
+    async fn find_successors(
+        &self,
+        ids: Vec<NodeId>,
+    ) -> Result<Vec<std::result::Result<Node, ClientError>>, ClientError> {
+        let successors = self
+            .node()?
+            .find_successors(ids)
+            .await
+            .change_context(ClientError::FindSuccessorFailed)?;
+
+        Ok(successors
+            .into_iter()
+            .map(|result| {
+                result.map_err(|err| match err {
+                    ServiceError::PeerUnreachable(node) => ClientError::PeerUnreachable(node),
+                    ServiceError::Timeout => ClientError::DeadlineExceeded,
+                    _ => ClientError::FindSuccessorFailed,
+                })
+            })
+            .collect())
+    }
+
+    async fn find_predecessor(&self, id: NodeId) -> Result<Node, ClientError> {
+        self.node()?
+            .find_predecessor(id)
+            .await
+            .change_context(ClientError::FindPredecessorFailed)
+    }
+
+    async fn successor(&self) -> Result<Node, ClientError> {
+        self.node()?
+            .get_successor()
+            .await
+            .change_context(ClientError::GetSuccessorFailed)
+    }
+
+    async fn successor_list(&self) -> Result<Vec<Node>, ClientError> {
+        self.node()?
+            .get_successor_list()
+            .await
+            .change_context(ClientError::GetSuccessorListFailed)
+    }
+
+    async fn predecessor(&self) -> Result<Option<Node>, ClientError> {
+        self.node()?
+            .get_predecessor()
+            .await
+            .change_context(ClientError::GetPredecessorFailed)
+    }
+
+    async fn predecessor_list(&self) -> Result<Vec<Node>, ClientError> {
+        self.node()?
+            .get_predecessor_list()
+            .await
+            .change_context(ClientError::GetPredecessorListFailed)
+    }
+
+    async fn node_info(&self) -> Result<NodeInfo, ClientError> {
+        self.node()?
+            .node_info()
+            .await
+            .change_context(ClientError::NodeInfoFailed)
+    }
+
+    async fn notify(&self, predecessor: Node) -> Result<(), ClientError> {
+        self.node()?.notify(predecessor).await;
+
+        Ok(())
+    }
+
+    async fn leave(&self, node: Node, predecessor: Option<Node>) -> Result<(), ClientError> {
+        self.node()?.handle_leave(node, predecessor);
+
+        Ok(())
+    }
+
+    async fn ping(&self) -> Result<NodeId, ClientError> {
+        Ok(self.node()?.ping())
+    }
+
+    async fn can_reach(&self, target: Node) -> Result<bool, ClientError> {
+        self.node()?
+            .can_reach(target)
+            .await
+            .change_context(ClientError::CanReachFailed)
+    }
+
+    async fn health(&self) -> Result<HealthStatus, ClientError> {
+        self.node()?
+            .health()
+            .await
+            .change_context(ClientError::HealthFailed)
+    }
+
+    async fn trigger(&self, task: MaintenanceTask) -> Result<(), ClientError> {
+        self.node()?
+            .trigger(task)
+            .await
+            .change_context(ClientError::TriggerFailed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 2], port))
+    }
+
+    async fn ring(ports: &[u16]) -> Vec<Arc<NodeService<InMemoryClient>>> {
+        let nodes: Vec<_> = ports
+            .iter()
+            .map(|port| Arc::new(NodeService::new(addr(*port), 3, None)))
+            .collect();
+        nodes
+            .iter()
+            .for_each(|node| InMemoryClient::register(node.clone()));
+
+        for node in &nodes[1..] {
+            node.join(Node::new(nodes[0].addr())).await.unwrap();
+        }
+
+        nodes
+    }
+
+    async fn maintenance_round(nodes: &[Arc<NodeService<InMemoryClient>>]) {
+        for node in nodes {
+            let _ = node.check_successor().await;
+            let _ = node.stabilize().await;
+            let _ = node.check_predecessor().await;
+            node.reconcile_predecessors().await;
+            node.reconcile_successors().await;
+            node.fix_fingers().await;
+        }
+    }
+
+    fn sorted_ids(nodes: &[Arc<NodeService<InMemoryClient>>]) -> Vec<NodeId> {
+        let mut ids: Vec<NodeId> = nodes.iter().map(|node| node.id()).collect();
+        ids.sort();
+        ids
+    }
+
+    #[tokio::test]
+    async fn ring_converges() {
+        let nodes = ring(&[43001, 43002, 43003, 43004, 43005, 43006]).await;
+        for _ in 0..nodes.len() {
+            maintenance_round(&nodes).await;
+        }
+
+        let ids = sorted_ids(&nodes);
+        for node in &nodes {
+            let position = ids.iter().position(|id| *id == node.id()).unwrap();
+            let successor = node.get_successor().await.unwrap();
+            assert_eq!(successor.id(), ids[(position + 1) % ids.len()]);
+
+            let predecessor = node.get_predecessor().await.unwrap().unwrap();
+            assert_eq!(
+                predecessor.id(),
+                ids[(position + ids.len() - 1) % ids.len()]
+            );
+        }
+
+        for node in &nodes {
+            for id in &ids {
+                let owner = node.find_successor(*id).await.unwrap();
+                assert_eq!(owner.id(), *id);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn traced_lookup_returns_path() {
+        let nodes = ring(&[43021, 43022, 43023, 43024, 43025, 43026]).await;
+        for _ in 0..nodes.len() {
+            maintenance_round(&nodes).await;
+        }
+
+        for node in &nodes {
+            for target in &nodes {
+                let (owner, path) = node.find_successor_traced(target.id()).await.unwrap();
+                assert_eq!(owner.id(), target.id());
+                assert_eq!(path[0], node.id());
+                assert!(path.len() <= nodes.len());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn batched_lookup_keeps_order() {
+        let nodes = ring(&[43051, 43052, 43053, 43054, 43055]).await;
+        for _ in 0..nodes.len() {
+            maintenance_round(&nodes).await;
+        }
+
+        let mut ids = sorted_ids(&nodes);
+        ids.reverse();
+        let client = InMemoryClient::init(nodes[0].addr()).await;
+        let owners = client.find_successors(ids.clone()).await.unwrap();
+
+        let owners: Vec<NodeId> = owners.into_iter().map(|node| node.unwrap().id()).collect();
+        assert_eq!(owners, ids);
+    }
+
+    #[tokio::test]
+    async fn find_predecessor_returns_preceding_node() {
+        let nodes = ring(&[43031, 43032, 43033, 43034, 43035, 43036]).await;
+        for _ in 0..nodes.len() {
+            maintenance_round(&nodes).await;
+        }
+
+        let ids = sorted_ids(&nodes);
+        for node in &nodes {
+            for (position, id) in ids.iter().enumerate() {
+                let expected = ids[(position + ids.len() - 1) % ids.len()];
+                let predecessor = node.find_predecessor(*id).await.unwrap();
+                assert_eq!(predecessor.id(), expected);
+
+                // An id right after a node is preceded by that node
+                let predecessor = node
+                    .find_predecessor(NodeId(id.0.wrapping_add(1)))
+                    .await
+                    .unwrap();
+                assert_eq!(predecessor.id(), *id);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn ring_recovers_from_failed_node() {
+        let mut nodes = ring(&[43011, 43012, 43013, 43014, 43015]).await;
+        for _ in 0..nodes.len() {
+            maintenance_round(&nodes).await;
+        }
+
+        let failed = nodes.remove(2);
+        InMemoryClient::unregister(failed.addr());
+        for _ in 0..nodes.len() {
+            maintenance_round(&nodes).await;
+        }
+
+        let ids = sorted_ids(&nodes);
+        for node in &nodes {
+            let position = ids.iter().position(|id| *id == node.id()).unwrap();
+            let successor = node.get_successor().await.unwrap();
+            assert_eq!(successor.id(), ids[(position + 1) % ids.len()]);
+        }
+    }
+
+    #[tokio::test]
+    async fn health_reflects_ring_state() {
+        let nodes = ring(&[43041, 43042, 43043, 43044]).await;
+        let client = InMemoryClient::init(nodes[1].addr()).await;
+
+        let health = client.health().await.unwrap();
+        assert!(!health.joined);
+        assert!(!health.has_predecessor);
+        assert!(health.successor_reachable);
+
+        for _ in 0..nodes.len() {
+            maintenance_round(&nodes).await;
+        }
+
+        for node in &nodes {
+            let client = InMemoryClient::init(node.addr()).await;
+            assert_eq!(
+                client.health().await.unwrap(),
+                HealthStatus {
+                    joined: true,
+                    has_predecessor: true,
+                    successor_reachable: true,
+                }
+            );
+        }
+
+        let successor = nodes[1].get_successor().await.unwrap();
+        InMemoryClient::unregister(successor.addr());
+        assert!(!client.health().await.unwrap().successor_reachable);
+    }
+}
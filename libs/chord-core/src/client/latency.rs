@@ -0,0 +1,110 @@
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Round-trip times of the RPC calls made by a client
+///
+/// The times are kept as an exponential moving average, so the recent calls weigh more
+/// than the old ones.
+#[derive(Debug, Default)]
+pub struct Latency {
+    stats: Mutex<Option<LatencyStats>>,
+}
+
+/// Snapshot of the round-trip times of a client
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencyStats {
+    /// Moving average of the round-trip times
+    pub average: Duration,
+    /// Number of calls the average is based on
+    pub samples: u64,
+}
+
+impl Latency {
+    /// Weight of the newest sample in the moving average
+    pub const SMOOTHING: f64 = 0.2;
+
+    /// Record the round-trip time of a call
+    ///
+    /// # Arguments
+    ///
+    /// * `rtt` - The round-trip time
+    pub fn record(&self, rtt: Duration) {
+        let mut stats = self.stats.lock().unwrap();
+        *stats = Some(match *stats {
+            Some(LatencyStats { average, samples }) => LatencyStats {
+                average: average.mul_f64(1.0 - Self::SMOOTHING) + rtt.mul_f64(Self::SMOOTHING),
+                samples: samples + 1,
+            },
+            None => LatencyStats {
+                average: rtt,
+                samples: 1,
+            },
+        });
+    }
+
+    /// Await the given call and record its round-trip time if it succeeded
+    ///
+    /// Failed calls are not recorded, as a refused connection returns much faster than
+    /// any real response.
+    ///
+    /// # Arguments
+    ///
+    /// * `call` - The RPC call
+    pub async fn measure<T, E>(&self, call: impl Future<Output = Result<T, E>>) -> Result<T, E> {
+        let start = Instant::now();
+        let result = call.await;
+        if result.is_ok() {
+            self.record(start.elapsed());
+        }
+
+        result
+    }
+
+    /// Get the recorded round-trip times, `None` if no call was recorded yet
+    pub fn stats(&self) -> Option<LatencyStats> {
+        *self.stats.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moving_average() {
+        let latency = Latency::default();
+        assert_eq!(latency.stats(), None);
+
+        latency.record(Duration::from_millis(10));
+        assert_eq!(
+            latency.stats(),
+            Some(LatencyStats {
+                average: Duration::from_millis(10),
+                samples: 1
+            })
+        );
+
+        latency.record(Duration::from_millis(60));
+        assert_eq!(
+            latency.stats(),
+            Some(LatencyStats {
+                average: Duration::from_millis(20),
+                samples: 2
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn measure_only_successful_calls() {
+        let latency = Latency::default();
+
+        let result = latency.measure(async { Err::<(), _>("failed") }).await;
+        assert!(result.is_err());
+        assert_eq!(latency.stats(), None);
+
+        let result = latency.measure(async { Ok::<_, ()>(42) }).await;
+        assert_eq!(result, Ok(42));
+        assert_eq!(latency.stats().unwrap().samples, 1);
+    }
+}
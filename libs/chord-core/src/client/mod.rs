@@ -1,10 +1,16 @@
+#[cfg(any(test, feature = "test-util"))]
+mod in_memory;
+mod latency;
 mod pool;
 
-use crate::{Node, NodeId};
+use crate::{HealthStatus, MaintenanceTask, Node, NodeId, NodeInfo};
 use async_trait::async_trait;
 use error_stack::Result;
+#[cfg(any(test, feature = "test-util"))]
+pub use in_memory::InMemoryClient;
+pub use latency::{Latency, LatencyStats};
 use mockall::automock;
-pub use pool::ClientsPool;
+pub use pool::{ClientFactory, ClientsPool};
 use std::net::SocketAddr;
 use thiserror::Error;
 
@@ -25,6 +31,32 @@ pub trait Client {
     /// * `id` - The id to find the successor for
     async fn find_successor(&self, id: NodeId) -> Result<Node, ClientError>;
 
+    /// Find a successor of a given id, returning the ids of the nodes the lookup went through.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id to find the successor for
+    async fn find_successor_traced(&self, id: NodeId) -> Result<(Node, Vec<NodeId>), ClientError>;
+
+    /// Find the successors of many ids in a single call.
+    ///
+    /// The results are in the order of the ids, with the error of the ids whose lookup failed.
+    ///
+    /// # Arguments
+    ///
+    /// * `ids` - The ids to find the successors for
+    async fn find_successors(
+        &self,
+        ids: Vec<NodeId>,
+    ) -> Result<Vec<std::result::Result<Node, ClientError>>, ClientError>;
+
+    /// Find the predecessor of a given id, the node whose successor is responsible for it.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id to find the predecessor for
+    async fn find_predecessor(&self, id: NodeId) -> Result<Node, ClientError>;
+
     /// Get the successor of the node
     async fn successor(&self) -> Result<Node, ClientError>;
 
@@ -34,6 +66,12 @@ pub trait Client {
     /// Get the predecessor of the node
     async fn predecessor(&self) -> Result<Option<Node>, ClientError>;
 
+    /// Get predecessor list of the node, starting at its predecessor
+    async fn predecessor_list(&self) -> Result<Vec<Node>, ClientError>;
+
+    /// Get the id, the predecessor and the successor list of the node in a single call
+    async fn node_info(&self) -> Result<NodeInfo, ClientError>;
+
     /// Notify the node about a new predecessor
     ///
     /// # Arguments
@@ -41,11 +79,54 @@ pub trait Client {
     /// * `predecessor` - The new predecessor
     async fn notify(&self, predecessor: Node) -> Result<(), ClientError>;
 
-    /// Ping the node
-    async fn ping(&self) -> Result<(), ClientError>;
+    /// Tell the node that its predecessor or one of its successors is leaving the ring
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node which is leaving
+    /// * `predecessor` - The predecessor of the leaving node, if it has one
+    async fn leave(&self, node: Node, predecessor: Option<Node>) -> Result<(), ClientError>;
+
+    /// Ping the node, returning its id
+    ///
+    /// The id lets the caller check that the address still belongs to the node it expected,
+    /// and not to another node which took it over, e.g. after a restart with another id.
+    async fn ping(&self) -> Result<NodeId, ClientError>;
+
+    /// Get the health of the node
+    async fn health(&self) -> Result<HealthStatus, ClientError>;
+
+    /// Ask the node whether it can reach the target node
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The node to check
+    async fn can_reach(&self, target: Node) -> Result<bool, ClientError>;
+
+    /// Ask the node to run a maintenance task right away, returning once it completed
+    ///
+    /// The node rejects the request unless it serves the admin requests.
+    ///
+    /// # Arguments
+    ///
+    /// * `task` - The task to run
+    async fn trigger(&self, task: MaintenanceTask) -> Result<(), ClientError>;
+
+    /// Get the round-trip times of the calls made by the client,
+    /// `None` if the client doesn't track them or made no calls yet
+    fn latency(&self) -> Option<LatencyStats> {
+        None
+    }
+
+    /// Release the resources held by the client, e.g. its connection or its worker thread,
+    /// before it's dropped
+    ///
+    /// The client is not meant to be used once closed. The clones of the client share its
+    /// resources, so they are closed too.
+    async fn close(&self) {}
 }
 
-#[derive(Debug, Clone, Error)]
+#[derive(Debug, Clone, PartialEq, Error)]
 pub enum ClientError {
     #[error("{0}")]
     ConnectionFailed(String),
@@ -55,19 +136,38 @@ pub enum ClientError {
     NotInitialized,
     #[error("Unexpected error")]
     Unexpected,
+    #[error("Deadline exceeded")]
+    DeadlineExceeded,
+    /// The called node responded, but a node it forwarded the request to is unreachable
+    #[error("Node {0} is unreachable")]
+    PeerUnreachable(NodeId),
 
     #[error("Ping failed")]
     PingFailed,
     #[error("Find successor failed")]
     FindSuccessorFailed,
+    #[error("Find predecessor failed")]
+    FindPredecessorFailed,
     #[error("Get successor failed")]
     GetSuccessorFailed,
     #[error("Get successor list failed")]
     GetSuccessorListFailed,
     #[error("Get predecessor failed")]
     GetPredecessorFailed,
+    #[error("Get predecessor list failed")]
+    GetPredecessorListFailed,
+    #[error("Get node info failed")]
+    NodeInfoFailed,
     #[error("Notify failed")]
     NotifyFailed,
+    #[error("Leave failed")]
+    LeaveFailed,
+    #[error("Can reach failed")]
+    CanReachFailed,
+    #[error("Health check failed")]
+    HealthFailed,
+    #[error("Trigger failed")]
+    TriggerFailed,
 }
 
 #[cfg(test)]
@@ -1,24 +1,67 @@
 use std::{
     collections::HashMap,
+    fmt,
+    future::Future,
+    net::SocketAddr,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
+use futures::future::{join_all, BoxFuture};
+
+use crate::client::LatencyStats;
 use crate::{Client, Node, NodeId};
 
-#[derive(Debug)]
+/// Function creating the client of the node at the given address
+pub type ClientFactory<C> = Arc<dyn Fn(SocketAddr) -> BoxFuture<'static, C> + Send + Sync>;
+
 pub struct ClientsPool<C: Client> {
     clients: Arc<Mutex<HashMap<NodeId, Arc<C>>>>,
+    factory: Option<ClientFactory<C>>,
 }
 
 impl<C: Client> Default for ClientsPool<C> {
     fn default() -> Self {
         Self {
             clients: Arc::new(Mutex::new(HashMap::new())),
+            factory: None,
         }
     }
 }
 
+impl<C: Client + fmt::Debug> fmt::Debug for ClientsPool<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientsPool")
+            .field("clients", &self.clients)
+            .field("factory", &self.factory.as_ref().map(|_| "custom"))
+            .finish()
+    }
+}
+
 impl<C: Client> ClientsPool<C> {
+    /// How long a client is given to connect when it's [warmed up](Self::warm_up)
+    pub const WARM_UP_TIMEOUT: Duration = Duration::from_secs(2);
+
+    /// Create a pool which creates its clients with the given factory, instead of
+    /// [`Client::init`]
+    ///
+    /// It lets the clients be configured with more than the address of their node, e.g. with
+    /// the TLS settings or the cluster secret of a custom transport.
+    ///
+    /// # Arguments
+    ///
+    /// * `factory` - The function creating the client of the node at the given address
+    pub fn with_factory<F, Fut>(factory: F) -> Self
+    where
+        F: Fn(SocketAddr) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = C> + Send + 'static,
+    {
+        Self {
+            clients: Arc::new(Mutex::new(HashMap::new())),
+            factory: Some(Arc::new(move |addr| Box::pin(factory(addr)))),
+        }
+    }
+
     /// Get the client for the given node.
     /// If the client is not yet initialized, it will be initialized.
     ///
@@ -35,7 +78,10 @@ impl<C: Client> ClientsPool<C> {
             Some(c) => c,
             None => {
                 log::debug!("Initializing client for node: {}", node.addr());
-                let client = C::init(node.addr()).await;
+                let client = match &self.factory {
+                    Some(factory) => factory(node.addr()).await,
+                    None => C::init(node.addr()).await,
+                };
                 let client = Arc::new(client);
                 {
                     let mut state = self.clients.lock().unwrap();
@@ -45,10 +91,80 @@ impl<C: Client> ClientsPool<C> {
             }
         }
     }
+
+    /// Initialize the clients of the given nodes in the background
+    ///
+    /// The connections are set up before the first call to the nodes, so the call doesn't pay
+    /// for them. The nodes which already have a client are skipped. A client which doesn't
+    /// connect within [`WARM_UP_TIMEOUT`](Self::WARM_UP_TIMEOUT) is dropped, it's initialized
+    /// again by the first call to its node.
+    ///
+    /// > **Note**
+    /// >
+    /// > Must be called within a Tokio runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `nodes` - The nodes to connect to
+    pub fn warm_up(&self, nodes: impl IntoIterator<Item = Node>)
+    where
+        C: Send + Sync + 'static,
+    {
+        let nodes: HashMap<NodeId, Node> = {
+            let state = self.clients.lock().unwrap();
+            nodes
+                .into_iter()
+                .filter(|node| !state.contains_key(&node.id()))
+                .map(|node| (node.id(), node))
+                .collect()
+        };
+        if nodes.is_empty() {
+            return;
+        }
+
+        let pool = Self {
+            clients: self.clients.clone(),
+            factory: self.factory.clone(),
+        };
+        tokio::spawn(async move {
+            let dials = nodes.values().map(|node| {
+                let pool = &pool;
+                async move {
+                    let dial = tokio::time::timeout(Self::WARM_UP_TIMEOUT, pool.get_or_init(node));
+                    if dial.await.is_err() {
+                        log::debug!("Warming up the client of {} timed out", node);
+                    }
+                }
+            });
+            join_all(dials).await;
+        });
+    }
+
+    /// Drop the client of the given node, so the next call to the node connects again
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the node
+    pub fn evict(&self, id: NodeId) {
+        self.clients.lock().unwrap().remove(&id);
+    }
+
+    /// Get the round-trip times of the calls made to each node
+    ///
+    /// Only the nodes with at least one recorded call are included.
+    pub fn latencies(&self) -> HashMap<NodeId, LatencyStats> {
+        let state = self.clients.lock().unwrap();
+        state
+            .iter()
+            .filter_map(|(id, client)| client.latency().map(|latency| (*id, latency)))
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use super::*;
     use crate::service::tests::MTX;
     use crate::Node;
@@ -83,4 +199,89 @@ mod tests {
             assert!(clients.contains_key(&node.id()));
         }
     }
+
+    #[tokio::test]
+    async fn test_latencies() {
+        let _m = get_lock(&MTX);
+        let ctx = MockClient::init_context();
+
+        ctx.expect().returning(|addr| {
+            let mut client = MockClient::new();
+            if addr.port() == 42081 {
+                client.expect_latency().returning(|| {
+                    Some(LatencyStats {
+                        average: Duration::from_millis(5),
+                        samples: 3,
+                    })
+                });
+            } else {
+                client.expect_latency().returning(|| None);
+            }
+            client
+        });
+
+        let measured = Node::new("[::1]:42081".parse().unwrap());
+        let unmeasured = Node::new("[::1]:42082".parse().unwrap());
+
+        let pool: ClientsPool<MockClient> = ClientsPool::default();
+        pool.get_or_init(&measured).await;
+        pool.get_or_init(&unmeasured).await;
+
+        let latencies = pool.latencies();
+        assert_eq!(latencies.len(), 1);
+        assert_eq!(latencies[&measured.id()].samples, 3);
+        assert_eq!(latencies[&measured.id()].average, Duration::from_millis(5));
+    }
+
+    #[tokio::test]
+    async fn test_getting_client_from_factory() {
+        let created = Arc::new(Mutex::new(vec![]));
+        let pool: ClientsPool<MockClient> = ClientsPool::with_factory({
+            let created = created.clone();
+            move |addr| {
+                created.lock().unwrap().push(addr);
+                async { MockClient::new() }
+            }
+        });
+
+        let node = Node::new("[::1]:42083".parse().unwrap());
+        pool.get_or_init(&node).await;
+        pool.get_or_init(&node).await;
+
+        assert_eq!(*created.lock().unwrap(), vec![node.addr()]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_warming_up_clients() {
+        let created = Arc::new(Mutex::new(vec![]));
+        let pool: ClientsPool<MockClient> = ClientsPool::with_factory({
+            let created = created.clone();
+            move |addr: SocketAddr| {
+                created.lock().unwrap().push(addr);
+                async move {
+                    // The node on port 42086 never accepts the connection
+                    if addr.port() == 42086 {
+                        tokio::time::sleep(Duration::from_secs(3600)).await;
+                    }
+                    MockClient::new()
+                }
+            }
+        });
+
+        let known = Node::new("[::1]:42084".parse().unwrap());
+        let new = Node::new("[::1]:42085".parse().unwrap());
+        let dead = Node::new("[::1]:42086".parse().unwrap());
+        pool.get_or_init(&known).await;
+
+        pool.warm_up(vec![known.clone(), new.clone(), new.clone(), dead.clone()]);
+        tokio::time::sleep(ClientsPool::<MockClient>::WARM_UP_TIMEOUT * 2).await;
+
+        let mut created = created.lock().unwrap().clone();
+        created.sort();
+        assert_eq!(created, vec![known.addr(), new.addr(), dead.addr()]);
+
+        let clients = pool.clients.lock().unwrap();
+        assert!(clients.contains_key(&new.id()));
+        assert!(!clients.contains_key(&dead.id()));
+    }
 }
@@ -0,0 +1,379 @@
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::net::SocketAddr;
+
+use error_stack::Result;
+
+use crate::client::ClientError;
+use crate::{Client, Node, NodeId, RingParams};
+
+/// A node visited while crawling the ring, with its link to the successor
+#[derive(Debug, Clone, PartialEq)]
+pub struct RingLink {
+    /// Address of the node
+    pub addr: SocketAddr,
+    /// Predecessor of the node, as known by the node
+    pub predecessor: Option<Node>,
+    /// Successor of the node, as known by the node
+    pub successor: Node,
+    /// Whether the predecessor of the successor is the node, `None` if the successor
+    /// was not visited
+    pub consistent: Option<bool>,
+    /// Parameters of the node, `None` if the node doesn't report them
+    pub params: Option<RingParams>,
+}
+
+/// Why the crawl of the ring stopped
+#[derive(Debug, Clone, PartialEq)]
+pub enum RingEnd {
+    /// The successors led back to the first node
+    Closed,
+    /// The successors led back to a node visited before, other than the first one
+    Loop(SocketAddr),
+    /// The maximum number of nodes was visited before getting back to the first node
+    MaxHops,
+    /// A node failed to respond
+    Unreachable(SocketAddr, ClientError),
+}
+
+/// Result of [`crawl_ring`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RingDump {
+    /// The visited nodes, in ring order starting at the first node
+    pub links: Vec<RingLink>,
+    /// Why the crawl stopped
+    pub end: RingEnd,
+}
+
+impl RingDump {
+    /// Returns true if the ring closed, every successor link is consistent and all the nodes
+    /// share the same parameters
+    pub fn is_healthy(&self) -> bool {
+        self.end == RingEnd::Closed
+            && self.links.iter().all(|link| link.consistent == Some(true))
+            && self.mismatched_params().is_empty()
+    }
+
+    /// Get the parameters shared by most of the visited nodes
+    ///
+    /// On a tie, the parameters of the node visited first win. Returns `None` if no node
+    /// reported its parameters.
+    pub fn common_params(&self) -> Option<RingParams> {
+        let mut counts: HashMap<RingParams, usize> = HashMap::new();
+        for params in self.links.iter().filter_map(|link| link.params) {
+            *counts.entry(params).or_default() += 1;
+        }
+
+        let mut common: Option<(RingParams, usize)> = None;
+        for params in self.links.iter().filter_map(|link| link.params) {
+            let count = counts[&params];
+            if common.is_none_or(|(_, max)| count > max) {
+                common = Some((params, count));
+            }
+        }
+
+        common.map(|(params, _)| params)
+    }
+
+    /// Get the visited nodes whose parameters differ from the [common ones](Self::common_params)
+    ///
+    /// The nodes which don't report their parameters are not included.
+    pub fn mismatched_params(&self) -> Vec<&RingLink> {
+        let Some(common) = self.common_params() else {
+            return vec![];
+        };
+
+        self.links
+            .iter()
+            .filter(|link| link.params.is_some_and(|params| params != common))
+            .collect()
+    }
+}
+
+/// Enumerate the nodes of the ring by following the successors, starting at the given node
+///
+/// Every visited node is asked for its routing state, see [`Client::node_info`], so a node costs
+/// a single call and its successor and predecessor are read at once. A link to a successor is
+/// consistent if the successor knows the node as its predecessor. The crawl stops when it gets
+/// back to the first node, or as soon as it can't go around the ring, see [`RingEnd`].
+///
+/// # Arguments
+///
+/// * `start` - The address of the first node
+/// * `max_hops` - The maximum number of nodes to visit
+pub async fn crawl_ring<C: Client>(start: SocketAddr, max_hops: usize) -> RingDump {
+    crawl_ring_with(start, max_hops, C::init).await
+}
+
+/// Crawl the ring like [`crawl_ring`], creating the clients with the given function
+///
+/// It lets a transport configure the clients with more than the address of their node, e.g.
+/// with the TLS settings or the cluster secret of the ring.
+///
+/// # Arguments
+///
+/// * `start` - The address of the first node
+/// * `max_hops` - The maximum number of nodes to visit
+/// * `connect` - The function creating the client of the node at the given address
+pub async fn crawl_ring_with<C, F, Fut>(start: SocketAddr, max_hops: usize, connect: F) -> RingDump
+where
+    C: Client,
+    F: Fn(SocketAddr) -> Fut,
+    Fut: Future<Output = C>,
+{
+    let mut links: Vec<RingLink> = Vec::new();
+    let mut visited = HashSet::new();
+    let mut addr = start;
+
+    let end = loop {
+        if links.len() >= max_hops {
+            break RingEnd::MaxHops;
+        }
+
+        let client = connect(addr).await;
+        let info = match client.node_info().await {
+            Ok(info) => info,
+            Err(err) => break RingEnd::Unreachable(addr, err.current_context().clone()),
+        };
+        let Some(successor) = info.successor_list.into_iter().next() else {
+            break RingEnd::Unreachable(addr, ClientError::NodeInfoFailed);
+        };
+        let predecessor = info.predecessor;
+        let params = info.params;
+
+        if let Some(previous) = links.last_mut() {
+            previous.consistent = Some(predecessor.as_ref().map(Node::addr) == Some(previous.addr));
+        }
+        visited.insert(addr);
+        links.push(RingLink {
+            addr,
+            predecessor,
+            successor: successor.clone(),
+            consistent: None,
+            params,
+        });
+
+        if successor.addr == start {
+            let first = links[0].predecessor.as_ref().map(Node::addr);
+            let last = links.last_mut().unwrap();
+            last.consistent = Some(first == Some(last.addr));
+            break RingEnd::Closed;
+        }
+        if visited.contains(&successor.addr) {
+            break RingEnd::Loop(successor.addr);
+        }
+        addr = successor.addr;
+    };
+
+    RingDump { links, end }
+}
+
+/// Find the node responsible for the given id, asking the given node
+///
+/// The lookup is routed through the ring like any other, see [`Client::find_successor`], so
+/// any node of the ring can be asked. The key is not read, only its place on the ring is looked
+/// up.
+///
+/// # Arguments
+///
+/// * `seed` - The address of a node of the ring
+/// * `id` - The id to find the owner of, see [`IntoNodeId`](crate::IntoNodeId) to map a key
+///   onto the ring
+pub async fn find_owner<C: Client>(seed: SocketAddr, id: NodeId) -> Result<Node, ClientError> {
+    find_owner_with(seed, id, C::init).await
+}
+
+/// Find the owner of the id like [`find_owner`], creating the client with the given function
+///
+/// # Arguments
+///
+/// * `seed` - The address of a node of the ring
+/// * `id` - The id to find the owner of
+/// * `connect` - The function creating the client of the node at the given address
+pub async fn find_owner_with<C, F, Fut>(
+    seed: SocketAddr,
+    id: NodeId,
+    connect: F,
+) -> Result<Node, ClientError>
+where
+    C: Client,
+    F: FnOnce(SocketAddr) -> Fut,
+    Fut: Future<Output = C>,
+{
+    let client = connect(seed).await;
+    client.find_successor(id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::InMemoryClient;
+    use crate::simulator::Simulator;
+
+    fn base_addr(ip: u8) -> SocketAddr {
+        SocketAddr::from(([127, 0, 2, ip], 44000))
+    }
+
+    #[tokio::test]
+    async fn crawl_stable_ring() {
+        let mut simulator = Simulator::new(base_addr(1), 3);
+        for id in [10, 20, 30, 40] {
+            simulator.add_node(id).await.unwrap();
+        }
+        simulator.run_until_stable(10).await.unwrap();
+        simulator.run(1).await;
+
+        let start = simulator.node(30).unwrap().addr();
+        let dump = crawl_ring::<InMemoryClient>(start, 10).await;
+
+        assert_eq!(dump.end, RingEnd::Closed);
+        assert!(dump.is_healthy());
+        let successors: Vec<u64> = dump
+            .links
+            .iter()
+            .map(|link| link.successor.id().into())
+            .collect();
+        assert_eq!(successors, vec![40, 10, 20, 30]);
+    }
+
+    #[tokio::test]
+    async fn crawl_stops_at_max_hops() {
+        let mut simulator = Simulator::new(base_addr(2), 3);
+        for id in [10, 20, 30, 40] {
+            simulator.add_node(id).await.unwrap();
+        }
+        simulator.run_until_stable(10).await.unwrap();
+
+        let start = simulator.node(10).unwrap().addr();
+        let dump = crawl_ring::<InMemoryClient>(start, 2).await;
+
+        assert_eq!(dump.end, RingEnd::MaxHops);
+        assert_eq!(dump.links.len(), 2);
+        assert!(!dump.is_healthy());
+    }
+
+    #[tokio::test]
+    async fn crawl_reports_unreachable_successor() {
+        let mut simulator = Simulator::new(base_addr(3), 3);
+        for id in [10, 20, 30] {
+            simulator.add_node(id).await.unwrap();
+        }
+        simulator.run_until_stable(10).await.unwrap();
+
+        let failed = simulator.node(20).unwrap().addr();
+        simulator.fail_node(20);
+
+        let start = simulator.node(10).unwrap().addr();
+        let dump = crawl_ring::<InMemoryClient>(start, 10).await;
+
+        assert!(matches!(
+            dump.end,
+            RingEnd::Unreachable(addr, ClientError::ConnectionFailed(_)) if addr == failed
+        ));
+        assert_eq!(dump.links.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn crawl_detects_inconsistent_links() {
+        let mut simulator = Simulator::new(base_addr(4), 3);
+        simulator.add_node(10).await.unwrap();
+        simulator.add_node(20).await.unwrap();
+
+        // 20 joined through 10, but neither node stabilized yet
+        let start = simulator.node(20).unwrap().addr();
+        let dump = crawl_ring::<InMemoryClient>(start, 10).await;
+
+        assert_eq!(dump.end, RingEnd::Loop(simulator.node(10).unwrap().addr()));
+        assert_eq!(dump.links[0].consistent, Some(false));
+        assert!(!dump.is_healthy());
+    }
+
+    #[tokio::test]
+    async fn crawl_reports_the_params_of_the_nodes() {
+        let mut simulator = Simulator::new(base_addr(5), 3);
+        for id in [10, 20, 30] {
+            simulator.add_node(id).await.unwrap();
+        }
+        simulator.run_until_stable(10).await.unwrap();
+        simulator.run(1).await;
+
+        let start = simulator.node(10).unwrap().addr();
+        let dump = crawl_ring::<InMemoryClient>(start, 10).await;
+
+        let params = simulator.node(10).unwrap().params();
+        assert_eq!(params.successor_list_size, 3);
+        assert!(dump.links.iter().all(|link| link.params == Some(params)));
+        assert_eq!(dump.common_params(), Some(params));
+        assert!(dump.mismatched_params().is_empty());
+        assert!(dump.is_healthy());
+    }
+
+    #[tokio::test]
+    async fn find_owner_from_any_node() {
+        let mut simulator = Simulator::new(base_addr(6), 3);
+        for id in [10, 20, 30, 40] {
+            simulator.add_node(id).await.unwrap();
+        }
+        simulator.run_until_stable(10).await.unwrap();
+
+        for seed in [10, 20, 30, 40] {
+            let seed = simulator.node(seed).unwrap().addr();
+            let owner = find_owner::<InMemoryClient>(seed, NodeId(25))
+                .await
+                .unwrap();
+            assert_eq!(owner.id(), NodeId(30));
+            let owner = find_owner::<InMemoryClient>(seed, NodeId(45))
+                .await
+                .unwrap();
+            assert_eq!(owner.id(), NodeId(10));
+        }
+
+        let failed = simulator.node(20).unwrap().addr();
+        simulator.fail_node(20);
+        let err = find_owner::<InMemoryClient>(failed, NodeId(25))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.current_context(),
+            ClientError::ConnectionFailed(_)
+        ));
+    }
+
+    #[test]
+    fn nodes_with_other_params_than_most_nodes_are_mismatched() {
+        let params = RingParams {
+            replication_factor: 3,
+            successor_list_size: 3,
+            key_bits: 64,
+        };
+        let other = RingParams {
+            replication_factor: 5,
+            ..params
+        };
+        let link = |port: u16, params: Option<RingParams>| RingLink {
+            addr: SocketAddr::from(([127, 0, 0, 1], port)),
+            predecessor: None,
+            successor: Node::new(SocketAddr::from(([127, 0, 0, 1], port + 1))),
+            consistent: Some(true),
+            params,
+        };
+        let dump = RingDump {
+            links: vec![
+                link(1, Some(other)),
+                link(2, Some(params)),
+                link(3, None),
+                link(4, Some(params)),
+            ],
+            end: RingEnd::Closed,
+        };
+
+        assert_eq!(dump.common_params(), Some(params));
+        let mismatched: Vec<SocketAddr> = dump
+            .mismatched_params()
+            .into_iter()
+            .map(|link| link.addr)
+            .collect();
+        assert_eq!(mismatched, vec![SocketAddr::from(([127, 0, 0, 1], 1))]);
+        assert!(!dump.is_healthy());
+    }
+}
@@ -0,0 +1,85 @@
+//! Deadline of the request being handled, carried along the RPC hops of a lookup
+//!
+//! A node handling a request runs it in a [`scope`] with the deadline received with the request,
+//! and the clients send the deadline of the current scope along with the requests they make on
+//! its behalf. On the wire, the deadline is the number of milliseconds since the Unix epoch.
+
+use std::future::Future;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+tokio::task_local! {
+    static DEADLINE: SystemTime;
+}
+
+/// Run the future with the given deadline, or with no deadline if it's `None`
+///
+/// # Arguments
+///
+/// * `deadline` - The time after which the request is not worth handling anymore
+/// * `f` - The handling of the request
+pub async fn scope<F: Future>(deadline: Option<SystemTime>, f: F) -> F::Output {
+    match deadline {
+        Some(deadline) => DEADLINE.scope(deadline, f).await,
+        None => f.await,
+    }
+}
+
+/// Get the deadline of the request being handled, if any
+pub fn current() -> Option<SystemTime> {
+    DEADLINE.try_with(|deadline| *deadline).ok()
+}
+
+/// Check if the deadline of the request being handled has passed
+pub fn expired() -> bool {
+    current().is_some_and(|deadline| deadline <= SystemTime::now())
+}
+
+/// Convert the deadline to the milliseconds since the Unix epoch sent on the wire
+///
+/// # Arguments
+///
+/// * `deadline` - The deadline to convert
+pub fn to_millis(deadline: SystemTime) -> u64 {
+    deadline
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Convert the milliseconds since the Unix epoch received on the wire to a deadline
+///
+/// # Arguments
+///
+/// * `millis` - The milliseconds since the Unix epoch
+pub fn from_millis(millis: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn deadline_is_set_within_the_scope() {
+        assert_eq!(current(), None);
+        assert!(!expired());
+
+        let future = SystemTime::now() + Duration::from_secs(60);
+        scope(Some(future), async {
+            assert_eq!(current(), Some(future));
+            assert!(!expired());
+        })
+        .await;
+
+        let past = SystemTime::now() - Duration::from_secs(1);
+        scope(Some(past), async { assert!(expired()) }).await;
+
+        scope(None, async { assert_eq!(current(), None) }).await;
+    }
+
+    #[test]
+    fn deadline_round_trips_through_millis() {
+        let deadline = from_millis(1_700_000_000_123);
+        assert_eq!(to_millis(deadline), 1_700_000_000_123);
+    }
+}
@@ -0,0 +1,199 @@
+use sha2::{Digest, Sha256};
+
+use crate::NodeId;
+
+/// Hash function mapping node addresses and data keys onto the ring
+///
+/// All the nodes in a ring have to use the same hasher, otherwise they won't agree on
+/// which node is responsible for a key.
+pub trait Hasher {
+    /// Hash the given bytes into an id on the ring
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The bytes to hash, e.g. the address of a node or a data key
+    fn hash(&self, bytes: &[u8]) -> NodeId;
+}
+
+/// Default hasher, using the first 8 bytes of the SHA-256 digest
+///
+/// This hasher is used by the `From<SocketAddr>` and `From<String>` conversions of [`NodeId`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Sha256Hasher;
+
+impl Hasher for Sha256Hasher {
+    fn hash(&self, bytes: &[u8]) -> NodeId {
+        let digest = Sha256::digest(bytes);
+        let mut id = [0; 8];
+        id.copy_from_slice(&digest[..8]);
+
+        NodeId(u64::from_be_bytes(id))
+    }
+}
+
+/// Hasher of the earlier releases, using seahash
+///
+/// The ids it derives differ from the ones of the [`Sha256Hasher`], so the nodes of a ring
+/// created by an earlier release keep using it until all of them are upgraded, see
+/// [`HashFunction`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SeaHasher;
+
+impl Hasher for SeaHasher {
+    fn hash(&self, bytes: &[u8]) -> NodeId {
+        NodeId(seahash::hash(bytes))
+    }
+}
+
+/// Hash function deriving the ids of the nodes, selected in the configuration of a node
+///
+/// The ids used to be derived with seahash, they're derived with SHA-256 by default since.
+/// A ring created by an earlier release is upgraded one node at a time by running the new
+/// nodes with [`SeaHash`](HashFunction::SeaHash), so they take the same positions on the ring.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum HashFunction {
+    /// See [`Sha256Hasher`]
+    #[default]
+    Sha256,
+    /// See [`SeaHasher`]
+    SeaHash,
+}
+
+impl Hasher for HashFunction {
+    fn hash(&self, bytes: &[u8]) -> NodeId {
+        match self {
+            HashFunction::Sha256 => Sha256Hasher.hash(bytes),
+            HashFunction::SeaHash => SeaHasher.hash(bytes),
+        }
+    }
+}
+
+/// Map a data key onto the ring
+///
+/// Every key is hashed with the [`Sha256Hasher`], so all the nodes and clients of a ring agree
+/// on the node responsible for it. The [`IntoNodeId`] conversions of the keys go through it.
+///
+/// > **Note**
+/// >
+/// > The keys used to be hashed with seahash, the applications which need to find their keys
+/// > where an earlier release put them hash them with the [`SeaHasher`] instead.
+///
+/// # Arguments
+///
+/// * `key` - The bytes of the key
+pub fn key_to_id(key: &[u8]) -> NodeId {
+    Sha256Hasher.hash(key)
+}
+
+/// Application key which can be mapped onto the ring
+///
+/// The byte and string keys are hashed with [`key_to_id`], a [`NodeId`] is already a position
+/// on the ring so it's taken as is. A string key maps to the same id as its UTF-8 bytes.
+pub trait IntoNodeId {
+    /// Get the id of the key on the ring
+    fn into_node_id(self) -> NodeId;
+}
+
+impl IntoNodeId for NodeId {
+    fn into_node_id(self) -> NodeId {
+        self
+    }
+}
+
+impl IntoNodeId for &[u8] {
+    fn into_node_id(self) -> NodeId {
+        key_to_id(self)
+    }
+}
+
+impl<const N: usize> IntoNodeId for &[u8; N] {
+    fn into_node_id(self) -> NodeId {
+        key_to_id(self)
+    }
+}
+
+impl IntoNodeId for &str {
+    fn into_node_id(self) -> NodeId {
+        key_to_id(self.as_bytes())
+    }
+}
+
+impl IntoNodeId for String {
+    fn into_node_id(self) -> NodeId {
+        key_to_id(self.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use super::*;
+    use crate::client::MockClient;
+    use crate::NodeService;
+
+    #[test]
+    fn sha256_hasher() {
+        // First 8 bytes of SHA-256("abc")
+        assert_eq!(Sha256Hasher.hash(b"abc"), NodeId(0xba7816bf8f01cfea));
+        assert_eq!(Sha256Hasher.hash(b"abc"), Sha256Hasher.hash(b"abc"));
+        assert_ne!(Sha256Hasher.hash(b"abc"), Sha256Hasher.hash(b"abd"));
+    }
+
+    #[test]
+    fn sea_hasher() {
+        assert_eq!(SeaHasher.hash(b"abc"), NodeId(seahash::hash(b"abc")));
+        assert_ne!(SeaHasher.hash(b"abc"), Sha256Hasher.hash(b"abc"));
+    }
+
+    #[test]
+    fn hash_functions() {
+        assert_eq!(HashFunction::default(), HashFunction::Sha256);
+        assert_eq!(HashFunction::Sha256.hash(b"abc"), Sha256Hasher.hash(b"abc"));
+        assert_eq!(HashFunction::SeaHash.hash(b"abc"), SeaHasher.hash(b"abc"));
+    }
+
+    #[test]
+    fn conversions_use_default_hasher() {
+        let addr = SocketAddr::from(([127, 0, 0, 1], 42000));
+
+        assert_eq!(
+            NodeId::from(addr),
+            Sha256Hasher.hash(addr.to_string().as_bytes())
+        );
+        assert_eq!(NodeId::from("key".to_string()), Sha256Hasher.hash(b"key"));
+    }
+
+    #[test]
+    fn keys_into_node_ids() {
+        let id = key_to_id(b"key");
+
+        assert_eq!(id, Sha256Hasher.hash(b"key"));
+        assert_eq!(b"key".into_node_id(), id);
+        assert_eq!(b"key".as_slice().into_node_id(), id);
+        assert_eq!("key".into_node_id(), id);
+        assert_eq!("key".to_string().into_node_id(), id);
+        assert_eq!(NodeId::from("key".to_string()), id);
+        // Pre-hashed ids are not hashed again
+        assert_eq!(NodeId(42).into_node_id(), NodeId(42));
+        assert_eq!(NodeService::<MockClient>::key_id(NodeId(42)), NodeId(42));
+        assert_eq!(NodeService::<MockClient>::key_id("key"), id);
+    }
+
+    #[test]
+    fn node_service_with_hasher() {
+        struct FixedHasher;
+
+        impl Hasher for FixedHasher {
+            fn hash(&self, _bytes: &[u8]) -> NodeId {
+                NodeId(42)
+            }
+        }
+
+        let addr = SocketAddr::from(([127, 0, 0, 1], 42000));
+        let service: NodeService<MockClient> =
+            NodeService::with_hasher(addr, 3, None, &FixedHasher);
+
+        assert_eq!(service.id(), NodeId(42));
+    }
+}
@@ -1,29 +1,48 @@
+pub mod auth;
+#[cfg(feature = "chaos")]
+pub mod chaos;
 pub mod client;
+pub mod crawl;
+pub mod deadline;
+mod hash;
+pub mod metrics;
 mod node;
+pub mod rate_limit;
+#[cfg(feature = "serde")]
+mod serialization;
 pub mod server;
 mod service;
+#[cfg(any(test, feature = "test-util"))]
+pub mod simulator;
+pub mod vnode;
 
-use seahash::hash;
-use std::fmt::Display;
+use std::fmt::{Debug, Display};
 use std::net::SocketAddr;
+use std::num::ParseIntError;
+use std::str::FromStr;
 
 pub use client::Client;
-pub use service::NodeService;
+pub use hash::{key_to_id, HashFunction, Hasher, IntoNodeId, SeaHasher, Sha256Hasher};
+pub use node::{Finger, NodeEvent};
+pub use service::{
+    Anomaly, HealthStatus, MaintenanceTask, NodeInfo, NodeService, RetryPolicy, RingCheck,
+    RingParams, Snapshot,
+};
 
 pub use service::error;
 
-#[derive(Clone, Copy, PartialEq, PartialOrd, Ord, Debug, Eq, Hash)]
+#[derive(Clone, Copy, PartialEq, PartialOrd, Ord, Eq, Hash)]
 pub struct NodeId(u64);
 
 impl From<SocketAddr> for NodeId {
     fn from(addr: SocketAddr) -> Self {
-        Self(hash(addr.to_string().as_bytes()))
+        Sha256Hasher.hash(addr.to_string().as_bytes())
     }
 }
 
 impl From<String> for NodeId {
     fn from(key: String) -> Self {
-        Self(hash(key.as_bytes()))
+        key_to_id(key.as_bytes())
     }
 }
 
@@ -39,19 +58,97 @@ impl From<u64> for NodeId {
     }
 }
 
+/// Parse a node id from a decimal number, or from a hex number prefixed with `0x`.
+/// Every 64-bit number is a valid position on the ring.
+impl FromStr for NodeId {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            Some(hex) => u64::from_str_radix(hex, 16).map(Self),
+            None => s.parse().map(Self),
+        }
+    }
+}
+
+impl NodeId {
+    /// Get the distance from this id to the other one, going clockwise around the ring
+    ///
+    /// The distance wraps around zero, so it's never larger than `u64::MAX`, and the distance
+    /// from an id to itself is 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The id to measure the distance to
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chord_rs_core::NodeId;
+    ///
+    /// assert_eq!(NodeId::from(5).clockwise_distance(&NodeId::from(15)), 10);
+    /// assert_eq!(NodeId::from(u64::MAX).clockwise_distance(&NodeId::from(1)), 2);
+    /// ```
+    pub fn clockwise_distance(&self, other: &NodeId) -> u64 {
+        other.0.wrapping_sub(self.0)
+    }
+
+    /// Get a short form of the id, for the logs
+    ///
+    /// It keeps the first 8 hex digits, which tell where the node is on the ring. Two ids
+    /// sharing them are hard to tell apart, so the full id is shown where it matters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chord_rs_core::NodeId;
+    ///
+    /// assert_eq!(NodeId::from(0x13401e3a5c1cd23d).short().to_string(), "0x13401e3a");
+    /// ```
+    pub fn short(&self) -> ShortNodeId {
+        ShortNodeId(self.0)
+    }
+}
+
+/// A node id is displayed in zero-padded hex, e.g. `0x13401e3a5c1cd23d`, which can be parsed back
 impl Display for NodeId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{:#018x}", self.0)
+    }
+}
+
+impl Debug for NodeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "NodeId({})", self)
+    }
+}
+
+/// The first 8 hex digits of a node id, see [`NodeId::short`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ShortNodeId(u64);
+
+impl Display for ShortNodeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#010x}", self.0 >> 32)
     }
 }
 
 /// A reference to a node in the chord ring
 #[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Node {
     id: NodeId,
     addr: SocketAddr,
 }
 
+/// A node is displayed as the short form of its id and its address, e.g.
+/// `0x13401e3a@127.0.0.1:42000`
+impl Display for Node {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}", self.id.short(), self.addr)
+    }
+}
+
 impl Node {
     pub fn new(addr: SocketAddr) -> Self {
         Self {
@@ -77,6 +174,11 @@ impl Node {
 
     /// Returns true if the given id is between 2 nodes on a ring
     ///
+    /// The interval is `(node1, node2]`: it starts right after `node1`, goes clockwise and
+    /// includes `node2`. If `node1 > node2` the interval wraps around zero. If `node1 == node2`
+    /// the interval is the whole ring, so every id is between them, including `node1` itself.
+    /// This is the interval a node is responsible for, `node1` being its predecessor.
+    ///
     /// # Arguments
     ///
     /// * `id` - The id to check
@@ -115,6 +217,26 @@ impl Node {
         }
     }
 
+    /// Returns true if the given id is strictly between 2 nodes on a ring
+    ///
+    /// The interval is `(node1, node2)`: the same as [`is_between_on_ring`](Self::is_between_on_ring)
+    /// without `node2`. If `node1 == node2` the interval is the whole ring except `node1`.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id to check
+    /// * `node1` - First node id
+    /// * `node2` - Second node id
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chord_rs_core::Node;
+    ///
+    /// assert_eq!(Node::is_between_on_ring_exclusive(10, 5, 15), true);
+    /// assert_eq!(Node::is_between_on_ring_exclusive(15, 5, 15), false);
+    /// assert_eq!(Node::is_between_on_ring_exclusive(5, 5, 5), false);
+    /// ```
     pub fn is_between_on_ring_exclusive(id: u64, node1: u64, node2: u64) -> bool {
         if node1 < node2 {
             node1 < id && id < node2
@@ -141,6 +263,23 @@ mod tests {
         assert_eq!(Node::is_between_on_ring(1, 2, 5), false);
     }
 
+    #[test]
+    fn test_clockwise_distance() {
+        assert_eq!(NodeId(5).clockwise_distance(&NodeId(5)), 0);
+        assert_eq!(NodeId(5).clockwise_distance(&NodeId(15)), 10);
+        assert_eq!(NodeId(15).clockwise_distance(&NodeId(5)), u64::MAX - 9);
+        assert_eq!(NodeId(u64::MAX).clockwise_distance(&NodeId(0)), 1);
+        assert_eq!(NodeId(0).clockwise_distance(&NodeId(u64::MAX)), u64::MAX);
+
+        // The distances there and back go around the whole ring
+        let (a, b) = (NodeId(1234), NodeId(u64::MAX - 42));
+        assert_eq!(
+            a.clockwise_distance(&b)
+                .wrapping_add(b.clockwise_distance(&a)),
+            0
+        );
+    }
+
     #[test]
     fn test_is_between_exclusive() {
         assert_eq!(Node::is_between_on_ring_exclusive(10, 5, 5), true);
@@ -153,4 +292,89 @@ mod tests {
         assert_eq!(Node::is_between_on_ring_exclusive(1, 1, 5), false);
         assert_eq!(Node::is_between_on_ring_exclusive(1, 2, 5), false);
     }
+
+    #[test]
+    fn test_display_node_id() {
+        assert_eq!(NodeId(42).to_string(), "0x000000000000002a");
+        assert_eq!(NodeId(u64::MAX).to_string(), "0xffffffffffffffff");
+        assert_eq!(format!("{:?}", NodeId(42)), "NodeId(0x000000000000002a)");
+        assert_eq!(NodeId(42).short().to_string(), "0x00000000");
+
+        let id = NodeId(0x13401e3a5c1cd23d);
+        assert_eq!(id.to_string().parse::<NodeId>(), Ok(id));
+
+        let node = Node::with_id(id, SocketAddr::from(([127, 0, 0, 1], 42000)));
+        assert_eq!(node.to_string(), "0x13401e3a@127.0.0.1:42000");
+    }
+
+    #[test]
+    fn test_parse_node_id() {
+        assert_eq!("42".parse::<NodeId>(), Ok(NodeId(42)));
+        assert_eq!("0x2a".parse::<NodeId>(), Ok(NodeId(42)));
+        assert_eq!("0XFFFFFFFFFFFFFFFF".parse::<NodeId>(), Ok(NodeId(u64::MAX)));
+
+        assert!("".parse::<NodeId>().is_err());
+        assert!("-1".parse::<NodeId>().is_err());
+        assert!("2a".parse::<NodeId>().is_err());
+        assert!("18446744073709551616".parse::<NodeId>().is_err());
+    }
+
+    /// Walk an 8-bit ring clockwise from `node1` to `node2`, returning the ids in `(node1, node2]`
+    fn walk(node1: u8, node2: u8) -> Vec<bool> {
+        let mut between = vec![false; 256];
+        let mut id = node1;
+        loop {
+            id = id.wrapping_add(1);
+            between[id as usize] = true;
+            if id == node2 {
+                return between;
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_between_on_8_bit_ring() {
+        for node1 in 0..=u8::MAX {
+            for node2 in 0..=u8::MAX {
+                let between = walk(node1, node2);
+                for id in 0..=u8::MAX {
+                    let (id_, node1_, node2_) = (id as u64, node1 as u64, node2 as u64);
+                    assert_eq!(
+                        Node::is_between_on_ring(id_, node1_, node2_),
+                        between[id as usize],
+                        "{id} in ({node1}, {node2}]"
+                    );
+                    assert_eq!(
+                        Node::is_between_on_ring_exclusive(id_, node1_, node2_),
+                        between[id as usize] && id != node2,
+                        "{id} in ({node1}, {node2})"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_between_at_the_ends_of_the_ring() {
+        assert!(Node::is_between_on_ring(0, u64::MAX, 0));
+        assert!(!Node::is_between_on_ring(u64::MAX, u64::MAX, 0));
+        assert!(Node::is_between_on_ring(u64::MAX, 0, u64::MAX));
+        assert!(!Node::is_between_on_ring(0, 0, u64::MAX));
+        assert!(!Node::is_between_on_ring_exclusive(0, u64::MAX, 0));
+        assert!(Node::is_between_on_ring_exclusive(0, u64::MAX, 1));
+    }
+
+    #[test]
+    fn test_is_between_in_two_node_ring() {
+        // Each node is responsible for the ids after the other node, up to and including itself
+        let (a, b) = (100, 200);
+        for id in [101, 150, 200] {
+            assert!(Node::is_between_on_ring(id, a, b));
+            assert!(!Node::is_between_on_ring(id, b, a));
+        }
+        for id in [201, u64::MAX, 0, 100] {
+            assert!(Node::is_between_on_ring(id, b, a));
+            assert!(!Node::is_between_on_ring(id, a, b));
+        }
+    }
 }
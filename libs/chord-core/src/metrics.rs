@@ -0,0 +1,188 @@
+use std::sync::OnceLock;
+
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, Opts, Registry,
+    TextEncoder,
+};
+
+/// Metrics of the node
+///
+/// All the metrics are registered in a dedicated registry, which can be exported
+/// in the Prometheus text format with [`gather`].
+pub struct Metrics {
+    registry: Registry,
+
+    /// Number of RPC calls handled by the node, per method
+    pub rpc_calls: IntCounterVec,
+    /// Number of nodes contacted to find a successor
+    pub lookup_hops: Histogram,
+    /// Number of nodes in the successor list
+    pub successor_list_length: IntGauge,
+    /// Whether the node knows its predecessor (1) or not (0)
+    pub predecessor_present: IntGauge,
+    /// Number of failed maintenance tasks, per task
+    pub task_failures: IntCounterVec,
+    /// Whether the successor chain failed to loop back to the node (1) or not (0)
+    pub partition_suspected: IntGauge,
+    /// Number of inbound requests rejected because their peer exceeded its rate
+    pub rate_limited_requests: IntCounter,
+    /// Number of lookups which went through the lookup cache, per result: `hit`, `miss` or
+    /// `stale` when the cached successor turned out to be wrong
+    pub lookup_cache: IntCounterVec,
+    /// Number of broken invariants found by the audits of the routing state, per
+    /// [kind](crate::Anomaly::kind)
+    pub audit_anomalies: IntCounterVec,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some("chord".to_string()), None)
+            .expect("Failed to create metrics registry");
+
+        let rpc_calls = IntCounterVec::new(
+            Opts::new("rpc_calls_total", "Number of RPC calls handled by the node"),
+            &["method"],
+        )
+        .unwrap();
+        let lookup_hops = Histogram::with_opts(
+            HistogramOpts::new(
+                "lookup_hops",
+                "Number of nodes contacted to find a successor",
+            )
+            .buckets(vec![0.0, 1.0, 2.0, 3.0, 4.0, 6.0, 8.0, 16.0]),
+        )
+        .unwrap();
+        let successor_list_length = IntGauge::new(
+            "successor_list_length",
+            "Number of nodes in the successor list",
+        )
+        .unwrap();
+        let predecessor_present = IntGauge::new(
+            "predecessor_present",
+            "Whether the node knows its predecessor",
+        )
+        .unwrap();
+        let task_failures = IntCounterVec::new(
+            Opts::new("task_failures_total", "Number of failed maintenance tasks"),
+            &["task"],
+        )
+        .unwrap();
+        let partition_suspected = IntGauge::new(
+            "partition_suspected",
+            "Whether the successor chain failed to loop back to the node",
+        )
+        .unwrap();
+        let rate_limited_requests = IntCounter::new(
+            "rate_limited_requests_total",
+            "Number of inbound requests rejected because their peer exceeded its rate",
+        )
+        .unwrap();
+        let lookup_cache = IntCounterVec::new(
+            Opts::new(
+                "lookup_cache_total",
+                "Number of lookups which went through the lookup cache",
+            ),
+            &["result"],
+        )
+        .unwrap();
+        let audit_anomalies = IntCounterVec::new(
+            Opts::new(
+                "audit_anomalies_total",
+                "Number of broken invariants found by the audits of the routing state",
+            ),
+            &["anomaly"],
+        )
+        .unwrap();
+
+        registry.register(Box::new(rpc_calls.clone())).unwrap();
+        registry.register(Box::new(lookup_hops.clone())).unwrap();
+        registry
+            .register(Box::new(successor_list_length.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(predecessor_present.clone()))
+            .unwrap();
+        registry.register(Box::new(task_failures.clone())).unwrap();
+        registry
+            .register(Box::new(partition_suspected.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(rate_limited_requests.clone()))
+            .unwrap();
+        registry.register(Box::new(lookup_cache.clone())).unwrap();
+        registry
+            .register(Box::new(audit_anomalies.clone()))
+            .unwrap();
+
+        Self {
+            registry,
+            rpc_calls,
+            lookup_hops,
+            successor_list_length,
+            predecessor_present,
+            task_failures,
+            partition_suspected,
+            rate_limited_requests,
+            lookup_cache,
+            audit_anomalies,
+        }
+    }
+
+    /// Count an RPC call of the given method
+    pub fn rpc_call(&self, method: &str) {
+        self.rpc_calls.with_label_values(&[method]).inc();
+    }
+
+    /// Count a lookup which went through the lookup cache, with its result
+    pub fn lookup_cache(&self, result: &str) {
+        self.lookup_cache.with_label_values(&[result]).inc();
+    }
+
+    /// Count a broken invariant found by an audit, with its kind
+    pub fn audit_anomaly(&self, kind: &str) {
+        self.audit_anomalies.with_label_values(&[kind]).inc();
+    }
+
+    /// Count a failure of the given maintenance task
+    pub fn task_failure(&self, task: &str) {
+        self.task_failures.with_label_values(&[task]).inc();
+    }
+}
+
+/// Get the metrics of the node
+pub fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(Metrics::new)
+}
+
+/// Export all the metrics in the Prometheus text format
+pub fn gather() -> String {
+    let mut buffer = Vec::new();
+    let encoder = TextEncoder::new();
+    encoder
+        .encode(&metrics().registry.gather(), &mut buffer)
+        .expect("Failed to encode metrics");
+
+    String::from_utf8(buffer).expect("Metrics are not valid UTF-8")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gather_metrics() {
+        metrics().rpc_call("ping");
+        metrics().task_failure("stabilize");
+        metrics().lookup_hops.observe(1.0);
+        metrics().lookup_cache("hit");
+
+        let output = gather();
+        assert!(output.contains("chord_rpc_calls_total{method=\"ping\"}"));
+        assert!(output.contains("chord_task_failures_total{task=\"stabilize\"}"));
+        assert!(output.contains("chord_lookup_hops_bucket"));
+        assert!(output.contains("chord_lookup_cache_total{result=\"hit\"}"));
+        assert!(output.contains("chord_successor_list_length"));
+        assert!(output.contains("chord_predecessor_present"));
+    }
+}
@@ -0,0 +1,17 @@
+use crate::Node;
+
+/// Change of the node's place in the ring
+///
+/// The events are broadcast to the receivers returned by
+/// [`NodeService::subscribe`](crate::NodeService::subscribe).
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeEvent {
+    /// The predecessor changed, `None` when it failed and was cleared
+    PredecessorChanged(Option<Node>),
+    /// The immediate successor changed
+    SuccessorChanged(Node),
+    /// The node stabilized for the first time since it started, it's part of the ring
+    Joined,
+    /// The node left the ring
+    Left,
+}
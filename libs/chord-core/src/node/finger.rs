@@ -1,9 +1,14 @@
-use crate::Node;
+use crate::{Node, NodeId};
 
 /// Finger table entry
+///
+/// The `i`-th finger of a node `n` is responsible for the ids in `[n + 2^(i - 1), n + 2^i)`,
+/// and points to the first node which succeeds the start of that interval.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Finger {
-    pub(crate) _start: u64,
+    pub(crate) start: u64,
+    pub(crate) end: u64,
     pub node: Node,
 }
 
@@ -11,12 +16,35 @@ impl Finger {
     /// Finger table size
     pub const FINGER_TABLE_SIZE: u8 = 64;
 
+    /// Get the first id of the interval the finger is responsible for
+    pub fn start(&self) -> NodeId {
+        NodeId(self.start)
+    }
+
+    /// Get the id right after the interval the finger is responsible for
+    ///
+    /// The end of the last finger wraps around the ring back to the node itself.
+    pub fn end(&self) -> NodeId {
+        NodeId(self.end)
+    }
+
+    /// Get the interval the finger is responsible for, as `(start, end)`
+    ///
+    /// The interval is half-open: it includes `start` but not `end`. It can wrap around the end
+    /// of the ring, in which case `start` is greater than `end`.
+    pub fn interval(&self) -> (NodeId, NodeId) {
+        (self.start(), self.end())
+    }
+
     /// Generate a finger id for a given node id and finger index.
     /// The finger id is calculated using the following formula:
     /// ```text
     /// (node_id + 2^(index - 1)) % 2^m
     /// ```
     ///
+    /// The sum wraps around the end of the ring, so the fingers of a node close to `u64::MAX`
+    /// land right after `0`.
+    ///
     /// Ref: https://pdos.csail.mit.edu/papers/ton:chord/paper-ton.pdf
     /// Ref: https://en.wikipedia.org/wiki/Chord_(peer-to-peer)#Finger_table
     ///
@@ -28,17 +56,19 @@ impl Finger {
         Self::sized_finger_id(Self::FINGER_TABLE_SIZE, node_id, index)
     }
 
+    /// Generate a finger id on a ring of `2^size` ids, see [`finger_id`](Self::finger_id)
     pub(crate) fn sized_finger_id(size: u8, node_id: u64, index: u8) -> u64 {
         if index == 0 {
             return node_id;
         }
 
-        let offset: u128 = 2_u128.pow((index - 1) as u32);
-        let power: u128 = 2_u128.pow(size as u32);
-
-        let id = (node_id as u128 + offset) % power;
+        // 2^(index - 1) is a multiple of 2^64 past the 64th finger, so it doesn't move the id
+        let offset = 1_u64.checked_shl((index - 1) as u32).unwrap_or(0);
+        let mask = 1_u64
+            .checked_shl(size as u32)
+            .map_or(u64::MAX, |power| power - 1);
 
-        id as u64
+        node_id.wrapping_add(offset) & mask
     }
 
     /// Initialize a new finger table for a node.
@@ -57,10 +87,11 @@ impl Finger {
 
         // We start at 1 because the calculation of the finger id is based on the index
         // of the finger. The calculation assumes that the index starts at 1.
-        for i in 1..(size + 1) {
+        for i in 1..=size {
             let finger_id = Self::sized_finger_id(size, node.id.0, i);
             fingers.push(Finger {
-                _start: finger_id,
+                start: finger_id,
+                end: Self::sized_finger_id(size, node.id.0, i + 1),
                 node: node.clone(),
             });
         }
@@ -111,6 +142,74 @@ mod tests {
         assert_eq!(Finger::sized_finger_id(M, node_id, 7), 1);
     }
 
+    #[test]
+    fn it_should_wrap_finger_id_around_the_ring() {
+        let node_id = u64::MAX;
+        assert_eq!(Finger::finger_id(node_id, 0), u64::MAX);
+        assert_eq!(Finger::finger_id(node_id, 1), 0);
+        assert_eq!(Finger::finger_id(node_id, 2), 1);
+        assert_eq!(Finger::finger_id(node_id, 3), 3);
+        assert_eq!(Finger::finger_id(node_id, 64), (1 << 63) - 1);
+        assert_eq!(Finger::finger_id(node_id, 65), u64::MAX);
+        assert_eq!(Finger::finger_id(node_id, u8::MAX), u64::MAX);
+
+        let node_id = u64::MAX - 4;
+        assert_eq!(Finger::finger_id(node_id, 3), u64::MAX);
+        assert_eq!(Finger::finger_id(node_id, 4), 3);
+        assert_eq!(Finger::finger_id(node_id, 64), (1 << 63) - 5);
+
+        let node_id = 1 << 63;
+        assert_eq!(Finger::finger_id(node_id, 63), (1 << 63) + (1 << 62));
+        assert_eq!(Finger::finger_id(node_id, 64), 0);
+
+        assert_eq!(Finger::finger_id(0, 1), 1);
+        assert_eq!(Finger::finger_id(0, 64), 1 << 63);
+
+        // The ids of a smaller ring are taken modulo its size
+        const M: u8 = 6;
+        assert_eq!(Finger::sized_finger_id(M, 63, 1), 0);
+        assert_eq!(Finger::sized_finger_id(M, 60, 4), 4);
+        assert_eq!(Finger::sized_finger_id(M, 60, 7), 60);
+    }
+
+    #[test]
+    fn it_should_follow_the_node_on_the_whole_ring() {
+        // Every finger is 2^(index - 1) ids clockwise from the node, whatever the node's position
+        for node_id in [
+            0,
+            1,
+            1 << 32,
+            1 << 63,
+            u64::MAX - (1 << 62),
+            u64::MAX - 1,
+            u64::MAX,
+        ] {
+            for index in 1..=Finger::FINGER_TABLE_SIZE {
+                let finger_id = Finger::finger_id(node_id, index);
+                assert_eq!(
+                    finger_id.wrapping_sub(node_id),
+                    1 << (index - 1),
+                    "finger {index} of {node_id}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn it_should_generate_finger_table_near_the_end_of_the_ring() {
+        let node = Node::with_id(
+            NodeId(u64::MAX - 1),
+            SocketAddr::from(([127, 0, 0, 1], 42001)),
+        );
+
+        let fingers = Finger::init_finger_table(node);
+
+        assert_eq!(fingers[0].start, u64::MAX);
+        assert_eq!(fingers[1].start, 0);
+        assert_eq!(fingers[2].start, 2);
+        assert_eq!(fingers[63].start, (1 << 63) - 2);
+    }
+
     #[test]
     fn it_should_generate_finger_table() {
         let node = Node::with_id(NodeId(1), SocketAddr::from(([127, 0, 0, 1], 42001)));
@@ -118,24 +217,45 @@ mod tests {
         let fingers = Finger::init_finger_table(node.clone());
 
         assert_eq!(fingers.len(), 64);
-        assert_eq!(fingers[0]._start, 2);
-        assert_eq!(fingers[1]._start, 3);
-        assert_eq!(fingers[2]._start, 5);
-        assert_eq!(fingers[3]._start, 9);
-        assert_eq!(fingers[4]._start, 17);
-        assert_eq!(fingers[5]._start, 33);
-        assert_eq!(fingers[15]._start, 32769);
-        assert_eq!(fingers[63]._start, 9223372036854775809);
+        assert_eq!(fingers[0].start, 2);
+        assert_eq!(fingers[1].start, 3);
+        assert_eq!(fingers[2].start, 5);
+        assert_eq!(fingers[3].start, 9);
+        assert_eq!(fingers[4].start, 17);
+        assert_eq!(fingers[5].start, 33);
+        assert_eq!(fingers[15].start, 32769);
+        assert_eq!(fingers[63].start, 9223372036854775809);
 
         let node = Node::with_id(NodeId(5), SocketAddr::from(([127, 0, 0, 1], 42001)));
         let fingers = Finger::sized_finger_table(6, node);
 
         assert_eq!(fingers.len(), 6);
-        assert_eq!(fingers[0]._start, 6);
-        assert_eq!(fingers[1]._start, 7);
-        assert_eq!(fingers[2]._start, 9);
-        assert_eq!(fingers[3]._start, 13);
-        assert_eq!(fingers[4]._start, 21);
-        assert_eq!(fingers[5]._start, 37);
+        assert_eq!(fingers[0].start, 6);
+        assert_eq!(fingers[1].start, 7);
+        assert_eq!(fingers[2].start, 9);
+        assert_eq!(fingers[3].start, 13);
+        assert_eq!(fingers[4].start, 21);
+        assert_eq!(fingers[5].start, 37);
+    }
+
+    #[test]
+    fn fingers_cover_consecutive_intervals() {
+        let node = Node::with_id(NodeId(1), SocketAddr::from(([127, 0, 0, 1], 42001)));
+        let fingers = Finger::init_finger_table(node);
+
+        assert_eq!(fingers[0].interval(), (NodeId(2), NodeId(3)));
+        assert_eq!(fingers[3].interval(), (NodeId(9), NodeId(17)));
+        for (i, finger) in fingers.iter().enumerate() {
+            assert_eq!(finger.end().0.wrapping_sub(finger.start().0), 1 << i);
+        }
+        for pair in fingers.windows(2) {
+            assert_eq!(pair[0].end(), pair[1].start());
+        }
+        // The last finger wraps around the ring back to the node
+        assert_eq!(fingers[63].end(), NodeId(1));
+
+        let node = Node::with_id(NodeId(5), SocketAddr::from(([127, 0, 0, 1], 42001)));
+        let fingers = Finger::sized_finger_table(6, node);
+        assert_eq!(fingers[5].interval(), (NodeId(37), NodeId(5)));
     }
 }
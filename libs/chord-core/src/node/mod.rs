@@ -1,5 +1,10 @@
 pub(crate) mod store;
 
+#[cfg(feature = "persistence")]
+pub(crate) mod persistence;
+
+mod events;
 mod finger;
 
-pub(super) use finger::Finger;
+pub use events::NodeEvent;
+pub use finger::Finger;
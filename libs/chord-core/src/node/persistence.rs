@@ -0,0 +1,169 @@
+use std::path::Path;
+
+use error_stack::{IntoReport, Result, ResultExt};
+
+use crate::error::PersistenceError;
+use crate::Node;
+
+const NODE_KEY: &str = "node";
+const PREDECESSOR_KEY: &str = "predecessor";
+const SUCCESSOR_LIST_KEY: &str = "successor_list";
+
+/// Disk-backed copy of the node's neighbours
+///
+/// The state is tied to the node which wrote it, so the state of a node with a different
+/// id or address is never restored.
+#[derive(Debug, Clone)]
+pub(crate) struct Persistence {
+    db: sled::Db,
+    node: Node,
+}
+
+/// The state restored from disk
+#[derive(Debug, PartialEq)]
+pub(crate) struct PersistedState {
+    pub(crate) predecessor: Option<Node>,
+    pub(crate) successor_list: Vec<Node>,
+}
+
+impl Persistence {
+    /// Open the store in the given directory, creating it if needed
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The directory of the store
+    /// * `node` - The node which owns the store
+    pub(crate) fn open(path: impl AsRef<Path>, node: Node) -> Result<Self, PersistenceError> {
+        let db = sled::open(path.as_ref())
+            .into_report()
+            .change_context(PersistenceError::Open(path.as_ref().to_path_buf()))?;
+
+        Ok(Self { db, node })
+    }
+
+    /// Load the state saved by the node, if any
+    pub(crate) fn load(&self) -> Result<Option<PersistedState>, PersistenceError> {
+        match self.get::<Node>(NODE_KEY)? {
+            Some(node) if node == self.node => {}
+            Some(node) => {
+                log::warn!("Ignoring the persisted state of another node: {}", node);
+                return Ok(None);
+            }
+            None => return Ok(None),
+        }
+
+        Ok(Some(PersistedState {
+            predecessor: self.get::<Option<Node>>(PREDECESSOR_KEY)?.flatten(),
+            successor_list: self.get(SUCCESSOR_LIST_KEY)?.unwrap_or_default(),
+        }))
+    }
+
+    /// Save the neighbours of the node
+    ///
+    /// # Arguments
+    ///
+    /// * `predecessor` - The predecessor of the node
+    /// * `successor_list` - The successor list of the node
+    pub(crate) fn save(
+        &self,
+        predecessor: Option<&Node>,
+        successor_list: &[Node],
+    ) -> Result<(), PersistenceError> {
+        let mut batch = sled::Batch::default();
+        batch.insert(NODE_KEY, Self::encode(&self.node)?);
+        batch.insert(PREDECESSOR_KEY, Self::encode(&predecessor)?);
+        batch.insert(SUCCESSOR_LIST_KEY, Self::encode(&successor_list)?);
+
+        self.db
+            .apply_batch(batch)
+            .into_report()
+            .change_context(PersistenceError::Write)
+    }
+
+    fn get<T: serde::de::DeserializeOwned>(
+        &self,
+        key: &str,
+    ) -> Result<Option<T>, PersistenceError> {
+        let value = self
+            .db
+            .get(key)
+            .into_report()
+            .change_context(PersistenceError::Read)?;
+
+        match value {
+            Some(value) => serde_json::from_slice(&value)
+                .into_report()
+                .change_context(PersistenceError::Corrupted(key.to_string()))
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn encode<T: serde::Serialize>(value: &T) -> Result<Vec<u8>, PersistenceError> {
+        serde_json::to_vec(value)
+            .into_report()
+            .change_context(PersistenceError::Write)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::node::store::tests::node;
+
+    /// A unique directory for the store of a test, removed when dropped
+    pub(crate) struct TempDir(pub(crate) PathBuf);
+
+    impl TempDir {
+        pub(crate) fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "chord-persistence-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = std::fs::remove_dir_all(&path);
+
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn save_and_load() {
+        let dir = TempDir::new("save_and_load");
+        let persistence = Persistence::open(&dir.0, node(1)).unwrap();
+        assert_eq!(persistence.load().unwrap(), None);
+
+        persistence
+            .save(Some(&node(40)), &[node(10), node(20)])
+            .unwrap();
+
+        assert_eq!(
+            persistence.load().unwrap(),
+            Some(PersistedState {
+                predecessor: Some(node(40)),
+                successor_list: vec![node(10), node(20)],
+            })
+        );
+    }
+
+    #[test]
+    fn ignore_state_of_another_node() {
+        let dir = TempDir::new("ignore_state_of_another_node");
+        let persistence = Persistence::open(&dir.0, node(1)).unwrap();
+        persistence.save(None, &[node(10)]).unwrap();
+
+        let persistence = Persistence {
+            node: node(2),
+            ..persistence
+        };
+        assert_eq!(persistence.load().unwrap(), None);
+    }
+}
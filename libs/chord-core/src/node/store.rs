@@ -1,7 +1,14 @@
-use std::sync::{Arc, Mutex};
+use std::num::NonZeroUsize;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock, RwLockWriteGuard};
 
-use crate::node::Finger;
-use crate::Node;
+use tokio::sync::{broadcast, Mutex, MutexGuard};
+
+#[cfg(feature = "persistence")]
+use crate::node::persistence::{PersistedState, Persistence};
+use crate::node::{Finger, NodeEvent};
+use crate::{Node, NodeId};
 
 /// A node in the chord ring
 ///
@@ -17,17 +24,109 @@ pub(crate) struct Db {
 
 #[derive(Debug)]
 struct Shared {
-    state: Mutex<State>,
+    /// The id of the current node
+    id: NodeId,
+    /// The number of nodes the data is replicated on
+    replication_factor: usize,
+    /// The routing state, copied on write so the readers get a snapshot of it without keeping
+    /// it locked, see [`Db::state`]
+    state: RwLock<Arc<State>>,
+    /// Serializes the writers which await between their reads and their updates, see
+    /// [`Db::write_async`]
+    writer: Mutex<()>,
+    /// Disk-backed copy of the predecessor and the successor list
+    #[cfg(feature = "persistence")]
+    persistence: Option<Persistence>,
+    /// Changes of the neighbours, broadcast to the subscribers
+    events: broadcast::Sender<NodeEvent>,
+    /// Incremented on every change of the neighbours or of the fingers, see
+    /// [`Db::generation`]
+    generation: AtomicU64,
 }
 
-#[derive(Debug)]
-struct State {
+/// Routing state of the node
+///
+/// A snapshot is never updated, the updates are applied to a copy of the state when a snapshot
+/// of it is still in use.
+#[derive(Debug, Clone)]
+pub(crate) struct State {
     predecessor: Option<Node>,
+    /// The predecessors of the predecessor, closest first
+    /// They replace the predecessor when it fails, like the successor list does for the
+    /// successor. Their number is limited by `successor_list_size` too.
+    predecessors: Vec<Node>,
     finger_table: Vec<Finger>,
     /// The list of immediate successors
     /// This list is used to keep track of some of the successors of the node.
     /// It's needed in case the most immediate successor fails.
     successor_list: Vec<Node>,
+    /// The maximum length of the successor list
+    successor_list_size: NonZeroUsize,
+}
+
+impl State {
+    fn new(node: Node, successor_list_size: NonZeroUsize) -> State {
+        let mut successors = Vec::with_capacity(successor_list_size.get());
+        successors.push(node.clone());
+
+        State {
+            predecessor: None,
+            predecessors: vec![],
+            finger_table: Finger::init_finger_table(node),
+            successor_list: successors,
+            successor_list_size,
+        }
+    }
+
+    /// Get the predecessor of the node
+    pub(crate) fn predecessor(&self) -> Option<&Node> {
+        self.predecessor.as_ref()
+    }
+
+    /// Get the successor list of the node, the first node being its successor
+    pub(crate) fn successor_list(&self) -> &[Node] {
+        &self.successor_list
+    }
+
+    /// Get the finger table of the node
+    pub(crate) fn finger_table(&self) -> &[Finger] {
+        &self.finger_table
+    }
+}
+
+/// The state locked for writing
+///
+/// The state is copied on the first change if a snapshot of it is still in use.
+struct StateWriter<'a>(RwLockWriteGuard<'a, Arc<State>>);
+
+impl Deref for StateWriter<'_> {
+    type Target = State;
+
+    fn deref(&self) -> &State {
+        &self.0
+    }
+}
+
+impl DerefMut for StateWriter<'_> {
+    fn deref_mut(&mut self) -> &mut State {
+        Arc::make_mut(&mut self.0)
+    }
+}
+
+/// Writer of the state which awaits between its reads and its updates, see [`Db::write_async`]
+///
+/// It's a [`Db`], the updates are made through its usual methods.
+pub(crate) struct AsyncWriter<'a> {
+    db: &'a Db,
+    _guard: MutexGuard<'a, ()>,
+}
+
+impl Deref for AsyncWriter<'_> {
+    type Target = Db;
+
+    fn deref(&self) -> &Db {
+        self.db
+    }
 }
 
 impl NodeStore {
@@ -35,11 +134,36 @@ impl NodeStore {
     ///
     /// # Arguments
     ///
-    /// * `successor` - The immediate successor of the current node
-    /// * `replication_factor` - The number of successors to keep track of
-    pub(crate) fn new(successor: Node, replication_factor: usize) -> Self {
+    /// * `node` - The current node, which is its own successor until it joins a ring
+    /// * `replication_factor` - The number of nodes the data is replicated on
+    /// * `successor_list_size` - The number of successors to keep track of
+    pub(crate) fn new(
+        node: Node,
+        replication_factor: usize,
+        successor_list_size: NonZeroUsize,
+    ) -> Self {
         Self {
-            db: Db::new(successor, replication_factor),
+            db: Db::new(node, replication_factor, successor_list_size),
+        }
+    }
+
+    /// Create a new node store which saves the node's neighbours to disk
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The current node, which is its own successor until it joins a ring
+    /// * `replication_factor` - The number of nodes the data is replicated on
+    /// * `successor_list_size` - The number of successors to keep track of
+    /// * `persistence` - The store on disk
+    #[cfg(feature = "persistence")]
+    pub(crate) fn with_persistence(
+        node: Node,
+        replication_factor: usize,
+        successor_list_size: NonZeroUsize,
+        persistence: Persistence,
+    ) -> Self {
+        Self {
+            db: Db::with_persistence(node, replication_factor, successor_list_size, persistence),
         }
     }
 
@@ -51,22 +175,30 @@ impl NodeStore {
 }
 
 impl Db {
+    /// How many events a subscriber can fall behind before it misses some
+    const EVENTS_CAPACITY: usize = 64;
+
     /// Create a new database
     ///
     /// # Arguments
     ///
-    /// * `node` - The immediate successor of the current node
-    /// * `replication_factor` - The number of successors to keep track of
-    pub(crate) fn new(node: Node, replication_factor: usize) -> Db {
-        let mut successors = Vec::with_capacity(replication_factor);
-        successors.push(node.clone());
-
+    /// * `node` - The current node, which is its own successor until it joins a ring
+    /// * `replication_factor` - The number of nodes the data is replicated on
+    /// * `successor_list_size` - The number of successors to keep track of
+    pub(crate) fn new(
+        node: Node,
+        replication_factor: usize,
+        successor_list_size: NonZeroUsize,
+    ) -> Db {
         let shared = Arc::new(Shared {
-            state: Mutex::new(State {
-                predecessor: None,
-                finger_table: Finger::init_finger_table(node),
-                successor_list: successors,
-            }),
+            id: node.id,
+            replication_factor,
+            state: RwLock::new(Arc::new(State::new(node, successor_list_size))),
+            writer: Mutex::new(()),
+            #[cfg(feature = "persistence")]
+            persistence: None,
+            events: broadcast::channel(Self::EVENTS_CAPACITY).0,
+            generation: AtomicU64::new(0),
             // background_task: Notify::new(),
         });
 
@@ -76,63 +208,264 @@ impl Db {
         Db { shared }
     }
 
+    /// Create a new database which saves the node's neighbours to disk
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The current node, which is its own successor until it joins a ring
+    /// * `replication_factor` - The number of nodes the data is replicated on
+    /// * `successor_list_size` - The number of successors to keep track of
+    /// * `persistence` - The store on disk
+    #[cfg(feature = "persistence")]
+    pub(crate) fn with_persistence(
+        node: Node,
+        replication_factor: usize,
+        successor_list_size: NonZeroUsize,
+        persistence: Persistence,
+    ) -> Db {
+        let shared = Arc::new(Shared {
+            id: node.id,
+            replication_factor,
+            state: RwLock::new(Arc::new(State::new(node, successor_list_size))),
+            writer: Mutex::new(()),
+            persistence: Some(persistence),
+            events: broadcast::channel(Self::EVENTS_CAPACITY).0,
+            generation: AtomicU64::new(0),
+        });
+
+        Db { shared }
+    }
+
+    /// Get the number of nodes the data is replicated on
+    pub(crate) fn replication_factor(&self) -> usize {
+        self.shared.replication_factor
+    }
+
+    /// Get the maximum length of the successor list
+    pub(crate) fn successor_list_size(&self) -> usize {
+        self.state().successor_list_size.get()
+    }
+
+    /// Load the neighbours saved on disk, if any
+    #[cfg(feature = "persistence")]
+    pub(crate) fn persisted(
+        &self,
+    ) -> error_stack::Result<Option<PersistedState>, crate::error::PersistenceError> {
+        match &self.shared.persistence {
+            Some(persistence) => persistence.load(),
+            None => Ok(None),
+        }
+    }
+
     /// Set the predecessor of the node
     ///
+    /// The previous predecessor and the predecessor list are kept as the predecessors of the
+    /// new one, except the nodes which don't precede it on the ring.
+    ///
     /// # Arguments
     ///
     /// * `predecessor` - The predecessor node
     pub(crate) fn set_predecessor(&self, predecessor: Node) {
-        let mut state = self.shared_state();
-        state.predecessor = Some(predecessor);
+        let mut state = self.shared_state_mut();
+        if state.predecessor.as_ref() != Some(&predecessor) {
+            self.emit(NodeEvent::PredecessorChanged(Some(predecessor.clone())));
+            self.changed();
+        }
+        let previous = state.predecessor.replace(predecessor.clone());
+        let candidates: Vec<Node> = previous
+            .into_iter()
+            .chain(std::mem::take(&mut state.predecessors))
+            .collect();
+        state.predecessors =
+            self.preceding(&predecessor, candidates, state.successor_list_size.get());
+        #[cfg(feature = "persistence")]
+        self.persist(&state);
 
         drop(state)
     }
 
-    /// Unset the predecessor of the node
+    /// Unset the predecessor of the node, and its predecessor list
     pub(crate) fn unset_predecessor(&self) {
-        let mut state = self.shared_state();
-        state.predecessor = None;
+        let mut state = self.shared_state_mut();
+        if state.predecessor.take().is_some() {
+            self.emit(NodeEvent::PredecessorChanged(None));
+            self.changed();
+        }
+        state.predecessors.clear();
+        #[cfg(feature = "persistence")]
+        self.persist(&state);
 
         drop(state)
     }
 
     /// Get the predecessor of the node
     pub(crate) fn predecessor(&self) -> Option<Node> {
-        let state = self.shared_state();
+        let state = self.state();
         state.predecessor.clone()
     }
 
+    /// Replace the failed predecessor with the first node of the predecessor list
+    ///
+    /// The predecessor is unset if the list is empty. Nothing is done if the predecessor changed
+    /// in the meantime. Returns the new predecessor, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `failed` - The predecessor which failed
+    pub(crate) fn replace_predecessor(&self, failed: &Node) -> Option<Node> {
+        let mut state = self.shared_state_mut();
+        if state.predecessor.as_ref() != Some(failed) {
+            return state.predecessor.clone();
+        }
+
+        state.predecessor = if state.predecessors.is_empty() {
+            None
+        } else {
+            Some(state.predecessors.remove(0))
+        };
+        self.emit(NodeEvent::PredecessorChanged(state.predecessor.clone()));
+        self.changed();
+        #[cfg(feature = "persistence")]
+        self.persist(&state);
+
+        state.predecessor.clone()
+    }
+
+    /// Set the predecessors of the predecessor
+    ///
+    /// The nodes which don't precede the predecessor on the ring are dropped, the rest is
+    /// ordered from the closest to the predecessor. Nothing is done if the node has no
+    /// predecessor.
+    ///
+    /// # Arguments
+    ///
+    /// * `predecessors` - The predecessors of the predecessor, e.g. its own predecessor list
+    pub(crate) fn set_predecessor_list(&self, predecessors: Vec<Node>) {
+        let mut state = self.shared_state_mut();
+        let Some(predecessor) = state.predecessor.clone() else {
+            return;
+        };
+
+        let predecessors =
+            self.preceding(&predecessor, predecessors, state.successor_list_size.get());
+        if state.predecessors != predecessors {
+            state.predecessors = predecessors;
+            self.changed();
+        }
+
+        drop(state)
+    }
+
+    /// Get the predecessor list of the node, starting at the predecessor
+    ///
+    /// The list is empty if the node has no predecessor.
+    pub(crate) fn predecessor_list(&self) -> Vec<Node> {
+        let state = self.state();
+        state
+            .predecessor
+            .iter()
+            .chain(state.predecessors.iter())
+            .cloned()
+            .collect()
+    }
+
+    /// Keep the candidates preceding the predecessor, from the closest to the farthest
+    ///
+    /// The list holds at most `capacity - 1` nodes, the predecessor taking the first place of
+    /// the predecessor list.
+    fn preceding(&self, predecessor: &Node, candidates: Vec<Node>, capacity: usize) -> Vec<Node> {
+        let id = self.shared.id;
+        let distance = |node: &Node| predecessor.id.0.wrapping_sub(node.id.0);
+        let mut preceding: Vec<Node> = candidates
+            .into_iter()
+            .filter(|node| {
+                node.id != predecessor.id
+                    && Node::is_between_on_ring_exclusive(node.id.0, id.0, predecessor.id.0)
+            })
+            .collect();
+        preceding.sort_by_key(distance);
+        preceding.dedup_by_key(|node| node.id);
+        preceding.truncate(capacity.saturating_sub(1));
+
+        preceding
+    }
+
     /// Set the successor of the node
     ///
+    /// The nodes of the successor list which don't follow the new successor on the ring are
+    /// dropped, the rest is shifted after the new successor.
+    ///
     /// # Arguments
     ///
     /// * `successor` - The successor node
     pub(crate) fn set_successor(&self, successor: Node) {
-        let mut state = self.shared_state();
-        log::debug!("Setting successor to {:?}", successor);
-        state.successor_list[0] = successor;
+        let mut state = self.shared_state_mut();
+        log::debug!("Setting successor to {}", successor);
+        let capacity = state.successor_list_size.get();
+        let id = self.shared.id;
+        let previous = state.successor_list.clone();
+        if state.successor_list.first() != Some(&successor) {
+            self.emit(NodeEvent::SuccessorChanged(successor.clone()));
+        }
+
+        if successor.id == id {
+            state.successor_list.clear();
+        } else {
+            state.successor_list.retain(|node| {
+                node.id != successor.id
+                    && Node::is_between_on_ring_exclusive(node.id.0, successor.id.0, id.0)
+            });
+            state.successor_list.truncate(capacity - 1);
+        }
+        state.successor_list.insert(0, successor);
+        if state.successor_list != previous {
+            self.changed();
+        }
+        #[cfg(feature = "persistence")]
+        self.persist(&state);
 
         drop(state)
     }
 
     /// Get the successor of the node
     pub(crate) fn successor(&self) -> Node {
-        let state = self.shared_state();
+        let state = self.state();
 
         state.successor_list[0].clone()
     }
 
     /// Set the successor list of the node
     ///
-    /// If successor_list contains more items than `replication_factor`, only the first `replication_factor` items are used.
+    /// If successor_list contains more items than `successor_list_size`, only the first `successor_list_size` items are used.
+    ///
+    /// The node itself is dropped from the list, e.g. when the successors of a small ring wrap
+    /// around back to it, as it would make the node route the lookups to itself. It's kept only
+    /// if it's the only successor, i.e. the node is alone in the ring.
     ///
     /// # Arguments
     ///
     /// * `successor_list` - The list of successors
     pub(crate) fn set_successor_list(&self, successor_list: Vec<Node>) {
-        let mut state = self.shared_state();
-        let capacity = state.successor_list.capacity();
-        state.successor_list.clear();
+        let id = self.shared.id;
+        let others: Vec<Node> = successor_list
+            .iter()
+            .filter(|node| node.id != id)
+            .cloned()
+            .collect();
+        let successor_list = if others.is_empty() {
+            successor_list
+        } else {
+            others
+        };
+
+        let mut state = self.shared_state_mut();
+        let capacity = state.successor_list_size.get();
+        if let Some(successor) = successor_list.first() {
+            if state.successor_list.first() != Some(successor) {
+                self.emit(NodeEvent::SuccessorChanged(successor.clone()));
+            }
+        }
+        let previous = std::mem::take(&mut state.successor_list);
 
         let items = if (successor_list.len() as usize) < capacity {
             successor_list.len()
@@ -143,19 +476,28 @@ impl Db {
         for i in 0..items {
             state.successor_list.push(successor_list[i].clone());
         }
+        if state.successor_list != previous {
+            self.changed();
+        }
+        #[cfg(feature = "persistence")]
+        self.persist(&state);
 
         drop(state)
     }
 
     /// Get the successor list of the node
     pub(crate) fn successor_list(&self) -> Vec<Node> {
-        let state = self.shared_state();
+        let state = self.state();
         state.successor_list.clone()
     }
 
     /// Get the closest preceding node
     /// This is used to find a node that is possibly responsible for a key
     ///
+    /// Both the finger table and the successor list are considered, so the successors can
+    /// shorten the lookup while the fingers are stale, e.g. right after the node joined the ring.
+    /// The candidate closest to the key on the ring wins.
+    ///
     /// # Arguments
     ///
     /// * `node_id` - The id of the current node
@@ -165,64 +507,216 @@ impl Db {
     ///
     /// The closest preceding node for the key
     pub(crate) fn closest_preceding_node(&self, node_id: u64, id: u64) -> Option<Node> {
-        let state = self.shared_state();
-
-        let fingers = state.finger_table.clone();
-        drop(state);
-
-        for finger in fingers.iter().rev() {
-            if Node::is_between_on_ring_exclusive(finger.node.id.into(), node_id, id) {
-                return Some(finger.node.clone());
-            }
-        }
+        let state = self.state();
 
-        None
+        state
+            .finger_table
+            .iter()
+            .map(|finger| &finger.node)
+            .chain(state.successor_list.iter())
+            .filter(|node| Node::is_between_on_ring_exclusive(node.id.into(), node_id, id))
+            .max_by_key(|node| node.id.0.wrapping_sub(node_id))
+            .cloned()
     }
 
     pub(crate) fn update_finger(&self, finger_id: usize, node: Node) {
-        let mut state = self.shared_state();
-        state.finger_table[finger_id].node = node;
+        let mut state = self.shared_state_mut();
+        if state.finger_table[finger_id].node != node {
+            state.finger_table[finger_id].node = node;
+            self.changed();
+        }
 
         drop(state);
     }
 
     pub(crate) fn finger_table(&self) -> Vec<Finger> {
-        let state = self.shared_state();
+        let state = self.state();
         state.finger_table.clone()
     }
 
-    fn shared_state(&self) -> std::sync::MutexGuard<State> {
-        let lock = self.shared.state.lock();
-        if let Ok(state) = lock {
-            return state;
-        } else {
-            log::error!("Could not lock state, error: {}", lock.unwrap_err());
-            panic!("Could not lock state");
+    /// Subscribe to the changes of the neighbours
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<NodeEvent> {
+        self.shared.events.subscribe()
+    }
+
+    /// Broadcast the event to the subscribers
+    ///
+    /// Sending never blocks, a subscriber which falls behind misses the oldest events.
+    pub(crate) fn emit(&self, event: NodeEvent) {
+        // Fails only when there are no subscribers
+        let _ = self.shared.events.send(event);
+    }
+
+    /// Get the generation of the routing state of the node
+    ///
+    /// It changes whenever the predecessor, the successor list or a finger changes, so anything
+    /// derived from the routing state can tell it's out of date.
+    pub(crate) fn generation(&self) -> u64 {
+        self.shared.generation.load(Ordering::Acquire)
+    }
+
+    /// Start a new generation of the routing state, see [`generation`](Self::generation)
+    fn changed(&self) {
+        self.shared.generation.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Save the neighbours of the node to disk, if the persistence is enabled
+    ///
+    /// It's called with the state locked for writing, so the saves happen in the same order as
+    /// the changes.
+    #[cfg(feature = "persistence")]
+    fn persist(&self, state: &State) {
+        if let Some(persistence) = &self.shared.persistence {
+            if let Err(err) = persistence.save(state.predecessor.as_ref(), &state.successor_list) {
+                log::error!("Failed to persist the node state: {:?}", err);
+            }
+        }
+    }
+
+    /// Get a snapshot of the routing state
+    ///
+    /// The state is locked only to take the snapshot, which is cheap, and the snapshot can be
+    /// kept across an `.await`. It doesn't see the later updates, and it's consistent: the
+    /// predecessor, the successor list and the fingers are read at once.
+    pub(crate) fn state(&self) -> Arc<State> {
+        match self.shared.state.read() {
+            Ok(state) => state.clone(),
+            Err(err) => {
+                log::error!("Could not lock state for reading, error: {}", err);
+                panic!("Could not lock state");
+            }
+        }
+    }
+
+    /// Wait for the other writers which await to be done, and start writing
+    ///
+    /// The state itself is never locked across an `.await`, the returned writer can be. It
+    /// serializes the writers which read the state, await, e.g. for other nodes to respond, and
+    /// update it, so they don't overwrite each other's updates. Every such writer of the node
+    /// service takes it, the updates made without it don't await between their reads and their
+    /// writes, e.g. the handling of a leaving node.
+    pub(crate) async fn write_async(&self) -> AsyncWriter<'_> {
+        AsyncWriter {
+            db: self,
+            _guard: self.shared.writer.lock().await,
+        }
+    }
+
+    /// Lock the state for writing
+    fn shared_state_mut(&self) -> StateWriter<'_> {
+        match self.shared.state.write() {
+            Ok(state) => StateWriter(state),
+            Err(err) => {
+                log::error!("Could not lock state for writing, error: {}", err);
+                panic!("Could not lock state");
+            }
         }
     }
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use crate::NodeId;
 
     use super::*;
     use std::net::SocketAddr;
 
+    /// A node listening on a port derived from its id
+    pub(crate) fn node(id: u64) -> Node {
+        let port = 42000 + (id % 1000) as u16;
+        Node::with_id(NodeId(id), SocketAddr::from(([127, 0, 0, 1], port)))
+    }
+
     #[test]
     fn test_new() {
         let node = Node::with_id(NodeId(1), SocketAddr::from(([127, 0, 0, 1], 42001)));
-        let store = NodeStore::new(node.clone(), 3);
+        let store = NodeStore::new(node.clone(), 3, NonZeroUsize::new(3).unwrap());
         let store = store.db();
 
         assert_eq!(store.successor(), node);
         assert_eq!(store.predecessor(), None);
     }
 
+    #[test]
+    fn test_concurrent_reads() {
+        let store = NodeStore::new(node(10), 3, NonZeroUsize::new(3).unwrap()).db();
+        let lists = [
+            vec![node(20), node(30), node(40)],
+            vec![node(25), node(35), node(45)],
+        ];
+        store.set_successor_list(lists[0].clone());
+
+        std::thread::scope(|scope| {
+            let readers: Vec<_> = (0..4)
+                .map(|_| {
+                    scope.spawn(|| {
+                        for _ in 0..1000 {
+                            let state = store.state();
+                            assert!(lists.iter().any(|list| state.successor_list() == list));
+                            assert_eq!(
+                                state.finger_table().len(),
+                                Finger::FINGER_TABLE_SIZE as usize
+                            );
+                        }
+                    })
+                })
+                .collect();
+
+            for i in 0..1000 {
+                store.set_successor_list(lists[i % 2].clone());
+            }
+            for reader in readers {
+                reader.join().unwrap();
+            }
+        });
+    }
+
+    #[test]
+    fn test_snapshot_is_not_updated() {
+        let store = NodeStore::new(node(10), 3, NonZeroUsize::new(3).unwrap()).db();
+        store.set_successor_list(vec![node(20), node(30)]);
+
+        let snapshot = store.state();
+        store.set_successor(node(15));
+        store.set_predecessor(node(5));
+
+        assert_eq!(snapshot.successor_list(), &[node(20), node(30)]);
+        assert_eq!(snapshot.predecessor(), None);
+        assert_eq!(
+            store.state().successor_list(),
+            &[node(15), node(20), node(30)]
+        );
+        assert_eq!(store.state().predecessor(), Some(&node(5)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_async_writers_are_serialized() {
+        let store = NodeStore::new(node(10), 3, NonZeroUsize::new(3).unwrap()).db();
+
+        let writer = store.write_async().await;
+        let other = store.clone();
+        let waiting = tokio::spawn(async move {
+            let writer = other.write_async().await;
+            writer.set_successor(node(30));
+        });
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        assert!(!waiting.is_finished());
+
+        // The state itself is not locked, the readers and the other updates go on
+        store.set_predecessor(node(5));
+        writer.set_successor(node(20));
+        assert_eq!(store.successor(), node(20));
+
+        drop(writer);
+        waiting.await.unwrap();
+        assert_eq!(store.successor(), node(30));
+        assert_eq!(store.predecessor(), Some(node(5)));
+    }
+
     #[test]
     fn test_predecessor() {
         let node = Node::with_id(NodeId(1), SocketAddr::from(([127, 0, 0, 1], 42001)));
-        let store = NodeStore::new(node.clone(), 3);
+        let store = NodeStore::new(node.clone(), 3, NonZeroUsize::new(3).unwrap());
         let predecessor = Node::with_id(NodeId(2), SocketAddr::from(([127, 0, 0, 1], 42002)));
         assert_eq!(store.db().predecessor(), None);
         store.db().set_predecessor(predecessor.clone());
@@ -236,7 +730,7 @@ mod tests {
     #[test]
     fn test_successor() {
         let node = Node::with_id(NodeId(1), SocketAddr::from(([127, 0, 0, 1], 42001)));
-        let store = NodeStore::new(node.clone(), 3);
+        let store = NodeStore::new(node.clone(), 3, NonZeroUsize::new(3).unwrap());
         let successor = Node::with_id(NodeId(2), SocketAddr::from(([127, 0, 0, 1], 42002)));
         assert_eq!(store.db().successor(), node);
         store.db().set_successor(successor.clone());
@@ -244,10 +738,150 @@ mod tests {
         assert_eq!(store.db().successor(), successor);
     }
 
+    #[test]
+    fn test_successor_list_after_successor_change() {
+        let store = NodeStore::new(node(10), 3, NonZeroUsize::new(3).unwrap()).db();
+        store.set_successor_list(vec![node(20), node(30), node(40)]);
+
+        // A node joined between the current node and its successor
+        store.set_successor(node(15));
+        assert_eq!(store.successor_list(), vec![node(15), node(20), node(30)]);
+
+        // A successor further down the list is promoted
+        store.set_successor(node(30));
+        assert_eq!(store.successor_list(), vec![node(30)]);
+
+        store.set_successor_list(vec![node(30), node(40), node(5)]);
+        store.set_successor(node(40));
+        assert_eq!(store.successor_list(), vec![node(40), node(5)]);
+
+        // A successor which doesn't precede any node of the list
+        store.set_successor(node(8));
+        assert_eq!(store.successor_list(), vec![node(8)]);
+
+        store.set_successor(node(10));
+        assert_eq!(store.successor_list(), vec![node(10)]);
+        assert_eq!(store.successor_list_size(), 3);
+    }
+
+    #[test]
+    fn test_successor_list_without_the_node_itself() {
+        let store = NodeStore::new(node(10), 3, NonZeroUsize::new(3).unwrap()).db();
+
+        // The successors of a small ring wrap around back to the node
+        store.set_successor_list(vec![node(20), node(10), node(30)]);
+        assert_eq!(store.successor_list(), vec![node(20), node(30)]);
+
+        store.set_successor_list(vec![node(10), node(20)]);
+        assert_eq!(store.successor(), node(20));
+
+        // The node alone in the ring is its own successor
+        store.set_successor_list(vec![node(10)]);
+        assert_eq!(store.successor_list(), vec![node(10)]);
+    }
+
+    #[test]
+    fn test_predecessor_list() {
+        let store = NodeStore::new(node(50), 3, NonZeroUsize::new(3).unwrap()).db();
+        assert!(store.predecessor_list().is_empty());
+
+        // Ignored without a predecessor
+        store.set_predecessor_list(vec![node(30)]);
+        assert!(store.predecessor_list().is_empty());
+
+        // The nodes which don't precede the predecessor are dropped, the list wraps around
+        store.set_predecessor(node(40));
+        store.set_predecessor_list(vec![node(45), node(90), node(30), node(50), node(20)]);
+        assert_eq!(store.predecessor_list(), vec![node(40), node(30), node(20)]);
+
+        // A closer predecessor keeps the previous one as its predecessor
+        store.set_predecessor(node(45));
+        assert_eq!(store.predecessor_list(), vec![node(45), node(40), node(30)]);
+
+        assert_eq!(store.replace_predecessor(&node(45)), Some(node(40)));
+        assert_eq!(store.predecessor_list(), vec![node(40), node(30)]);
+        // The predecessor changed in the meantime
+        assert_eq!(store.replace_predecessor(&node(45)), Some(node(40)));
+
+        store.unset_predecessor();
+        assert!(store.predecessor_list().is_empty());
+        assert_eq!(store.replace_predecessor(&node(40)), None);
+    }
+
+    #[test]
+    fn test_successor_list_size_independent_of_replication_factor() {
+        let store = NodeStore::new(node(10), 2, NonZeroUsize::new(4).unwrap()).db();
+        assert_eq!(store.replication_factor(), 2);
+        assert_eq!(store.successor_list_size(), 4);
+
+        store.set_successor_list(vec![node(20), node(30), node(40), node(50), node(60)]);
+        assert_eq!(
+            store.successor_list(),
+            vec![node(20), node(30), node(40), node(50)]
+        );
+
+        store.set_successor(node(15));
+        assert_eq!(
+            store.successor_list(),
+            vec![node(15), node(20), node(30), node(40)]
+        );
+    }
+
+    #[test]
+    fn test_events() {
+        let store = NodeStore::new(node(10), 3, NonZeroUsize::new(3).unwrap());
+        let mut events = store.db().subscribe();
+
+        store.db().set_predecessor(node(5));
+        store.db().set_predecessor(node(5));
+        store.db().unset_predecessor();
+        store.db().unset_predecessor();
+        store.db().set_successor(node(20));
+        store.db().set_successor(node(20));
+        store.db().set_successor_list(vec![node(20), node(30)]);
+        store.db().set_successor_list(vec![node(30)]);
+
+        assert_eq!(
+            events.try_recv(),
+            Ok(NodeEvent::PredecessorChanged(Some(node(5))))
+        );
+        assert_eq!(events.try_recv(), Ok(NodeEvent::PredecessorChanged(None)));
+        assert_eq!(events.try_recv(), Ok(NodeEvent::SuccessorChanged(node(20))));
+        assert_eq!(events.try_recv(), Ok(NodeEvent::SuccessorChanged(node(30))));
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_generation_changes_with_the_routing_state() {
+        let db = NodeStore::new(node(10), 3, NonZeroUsize::new(3).unwrap()).db();
+        let mut generation = db.generation();
+        let mut changed = || {
+            let previous = std::mem::replace(&mut generation, db.generation());
+            previous != generation
+        };
+
+        db.set_predecessor(node(5));
+        assert!(changed());
+        db.set_predecessor(node(5));
+        assert!(!changed());
+        db.set_successor(node(20));
+        assert!(changed());
+        db.set_successor_list(vec![node(20)]);
+        assert!(!changed());
+        db.set_successor_list(vec![node(20), node(30)]);
+        assert!(changed());
+        db.update_finger(0, node(20));
+        assert!(changed());
+        db.update_finger(0, node(20));
+        assert!(!changed());
+        db.unset_predecessor();
+        assert!(changed());
+    }
+
     #[test]
     fn test_closest_preceding_node() {
         let node = Node::with_id(NodeId(10), SocketAddr::from(([127, 0, 0, 1], 42001)));
-        let store = NodeStore::new(node.clone(), 3);
+        let store = NodeStore::new(node.clone(), 3, NonZeroUsize::new(3).unwrap());
         let successor = Node::with_id(NodeId(20), SocketAddr::from(([127, 0, 0, 1], 42002)));
         let predecessor = Node::with_id(NodeId(1), SocketAddr::from(([127, 0, 0, 1], 42003)));
         store.db().set_predecessor(predecessor.clone());
@@ -258,7 +892,7 @@ mod tests {
             .iter()
             .enumerate()
             .for_each(|(i, finger)| {
-                if finger._start < 20 {
+                if finger.start < 20 {
                     store.db().update_finger(i, successor.clone());
                 } else {
                     store.db().update_finger(i, predecessor.clone());
@@ -281,16 +915,101 @@ mod tests {
         assert_eq!(store.db().closest_preceding_node(10, 28), Some(successor));
     }
 
+    #[test]
+    fn test_closest_preceding_node_with_stale_fingers() {
+        let store = NodeStore::new(node(10), 3, NonZeroUsize::new(3).unwrap());
+
+        // The fingers still point at the node itself, only the successors are known
+        store
+            .db()
+            .set_successor_list(vec![node(20), node(30), node(40)]);
+
+        assert_eq!(store.db().closest_preceding_node(10, 15), None);
+        assert_eq!(store.db().closest_preceding_node(10, 25), Some(node(20)));
+        assert_eq!(store.db().closest_preceding_node(10, 35), Some(node(30)));
+        assert_eq!(store.db().closest_preceding_node(10, 100), Some(node(40)));
+        assert_eq!(store.db().closest_preceding_node(10, 5), Some(node(40)));
+    }
+
+    #[test]
+    fn test_closest_preceding_node_prefers_closest_candidate() {
+        let store = NodeStore::new(node(10), 3, NonZeroUsize::new(3).unwrap());
+
+        // A stale finger at 20 and a fresh successor list which knows nodes closer to the key
+        for i in 0..Finger::FINGER_TABLE_SIZE as usize {
+            store.db().update_finger(i, node(20));
+        }
+        store
+            .db()
+            .set_successor_list(vec![node(20), node(30), node(40)]);
+        assert_eq!(store.db().closest_preceding_node(10, 45), Some(node(40)));
+
+        // And the other way around, a finger closer to the key than any successor
+        store
+            .db()
+            .update_finger(Finger::FINGER_TABLE_SIZE as usize - 1, node(90));
+        assert_eq!(store.db().closest_preceding_node(10, 95), Some(node(90)));
+        assert_eq!(store.db().closest_preceding_node(10, 45), Some(node(40)));
+    }
+
+    #[test]
+    fn test_closest_preceding_node_wrapping_around() {
+        let near_max = u64::MAX - 100;
+        let store = NodeStore::new(node(u64::MAX - 200), 3, NonZeroUsize::new(3).unwrap());
+
+        // The node's fingers span the end of the ring, the ids are past `u64::MAX`
+        for i in 0..Finger::FINGER_TABLE_SIZE as usize {
+            let finger = match i {
+                0..=6 => near_max,
+                7..=8 => 5,
+                _ => 1 << 40,
+            };
+            store.db().update_finger(i, node(finger));
+        }
+        store
+            .db()
+            .set_successor_list(vec![node(near_max), node(u64::MAX), node(5)]);
+
+        let (node_id, max) = (u64::MAX - 200, u64::MAX);
+        assert_eq!(
+            store.db().closest_preceding_node(node_id, 3),
+            Some(node(max))
+        );
+        assert_eq!(
+            store.db().closest_preceding_node(node_id, 10),
+            Some(node(5))
+        );
+        assert_eq!(
+            store.db().closest_preceding_node(node_id, 1 << 41),
+            Some(node(1 << 40))
+        );
+        assert_eq!(
+            store.db().closest_preceding_node(node_id, max),
+            Some(node(near_max))
+        );
+        assert_eq!(
+            store.db().closest_preceding_node(node_id, 0),
+            Some(node(max))
+        );
+        assert_eq!(store.db().closest_preceding_node(node_id, max - 150), None);
+
+        // A lookup of the node's own id goes around the whole ring
+        assert_eq!(
+            store.db().closest_preceding_node(node_id, node_id),
+            Some(node(1 << 40))
+        );
+    }
+
     #[test]
     fn test_successor_list_init() {
         let node = Node::with_id(NodeId(10), SocketAddr::from(([127, 0, 0, 1], 42001)));
-        let store = NodeStore::new(node.clone(), 3);
+        let store = NodeStore::new(node.clone(), 3, NonZeroUsize::new(3).unwrap());
 
         let successors = store
             .db()
             .shared
             .state
-            .lock()
+            .read()
             .unwrap()
             .successor_list
             .clone();
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::num::NonZeroU32;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::metrics::metrics;
+
+/// Token bucket of a single peer
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Limit of the inbound requests, per peer
+///
+/// Every peer gets its own token bucket, which holds up to one second worth of requests and is
+/// refilled at the given rate. A request is admitted if there is a token left in the bucket of
+/// its peer, so a peer flooding the node only exhausts its own bucket.
+///
+/// > **Note**
+/// >
+/// > The peers are identified by their IP address, not by their port, as a node opens new
+/// > connections from new ports.
+#[derive(Debug)]
+pub struct RateLimiter {
+    rate: f64,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+impl RateLimiter {
+    /// Number of tracked peers above which the full buckets are dropped
+    const MAX_IDLE_PEERS: usize = 1024;
+
+    /// Create a rate limiter
+    ///
+    /// # Arguments
+    ///
+    /// * `requests_per_second` - The number of requests every peer can send per second
+    pub fn new(requests_per_second: NonZeroU32) -> Self {
+        Self {
+            rate: requests_per_second.get() as f64,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a token from the bucket of the peer
+    ///
+    /// Returns `false` if the peer exceeded its rate, in which case the request should be
+    /// rejected.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The address of the peer which sent the request
+    pub fn check(&self, peer: IpAddr) -> bool {
+        let admitted = self.check_at(peer, Instant::now());
+        if !admitted {
+            metrics().rate_limited_requests.inc();
+        }
+
+        admitted
+    }
+
+    fn check_at(&self, peer: IpAddr, now: Instant) -> bool {
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() > Self::MAX_IDLE_PEERS {
+            // A full bucket is the same as a missing one
+            buckets.retain(|_, bucket| self.refill(*bucket, now).tokens < self.rate);
+        }
+
+        let bucket = buckets.entry(peer).or_insert(Bucket {
+            tokens: self.rate,
+            updated: now,
+        });
+        *bucket = self.refill(*bucket, now);
+        if bucket.tokens < 1.0 {
+            return false;
+        }
+        bucket.tokens -= 1.0;
+
+        true
+    }
+
+    fn refill(&self, bucket: Bucket, now: Instant) -> Bucket {
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        Bucket {
+            tokens: (bucket.tokens + elapsed * self.rate).min(self.rate),
+            updated: now,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+    use std::time::Duration;
+
+    use super::*;
+
+    fn peer(last: u8) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::new(10, 0, 0, last))
+    }
+
+    #[test]
+    fn rejects_requests_over_the_rate() {
+        let limiter = RateLimiter::new(NonZeroU32::new(3).unwrap());
+        let now = Instant::now();
+
+        assert!(limiter.check_at(peer(1), now));
+        assert!(limiter.check_at(peer(1), now));
+        assert!(limiter.check_at(peer(1), now));
+        assert!(!limiter.check_at(peer(1), now));
+
+        // The other peers are not affected
+        assert!(limiter.check_at(peer(2), now));
+    }
+
+    #[test]
+    fn refills_over_time() {
+        let limiter = RateLimiter::new(NonZeroU32::new(2).unwrap());
+        let now = Instant::now();
+
+        assert!(limiter.check_at(peer(1), now));
+        assert!(limiter.check_at(peer(1), now));
+        assert!(!limiter.check_at(peer(1), now));
+
+        let later = now + Duration::from_millis(500);
+        assert!(limiter.check_at(peer(1), later));
+        assert!(!limiter.check_at(peer(1), later));
+
+        // The bucket never holds more than one second worth of requests
+        let much_later = now + Duration::from_secs(60);
+        assert!(limiter.check_at(peer(1), much_later));
+        assert!(limiter.check_at(peer(1), much_later));
+        assert!(!limiter.check_at(peer(1), much_later));
+    }
+
+    #[test]
+    fn forgets_idle_peers() {
+        let limiter = RateLimiter::new(NonZeroU32::new(1).unwrap());
+        let now = Instant::now();
+        for i in 0..=RateLimiter::MAX_IDLE_PEERS as u32 {
+            assert!(limiter.check_at(IpAddr::V4(Ipv4Addr::from(i)), now));
+        }
+
+        limiter.check_at(peer(1), now + Duration::from_secs(1));
+        assert_eq!(limiter.buckets.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::NodeId;
+
+/// A node id is serialized as a number.
+impl Serialize for NodeId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.0)
+    }
+}
+
+/// A node id can be deserialized from a number or from a hex string, with or
+/// without the `0x` prefix, e.g. `"0x13401e3a5c1cd23d"`.
+impl<'de> Deserialize<'de> for NodeId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(NodeIdVisitor)
+    }
+}
+
+struct NodeIdVisitor;
+
+impl<'de> Visitor<'de> for NodeIdVisitor {
+    type Value = NodeId;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an unsigned 64-bit integer or a hex string")
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<NodeId, E> {
+        Ok(NodeId(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<NodeId, E> {
+        u64::try_from(value)
+            .map(NodeId)
+            .map_err(|_| E::invalid_value(de::Unexpected::Signed(value), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<NodeId, E> {
+        let hex = value
+            .strip_prefix("0x")
+            .or_else(|| value.strip_prefix("0X"))
+            .unwrap_or(value);
+
+        u64::from_str_radix(hex, 16)
+            .map(NodeId)
+            .map_err(|_| E::invalid_value(de::Unexpected::Str(value), &self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use crate::{Finger, Node, NodeId, Snapshot};
+
+    #[test]
+    fn node_id_as_number() {
+        let id = NodeId(1386533886225134333);
+
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, "1386533886225134333");
+        assert_eq!(serde_json::from_str::<NodeId>(&json).unwrap(), id);
+    }
+
+    #[test]
+    fn node_id_from_hex() {
+        assert_eq!(
+            serde_json::from_str::<NodeId>("\"0x133ddc1e3a1b5cfd\"").unwrap(),
+            NodeId(0x133ddc1e3a1b5cfd)
+        );
+        assert_eq!(
+            serde_json::from_str::<NodeId>("\"ffffffffffffffff\"").unwrap(),
+            NodeId(u64::MAX)
+        );
+
+        assert!(serde_json::from_str::<NodeId>("\"0xnothex\"").is_err());
+        assert!(serde_json::from_str::<NodeId>("\"0x10000000000000000\"").is_err());
+        assert!(serde_json::from_str::<NodeId>("-1").is_err());
+    }
+
+    #[test]
+    fn node_round_trip() {
+        let v4 = Node::with_id(10, SocketAddr::from(([127, 0, 0, 1], 42001)));
+        let v6 = Node::with_id(20, "[::1]:42002".parse::<SocketAddr>().unwrap());
+
+        let json = serde_json::to_string(&v4).unwrap();
+        assert_eq!(json, r#"{"id":10,"addr":"127.0.0.1:42001"}"#);
+        assert_eq!(serde_json::from_str::<Node>(&json).unwrap(), v4);
+
+        let json = serde_json::to_string(&v6).unwrap();
+        assert_eq!(json, r#"{"id":20,"addr":"[::1]:42002"}"#);
+        assert_eq!(serde_json::from_str::<Node>(&json).unwrap(), v6);
+    }
+
+    #[test]
+    fn finger_round_trip() {
+        let node = Node::with_id(10, SocketAddr::from(([127, 0, 0, 1], 42001)));
+        let finger = Finger::init_finger_table(node.clone()).remove(3);
+
+        let json = serde_json::to_string(&finger).unwrap();
+        assert_eq!(
+            json,
+            r#"{"start":18,"end":26,"node":{"id":10,"addr":"127.0.0.1:42001"}}"#
+        );
+
+        let finger: Finger = serde_json::from_str(&json).unwrap();
+        assert_eq!(finger.start, 18);
+        assert_eq!(finger.node, node);
+    }
+
+    #[test]
+    fn snapshot_round_trip() {
+        let node = Node::with_id(10, SocketAddr::from(([127, 0, 0, 1], 42001)));
+        let successor = Node::with_id(20, SocketAddr::from(([127, 0, 0, 1], 42002)));
+        let snapshot = Snapshot {
+            node: node.clone(),
+            predecessor: None,
+            successor_list: vec![successor.clone()],
+            finger_table: Finger::init_finger_table(node.clone()),
+        };
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let snapshot: Snapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(snapshot.node, node);
+        assert_eq!(snapshot.predecessor, None);
+        assert_eq!(snapshot.successor_list, vec![successor]);
+        assert_eq!(snapshot.finger_table.len(), 64);
+        assert_eq!(snapshot.finger_table[3].start, 18);
+    }
+}
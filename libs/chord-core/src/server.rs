@@ -1,55 +1,953 @@
-use std::{net::SocketAddr, sync::Arc, time::Duration};
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::{future::Future, net::SocketAddr, sync::Arc, time::Duration};
 
-use crate::{Client, Node, NodeService};
+use error_stack::{Report, Result};
+use futures::future::BoxFuture;
+use futures::FutureExt;
 
+use crate::client::ClientFactory;
+use crate::error::ServiceError;
+use crate::metrics::metrics;
+use crate::{Client, Node, NodeId, NodeService, RetryPolicy, RingCheck};
+
+/// Parameters of a node, shared by all the transports
+///
+/// The server builds one config and hands it to the transport it runs, so the transports can't
+/// disagree on the defaults.
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// Id of the node, if not set it's derived from the address
+    pub node_id: Option<NodeId>,
+    /// Addresses of nodes in the ring to join, tried in order. If empty, the node creates a new
+    /// ring if [`bootstrap`](JoinConfig::bootstrap) is set
+    pub ring: Vec<SocketAddr>,
+    /// Source of seeds resolved again before every attempt to join the ring, tried before the
+    /// ones in `ring`, see [`join_ring_with`]
+    pub seed_resolver: Option<SeedResolver>,
+    /// Number of nodes the data is replicated on
+    pub replication_factor: usize,
+    /// Number of successors to keep track of, defaults to the replication factor
+    pub successor_list_size: Option<NonZeroUsize>,
+    /// Maximum number of requests the maintenance tasks send to other nodes at the same time,
+    /// if not set the requests are not limited, see [`NodeService::with_outbound_limit`]
+    pub max_outbound_requests: Option<NonZeroUsize>,
+    /// Whether the nodes which notify the node are pinged before they're adopted as its
+    /// predecessor, see [`NodeService::with_notify_verification`]
+    pub verify_notify: bool,
+    /// Cache of the lookups, if not set the lookups are not cached, see
+    /// [`NodeService::with_lookup_cache`]
+    pub lookup_cache: Option<LookupCacheConfig>,
+    /// Retries of the lookups failing transiently, see [`NodeService::with_lookup_retry`]
+    pub lookup_retry: RetryPolicy,
+    /// Whether the clients of the fingers are set up after they're fixed, see
+    /// [`NodeService::with_finger_warm_up`]
+    pub warm_up_fingers: bool,
+    /// Intervals of the periodic maintenance tasks
+    pub background: BackgroundConfig,
+    /// Retry policy of joining the ring
+    pub join: JoinConfig,
+    /// Directory where the state of the node is kept, requires the `persistence` feature
+    pub data_dir: Option<PathBuf>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            node_id: None,
+            ring: vec![],
+            seed_resolver: None,
+            replication_factor: 3,
+            successor_list_size: None,
+            max_outbound_requests: None,
+            verify_notify: false,
+            lookup_cache: None,
+            lookup_retry: RetryPolicy::default(),
+            warm_up_fingers: false,
+            background: BackgroundConfig::default(),
+            join: JoinConfig::default(),
+            data_dir: None,
+        }
+    }
+}
+
+/// Start the node: create its service, join the ring and start the background tasks
+///
+/// The node doesn't join the ring if it restored its neighbours, see [`start_node`].
+///
+/// # Arguments
+///
+/// * `addr` - The address of the node
+/// * `config` - The parameters of the node
+///
+/// # Panics
+///
+/// If the node fails to join the ring through all the seeds, or if it has no seed and it's not
+/// allowed to create a new ring, see [`JoinConfig::bootstrap`].
+pub async fn launch_node<T: Client + Clone + Sync + Send + 'static>(
+    addr: SocketAddr,
+    config: &ServerConfig,
+) -> Arc<NodeService<T>> {
+    launch_node_with(addr, config, None).await
+}
+
+/// Start the node like [`launch_node`], creating its clients with the given factory
+///
+/// It lets a transport configure the clients of the node with more than the address of their
+/// node, e.g. with the TLS settings or the cluster secret of the node, see
+/// [`NodeService::with_client_factory`].
+///
+/// # Arguments
+///
+/// * `addr` - The address of the node
+/// * `config` - The parameters of the node
+/// * `clients` - The factory of the clients, if not set they're created with [`Client::init`]
+pub async fn launch_node_with<T: Client + Clone + Sync + Send + 'static>(
+    addr: SocketAddr,
+    config: &ServerConfig,
+    clients: Option<ClientFactory<T>>,
+) -> Arc<NodeService<T>> {
+    let (node_service, restored) = start_node_with(addr, config, clients).await;
+
+    let resolver = config.seed_resolver.as_ref();
+    if !restored {
+        if let Err(err) =
+            join_ring_with(node_service.clone(), &config.ring, resolver, config.join).await
+        {
+            log::error!("{:?}", err);
+            if config.ring.is_empty() && resolver.is_none() {
+                panic!("No ring to join, and the node is not allowed to create a new one");
+            }
+            panic!("Failed to join the ring through any of {:?}", config.ring);
+        }
+    }
+    background_tasks(node_service.clone(), config.background);
+
+    node_service
+}
+
+/// Create the service of the node
+///
+/// If [`data_dir`](ServerConfig::data_dir) is set, the node keeps its neighbours in it and tries
+/// to restore the ones it had before a restart. Returns the service and whether the neighbours
+/// were restored, in which case the node doesn't need to join the ring again.
+///
+/// > **Note**
+/// >
+/// > Setting `data_dir` requires the `persistence` feature.
+///
+/// # Arguments
+///
+/// * `addr` - The address of the node
+/// * `config` - The parameters of the node, the ring it joins and its background tasks are
+///   ignored
+pub async fn start_node<T: Client + Clone + Sync + Send + 'static>(
+    addr: SocketAddr,
+    config: &ServerConfig,
+) -> (Arc<NodeService<T>>, bool) {
+    start_node_with(addr, config, None).await
+}
+
+/// Create the service of the node like [`start_node`], creating its clients with the given
+/// factory
+///
+/// The factory is set before the neighbours are restored, so they're pinged with the
+/// configured clients.
+///
+/// # Arguments
+///
+/// * `addr` - The address of the node
+/// * `config` - The parameters of the node, the ring it joins and its background tasks are
+///   ignored
+/// * `clients` - The factory of the clients, if not set they're created with [`Client::init`]
+pub async fn start_node_with<T: Client + Clone + Sync + Send + 'static>(
+    addr: SocketAddr,
+    config: &ServerConfig,
+    clients: Option<ClientFactory<T>>,
+) -> (Arc<NodeService<T>>, bool) {
+    let id = config.node_id.unwrap_or_else(|| addr.into());
+    let replication_factor = config.replication_factor;
+    let successor_list_size = config.successor_list_size;
+    let configure = |mut node_service: NodeService<T>| {
+        if let Some(factory) = clients.clone() {
+            node_service = node_service.with_client_factory(move |addr| factory(addr));
+        }
+        node_service = node_service
+            .with_notify_verification(config.verify_notify)
+            .with_lookup_retry(config.lookup_retry)
+            .with_finger_warm_up(config.warm_up_fingers);
+        if let Some(max_requests) = config.max_outbound_requests {
+            node_service = node_service.with_outbound_limit(max_requests);
+        }
+        if let Some(cache) = config.lookup_cache {
+            node_service = node_service.with_lookup_cache(cache.capacity, cache.ttl);
+        }
+        node_service
+    };
+    let Some(data_dir) = config.data_dir.as_deref() else {
+        return (
+            Arc::new(configure(NodeService::with_id(
+                id,
+                addr,
+                replication_factor,
+                successor_list_size,
+            ))),
+            false,
+        );
+    };
+
+    #[cfg(feature = "persistence")]
+    {
+        let node_service = match NodeService::with_persistence(
+            id,
+            addr,
+            replication_factor,
+            successor_list_size,
+            data_dir,
+        ) {
+            Ok(node_service) => Arc::new(configure(node_service)),
+            Err(err) => {
+                log::error!("Failed to open the data directory: {:?}", err);
+                panic!("Failed to open the data directory: {:?}", data_dir);
+            }
+        };
+        let restored = node_service.restore().await;
+
+        (node_service, restored)
+    }
+
+    #[cfg(not(feature = "persistence"))]
+    {
+        log::error!("Persistence is not enabled, cannot use {:?}", data_dir);
+        panic!("The `persistence` feature is required to use a data directory");
+    }
+}
+
+/// Size of the cache of the lookups, see [`NodeService::with_lookup_cache`]
+#[derive(Debug, Clone, Copy)]
+pub struct LookupCacheConfig {
+    /// Maximum number of cached successors
+    pub capacity: NonZeroUsize,
+    /// How long a successor is cached for
+    pub ttl: Duration,
+}
+
+/// Retry policy of [`join_ring`]
+#[derive(Debug, Clone, Copy)]
+pub struct JoinConfig {
+    /// How many times to try the whole list of seeds
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on every following retry
+    pub base_delay: Duration,
+    /// Upper bound of the delay between the retries
+    pub max_delay: Duration,
+    /// Whether the node creates a new ring when it has no other node to join through. Not set by
+    /// default: joining fails instead, so a node given a wrong seed doesn't form a ring of its own
+    pub bootstrap: bool,
+}
+
+impl Default for JoinConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            bootstrap: false,
+        }
+    }
+}
+
+impl JoinConfig {
+    /// Delay before the given retry
+    ///
+    /// The delay grows exponentially with the retry, up to [`max_delay`](Self::max_delay).
+    /// A random jitter of up to half of the delay is subtracted from it, so nodes started at
+    /// the same time don't retry in lockstep against the same seed.
+    ///
+    /// # Arguments
+    ///
+    /// * `retry` - The number of the retry, starting at 1
+    pub fn delay(&self, retry: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            .min(self.max_delay);
+
+        delay - delay.mul_f64(rand::random::<f64>() / 2.0)
+    }
+}
+
+/// Join the ring through one of the seed nodes
+///
+/// The seeds are tried in order until one of them lets the node join the ring. If none of them
+/// does, the whole list is retried after a [delay](JoinConfig::delay), up to
+/// [`max_retries`](JoinConfig::max_retries) times. Returns an error if all the attempts failed,
+/// in which case the node is not part of the ring.
+///
+/// The address of the node itself is skipped, so the same list of seeds can be given to all the
+/// nodes of the ring. If there is no other seed, the node creates a new ring instead, as long as
+/// [`bootstrap`](JoinConfig::bootstrap) is set. Otherwise it fails right away, unless a resolver
+/// may find other seeds on the next attempts.
+///
+/// # Arguments
+///
+/// * `node_service` - The service of the joining node
+/// * `seeds` - Addresses of nodes in the ring
+/// * `config` - The retry policy
 pub async fn join_ring<T: Client + Clone + Sync + Send + 'static>(
     node_service: Arc<NodeService<T>>,
-    ring: SocketAddr,
-    max_retries: u32,
-) {
-    // TODO: make this configurable
-    const WAIT_BETWEEN_RETRIES: Duration = Duration::from_secs(3);
+    seeds: &[SocketAddr],
+    config: JoinConfig,
+) -> Result<(), ServiceError> {
+    join_ring_with(node_service, seeds, None, config).await
+}
+
+/// Join the ring through one of the seed nodes, resolving the seeds again before every attempt
+///
+/// Works like [`join_ring`], the seeds returned by the resolver being tried before the given
+/// ones. As they're resolved again before every retry, the node can join through the nodes
+/// which replaced the ones it was first given, e.g. the pods of a restarted deployment.
+///
+/// # Arguments
+///
+/// * `node_service` - The service of the joining node
+/// * `seeds` - Addresses of nodes in the ring, tried after the resolved ones
+/// * `resolver` - Source of the seeds, if not set only `seeds` are tried
+/// * `config` - The retry policy
+pub async fn join_ring_with<T: Client + Clone + Sync + Send + 'static>(
+    node_service: Arc<NodeService<T>>,
+    seeds: &[SocketAddr],
+    resolver: Option<&SeedResolver>,
+    config: JoinConfig,
+) -> Result<(), ServiceError> {
     let mut attempt = 0;
     loop {
         attempt += 1;
-        log::info!("{} attempt to join ring: {:?}", attempt, ring);
+        let mut seeds = match resolver {
+            Some(resolver) => resolver.resolve(seeds).await,
+            None => seeds.to_vec(),
+        };
+        seeds.retain(|seed| *seed != node_service.addr());
+        if seeds.is_empty() {
+            if config.bootstrap {
+                log::info!("No other node to join, creating a new ring");
+                return Ok(());
+            }
+            if resolver.is_none() {
+                return Err(Report::new(ServiceError::JoinFailed).attach_printable(
+                    "No other node to join, and the node is not allowed to create a new ring",
+                ));
+            }
+            log::warn!("No other node to join yet, bootstrapping a new ring is not allowed");
+        }
 
-        let node = Node::new(ring);
-        tokio::time::sleep(Duration::from_secs(1)).await;
+        for seed in &seeds {
+            log::info!("{} attempt to join ring: {:?}", attempt, seed);
 
-        if let Ok(_) = node_service.join(node).await {
-            log::info!("Joined ring: {:?}", ring);
-            break;
-        } else {
-            if attempt >= max_retries {
-                log::error!("Failed to join ring: {:?}", ring);
-                panic!("Failed to join ring: {:?}", ring)
+            match node_service.join(Node::new(*seed)).await {
+                Ok(_) => {
+                    log::info!("Joined ring through seed: {:?}", seed);
+                    return Ok(());
+                }
+                Err(err) => log::warn!("Failed to join ring through {:?}: {}", seed, err),
             }
         }
 
-        tokio::time::sleep(WAIT_BETWEEN_RETRIES).await;
+        if attempt >= config.max_retries {
+            return Err(Report::new(ServiceError::JoinFailed)
+                .attach_printable(format!("Seeds: {:?}", seeds))
+                .attach_printable(format!("Attempts: {}", attempt)));
+        }
+
+        let delay = config.delay(attempt);
+        log::info!("Retrying to join ring in {:?}", delay);
+        tokio::time::sleep(delay).await;
     }
 }
 
+/// Source of the seeds of a node, e.g. the DNS records of its peers
+///
+/// The seeds are resolved again before every attempt to join the ring, see [`join_ring_with`].
+#[derive(Clone)]
+pub struct SeedResolver {
+    resolve: Arc<dyn Fn() -> BoxFuture<'static, Vec<SocketAddr>> + Send + Sync>,
+}
+
+impl SeedResolver {
+    /// Create the resolver from a function looking up the seeds
+    ///
+    /// # Arguments
+    ///
+    /// * `resolve` - Looks up the seeds, in the order they should be tried
+    pub fn new<F, Fut>(resolve: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Vec<SocketAddr>> + Send + 'static,
+    {
+        Self {
+            resolve: Arc::new(move || resolve().boxed()),
+        }
+    }
+
+    /// Resolve the seeds, followed by the fallback ones which were not resolved
+    ///
+    /// # Arguments
+    ///
+    /// * `fallback` - Seeds tried after the resolved ones, or alone if none is resolved
+    pub async fn resolve(&self, fallback: &[SocketAddr]) -> Vec<SocketAddr> {
+        let mut seeds = (self.resolve)().await;
+        for seed in fallback {
+            if !seeds.contains(seed) {
+                seeds.push(*seed);
+            }
+        }
+
+        seeds
+    }
+}
+
+impl fmt::Debug for SeedResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SeedResolver").finish_non_exhaustive()
+    }
+}
+
+/// Intervals of the periodic maintenance tasks
+#[derive(Debug, Clone, Copy)]
+pub struct BackgroundConfig {
+    /// How often to check the successor and stabilize the node
+    pub stabilize_interval: Duration,
+    /// How often to refresh the finger table
+    pub fix_fingers_interval: Duration,
+    /// How many fingers to refresh every time
+    pub fix_fingers: FixFingers,
+    /// How often to check if the predecessor is alive and to refresh the predecessor list
+    pub check_predecessor_interval: Duration,
+    /// How often to refresh the successor list
+    pub reconcile_successors_interval: Duration,
+    /// How often to check that the successor chain loops back to the node, see
+    /// [`NodeService::check_ring`]. The ring is not checked if not set, as every check walks
+    /// the whole ring
+    pub check_ring_interval: Option<Duration>,
+    /// How many successors to follow when checking the ring, see [`NodeService::check_ring`]
+    pub check_ring_max_hops: usize,
+    /// How often to audit the routing state of the node, see [`NodeService::audit`]. The state
+    /// is not audited if not set
+    pub audit_interval: Option<Duration>,
+    /// How often to ping all the neighbours at once, see [`NodeService::sweep_neighbours`]. The
+    /// neighbours are not swept if not set
+    pub sweep_interval: Option<Duration>,
+}
+
+impl Default for BackgroundConfig {
+    fn default() -> Self {
+        Self {
+            stabilize_interval: Duration::from_secs(1),
+            fix_fingers_interval: Duration::from_secs(1),
+            fix_fingers: FixFingers::default(),
+            check_predecessor_interval: Duration::from_secs(1),
+            reconcile_successors_interval: Duration::from_secs(1),
+            check_ring_interval: None,
+            check_ring_max_hops: 64,
+            audit_interval: None,
+            sweep_interval: None,
+        }
+    }
+}
+
+/// How many fingers are refreshed by every run of the fix fingers task
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FixFingers {
+    /// All the fingers, see [`NodeService::fix_fingers`]
+    #[default]
+    All,
+    /// The next finger in turn, see [`NodeService::fix_next_finger`]. It puts a much lower load
+    /// on the ring, so the interval can be shorter
+    One,
+}
+
+/// Start the periodic maintenance tasks of the node
+///
+/// Each task runs in its own loop, waiting for its interval between the runs.
+///
+/// # Arguments
+///
+/// * `node_service` - The service of the node
+/// * `config` - The intervals of the tasks
 pub fn background_tasks<T: Client + Clone + Sync + Send + 'static>(
     node_service: Arc<NodeService<T>>,
+    config: BackgroundConfig,
 ) {
-    let service = node_service.clone();
+    periodically(
+        config.stabilize_interval,
+        node_service.clone(),
+        |service| async move {
+            if let Err(err) = service.check_successor().await {
+                metrics().task_failure("check_successor");
+                log::error!("Check successor error: {:?}", err);
+            }
 
-    tokio::spawn(async move {
-        loop {
-            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
             if let Err(err) = service.stabilize().await {
+                metrics().task_failure("stabilize");
                 log::error!("Stabilize error: {:?}", err);
             }
 
+            service.record_state();
+        },
+    );
+
+    periodically(
+        config.check_predecessor_interval,
+        node_service.clone(),
+        |service| async move {
             if let Err(err) = service.check_predecessor().await {
+                metrics().task_failure("check_predecessor");
                 log::error!("Check predecessor error: {:?}", err);
             }
+            service.reconcile_predecessors().await;
+
+            service.record_state();
+        },
+    );
 
+    periodically(
+        config.reconcile_successors_interval,
+        node_service.clone(),
+        |service| async move {
             service.reconcile_successors().await;
 
-            service.fix_fingers().await;
+            service.record_state();
+        },
+    );
+
+    if let Some(interval) = config.check_ring_interval {
+        let max_hops = config.check_ring_max_hops;
+        periodically(interval, node_service.clone(), move |service| async move {
+            match service.check_ring(max_hops).await {
+                Ok(RingCheck::Closed) => metrics().partition_suspected.set(0),
+                Ok(RingCheck::Partitioned) => {
+                    metrics().partition_suspected.set(1);
+                    log::warn!("Successor chain doesn't loop back to this node, the ring may be partitioned");
+                }
+                Ok(RingCheck::Inconclusive) => {
+                    metrics().partition_suspected.set(0);
+                    log::debug!(
+                        "Successor chain doesn't loop back to this node within {} hops, the ring may be larger",
+                        max_hops
+                    );
+                }
+                Err(err) => {
+                    metrics().task_failure("check_ring");
+                    log::error!("Check ring error: {:?}", err);
+                }
+            }
+        });
+    }
+
+    if let Some(interval) = config.audit_interval {
+        periodically(interval, node_service.clone(), |service| async move {
+            for anomaly in service.audit() {
+                metrics().audit_anomaly(anomaly.kind());
+                log::warn!("Audit found an anomaly: {}", anomaly);
+            }
+        });
+    }
+
+    if let Some(interval) = config.sweep_interval {
+        periodically(interval, node_service.clone(), |service| async move {
+            let dead = service.sweep_neighbours().await;
+            if !dead.is_empty() {
+                log::info!("Sweep found {} dead neighbours", dead.len());
+            }
+
+            service.record_state();
+        });
+    }
+
+    periodically(
+        config.fix_fingers_interval,
+        node_service,
+        move |service| async move {
+            match config.fix_fingers {
+                FixFingers::All => service.fix_fingers().await,
+                FixFingers::One => service.fix_next_finger().await,
+            }
+        },
+    );
+}
+
+/// Spawn a task which runs `task` every `interval`
+fn periodically<T, F, Fut>(interval: Duration, node_service: Arc<NodeService<T>>, task: F)
+where
+    T: Client + Clone + Sync + Send + 'static,
+    F: Fn(Arc<NodeService<T>>) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send,
+{
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            task(node_service.clone()).await;
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use error_stack::Report;
+    use futures::FutureExt;
+
+    use super::{JoinConfig, SeedResolver, ServerConfig};
+    use crate::client::{ClientError, ClientFactory, MockClient};
+    use crate::error::ServiceError;
+    use crate::service::tests::{get_lock, node, MTX};
+    use crate::{NodeId, NodeService};
+
+    #[tokio::test(start_paused = true)]
+    async fn join_ring_through_next_seed() {
+        let _m = get_lock(&MTX);
+        let ctx = MockClient::init_context();
+
+        ctx.expect().returning(|addr: SocketAddr| {
+            let mut client = MockClient::new();
+            if addr.port() == 42010 {
+                client.expect_find_successor().returning(|_| {
+                    Err(Report::new(ClientError::ConnectionFailed(
+                        "Connection refused".to_string(),
+                    )))
+                });
+            }
+            if addr.port() == 42020 {
+                client
+                    .expect_find_successor()
+                    .times(1)
+                    .returning(|_| Ok(node(30)));
+            }
+
+            client
+        });
+        let service: Arc<NodeService<MockClient>> = Arc::new(NodeService::new(
+            SocketAddr::from(([127, 0, 0, 1], 42001)),
+            3,
+            None,
+        ));
+
+        let config = JoinConfig {
+            max_retries: 1,
+            ..Default::default()
+        };
+        super::join_ring(service.clone(), &[node(10).addr, node(20).addr], config)
+            .await
+            .unwrap();
+
+        assert_eq!(service.store().successor().id, NodeId(30));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn join_ring_retries_failing_seed() {
+        let _m = get_lock(&MTX);
+        let ctx = MockClient::init_context();
+        let calls = Arc::new(AtomicU32::new(0));
+
+        let seed_calls = calls.clone();
+        ctx.expect().returning(move |addr: SocketAddr| {
+            let mut client = MockClient::new();
+            if addr.port() == 42010 {
+                let calls = seed_calls.clone();
+                client.expect_find_successor().returning(move |_| {
+                    if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                        Err(Report::new(ClientError::ConnectionFailed(
+                            "Connection refused".to_string(),
+                        )))
+                    } else {
+                        Ok(node(30))
+                    }
+                });
+            }
+
+            client
+        });
+        let service: Arc<NodeService<MockClient>> = Arc::new(NodeService::new(
+            SocketAddr::from(([127, 0, 0, 1], 42001)),
+            3,
+            None,
+        ));
+
+        let config = JoinConfig {
+            max_retries: 3,
+            ..Default::default()
+        };
+        super::join_ring(service.clone(), &[node(10).addr], config)
+            .await
+            .unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(service.store().successor().id, NodeId(30));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn join_ring_fails_after_max_retries() {
+        let _m = get_lock(&MTX);
+        let ctx = MockClient::init_context();
+
+        ctx.expect().returning(|_| {
+            let mut client = MockClient::new();
+            client.expect_find_successor().returning(|_| {
+                Err(Report::new(ClientError::ConnectionFailed(
+                    "Connection refused".to_string(),
+                )))
+            });
+
+            client
+        });
+        let service: Arc<NodeService<MockClient>> = Arc::new(NodeService::new(
+            SocketAddr::from(([127, 0, 0, 1], 42001)),
+            3,
+            None,
+        ));
+
+        let result =
+            super::join_ring(service.clone(), &[node(10).addr], JoinConfig::default()).await;
+
+        assert!(matches!(
+            result.unwrap_err().current_context(),
+            ServiceError::JoinFailed
+        ));
+        assert_eq!(service.store().successor().id, service.id());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn join_ring_skips_the_node_itself() {
+        let _m = get_lock(&MTX);
+        let ctx = MockClient::init_context();
+
+        ctx.expect().returning(|addr: SocketAddr| {
+            let mut client = MockClient::new();
+            if addr.port() == 42020 {
+                client
+                    .expect_find_successor()
+                    .times(1)
+                    .returning(|_| Ok(node(30)));
+            }
+
+            client
+        });
+        let addr = SocketAddr::from(([127, 0, 0, 1], 42001));
+        let service: Arc<NodeService<MockClient>> = Arc::new(NodeService::new(addr, 3, None));
+
+        let bootstrap = JoinConfig {
+            bootstrap: true,
+            ..Default::default()
+        };
+        super::join_ring(service.clone(), &[addr], bootstrap)
+            .await
+            .unwrap();
+        assert_eq!(service.store().successor().id, service.id());
+
+        super::join_ring(
+            service.clone(),
+            &[addr, node(20).addr],
+            JoinConfig::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(service.store().successor().id, NodeId(30));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn join_ring_without_bootstrap_fails_when_there_is_no_other_node() {
+        let _m = get_lock(&MTX);
+        let ctx = MockClient::init_context();
+        ctx.expect().never();
+
+        let addr = SocketAddr::from(([127, 0, 0, 1], 42001));
+        let service: Arc<NodeService<MockClient>> = Arc::new(NodeService::new(addr, 3, None));
+        let config = JoinConfig {
+            bootstrap: false,
+            ..Default::default()
+        };
+
+        for seeds in [vec![], vec![addr]] {
+            let result = super::join_ring(service.clone(), &seeds, config).await;
+            assert!(matches!(
+                result.unwrap_err().current_context(),
+                ServiceError::JoinFailed
+            ));
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn join_ring_without_bootstrap_waits_for_the_resolver_to_find_a_node() {
+        let _m = get_lock(&MTX);
+        let ctx = MockClient::init_context();
+
+        ctx.expect().returning(|addr: SocketAddr| {
+            let mut client = MockClient::new();
+            if addr.port() == 42020 {
+                client
+                    .expect_find_successor()
+                    .times(1)
+                    .returning(|_| Ok(node(30)));
+            }
+
+            client
+        });
+        let service: Arc<NodeService<MockClient>> = Arc::new(NodeService::new(
+            SocketAddr::from(([127, 0, 0, 1], 42001)),
+            3,
+            None,
+        ));
+        // No other node is up yet when the node first looks for one
+        let resolutions = Arc::new(AtomicU32::new(0));
+        let counter = resolutions.clone();
+        let resolver = SeedResolver::new(move || {
+            let first = counter.fetch_add(1, Ordering::SeqCst) == 0;
+            async move {
+                if first {
+                    vec![]
+                } else {
+                    vec![node(20).addr]
+                }
+            }
+        });
+
+        let config = JoinConfig {
+            max_retries: 3,
+            bootstrap: false,
+            ..Default::default()
+        };
+        super::join_ring_with(service.clone(), &[], Some(&resolver), config)
+            .await
+            .unwrap();
+
+        assert_eq!(resolutions.load(Ordering::SeqCst), 2);
+        assert_eq!(service.store().successor().id, NodeId(30));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn join_ring_resolves_the_seeds_before_every_attempt() {
+        let _m = get_lock(&MTX);
+        let ctx = MockClient::init_context();
+
+        ctx.expect().returning(|addr: SocketAddr| {
+            let mut client = MockClient::new();
+            if addr.port() == 42010 {
+                client.expect_find_successor().returning(|_| {
+                    Err(Report::new(ClientError::ConnectionFailed(
+                        "Connection refused".to_string(),
+                    )))
+                });
+            }
+            if addr.port() == 42020 {
+                client
+                    .expect_find_successor()
+                    .times(1)
+                    .returning(|_| Ok(node(30)));
+            }
+
+            client
+        });
+        let service: Arc<NodeService<MockClient>> = Arc::new(NodeService::new(
+            SocketAddr::from(([127, 0, 0, 1], 42001)),
+            3,
+            None,
+        ));
+        // The first seed is gone by the time the join is retried, and replaced by another node
+        let resolutions = Arc::new(AtomicU32::new(0));
+        let counter = resolutions.clone();
+        let resolver = SeedResolver::new(move || {
+            let first = counter.fetch_add(1, Ordering::SeqCst) == 0;
+            async move { vec![if first { node(10).addr } else { node(20).addr }] }
+        });
+
+        let config = JoinConfig {
+            max_retries: 3,
+            ..Default::default()
+        };
+        super::join_ring_with(service.clone(), &[], Some(&resolver), config)
+            .await
+            .unwrap();
+
+        assert_eq!(resolutions.load(Ordering::SeqCst), 2);
+        assert_eq!(service.store().successor().id, NodeId(30));
+    }
+
+    #[tokio::test]
+    async fn resolved_seeds_are_followed_by_the_fallback_ones() {
+        let resolver = SeedResolver::new(|| async { vec![node(10).addr, node(20).addr] });
+
+        let seeds = resolver.resolve(&[node(20).addr, node(30).addr]).await;
+        assert_eq!(seeds, vec![node(10).addr, node(20).addr, node(30).addr]);
+
+        let resolver = SeedResolver::new(|| async { vec![] });
+        assert_eq!(
+            resolver.resolve(&[node(30).addr]).await,
+            vec![node(30).addr]
+        );
+    }
+
+    #[test]
+    fn join_delay_grows_exponentially_with_jitter() {
+        let config = JoinConfig {
+            max_retries: 10,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(10),
+            ..Default::default()
+        };
+
+        for (retry, max) in [(1, 1), (2, 2), (3, 4), (4, 8), (5, 10), (10, 10)] {
+            let max = Duration::from_secs(max);
+            for _ in 0..100 {
+                let delay = config.delay(retry);
+                assert!(
+                    max / 2 <= delay && delay <= max,
+                    "{delay:?} for retry {retry}"
+                );
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn start_node_with_configured_id() {
+        let addr = SocketAddr::from(([127, 0, 0, 1], 42001));
+
+        let config = ServerConfig {
+            node_id: Some(NodeId(7)),
+            ..Default::default()
+        };
+        let (service, restored) = super::start_node::<MockClient>(addr, &config).await;
+        assert!(!restored);
+        assert_eq!(service.id(), NodeId(7));
+        assert_eq!(service.store().successor().id, NodeId(7));
+
+        let (service, _) = super::start_node::<MockClient>(addr, &Default::default()).await;
+        assert_eq!(service.id(), NodeId::from(addr));
+    }
+
+    #[tokio::test]
+    async fn start_node_with_a_client_factory() {
+        let addr = SocketAddr::from(([127, 0, 0, 1], 42001));
+        let created = Arc::new(AtomicU32::new(0));
+        let factory: ClientFactory<MockClient> = Arc::new({
+            let created = created.clone();
+            move |_| {
+                created.fetch_add(1, Ordering::SeqCst);
+                async {
+                    let mut client = MockClient::new();
+                    client.expect_ping().returning(|| Ok(NodeId(10)));
+                    client
+                }
+                .boxed()
+            }
+        });
+        let (service, _) = super::start_node_with(addr, &Default::default(), Some(factory)).await;
+
+        assert!(service.can_reach(node(10)).await.unwrap());
+        assert_eq!(created.load(Ordering::SeqCst), 1);
+    }
+}
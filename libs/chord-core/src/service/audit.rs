@@ -0,0 +1,119 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::{Node, NodeId};
+
+/// Broken invariant of the routing state of a node, found by
+/// [`NodeService::audit`](crate::NodeService::audit)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Anomaly {
+    /// The successor list is empty, so the node has no successor
+    EmptySuccessorList,
+    /// An entry of the successor list doesn't follow the previous one on the ring, or is the
+    /// node itself while it knows other nodes
+    MisorderedSuccessor { index: usize, node: Node },
+    /// An entry of the successor list recently failed to respond
+    DeadSuccessor { index: usize, node: Node },
+    /// The predecessor is the node itself while it knows other nodes, or is placed between the
+    /// node and its successor
+    MisplacedPredecessor { predecessor: Node, successor: Node },
+    /// The interval of a finger doesn't start where the interval of the previous one ends
+    OverlappingFingers { index: usize },
+}
+
+impl Anomaly {
+    /// Get the kind of the anomaly, used as the label of its metric
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Anomaly::EmptySuccessorList => "empty_successor_list",
+            Anomaly::MisorderedSuccessor { .. } => "misordered_successor",
+            Anomaly::DeadSuccessor { .. } => "dead_successor",
+            Anomaly::MisplacedPredecessor { .. } => "misplaced_predecessor",
+            Anomaly::OverlappingFingers { .. } => "overlapping_fingers",
+        }
+    }
+}
+
+impl fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Anomaly::EmptySuccessorList => write!(f, "The successor list is empty"),
+            Anomaly::MisorderedSuccessor { index, node } => write!(
+                f,
+                "Successor {} at {} doesn't follow the previous successor on the ring",
+                node, index
+            ),
+            Anomaly::DeadSuccessor { index, node } => write!(
+                f,
+                "Successor {} at {} recently failed to respond",
+                node, index
+            ),
+            Anomaly::MisplacedPredecessor {
+                predecessor,
+                successor,
+            } => write!(
+                f,
+                "Predecessor {} doesn't precede the node, its successor is {}",
+                predecessor, successor
+            ),
+            Anomaly::OverlappingFingers { index } => write!(
+                f,
+                "Finger {} doesn't start where the previous finger ends",
+                index
+            ),
+        }
+    }
+}
+
+/// Nodes which recently failed to respond to the maintenance tasks
+///
+/// The oldest node is forgotten past [`CAPACITY`](Self::CAPACITY) nodes, and a node is
+/// forgotten as soon as it responds again.
+#[derive(Debug, Default)]
+pub(crate) struct FailedNodes {
+    nodes: VecDeque<NodeId>,
+}
+
+impl FailedNodes {
+    /// Maximum number of nodes remembered
+    pub(crate) const CAPACITY: usize = 64;
+
+    /// Remember that the node failed to respond
+    pub(crate) fn insert(&mut self, id: NodeId) {
+        self.remove(id);
+        if self.nodes.len() == Self::CAPACITY {
+            self.nodes.pop_front();
+        }
+        self.nodes.push_back(id);
+    }
+
+    /// Forget the node, it responded again
+    pub(crate) fn remove(&mut self, id: NodeId) {
+        self.nodes.retain(|node| *node != id);
+    }
+
+    /// Check if the node recently failed to respond
+    pub(crate) fn contains(&self, id: NodeId) -> bool {
+        self.nodes.contains(&id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_nodes_forget_the_oldest_past_capacity() {
+        let mut failed = FailedNodes::default();
+        for id in 0..=FailedNodes::CAPACITY as u64 {
+            failed.insert(NodeId(id));
+        }
+
+        assert!(!failed.contains(NodeId(0)));
+        assert!(failed.contains(NodeId(1)));
+        assert!(failed.contains(NodeId(FailedNodes::CAPACITY as u64)));
+
+        failed.remove(NodeId(1));
+        assert!(!failed.contains(NodeId(1)));
+    }
+}
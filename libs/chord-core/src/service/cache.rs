@@ -0,0 +1,280 @@
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
+use std::time::{Duration, Instant};
+
+use crate::{Node, NodeId};
+
+/// Cache of the successors found by the lookups, see
+/// [`NodeService::with_lookup_cache`](crate::NodeService::with_lookup_cache)
+///
+/// The successor `s` of an id is also the successor of every id between it and `s`, so an entry
+/// maps the range of ids `[start, s]` to `s`. The entries are keyed by the id of their node, so
+/// the entry covering an id is the first one at or after it on the ring.
+///
+/// An entry is only valid for the routing state of the node it was found with, the whole cache
+/// is cleared when the [generation](crate::node::store::Db::generation) of the routing state
+/// changes.
+#[derive(Debug)]
+pub(crate) struct LookupCache {
+    /// Maximum number of entries, the least recently used one is evicted past it
+    capacity: NonZeroUsize,
+    /// How long an entry is used for after it's inserted
+    ttl: Duration,
+    /// Generation of the routing state the entries were found with
+    generation: u64,
+    entries: BTreeMap<NodeId, Entry>,
+    /// Incremented on every use of an entry, to find the least recently used one
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    /// First id of the range, the last one is the id of the node
+    start: NodeId,
+    node: Node,
+    inserted: Instant,
+    last_used: u64,
+}
+
+impl Entry {
+    /// Check if the id is in the range of the entry
+    fn covers(&self, id: NodeId) -> bool {
+        Self::distance(id, self.node.id) <= Self::distance(self.start, self.node.id)
+    }
+
+    /// Get the distance from `from` to `to`, going clockwise on the ring
+    fn distance(from: NodeId, to: NodeId) -> u64 {
+        to.0.wrapping_sub(from.0)
+    }
+}
+
+impl LookupCache {
+    /// Create an empty cache
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The maximum number of entries
+    /// * `ttl` - How long an entry is used for after it's inserted
+    pub(crate) fn new(capacity: NonZeroUsize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            generation: 0,
+            entries: BTreeMap::new(),
+            clock: 0,
+        }
+    }
+
+    /// Get the cached successor of the id
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id to find the successor for
+    /// * `generation` - The current generation of the routing state
+    pub(crate) fn get(&mut self, id: NodeId, generation: u64) -> Option<Node> {
+        self.refresh(generation);
+
+        let key = *self
+            .entries
+            .range(id..)
+            .next()
+            .or_else(|| self.entries.iter().next())?
+            .0;
+        let entry = self.entries.get_mut(&key)?;
+        if entry.inserted.elapsed() >= self.ttl {
+            self.entries.remove(&key);
+            return None;
+        }
+        if !entry.covers(id) {
+            return None;
+        }
+
+        self.clock += 1;
+        entry.last_used = self.clock;
+        Some(entry.node.clone())
+    }
+
+    /// Cache the successor of the id
+    ///
+    /// The entries the new one shows to be wrong are dropped or shortened. The successor is
+    /// ignored if it was found with an older generation of the routing state than the cached
+    /// ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id which was looked up
+    /// * `successor` - The successor found for the id
+    /// * `generation` - The generation of the routing state the successor was found with
+    pub(crate) fn insert(&mut self, id: NodeId, successor: Node, generation: u64) {
+        if generation < self.generation {
+            return;
+        }
+        self.refresh(generation);
+
+        // No node is between the id and its successor, the entries of such nodes are stale
+        let end = successor.id;
+        self.entries.retain(|&node, _| {
+            node == end || Entry::distance(id, node) >= Entry::distance(id, end)
+        });
+        // The next node is not responsible for the ids up to the successor anymore
+        let after_end = NodeId(end.0.wrapping_add(1));
+        let next = self
+            .entries
+            .range(after_end..)
+            .next()
+            .or_else(|| self.entries.iter().next())
+            .map(|(&node, _)| node)
+            .filter(|&node| node != end);
+        if let Some(next) = next.and_then(|node| self.entries.get_mut(&node)) {
+            if next.covers(end) {
+                next.start = after_end;
+            }
+        }
+
+        self.clock += 1;
+        let start = match self.entries.get(&end) {
+            Some(entry) if entry.node == successor && entry.covers(id) => entry.start,
+            _ => id,
+        };
+        self.entries.insert(
+            end,
+            Entry {
+                start,
+                node: successor,
+                inserted: Instant::now(),
+                last_used: self.clock,
+            },
+        );
+
+        if self.entries.len() > self.capacity.get() {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(&node, _)| node);
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+
+    /// Remove the entry of the node, e.g. because it left the ring
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The id of the node
+    pub(crate) fn evict(&mut self, node: NodeId) {
+        self.entries.remove(&node);
+    }
+
+    /// Clear the cache if the routing state changed since the entries were found
+    fn refresh(&mut self, generation: u64) {
+        if generation != self.generation {
+            self.entries.clear();
+            self.generation = generation;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::store::tests::node;
+
+    fn cache(capacity: usize) -> LookupCache {
+        LookupCache::new(
+            NonZeroUsize::new(capacity).unwrap(),
+            Duration::from_secs(60),
+        )
+    }
+
+    #[test]
+    fn ids_between_the_lookup_and_the_successor_are_cached() {
+        let mut cache = cache(8);
+        cache.insert(NodeId(10), node(20), 0);
+
+        assert_eq!(cache.get(NodeId(10), 0), Some(node(20)));
+        assert_eq!(cache.get(NodeId(15), 0), Some(node(20)));
+        assert_eq!(cache.get(NodeId(20), 0), Some(node(20)));
+        assert_eq!(cache.get(NodeId(9), 0), None);
+        assert_eq!(cache.get(NodeId(21), 0), None);
+    }
+
+    #[test]
+    fn range_is_extended_by_the_lookups_of_the_same_successor() {
+        let mut cache = cache(8);
+        cache.insert(NodeId(15), node(20), 0);
+        cache.insert(NodeId(10), node(20), 0);
+        cache.insert(NodeId(18), node(20), 0);
+
+        assert_eq!(cache.get(NodeId(12), 0), Some(node(20)));
+    }
+
+    #[test]
+    fn range_wraps_around_the_ring() {
+        let mut cache = cache(8);
+        cache.insert(NodeId(u64::MAX - 5), node(3), 0);
+
+        assert_eq!(cache.get(NodeId(u64::MAX), 0), Some(node(3)));
+        assert_eq!(cache.get(NodeId(1), 0), Some(node(3)));
+        assert_eq!(cache.get(NodeId(4), 0), None);
+    }
+
+    #[test]
+    fn new_generation_clears_the_cache() {
+        let mut cache = cache(8);
+        cache.insert(NodeId(10), node(20), 0);
+
+        assert_eq!(cache.get(NodeId(15), 1), None);
+        cache.insert(NodeId(30), node(40), 0);
+        assert_eq!(cache.get(NodeId(35), 1), None);
+    }
+
+    #[test]
+    fn expired_entries_are_not_used() {
+        let mut cache = LookupCache::new(NonZeroUsize::new(8).unwrap(), Duration::ZERO);
+        cache.insert(NodeId(10), node(20), 0);
+
+        assert_eq!(cache.get(NodeId(15), 0), None);
+    }
+
+    #[test]
+    fn least_recently_used_entry_is_evicted() {
+        let mut cache = cache(2);
+        cache.insert(NodeId(10), node(20), 0);
+        cache.insert(NodeId(30), node(40), 0);
+        assert!(cache.get(NodeId(15), 0).is_some());
+
+        cache.insert(NodeId(50), node(60), 0);
+
+        assert_eq!(cache.get(NodeId(15), 0), Some(node(20)));
+        assert_eq!(cache.get(NodeId(35), 0), None);
+        assert_eq!(cache.get(NodeId(55), 0), Some(node(60)));
+    }
+
+    #[test]
+    fn node_found_in_a_cached_range_replaces_the_stale_entries() {
+        let mut cache = cache(8);
+        cache.insert(NodeId(10), node(30), 0);
+
+        // A node joined at 20
+        cache.insert(NodeId(12), node(20), 0);
+        assert_eq!(cache.get(NodeId(15), 0), Some(node(20)));
+        assert_eq!(cache.get(NodeId(25), 0), Some(node(30)));
+        assert_eq!(cache.get(NodeId(20), 0), Some(node(20)));
+
+        // The node at 30 left, its ids belong to the node at 40
+        cache.insert(NodeId(22), node(40), 0);
+        assert_eq!(cache.get(NodeId(25), 0), Some(node(40)));
+    }
+
+    #[test]
+    fn evicted_node_is_not_used() {
+        let mut cache = cache(8);
+        cache.insert(NodeId(10), node(20), 0);
+
+        cache.evict(NodeId(20));
+
+        assert_eq!(cache.get(NodeId(15), 0), None);
+    }
+}
@@ -1,14 +1,29 @@
 use async_recursion::async_recursion;
 use error_stack::{Report, Result, ResultExt};
+use futures::future::join_all;
+use tokio::sync::Semaphore;
+use tracing::Instrument;
 
-use crate::client::{ClientError, ClientsPool};
+use crate::client::{ClientError, ClientsPool, LatencyStats};
+use crate::metrics::metrics;
 use crate::node::store::{Db, NodeStore};
-use crate::node::Finger;
-use crate::{Client, Node, NodeId};
+use crate::node::{Finger, NodeEvent};
+use crate::service::audit::FailedNodes;
+use crate::service::cache::LookupCache;
+use crate::service::error::ClientResultExt;
+use crate::{deadline, Client, Hasher, IntoNodeId, Node, NodeId};
+use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use std::vec;
 
+mod audit;
+mod cache;
+
+pub use audit::Anomaly;
 #[cfg(test)]
 pub(crate) mod tests;
 
@@ -17,266 +32,1965 @@ pub struct NodeService<C: Client> {
     id: NodeId,
     addr: SocketAddr,
     store: NodeStore,
+    /// Whether the node stabilized at least once since it started
+    stabilized: AtomicBool,
+    /// Index of the finger fixed by the next call of [`fix_next_finger`](Self::fix_next_finger)
+    next_finger: AtomicUsize,
+    /// Budget of the requests the maintenance tasks send to other nodes at the same time, see
+    /// [`with_outbound_limit`](Self::with_outbound_limit)
+    outbound: Semaphore,
+    /// Whether a node is pinged before it's adopted as the predecessor, see
+    /// [`with_notify_verification`](Self::with_notify_verification)
+    verify_notify: bool,
+    /// Successors found by the previous lookups, see [`with_lookup_cache`](Self::with_lookup_cache)
+    lookup_cache: Option<Mutex<LookupCache>>,
+    /// Retries of the lookups failing transiently, see [`with_lookup_retry`](Self::with_lookup_retry)
+    lookup_retry: RetryPolicy,
+    /// Whether the clients of the fingers are set up after they're fixed, see
+    /// [`with_finger_warm_up`](Self::with_finger_warm_up)
+    warm_up_fingers: bool,
+    /// Nodes which recently failed to respond, checked by [`audit`](Self::audit)
+    failed_nodes: Mutex<FailedNodes>,
+
+    clients: ClientsPool<C>,
+}
+
+/// Health of a node, as reported to load balancers and orchestrators
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HealthStatus {
+    /// The node stabilized at least once, so it's part of the ring
+    pub joined: bool,
+    /// The node knows its predecessor
+    pub has_predecessor: bool,
+    /// The successor of the node responds to pings
+    pub successor_reachable: bool,
+}
+
+/// Outcome of [`NodeService::check_ring`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RingCheck {
+    /// The successor chain loops back to the node
+    Closed,
+    /// The successor chain passes over the node or loops without it, so the node is not part
+    /// of the ring its successors form
+    Partitioned,
+    /// The hops ran out before getting back to the node, the ring may just be larger
+    Inconclusive,
+}
+
+/// Maintenance task of a node, which can be run on demand by an administrator
+///
+/// See [`NodeService::trigger`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaintenanceTask {
+    /// [`NodeService::stabilize`]
+    Stabilize,
+    /// [`NodeService::fix_fingers`]
+    FixFingers,
+    /// [`NodeService::reconcile_successors`]
+    ReconcileSuccessors,
+}
+
+impl std::fmt::Display for MaintenanceTask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MaintenanceTask::Stabilize => write!(f, "stabilize"),
+            MaintenanceTask::FixFingers => write!(f, "fix fingers"),
+            MaintenanceTask::ReconcileSuccessors => write!(f, "reconcile successors"),
+        }
+    }
+}
+
+/// Retry policy of the lookups which fail transiently
+///
+/// See [`NodeService::with_lookup_retry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// How many times a lookup is retried, the lookups are not retried if it's 0
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on every following retry
+    pub base_delay: Duration,
+    /// Upper bound of the delay between the retries
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            base_delay: Duration::from_millis(50),
+            max_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before the given retry
+    ///
+    /// The delay grows exponentially with the retry, up to [`max_delay`](Self::max_delay).
+    ///
+    /// # Arguments
+    ///
+    /// * `retry` - The number of the retry, starting at 1
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            .min(self.max_delay)
+    }
+}
+
+/// Routing state of a node, as reported to the tools crawling the ring
+///
+/// See [`NodeService::node_info`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeInfo {
+    pub id: NodeId,
+    pub addr: SocketAddr,
+    pub predecessor: Option<Node>,
+    /// The successors of the node, the first one is its immediate successor
+    pub successor_list: Vec<Node>,
+    /// The parameters the node is configured with, `None` if the node doesn't report them
+    pub params: Option<RingParams>,
+}
+
+/// Parameters of a node which must be the same on all the nodes of a ring
+///
+/// See [`NodeService::params`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RingParams {
+    /// The number of nodes the data is replicated on
+    pub replication_factor: usize,
+    /// The maximum number of successors the node keeps track of
+    pub successor_list_size: usize,
+    /// The size of the key space in bits, the ids being in `[0, 2^key_bits)`
+    pub key_bits: u8,
+}
+
+impl std::fmt::Display for RingParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "replication factor {}, successor list size {}, {}-bit key space",
+            self.replication_factor, self.successor_list_size, self.key_bits
+        )
+    }
+}
+
+/// State of a node, to back it up and restore it in another process
+///
+/// See [`NodeService::snapshot`] and [`NodeService::restore_snapshot`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    /// The node the snapshot was taken of
+    pub node: Node,
+    pub predecessor: Option<Node>,
+    pub successor_list: Vec<Node>,
+    pub finger_table: Vec<Finger>,
+}
+
+impl<C: Client + Clone + Sync + Send + 'static> NodeService<C> {
+    /// How long to wait for a ping when checking if a node is reachable, or for a call made
+    /// while writing the store
+    pub const REACHABILITY_TIMEOUT: Duration = Duration::from_millis(500);
+
+    /// Maximum number of concurrent lookups when fixing the fingers
+    pub const FIX_FINGERS_CONCURRENCY: usize = 8;
+
+    /// Maximum number of concurrent lookups of a batch
+    pub const FIND_SUCCESSORS_CONCURRENCY: usize = 8;
+
+    /// Maximum number of ids of a batch, see [`find_successors`](Self::find_successors)
+    pub const MAX_FIND_SUCCESSORS_BATCH: usize = 1024;
+
+    /// Maximum number of neighbours pinged at the same time by
+    /// [`sweep_neighbours`](Self::sweep_neighbours)
+    pub const SWEEP_CONCURRENCY: usize = 8;
+
+    /// Get the number of successors to keep track of, the replication factor if not set
+    ///
+    /// # Arguments
+    ///
+    /// * `replication_factor` - The number of nodes the data is replicated on
+    /// * `successor_list_size` - The number of successors to keep track of
+    ///
+    /// # Panics
+    ///
+    /// If the successor list size is not set and the replication factor is 0, the successor
+    /// list always holds at least the successor.
+    fn successor_list_capacity(
+        replication_factor: usize,
+        successor_list_size: Option<NonZeroUsize>,
+    ) -> NonZeroUsize {
+        successor_list_size
+            .or(NonZeroUsize::new(replication_factor))
+            .expect("The successor list size must be at least 1")
+    }
+
+    /// Create a new node service
+    ///
+    /// # Arguments
+    ///
+    /// * `socket_addr` - The address of the node
+    /// * `replication_factor` - The number of nodes the data is replicated on
+    /// * `successor_list_size` - The number of successors to keep track of, defaults to the
+    ///   replication factor, which must then be at least 1
+    pub fn new(
+        socket_addr: SocketAddr,
+        replication_factor: usize,
+        successor_list_size: Option<NonZeroUsize>,
+    ) -> Self {
+        let id: NodeId = socket_addr.into();
+        Self::with_id(id, socket_addr, replication_factor, successor_list_size)
+    }
+
+    /// Create a new node service, deriving the node's id with the given hasher
+    ///
+    /// # Arguments
+    ///
+    /// * `socket_addr` - The address of the node
+    /// * `replication_factor` - The number of nodes the data is replicated on
+    /// * `successor_list_size` - The number of successors to keep track of, defaults to the
+    ///   replication factor, which must then be at least 1
+    /// * `hasher` - The hasher used to derive the node's id from its address
+    pub fn with_hasher(
+        socket_addr: SocketAddr,
+        replication_factor: usize,
+        successor_list_size: Option<NonZeroUsize>,
+        hasher: &impl Hasher,
+    ) -> Self {
+        let id = hasher.hash(socket_addr.to_string().as_bytes());
+        Self::with_id(id, socket_addr, replication_factor, successor_list_size)
+    }
+
+    /// Create a new node service which saves its neighbours to disk
+    ///
+    /// The saved neighbours are not used until [`restore`](Self::restore) is called.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the node
+    /// * `socket_addr` - The address of the node
+    /// * `replication_factor` - The number of nodes the data is replicated on
+    /// * `successor_list_size` - The number of successors to keep track of, defaults to the
+    ///   replication factor, which must then be at least 1
+    /// * `path` - The directory where the state is kept
+    #[cfg(feature = "persistence")]
+    pub fn with_persistence(
+        id: impl Into<NodeId>,
+        socket_addr: SocketAddr,
+        replication_factor: usize,
+        successor_list_size: Option<NonZeroUsize>,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<Self, error::PersistenceError> {
+        use crate::node::persistence::Persistence;
+
+        let node = Node::with_id(id, socket_addr);
+        let persistence = Persistence::open(path, node.clone())?;
+        let store = NodeStore::with_persistence(
+            node.clone(),
+            replication_factor,
+            Self::successor_list_capacity(replication_factor, successor_list_size),
+            persistence,
+        );
+
+        Ok(Self {
+            id: node.id,
+            addr: socket_addr,
+            store,
+            stabilized: AtomicBool::new(false),
+            next_finger: AtomicUsize::new(0),
+            outbound: Semaphore::new(Semaphore::MAX_PERMITS),
+            verify_notify: false,
+            lookup_cache: None,
+            lookup_retry: RetryPolicy::default(),
+            warm_up_fingers: false,
+            failed_nodes: Default::default(),
+            clients: ClientsPool::default(),
+        })
+    }
+
+    /// Create a new node service with the given id, instead of deriving it from its address
+    ///
+    /// It lets a node keep its position on the ring when it's restarted on another address.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the node
+    /// * `addr` - The address of the node
+    /// * `replication_factor` - The number of nodes the data is replicated on
+    /// * `successor_list_size` - The number of successors to keep track of, defaults to the
+    ///   replication factor, which must then be at least 1
+    pub fn with_id(
+        id: impl Into<NodeId>,
+        addr: SocketAddr,
+        replication_factor: usize,
+        successor_list_size: Option<NonZeroUsize>,
+    ) -> Self {
+        let id = id.into();
+        let store = NodeStore::new(
+            Node::with_id(id, addr),
+            replication_factor,
+            Self::successor_list_capacity(replication_factor, successor_list_size),
+        );
+        Self {
+            id,
+            addr,
+            store,
+            stabilized: AtomicBool::new(false),
+            next_finger: AtomicUsize::new(0),
+            outbound: Semaphore::new(Semaphore::MAX_PERMITS),
+            verify_notify: false,
+            lookup_cache: None,
+            lookup_retry: RetryPolicy::default(),
+            warm_up_fingers: false,
+            failed_nodes: Default::default(),
+            clients: ClientsPool::default(),
+        }
+    }
+
+    /// Create the clients of the other nodes with the given factory, instead of
+    /// [`Client::init`]
+    ///
+    /// It lets a custom transport configure its clients with more than the address of their
+    /// node, or a test hand out clients prepared in advance.
+    ///
+    /// # Arguments
+    ///
+    /// * `factory` - The function creating the client of the node at the given address
+    pub fn with_client_factory<F, Fut>(mut self, factory: F) -> Self
+    where
+        F: Fn(SocketAddr) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = C> + Send + 'static,
+    {
+        self.clients = ClientsPool::with_factory(factory);
+        self
+    }
+
+    /// Limit the number of requests the maintenance tasks send to other nodes at the same time
+    ///
+    /// The budget is shared by [`stabilize`](Self::stabilize),
+    /// [`check_predecessor`](Self::check_predecessor), [`check_successor`](Self::check_successor),
+    /// [`reconcile_successors`](Self::reconcile_successors), [`check_ring`](Self::check_ring) and
+    /// the lookups of the fix fingers tasks, so it bounds the connections the node opens
+    /// regardless of the size of the ring. The tasks over the budget wait for a request to
+    /// finish. By default the requests are not limited.
+    ///
+    /// > **Note**
+    /// >
+    /// > The lookups forwarded on behalf of other nodes are not limited.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_requests` - The maximum number of concurrent requests, must be greater than zero
+    pub fn with_outbound_limit(mut self, max_requests: NonZeroUsize) -> Self {
+        self.outbound = Semaphore::new(max_requests.get());
+        self
+    }
+
+    /// Ping the nodes which notify the current node before adopting them as the predecessor
+    ///
+    /// A node is adopted only if it responds with its id within
+    /// [`REACHABILITY_TIMEOUT`](Self::REACHABILITY_TIMEOUT), so a buggy or unreachable peer can't
+    /// set a predecessor which would be removed by the next
+    /// [`check_predecessor`](Self::check_predecessor). By default the nodes are not verified.
+    ///
+    /// # Arguments
+    ///
+    /// * `verify` - Whether to ping the notifying nodes
+    pub fn with_notify_verification(mut self, verify: bool) -> Self {
+        self.verify_notify = verify;
+        self
+    }
+
+    /// Cache the successors found by the lookups, so the repeated lookups don't go around the
+    /// ring again
+    ///
+    /// The successor found for an id is also the successor of the ids between them, so a cached
+    /// successor serves a whole range of ids. The cache is cleared whenever the predecessor, the
+    /// successor list or a finger of the node changes. A cached successor is checked by asking
+    /// it for its predecessor before it's returned, so a lookup served from the cache costs a
+    /// single request: a successor which left the ring, or which another node joined in front
+    /// of, is evicted and the lookup goes through the ring. By default the lookups are not
+    /// cached.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The maximum number of cached successors, the least recently used one is
+    ///   evicted past it
+    /// * `ttl` - How long a successor is cached for
+    pub fn with_lookup_cache(mut self, capacity: NonZeroUsize, ttl: Duration) -> Self {
+        self.lookup_cache = Some(Mutex::new(LookupCache::new(capacity, ttl)));
+        self
+    }
+
+    /// Retry the lookups of [`find_successor`](Self::find_successor) which fail transiently
+    ///
+    /// A lookup is retried when a node on its route is unreachable or doesn't respond in time,
+    /// see [`ServiceError::is_transient`](error::ServiceError::is_transient). The route is
+    /// resolved again on every attempt, so a retry can go around a node which left the ring
+    /// during the lookup. The other errors are returned right away. By default the lookups are
+    /// not retried.
+    ///
+    /// > **Note**
+    /// >
+    /// > A lookup running in a [`deadline::scope`] is not retried past its deadline.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - How many times and how soon the lookups are retried
+    pub fn with_lookup_retry(mut self, policy: RetryPolicy) -> Self {
+        self.lookup_retry = policy;
+        self
+    }
+
+    /// Connect to the fingers in the background once they're fixed
+    ///
+    /// After [`fix_fingers`](Self::fix_fingers) or [`fix_next_finger`](Self::fix_next_finger),
+    /// the clients of the fingers the node isn't connected to yet are
+    /// [warmed up](ClientsPool::warm_up), so the first lookups routed through them don't pay for
+    /// the connection. The maintenance task doesn't wait for the connections: a finger which
+    /// doesn't respond is skipped quietly and connected to by the first call to it. By default
+    /// the clients are set up on their first use.
+    ///
+    /// # Arguments
+    ///
+    /// * `warm_up` - Whether to connect to the fingers after they're fixed
+    pub fn with_finger_warm_up(mut self, warm_up: bool) -> Self {
+        self.warm_up_fingers = warm_up;
+        self
+    }
+
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    /// Get the id of an application key on the ring
+    ///
+    /// The successor of the id, see [`find_successor`](Self::find_successor), is the node
+    /// responsible for the key. Byte and string keys are hashed with
+    /// [`key_to_id`](crate::key_to_id), a [`NodeId`] is taken as is.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to map onto the ring
+    pub fn key_id(key: impl IntoNodeId) -> NodeId {
+        key.into_node_id()
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Get the number of nodes the data is replicated on
+    pub fn replication_factor(&self) -> usize {
+        self.store().replication_factor()
+    }
+
+    /// Get the maximum number of successors the node keeps track of
+    pub fn successor_list_size(&self) -> usize {
+        self.store().successor_list_size()
+    }
+
+    /// Get the parameters of the node which must be the same on all the nodes of the ring
+    pub fn params(&self) -> RingParams {
+        RingParams {
+            replication_factor: self.replication_factor(),
+            successor_list_size: self.successor_list_size(),
+            key_bits: Finger::FINGER_TABLE_SIZE,
+        }
+    }
+
+    pub(crate) fn store(&self) -> Db {
+        self.store.db()
+    }
+
+    /// Find the successor of the given id.
+    ///
+    /// If the given id is in the range of the current node and its successor, the successor is returned.
+    /// Otherwise, the successor of the closest preceding node is returned.
+    ///
+    /// The lookups failing transiently are retried according to the
+    /// [retry policy](Self::with_lookup_retry).
+    ///
+    /// > **Note**
+    /// >
+    /// > If the lookup runs in a [`deadline::scope`], it fails with
+    /// > [`Timeout`](error::ServiceError::Timeout) once the deadline has passed,
+    /// > instead of being forwarded to the next node.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id to find the successor for
+    #[tracing::instrument(skip_all, fields(node.id = %self.id, node.addr = %self.addr, target = %id))]
+    pub async fn find_successor(&self, id: NodeId) -> Result<Node, error::ServiceError> {
+        metrics().rpc_call("find_successor");
+        self.lookup_with_retry(id).await
+    }
+
+    /// Find the successors of many ids at once
+    ///
+    /// The ids are looked up concurrently, at most
+    /// [`FIND_SUCCESSORS_CONCURRENCY`](Self::FIND_SUCCESSORS_CONCURRENCY) at a time, each like
+    /// [`find_successor`](Self::find_successor), with its deadline and retries. The results are
+    /// in the order of the ids, an id whose lookup failed gets its error without failing the
+    /// rest of the batch.
+    ///
+    /// A batch of more than [`MAX_FIND_SUCCESSORS_BATCH`](Self::MAX_FIND_SUCCESSORS_BATCH) ids
+    /// is rejected with [`InvalidArgument`](error::ServiceError::InvalidArgument).
+    ///
+    /// # Arguments
+    ///
+    /// * `ids` - The ids to find the successors for
+    #[tracing::instrument(skip_all, fields(node.id = %self.id, node.addr = %self.addr, batch = ids.len()))]
+    pub async fn find_successors(
+        &self,
+        ids: Vec<NodeId>,
+    ) -> Result<Vec<std::result::Result<Node, error::ServiceError>>, error::ServiceError> {
+        metrics().rpc_call("find_successors");
+        if ids.len() > Self::MAX_FIND_SUCCESSORS_BATCH {
+            return Err(Report::new(error::ServiceError::InvalidArgument(format!(
+                "A batch holds at most {} ids, got {}",
+                Self::MAX_FIND_SUCCESSORS_BATCH,
+                ids.len()
+            ))));
+        }
+
+        let permits = Semaphore::new(Self::FIND_SUCCESSORS_CONCURRENCY);
+        let lookups = ids.into_iter().map(|id| {
+            let permits = &permits;
+            async move {
+                let _permit = permits.acquire().await;
+                self.lookup_with_retry(id).await.map_err(|err| {
+                    tracing::debug!(target = %id, "Lookup of a batch failed: {:?}", err);
+                    err.current_context().clone()
+                })
+            }
+        });
+
+        Ok(join_all(lookups).await)
+    }
+
+    /// Find the successor of the given id, retrying the lookups failing transiently until the
+    /// deadline of the request, without counting it as an RPC call.
+    async fn lookup_with_retry(&self, id: NodeId) -> Result<Node, error::ServiceError> {
+        let mut retry = 0;
+        loop {
+            Self::check_deadline()?;
+            match self.lookup(id).await {
+                Err(err)
+                    if err.current_context().is_transient()
+                        && retry < self.lookup_retry.max_retries =>
+                {
+                    retry += 1;
+                    let delay = self.lookup_retry.delay(retry);
+                    tracing::debug!(retry, ?delay, "Retrying a lookup: {:?}", err);
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Find the successor of the given id, without counting it as an RPC call.
+    async fn lookup(&self, id: NodeId) -> Result<Node, error::ServiceError> {
+        if let Some(successor) = self.find_immediate_successor(id).await? {
+            metrics().lookup_hops.observe(0.0);
+            return Ok(successor);
+        }
+        if let Some(successor) = self.cached_successor(id).await {
+            return Ok(successor);
+        }
+
+        // Taken before the lookup, so a change of the routing state during it is not missed
+        let generation = self.store().generation();
+        let successor = self.find_successor_using_finger_table(id, None, 1).await?;
+        if let Some(mut cache) = self.lookup_cache() {
+            cache.insert(id, successor.clone(), generation);
+        }
+
+        Ok(successor)
+    }
+
+    /// Get the cached successor of the id, if it's still the successor
+    ///
+    /// The successor is asked for its predecessor, it's still the successor of the id if the id
+    /// is between them. Otherwise, or if it doesn't respond, it's evicted from the cache.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id to find the successor for
+    async fn cached_successor(&self, id: NodeId) -> Option<Node> {
+        let generation = self.store().generation();
+        let successor = self.lookup_cache()?.get(id, generation);
+        let Some(successor) = successor else {
+            metrics().lookup_cache("miss");
+            return None;
+        };
+
+        let client: Arc<C> = self.client(&successor).await;
+        let predecessor = tokio::time::timeout(Self::REACHABILITY_TIMEOUT, client.predecessor());
+        match predecessor.await {
+            Ok(Ok(Some(predecessor)))
+                if Node::is_between_on_ring(id.0, predecessor.id.0, successor.id.0) =>
+            {
+                metrics().lookup_cache("hit");
+                Some(successor)
+            }
+            response => {
+                tracing::debug!(
+                    target = %id,
+                    successor.id = %successor.id,
+                    "Evicting a stale cached successor: {:?}",
+                    response
+                );
+                metrics().lookup_cache("stale");
+                if let Some(mut cache) = self.lookup_cache() {
+                    cache.evict(successor.id);
+                }
+                None
+            }
+        }
+    }
+
+    /// Lock the lookup cache, if it's enabled
+    fn lookup_cache(&self) -> Option<std::sync::MutexGuard<'_, LookupCache>> {
+        let cache = self.lookup_cache.as_ref()?;
+        Some(
+            cache
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        )
+    }
+
+    /// Fail if the deadline of the request being handled has passed
+    fn check_deadline() -> Result<(), error::ServiceError> {
+        if deadline::expired() {
+            tracing::debug!("Dropping the lookup, its deadline has passed");
+            return Err(Report::new(error::ServiceError::Timeout));
+        }
+
+        Ok(())
+    }
+
+    /// Find the successor of the given id using the successor list.
+    async fn find_immediate_successor(
+        &self,
+        id: NodeId,
+    ) -> Result<Option<Node>, error::ServiceError> {
+        let successors = self.store().successor_list();
+        for successor in successors {
+            if Node::is_between_on_ring(id.0, self.id.0, successor.id.0) {
+                return Ok(Some(successor));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Find the successor of the given id using the finger table.
+    /// This method is called recursively until the successor is found or until the closest preceding node is the current node.
+    ///
+    /// If a node fails to respond, it's id is used to find new closest preceding node.
+    /// If all nodes fail to respond, the last of them is reported as
+    /// [unreachable](error::ServiceError::PeerUnreachable), so the lookup is retried.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id to find the successor for
+    /// * `failing_node` - The id of the node that failed to respond. It is used to find the new closest preceding node.    
+    /// * `hops` - The number of nodes contacted so far, including the current attempt
+    #[async_recursion]
+    async fn find_successor_using_finger_table(
+        &self,
+        id: NodeId,
+        failing_node: Option<NodeId>,
+        hops: u32,
+    ) -> Result<Node, error::ServiceError> {
+        let search_id = failing_node.unwrap_or(id);
+        let n = self.closest_preceding_node(search_id);
+
+        if n.id == self.id {
+            let error = format!("Cannot find successor of id '{}' using finger table", id);
+            match failing_node {
+                Some(failing_node) => {
+                    tracing::warn!("{}", error);
+                    return Err(
+                        Report::new(error::ServiceError::PeerUnreachable(failing_node))
+                            .attach_printable(error),
+                    );
+                }
+                None if self.store().successor().id == self.id => {
+                    return Err(Report::new(error::ServiceError::NotJoined));
+                }
+                None => {
+                    tracing::error!("{}", error);
+                    return Err(Report::new(error::ServiceError::Internal(error)));
+                }
+            }
+        }
+
+        Self::check_deadline()?;
+        tracing::debug!(hops, next.id = %n.id, next.addr = %n.addr, "Forwarding lookup");
+        let client: Arc<C> = self.client(&n).await;
+        match client.find_successor(id).await {
+            Ok(successor) => {
+                metrics().lookup_hops.observe(hops as f64);
+                Result::Ok(successor)
+            }
+            Err(report) => match (*report.current_context()).clone() {
+                ClientError::ConnectionFailed(_) => {
+                    self.find_successor_using_finger_table(id, Some(n.id), hops + 1)
+                        .await
+                }
+                err => Result::Err(report.change_context((n.id, &err).into())),
+            },
+        }
+    }
+
+    /// Find the successor of the given id, recording the path of the lookup.
+    ///
+    /// Works like [`find_successor`](Self::find_successor), but also returns the ids of the
+    /// nodes the lookup went through, starting with the current node and ending with the node
+    /// which found the successor in its successor list.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id to find the successor for
+    #[tracing::instrument(skip_all, fields(node.id = %self.id, node.addr = %self.addr, target = %id))]
+    pub async fn find_successor_traced(
+        &self,
+        id: NodeId,
+    ) -> Result<(Node, Vec<NodeId>), error::ServiceError> {
+        metrics().rpc_call("find_successor_traced");
+        if let Some(successor) = self.find_immediate_successor(id).await? {
+            Ok((successor, vec![self.id]))
+        } else {
+            self.find_successor_traced_using_finger_table(id, None)
+                .await
+        }
+    }
+
+    /// Find the successor of the given id using the finger table, recording the path of the
+    /// lookup. Nodes failing to respond are skipped like in
+    /// [`find_successor_using_finger_table`](Self::find_successor_using_finger_table), and are
+    /// not part of the path.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id to find the successor for
+    /// * `failing_node` - The id of the node that failed to respond.
+    #[async_recursion]
+    async fn find_successor_traced_using_finger_table(
+        &self,
+        id: NodeId,
+        failing_node: Option<NodeId>,
+    ) -> Result<(Node, Vec<NodeId>), error::ServiceError> {
+        let n = self.closest_preceding_node(failing_node.unwrap_or(id));
+
+        if n.id == self.id {
+            let error = format!("Cannot find successor of id '{}' using finger table", id);
+            match failing_node {
+                Some(failing_node) => {
+                    tracing::warn!("{}", error);
+                    return Err(
+                        Report::new(error::ServiceError::PeerUnreachable(failing_node))
+                            .attach_printable(error),
+                    );
+                }
+                None if self.store().successor().id == self.id => {
+                    return Err(Report::new(error::ServiceError::NotJoined));
+                }
+                None => {
+                    tracing::error!("{}", error);
+                    return Err(Report::new(error::ServiceError::Internal(error)));
+                }
+            }
+        }
+
+        let client: Arc<C> = self.client(&n).await;
+        match client.find_successor_traced(id).await {
+            Ok((successor, hops)) => {
+                let mut path = vec![self.id];
+                path.extend(hops);
+                Ok((successor, path))
+            }
+            Err(report) => match (*report.current_context()).clone() {
+                ClientError::ConnectionFailed(_) => {
+                    self.find_successor_traced_using_finger_table(id, Some(n.id))
+                        .await
+                }
+                err => Result::Err(report.change_context((n.id, &err).into())),
+            },
+        }
+    }
+
+    /// Find the predecessor of the given id.
+    ///
+    /// The predecessor is the node whose successor is responsible for the id. If the id is in
+    /// the range of the current node and its successor, the current node is returned.
+    /// Otherwise, the lookup is forwarded to the closest preceding node.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id to find the predecessor for
+    #[tracing::instrument(skip_all, fields(node.id = %self.id, node.addr = %self.addr, target = %id))]
+    pub async fn find_predecessor(&self, id: NodeId) -> Result<Node, error::ServiceError> {
+        metrics().rpc_call("find_predecessor");
+        let successor = self.store().successor();
+        if Node::is_between_on_ring(id.0, self.id.0, successor.id.0) {
+            Ok(Node::with_id(self.id, self.addr))
+        } else {
+            self.find_predecessor_using_finger_table(id, None).await
+        }
+    }
+
+    /// Find the predecessor of the given id using the finger table.
+    /// Nodes failing to respond are skipped like in
+    /// [`find_successor_using_finger_table`](Self::find_successor_using_finger_table).
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id to find the predecessor for
+    /// * `failing_node` - The id of the node that failed to respond.
+    #[async_recursion]
+    async fn find_predecessor_using_finger_table(
+        &self,
+        id: NodeId,
+        failing_node: Option<NodeId>,
+    ) -> Result<Node, error::ServiceError> {
+        let n = self.closest_preceding_node(failing_node.unwrap_or(id));
+
+        if n.id == self.id {
+            let error = format!("Cannot find predecessor of id '{}' using finger table", id);
+            match failing_node {
+                Some(failing_node) => {
+                    tracing::warn!("{}", error);
+                    return Err(
+                        Report::new(error::ServiceError::PeerUnreachable(failing_node))
+                            .attach_printable(error),
+                    );
+                }
+                None if self.store().successor().id == self.id => {
+                    return Err(Report::new(error::ServiceError::NotJoined));
+                }
+                None => {
+                    tracing::error!("{}", error);
+                    return Err(Report::new(error::ServiceError::Internal(error)));
+                }
+            }
+        }
+
+        let client: Arc<C> = self.client(&n).await;
+        match client.find_predecessor(id).await {
+            Ok(predecessor) => Ok(predecessor),
+            Err(report) => match (*report.current_context()).clone() {
+                ClientError::ConnectionFailed(_) => {
+                    self.find_predecessor_using_finger_table(id, Some(n.id))
+                        .await
+                }
+                err => Result::Err(report.change_context((n.id, &err).into())),
+            },
+        }
+    }
+
+    pub async fn get_predecessor(&self) -> Result<Option<Node>, error::ServiceError> {
+        metrics().rpc_call("get_predecessor");
+        Ok(self.store().predecessor())
+    }
+
+    /// Check if the node owns the given id, i.e. if the id is in `(predecessor, node]`
+    ///
+    /// A node without a known predecessor owns the id only if it's alone in the ring, as it
+    /// can't tell where its range starts otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id to check
+    pub fn owns(&self, id: NodeId) -> bool {
+        let store = self.store();
+        match store.predecessor() {
+            Some(predecessor) => Node::is_between_on_ring(id.0, predecessor.id.0, self.id.0),
+            None => store.successor().id == self.id,
+        }
+    }
+
+    pub async fn get_successor(&self) -> Result<Node, error::ServiceError> {
+        metrics().rpc_call("get_successor");
+        Ok(self.store().successor())
+    }
+
+    pub async fn get_successor_list(&self) -> Result<Vec<Node>, error::ServiceError> {
+        metrics().rpc_call("get_successor_list");
+        Ok(self.store().successor_list())
+    }
+
+    /// Get the predecessor list of the node, starting at its predecessor
+    ///
+    /// The list is empty if the node has no predecessor, see
+    /// [`reconcile_predecessors`](Self::reconcile_predecessors).
+    pub async fn get_predecessor_list(&self) -> Result<Vec<Node>, error::ServiceError> {
+        metrics().rpc_call("get_predecessor_list");
+        Ok(self.store().predecessor_list())
+    }
+
+    /// Get the routing state of the node
+    ///
+    /// It lets the tools crawling the ring get the predecessor and the successors of a node in a
+    /// single call, and the state is read at once, so they are consistent with each other. The
+    /// parameters of the node are reported as well, to find the nodes configured differently.
+    pub async fn node_info(&self) -> Result<NodeInfo, error::ServiceError> {
+        metrics().rpc_call("node_info");
+        let state = self.store().state();
+        Ok(NodeInfo {
+            id: self.id,
+            addr: self.addr,
+            predecessor: state.predecessor().cloned(),
+            successor_list: state.successor_list().to_vec(),
+            params: Some(self.params()),
+        })
+    }
+
+    /// Handle a ping from another node
+    ///
+    /// It doesn't do anything besides recording the call, the transport is expected to
+    /// respond right away with the returned id of the node.
+    pub fn ping(&self) -> NodeId {
+        metrics().rpc_call("ping");
+        self.id
+    }
+
+    /// Report the health of the node
+    ///
+    /// Unlike [`ping`](Self::ping), which only shows the node responds, it shows whether the node
+    /// is part of the ring. The successor is pinged, waiting at most
+    /// [`REACHABILITY_TIMEOUT`](Self::REACHABILITY_TIMEOUT). A node which is its own successor
+    /// always reaches it.
+    pub async fn health(&self) -> Result<HealthStatus, error::ServiceError> {
+        metrics().rpc_call("health");
+        let store = self.store();
+        let successor = store.successor();
+        let successor_reachable = successor.id == self.id || self.is_reachable(&successor).await;
+
+        Ok(HealthStatus {
+            joined: self.stabilized.load(Ordering::Relaxed),
+            has_predecessor: store.predecessor().is_some(),
+            successor_reachable,
+        })
+    }
+
+    /// Run a maintenance task right away, instead of waiting for its next periodic run
+    ///
+    /// Returns once the task completed, so the caller sees its effects, e.g. when debugging a
+    /// stuck node or in a test against a live node.
+    ///
+    /// > **Note**
+    /// >
+    /// > The transports serve the request of an administrator only with their `admin` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `task` - The task to run
+    pub async fn trigger(&self, task: MaintenanceTask) -> Result<(), error::ServiceError> {
+        metrics().rpc_call("trigger");
+        log::info!("Running {} on demand", task);
+        match task {
+            MaintenanceTask::Stabilize => self.stabilize().await?,
+            MaintenanceTask::FixFingers => self.fix_fingers().await,
+            MaintenanceTask::ReconcileSuccessors => self.reconcile_successors().await,
+        }
+
+        Ok(())
+    }
+
+    /// Join the chord ring.
+    ///
+    /// This method is used to join the chord ring. It will find the successor of its own id
+    /// and set it as the successor.
+    ///
+    /// If the given node has the address of the current node, no request is made: the node
+    /// creates a new ring on its own, being its own successor.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node to join the ring with. It's an existing node in the ring.
+    pub async fn join(&self, node: Node) -> Result<(), error::ServiceError> {
+        if node.addr == self.addr {
+            log::info!(
+                "The seed {:?} is the node itself, creating a new ring",
+                node.addr
+            );
+            self.store()
+                .set_successor(Node::with_id(self.id, self.addr));
+            return Ok(());
+        }
+
+        let client: Arc<C> = self.client(&node).await;
+        let successor = client.find_successor(self.id).await.peer_context(node.id)?;
+        if successor.id == self.id && successor.addr != self.addr {
+            log::warn!(
+                "Node {:?} has the same id as this node ({}), one of them should use another id",
+                successor.addr,
+                self.id
+            );
+        }
+        self.store().set_successor(successor);
+
+        Ok(())
+    }
+
+    /// Notify the node about a potential new predecessor.
+    ///
+    /// If the predecessor is not set or the given node is in the range of the current node and the
+    /// predecessor, the predecessor is set to the given node. A notify from the node itself is
+    /// ignored, and so is a notify from a node which doesn't respond to a ping if the nodes are
+    /// [verified](Self::with_notify_verification).
+    ///
+    /// Notifying the node again with its current predecessor doesn't change anything. The node is
+    /// verified with the store written with [`write_async`](Db::write_async), so the other
+    /// updates of the neighbours wait for the verification.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node which might be the new predecessor
+    pub async fn notify(&self, node: Node) {
+        metrics().rpc_call("notify");
+        self.failed_nodes.lock().unwrap().remove(node.id);
+        if !self.is_predecessor_candidate(&node) {
+            return;
+        }
+        let db = self.store();
+        let _store = db.write_async().await;
+        if self.verify_notify && !self.is_reachable(&node).await {
+            log::debug!("Ignoring notify from unreachable node {}", node);
+            return;
+        }
+
+        self.adopt_predecessor(node);
+    }
+
+    /// Check if the node would be a closer predecessor than the current one
+    fn is_predecessor_candidate(&self, node: &Node) -> bool {
+        if node.id == self.id {
+            return false;
+        }
+
+        match self.store().predecessor() {
+            Some(predecessor) => {
+                predecessor.id != node.id
+                    && Node::is_between_on_ring(node.id.0, predecessor.id.0, self.id.0)
+            }
+            None => true,
+        }
+    }
+
+    /// Set the node as the predecessor if it's a candidate, see
+    /// [`is_predecessor_candidate`](Self::is_predecessor_candidate)
+    ///
+    /// The predecessor is checked again, as it may have changed while the node was verified.
+    fn adopt_predecessor(&self, node: Node) {
+        if self.is_predecessor_candidate(&node) {
+            self.store().set_predecessor(node);
+        }
+    }
+
+    /// Leave the ring
+    ///
+    /// The successor is told that the node is leaving, along with the node's predecessor, so it
+    /// can adopt the predecessor right away instead of waiting for
+    /// [`check_predecessor`](Self::check_predecessor) to notice the node is gone. The
+    /// predecessor promotes its next successor on its next
+    /// [`check_successor`](Self::check_successor).
+    ///
+    /// > **Note**
+    /// >
+    /// > The node keeps serving requests, it should be stopped once this method returns.
+    #[tracing::instrument(skip_all, fields(node.id = %self.id, node.addr = %self.addr))]
+    pub async fn leave(&self) -> Result<(), error::ServiceError> {
+        let successor = self.store().successor();
+        if successor.id != self.id {
+            let client: Arc<C> = self.client(&successor).await;
+            client
+                .leave(
+                    Node::with_id(self.id, self.addr),
+                    self.store().predecessor(),
+                )
+                .await
+                .peer_context(successor.id)?;
+        }
+
+        self.stabilized.store(false, Ordering::Relaxed);
+        self.store().emit(NodeEvent::Left);
+
+        Ok(())
+    }
+
+    /// Handle a node leaving the ring
+    ///
+    /// If the leaving node is the predecessor, it's cleared and the predecessor of the leaving
+    /// node is adopted through [`notify`](Self::notify). The leaving node is also removed from
+    /// the successor list, and if it was the only known successor, the node becomes its own
+    /// successor.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node which is leaving
+    /// * `predecessor` - The predecessor of the leaving node, if it has one
+    pub fn handle_leave(&self, node: Node, predecessor: Option<Node>) {
+        metrics().rpc_call("leave");
+        if self.store().predecessor().map(|p| p.id) == Some(node.id) {
+            self.store().unset_predecessor();
+            if let Some(predecessor) = predecessor.filter(|p| p.id != node.id) {
+                self.adopt_predecessor(predecessor);
+            }
+        }
+
+        let successors = self.store().successor_list();
+        if successors.iter().any(|s| s.id == node.id) {
+            let mut successors: Vec<Node> =
+                successors.into_iter().filter(|s| s.id != node.id).collect();
+            if successors.is_empty() {
+                // The leaving node is also the successor only in a ring of two nodes
+                successors.push(Node::with_id(self.id, self.addr));
+            }
+            self.store().set_successor_list(successors);
+        }
+    }
+
+    /// Stabilize the node
+    ///
+    /// This method is used to stabilize the node. It will check if a predecessor of the successor
+    /// is in the range of the current node and its successor. If so, the successor will be set to
+    /// the retrieved predecessor.
+    ///
+    /// It will also notify the successor about the current node.
+    ///
+    /// When the node is its own successor, i.e. it's alone on the ring, its own predecessor is
+    /// read from the local state and nothing is sent over the network.
+    ///
+    /// The successor is updated with the store written with [`write_async`](Db::write_async),
+    /// its predecessor is read within [`REACHABILITY_TIMEOUT`](Self::REACHABILITY_TIMEOUT). The
+    /// writer is released before notifying the successor, as the successor may be stabilizing
+    /// towards this node at the same time.
+    ///
+    /// > **Note**
+    /// >
+    /// > This method should be called periodically.
+    #[tracing::instrument(skip_all, fields(node.id = %self.id, node.addr = %self.addr))]
+    pub async fn stabilize(&self) -> Result<(), error::ServiceError> {
+        let _permit = self.outbound.acquire().await;
+        let db = self.store();
+        let store = db.write_async().await;
+        let successor = store.successor();
+        let result = if successor.id == self.id {
+            Ok(store.predecessor())
+        } else {
+            self.call_while_writing(
+                &successor,
+                |client| async move { client.predecessor().await },
+            )
+            .await
+        };
+
+        if let Ok(Some(x)) = result {
+            if Node::is_between_on_ring(x.id.0, self.id.0, store.successor().id.0) {
+                tracing::debug!(successor.id = %x.id, successor.addr = %x.addr, "New successor");
+                store.set_successor(x);
+            }
+        }
+
+        let successor = store.successor();
+        drop(store);
+        // A node is never its own predecessor, so it doesn't notify itself
+        if successor.id != self.id {
+            let client: Arc<C> = self.client(&successor).await;
+            client
+                .notify(Node {
+                    id: self.id,
+                    addr: self.addr,
+                })
+                .await
+                .peer_context(successor.id)?;
+        }
+        if !self.stabilized.swap(true, Ordering::Relaxed) {
+            self.store().emit(NodeEvent::Joined);
+        }
+
+        Ok(())
+    }
+
+    /// Reconcile successors
+    ///
+    /// This method is used to refresh the successor list using the successor list of the
+    /// immediate successor. The retrieved list is merged with the current one, so a successor
+    /// which returns a short list (e.g. it has just joined the ring) doesn't wipe out the backup
+    /// successors we already know about. The merged list is kept in ring order, starting at the
+    /// immediate successor.
+    ///
+    /// If the successor fails to respond within
+    /// [`REACHABILITY_TIMEOUT`](Self::REACHABILITY_TIMEOUT), it is removed from the list, unless
+    /// it's the only known successor.
+    ///
+    /// Nothing is done when the node is its own successor, its successor list has no other
+    /// entry to merge. The store is written with [`write_async`](Db::write_async), so the list
+    /// isn't merged into one the other updates changed in the meantime.
+    ///
+    /// > **Note**
+    /// >
+    /// > This method should be called periodically.
+    pub async fn reconcile_successors(&self) {
+        let _permit = self.outbound.acquire().await;
+        let db = self.store();
+        let store = db.write_async().await;
+        let successor = store.successor();
+        if successor.id == self.id {
+            return;
+        }
+        let successors = self
+            .call_while_writing(
+                &successor,
+                |client| async move { client.successor_list().await },
+            )
+            .await;
+
+        match successors {
+            Ok(successors) => {
+                self.failed_nodes.lock().unwrap().remove(successor.id);
+                let mut candidates = successors;
+                candidates.extend(store.successor_list());
+
+                let new_successors = self.merge_successors(successor, candidates);
+                store.set_successor_list(new_successors);
+            }
+            Err(err) => {
+                self.failed_nodes.lock().unwrap().insert(successor.id);
+                let successors = store.successor_list();
+                if successors.len() < 2 {
+                    log::info!(
+                        "Successor {} is down, but there is no other known successor",
+                        successor
+                    );
+                    return;
+                }
+
+                log::info!(
+                    "Successor {} is down, removing from the successor list",
+                    successor
+                );
+                log::debug!("Successor {} error: {err:?}", successor);
+
+                store.set_successor_list(successors[1..].to_vec());
+            }
+        }
+    }
+
+    /// Merge the candidates into a successor list starting at the given successor.
+    ///
+    /// Duplicates are removed and the candidates are ordered by their distance from the current
+    /// node on the ring. Candidates placed between the current node and the successor are
+    /// dropped, as the successor is the closest known node.
+    fn merge_successors(&self, successor: Node, candidates: Vec<Node>) -> Vec<Node> {
+        let distance = |node: &Node| node.id.0.wrapping_sub(self.id.0);
+        let successor_distance = distance(&successor);
+
+        let mut seen = HashSet::new();
+        seen.insert(successor.id);
+
+        let mut candidates: Vec<Node> = candidates
+            .into_iter()
+            .filter(|node| distance(node) > successor_distance)
+            .filter(|node| seen.insert(node.id))
+            .collect();
+        candidates.sort_by_key(distance);
+
+        let mut successors = vec![successor];
+        successors.extend(candidates);
+
+        successors
+    }
+
+    /// Reconcile predecessors
+    ///
+    /// This method is used to refresh the predecessor list using the predecessor list of the
+    /// immediate predecessor, the same way [`reconcile_successors`](Self::reconcile_successors)
+    /// refreshes the successor list. The list lets the node fall back to the next predecessor
+    /// when the immediate one fails, see [`check_predecessor`](Self::check_predecessor).
+    ///
+    /// Nothing is done if the node has no predecessor, or if it fails to respond.
+    ///
+    /// > **Note**
+    /// >
+    /// > This method should be called periodically.
+    pub async fn reconcile_predecessors(&self) {
+        let _permit = self.outbound.acquire().await;
+        let Some(predecessor) = self.store().predecessor() else {
+            return;
+        };
+        let client: Arc<C> = self.client(&predecessor).await;
+
+        match client.predecessor_list().await {
+            Ok(predecessors) => self.store().set_predecessor_list(predecessors),
+            Err(err) => log::debug!("Predecessor {} error: {err:?}", predecessor),
+        }
+    }
+
+    /// Check predecessor
+    ///
+    /// This method is used to check if the predecessor is still alive. If not, or if another
+    /// node responds on its address, the predecessor is replaced with the next node of the
+    /// predecessor list, or set to `None` if the list is empty. The actual predecessor sets
+    /// itself again when it stabilizes.
+    ///
+    /// > **Note**
+    /// >
+    /// > This method should be called periodically.
+    #[tracing::instrument(skip_all, fields(node.id = %self.id, node.addr = %self.addr))]
+    pub async fn check_predecessor(&self) -> Result<(), error::ServiceError> {
+        let _permit = self.outbound.acquire().await;
+        if let Some(predecessor) = self.store().predecessor() {
+            let client: Arc<C> = self.client(&predecessor).await;
+            match client.ping().await {
+                Ok(id) if id == predecessor.id => {
+                    self.failed_nodes.lock().unwrap().remove(predecessor.id);
+                    Ok(())
+                }
+                Ok(id) => {
+                    tracing::info!(
+                        predecessor.id = %predecessor.id,
+                        predecessor.addr = %predecessor.addr,
+                        "Predecessor address is used by node {}, removing",
+                        id
+                    );
+                    self.clients.evict(predecessor.id);
+                    self.failed_nodes.lock().unwrap().insert(predecessor.id);
+                    self.replace_predecessor(&predecessor);
+                    Ok(())
+                }
+                Err(err) => {
+                    tracing::info!(
+                        predecessor.id = %predecessor.id,
+                        predecessor.addr = %predecessor.addr,
+                        "Predecessor is down, removing. Error: {:?}",
+                        err
+                    );
+                    self.failed_nodes.lock().unwrap().insert(predecessor.id);
+                    self.replace_predecessor(&predecessor);
+                    Ok(())
+                }
+            }
+        } else {
+            Ok(())
+        }
+    }
 
-    clients: ClientsPool<C>,
-}
+    /// Replace the failed predecessor with the next node of the predecessor list
+    fn replace_predecessor(&self, predecessor: &Node) {
+        if let Some(next) = self.store().replace_predecessor(predecessor) {
+            tracing::info!(predecessor.id = %next.id, predecessor.addr = %next.addr, "Promoting");
+        }
+    }
 
-impl<C: Client + Clone + Sync + Send + 'static> NodeService<C> {
-    /// Create a new node service
+    /// Check successor
     ///
-    /// # Arguments
+    /// This method is used to check if the immediate successor is still alive. If it fails to
+    /// respond, or another node responds on its address, the next node from the successor list
+    /// is promoted to be the immediate successor.
     ///
-    /// * `socket_addr` - The address of the node
-    /// * `replication_factor` - The number of successors to keep track of
-    pub fn new(socket_addr: SocketAddr, replication_factor: usize) -> Self {
-        let id: NodeId = socket_addr.into();
-        Self::with_id(id, socket_addr, replication_factor)
-    }
+    /// The node doesn't ping itself when it's its own successor. The store is written with
+    /// [`write_async`](Db::write_async), so the successor promoted is the next one of the list
+    /// the pinged successor heads. A successor which doesn't respond within
+    /// [`REACHABILITY_TIMEOUT`](Self::REACHABILITY_TIMEOUT) is reported as a timeout, and kept.
+    ///
+    /// > **Note**
+    /// >
+    /// > This method should be called periodically.
+    pub async fn check_successor(&self) -> Result<(), error::ServiceError> {
+        let _permit = self.outbound.acquire().await;
+        let db = self.store();
+        let _store = db.write_async().await;
+        let successor = self.store().successor();
+        if successor.id == self.id {
+            return Ok(());
+        }
+        let ping = self
+            .call_while_writing(&successor, |client| async move { client.ping().await })
+            .await;
 
-    fn with_id(id: impl Into<NodeId>, addr: SocketAddr, replication_factor: usize) -> Self {
-        let id = id.into();
-        let store = NodeStore::new(Node::with_id(id, addr), replication_factor);
-        Self {
-            id,
-            addr,
-            store,
-            clients: ClientsPool::default(),
+        match ping {
+            Ok(id) if id == successor.id => {
+                self.failed_nodes.lock().unwrap().remove(successor.id);
+                Ok(())
+            }
+            Ok(id) => {
+                log::info!(
+                    "Successor address {} is used by node {}",
+                    successor.addr,
+                    id.short()
+                );
+                self.clients.evict(successor.id);
+                self.failed_nodes.lock().unwrap().insert(successor.id);
+                self.promote_next_successor(&successor);
+                Ok(())
+            }
+            Err(report) => match report.current_context() {
+                ClientError::ConnectionFailed(_) => {
+                    log::info!("Successor {} is down. Error: {:?}", successor, report);
+                    self.failed_nodes.lock().unwrap().insert(successor.id);
+                    self.promote_next_successor(&successor);
+                    Ok(())
+                }
+                err => {
+                    let err = (successor.id, err).into();
+                    Err(report.change_context(err))
+                }
+            },
         }
     }
 
-    pub fn id(&self) -> NodeId {
-        self.id
-    }
+    /// Replace the failed successor with the next node of the successor list
+    ///
+    /// The successor is kept if there is no other successor to promote.
+    fn promote_next_successor(&self, successor: &Node) {
+        let successors = self.store().successor_list();
+        if successors.len() < 2 {
+            log::error!(
+                "Successor {} failed and there is no other successor to promote",
+                successor
+            );
+            return;
+        }
 
-    pub(crate) fn store(&self) -> Db {
-        self.store.db()
+        log::info!("Promoting {}", successors[1]);
+        self.store().set_successor_list(successors[1..].to_vec());
     }
 
-    /// Find the successor of the given id.
+    /// Ping the whole successor list and the predecessor in one pass
     ///
-    /// If the given id is in the range of the current node and its successor, the successor is returned.
-    /// Otherwise, the successor of the closest preceding node is returned.
+    /// Unlike [`check_successor`](Self::check_successor) and
+    /// [`check_predecessor`](Self::check_predecessor), which ping one node at a time, the
+    /// neighbours are pinged concurrently, at most [`SWEEP_CONCURRENCY`](Self::SWEEP_CONCURRENCY)
+    /// at a time, each waiting at most [`REACHABILITY_TIMEOUT`](Self::REACHABILITY_TIMEOUT).
     ///
-    /// # Arguments
+    /// The neighbours which fail to respond, or for which another node responds, are evicted:
+    /// they're dropped from the successor list, so the first live successor is promoted, and a
+    /// dead predecessor is replaced with the next node of the predecessor list. The successor
+    /// list is left unchanged if none of its nodes responds, as there is nothing to promote.
+    /// Sweeps don't overlap with each other nor with the other updates of the neighbours which
+    /// await, see [`write_async`](Db::write_async).
     ///
-    /// * `id` - The id to find the successor for
-    pub async fn find_successor(&self, id: NodeId) -> Result<Node, error::ServiceError> {
-        if let Some(successor) = self.find_immediate_successor(id).await? {
-            Ok(successor)
-        } else {
-            self.find_successor_using_finger_table(id, None).await
+    /// Returns the neighbours which failed to respond.
+    ///
+    /// > **Note**
+    /// >
+    /// > This method should be called periodically.
+    #[tracing::instrument(skip_all, fields(node.id = %self.id, node.addr = %self.addr))]
+    pub async fn sweep_neighbours(&self) -> Vec<Node> {
+        let db = self.store();
+        let store = db.write_async().await;
+        let state = store.state();
+        let mut neighbours: Vec<Node> = vec![];
+        let predecessor = state.predecessor().cloned();
+        for node in state.successor_list().iter().cloned().chain(predecessor) {
+            if node.id != self.id && !neighbours.iter().any(|n| n.id == node.id) {
+                neighbours.push(node);
+            }
         }
-    }
 
-    /// Find the successor of the given id using the successor list.
-    async fn find_immediate_successor(
-        &self,
-        id: NodeId,
-    ) -> Result<Option<Node>, error::ServiceError> {
-        let successors = self.store().successor_list();
-        for successor in successors {
-            if Node::is_between_on_ring(id.0, self.id.0, successor.id.0) {
-                return Ok(Some(successor));
+        let permits = Semaphore::new(Self::SWEEP_CONCURRENCY);
+        let pings = neighbours.into_iter().map(|node| {
+            let permits = &permits;
+            async move {
+                let _permit = permits.acquire().await;
+                let _outbound = self.outbound.acquire().await;
+                let reachable = self.is_reachable(&node).await;
+                (node, reachable)
+            }
+        });
+
+        let mut dead = vec![];
+        for (node, reachable) in join_all(pings).await {
+            let mut failed = self.failed_nodes.lock().unwrap();
+            if reachable {
+                failed.remove(node.id);
+            } else {
+                failed.insert(node.id);
+                self.clients.evict(node.id);
+                dead.push(node);
             }
         }
+        if dead.is_empty() {
+            return dead;
+        }
+        let is_dead = |node: &Node| dead.iter().any(|n| n.id == node.id);
 
-        Ok(None)
+        let successors = store.successor_list();
+        let alive: Vec<Node> = successors
+            .iter()
+            .filter(|node| !is_dead(node))
+            .cloned()
+            .collect();
+        if alive.is_empty() {
+            log::error!("No successor responded, keeping the successor list");
+        } else if alive.len() < successors.len() {
+            log::info!(
+                "Dropping {} dead successors, the successor is {}",
+                successors.len() - alive.len(),
+                alive[0]
+            );
+            store.set_successor_list(alive);
+        }
+
+        if let Some(predecessor) = store.predecessor().filter(|node| is_dead(node)) {
+            log::info!("Predecessor {} is down, removing", predecessor);
+            self.replace_predecessor(&predecessor);
+        }
+
+        dead
     }
 
-    /// Find the successor of the given id using the finger table.
-    /// This method is called recursively until the successor is found or until the closest preceding node is the current node.
+    /// Check if the given node is reachable from the current node
     ///
-    /// If a node fails to respond, it's id is used to find new closest preceding node.
-    /// If all nodes fail to respond, an error is returned.
+    /// This method is used to diagnose network partitions. It pings the target node and reports
+    /// whether it responded within [`REACHABILITY_TIMEOUT`](Self::REACHABILITY_TIMEOUT).
     ///
     /// # Arguments
     ///
-    /// * `id` - The id to find the successor for
-    /// * `failing_node` - The id of the node that failed to respond. It is used to find the new closest preceding node.    
-    #[async_recursion]
-    async fn find_successor_using_finger_table(
-        &self,
-        id: NodeId,
-        failing_node: Option<NodeId>,
-    ) -> Result<Node, error::ServiceError> {
-        let search_id = failing_node.unwrap_or(id);
-        let n = self.closest_preceding_node(search_id);
+    /// * `target` - The node to check
+    pub async fn can_reach(&self, target: Node) -> Result<bool, error::ServiceError> {
+        metrics().rpc_call("can_reach");
+        Ok(self.is_reachable(&target).await)
+    }
 
-        if n.id == self.id {
-            let error = format!("Cannot find successor of id '{}' using finger table", id);
-            log::error!("{}", error);
-            return Err(Report::new(error::ServiceError::Unexpected));
+    /// Check if the successor chain loops back to the current node
+    ///
+    /// This method is used to detect ring partitions. It follows the successors, starting at the
+    /// immediate successor, and reports the ring as [closed](RingCheck::Closed) if it reaches
+    /// the current node within `max_hops`. It's [partitioned](RingCheck::Partitioned) if the
+    /// chain passes over the current node or enters a loop which doesn't include it, in both
+    /// cases the current node is not part of the ring its successors form. The check is
+    /// [inconclusive](RingCheck::Inconclusive) if the chain is longer than `max_hops`.
+    ///
+    /// > **Note**
+    /// >
+    /// > A check walks the whole ring, one request per node, so it's expensive on large rings.
+    /// > The outbound budget is taken only for each request, see
+    /// > [`with_outbound_limit`](Self::with_outbound_limit).
+    ///
+    /// # Arguments
+    ///
+    /// * `max_hops` - The maximum number of successors to follow
+    pub async fn check_ring(&self, max_hops: usize) -> Result<RingCheck, error::ServiceError> {
+        let mut visited = HashSet::new();
+        let mut current = self.store().successor();
+        for _ in 0..max_hops {
+            if current.id == self.id {
+                return Ok(RingCheck::Closed);
+            }
+            if !visited.insert(current.id) {
+                log::debug!("Successor chain loops at {}", current);
+                return Ok(RingCheck::Partitioned);
+            }
+
+            let client: Arc<C> = self.client(&current).await;
+            let permit = self.outbound.acquire().await;
+            let next = client
+                .successor()
+                .await
+                .peer_context(current.id)
+                .attach_printable_lazy(|| format!("Node: {}", current))?;
+            drop(permit);
+            if next.id != self.id
+                && Node::is_between_on_ring_exclusive(self.id.0, current.id.0, next.id.0)
+            {
+                log::debug!(
+                    "Successor chain passes over this node between {} and {}",
+                    current,
+                    next
+                );
+                return Ok(RingCheck::Partitioned);
+            }
+            current = next;
         }
 
-        let client: Arc<C> = self.client(&n).await;
-        match client.find_successor(id).await {
-            Ok(successor) => Result::Ok(successor),
-            Err(report) => match (*report.current_context()).clone() {
-                ClientError::ConnectionFailed(_) => {
-                    self.find_successor_using_finger_table(id, Some(n.id)).await
-                }
-                err => Result::Err(report.change_context(err.into())),
-            },
+        if current.id == self.id {
+            Ok(RingCheck::Closed)
+        } else {
+            Ok(RingCheck::Inconclusive)
         }
     }
 
-    pub async fn get_predecessor(&self) -> Result<Option<Node>, error::ServiceError> {
-        Ok(self.store().predecessor())
-    }
+    /// Check the invariants of the routing state of the node
+    ///
+    /// The checks only read the local state, no other node is contacted:
+    /// - the successor list is not empty, its first entry being the successor
+    /// - the successor list is in ring order, starting after the node
+    /// - no successor recently failed to respond to the maintenance tasks
+    /// - the predecessor precedes the node, i.e. it's not between the node and its successor
+    /// - the intervals of the fingers don't overlap
+    ///
+    /// Returns the broken invariants, the state is consistent if none is returned. The state is
+    /// not repaired, the maintenance tasks are expected to fix it.
+    pub fn audit(&self) -> Vec<Anomaly> {
+        let mut anomalies = vec![];
+        let state = self.store().state();
+        let successors = state.successor_list();
+        let Some(successor) = successors.first().cloned() else {
+            return vec![Anomaly::EmptySuccessorList];
+        };
 
-    pub async fn get_successor(&self) -> Result<Node, error::ServiceError> {
-        Ok(self.store().successor())
-    }
+        let alone = successors.len() == 1 && successor.id == self.id;
+        let mut previous = 0;
+        let failed = self.failed_nodes.lock().unwrap();
+        for (index, node) in successors.iter().cloned().enumerate() {
+            let distance = self.id.clockwise_distance(&node.id);
+            if !alone && (distance == 0 || (index > 0 && distance <= previous)) {
+                anomalies.push(Anomaly::MisorderedSuccessor {
+                    index,
+                    node: node.clone(),
+                });
+            }
+            if failed.contains(node.id) {
+                anomalies.push(Anomaly::DeadSuccessor { index, node });
+            }
+            previous = distance;
+        }
+        drop(failed);
 
-    pub async fn get_successor_list(&self) -> Result<Vec<Node>, error::ServiceError> {
-        Ok(self.store().successor_list())
+        if let Some(predecessor) = state.predecessor().cloned() {
+            let is_self = predecessor.id == self.id && !alone;
+            let after_self =
+                Node::is_between_on_ring_exclusive(predecessor.id.0, self.id.0, successor.id.0);
+            if is_self || after_self {
+                anomalies.push(Anomaly::MisplacedPredecessor {
+                    predecessor,
+                    successor,
+                });
+            }
+        }
+
+        for (index, pair) in state.finger_table().windows(2).enumerate() {
+            if pair[0].end() != pair[1].start() {
+                anomalies.push(Anomaly::OverlappingFingers { index: index + 1 });
+            }
+        }
+
+        anomalies
     }
 
-    /// Join the chord ring.
+    /// Merge the ring of the current node with the ring of the given seed
     ///
-    /// This method is used to join the chord ring. It will find the successor of its own id
-    /// and set it as the successor.
+    /// This method is used to recover from a partition, after [`check_ring`](Self::check_ring)
+    /// reported it. Like [`join`](Self::join), it looks up the successor of the current node
+    /// through the seed, but it only replaces the current successor if the found node is closer.
+    /// The found node is then notified about the current node, so stabilization pulls the
+    /// current node into the ring of the seed as well.
     ///
     /// # Arguments
     ///
-    /// * `node` - The node to join the ring with. It's an existing node in the ring.
-    pub async fn join(&self, node: Node) -> Result<(), error::ServiceError> {
-        let client: Arc<C> = self.client(&node).await;
-        let successor = client
-            .find_successor(self.id)
-            .await
-            .change_context(error::ServiceError::Unexpected)?;
-        self.store().set_successor(successor);
+    /// * `seed` - A node of the other ring
+    pub async fn heal_partition(&self, seed: Node) -> Result<(), error::ServiceError> {
+        let client: Arc<C> = self.client(&seed).await;
+        let successor = client.find_successor(self.id).await.peer_context(seed.id)?;
+        if successor.id == self.id {
+            return Ok(());
+        }
 
-        Ok(())
+        let current = self.store().successor();
+        if current.id == self.id
+            || Node::is_between_on_ring_exclusive(successor.id.0, self.id.0, current.id.0)
+        {
+            log::info!(
+                "Healing partition, new successor: {} (was {})",
+                successor,
+                current
+            );
+            self.store().set_successor(successor.clone());
+        }
+
+        let client: Arc<C> = self.client(&successor).await;
+        client
+            .notify(Node {
+                id: self.id,
+                addr: self.addr,
+            })
+            .await
+            .peer_context(successor.id)
     }
 
-    /// Notify the node about a potential new predecessor.
+    /// Call the given node while the store is written, waiting at most
+    /// [`REACHABILITY_TIMEOUT`](Self::REACHABILITY_TIMEOUT)
     ///
-    /// If the predecessor is not set or the given node is in the range of the current node and the
-    /// predecessor, the predecessor is set to the given node.
+    /// The writer holds up the other updates of the store, so a node which doesn't respond
+    /// can't hold them up for longer. A call which doesn't complete in time, including the
+    /// connection to the node, fails with [`ClientError::DeadlineExceeded`].
     ///
     /// # Arguments
     ///
-    /// * `node` - The node which might be the new predecessor
-    pub fn notify(&self, node: Node) {
-        let predecessor = self.store().predecessor();
-        if predecessor.is_none()
-            || Node::is_between_on_ring(node.id.0, predecessor.unwrap().id.0, self.id.0)
-        {
-            self.store().set_predecessor(node);
+    /// * `node` - The node to call
+    /// * `call` - The call to make with the client of the node
+    async fn call_while_writing<T, F, Fut>(&self, node: &Node, call: F) -> Result<T, ClientError>
+    where
+        F: FnOnce(Arc<C>) -> Fut,
+        Fut: std::future::Future<Output = Result<T, ClientError>>,
+    {
+        let call = async { call(self.client(node).await).await };
+        match tokio::time::timeout(Self::REACHABILITY_TIMEOUT, call).await {
+            Ok(result) => result,
+            Err(_) => Err(Report::new(ClientError::DeadlineExceeded)
+                .attach_printable(format!("Node {} did not respond in time", node))),
         }
     }
 
-    /// Stabilize the node
+    /// Ping the given node, waiting at most [`REACHABILITY_TIMEOUT`](Self::REACHABILITY_TIMEOUT)
     ///
-    /// This method is used to stabilize the node. It will check if a predecessor of the successor
-    /// is in the range of the current node and its successor. If so, the successor will be set to
-    /// the retrieved predecessor.
+    /// The node is reachable only if it responds with its own id.
+    async fn is_reachable(&self, target: &Node) -> bool {
+        let client: Arc<C> = self.client(target).await;
+        match tokio::time::timeout(Self::REACHABILITY_TIMEOUT, client.ping()).await {
+            Ok(Ok(id)) if id == target.id => true,
+            Ok(Ok(id)) => {
+                log::debug!("Node {} responds on the address of {}", id.short(), target);
+                false
+            }
+            Ok(Err(err)) => {
+                log::debug!("Node {} is not reachable: {err:?}", target);
+                false
+            }
+            Err(_) => {
+                log::debug!("Node {} did not respond in time", target);
+                false
+            }
+        }
+    }
+
+    /// Restore the neighbours saved on disk
     ///
-    /// It will also notify the successor about the current node.
+    /// Every persisted node is pinged before it's trusted, the nodes which don't respond are
+    /// dropped. Returns `true` if at least one successor was restored, in which case the node
+    /// doesn't need to join the ring again.
+    #[cfg(feature = "persistence")]
+    pub async fn restore(&self) -> bool {
+        let persisted = match self.store().persisted() {
+            Ok(Some(persisted)) => persisted,
+            Ok(None) => return false,
+            Err(err) => {
+                log::error!("Failed to load the persisted state: {:?}", err);
+                return false;
+            }
+        };
+
+        self.restore_neighbours(persisted.predecessor, persisted.successor_list)
+            .await
+    }
+
+    /// Take a snapshot of the node's place in the ring
+    ///
+    /// The snapshot can be restored with [`restore_snapshot`](Self::restore_snapshot), also by
+    /// another process, and it doesn't need the `persistence` feature.
+    pub fn snapshot(&self) -> Snapshot {
+        let state = self.store().state();
+        Snapshot {
+            node: Node::with_id(self.id, self.addr),
+            predecessor: state.predecessor().cloned(),
+            successor_list: state.successor_list().to_vec(),
+            finger_table: state.finger_table().to_vec(),
+        }
+    }
+
+    /// Restore the node's place in the ring from a snapshot
+    ///
+    /// Like in [`restore`](Self::restore), every node of the snapshot is pinged before it's
+    /// trusted and the nodes which don't respond are dropped, so the background tasks should be
+    /// started only once this method returns. Returns `true` if at least one successor was
+    /// restored, in which case the node doesn't need to join the ring again.
     ///
     /// > **Note**
     /// >
-    /// > This method should be called periodically.
-    pub async fn stabilize(&self) -> Result<(), error::ServiceError> {
-        let successor = self.store().successor();
-        let client: Arc<C> = self.client(&successor).await;
-        let result = client.predecessor().await;
-        drop(client);
+    /// > A snapshot of a node with another id is not restored, since its fingers point to the
+    /// > successors of other ids. The address of the node can change.
+    ///
+    /// # Arguments
+    ///
+    /// * `snapshot` - The snapshot, see [`snapshot`](Self::snapshot)
+    pub async fn restore_snapshot(&self, snapshot: Snapshot) -> bool {
+        if snapshot.node.id != self.id {
+            log::warn!("Ignoring the snapshot of another node: {}", snapshot.node);
+            return false;
+        }
 
-        if let Ok(Some(x)) = result {
-            if Node::is_between_on_ring(x.id.0, self.id.0, self.store().successor().id.0) {
-                self.store().set_successor(x);
+        let fingers: HashMap<NodeId, Node> = snapshot
+            .finger_table
+            .iter()
+            .filter(|finger| finger.node.id != self.id)
+            .map(|finger| (finger.node.id, finger.node.clone()))
+            .collect();
+        let reachable: HashSet<NodeId> = join_all(
+            fingers
+                .into_values()
+                .map(|node| async move { self.is_reachable(&node).await.then_some(node.id) }),
+        )
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
+
+        let store = self.store();
+        for (index, finger) in snapshot.finger_table.into_iter().enumerate() {
+            if reachable.contains(&finger.node.id) {
+                store.update_finger(index, finger.node);
             }
         }
 
-        let successor = self.store().successor();
-        let client: Arc<C> = self.client(&successor).await;
-
-        client
-            .notify(Node {
-                id: self.id,
-                addr: self.addr,
-            })
+        self.restore_neighbours(snapshot.predecessor, snapshot.successor_list)
             .await
-            .change_context(error::ServiceError::Unexpected)?;
-
-        Ok(())
     }
 
-    pub async fn reconcile_successors(&self) {
-        let successor = self.store().successor();
-        let client: Arc<C> = self.client(&successor).await;
-
-        match client.successor_list().await {
-            Ok(successors) => {
-                let mut new_successors = vec![successor];
-                new_successors.extend(successors);
-
-                self.store().set_successor_list(new_successors);
+    /// Set the neighbours which respond to pings, returns `true` if a successor was set
+    ///
+    /// The neighbours are pinged concurrently, like in a [sweep](Self::sweep_neighbours). The
+    /// store is then written with [`write_async`](Db::write_async), so a concurrent sweep doesn't
+    /// overwrite the restored neighbours with the ones it pinged before.
+    async fn restore_neighbours(
+        &self,
+        predecessor: Option<Node>,
+        successor_list: Vec<Node>,
+    ) -> bool {
+        let permits = Semaphore::new(Self::SWEEP_CONCURRENCY);
+        let ping = |node: Node| {
+            let permits = &permits;
+            async move {
+                let _permit = permits.acquire().await;
+                let reachable = self.is_reachable(&node).await;
+                (node, reachable)
             }
-            Err(err) => {
-                log::info!(
-                    "Successor {:?} is down, removing from the successor list",
-                    successor.addr
-                );
-                log::debug!("Successor {:?} error: {err:?}", successor.addr);
+        };
+        let successor_pings = successor_list
+            .into_iter()
+            .filter(|successor| successor.id != self.id)
+            .map(ping);
+        let predecessor_ping = predecessor
+            .filter(|predecessor| predecessor.id != self.id)
+            .map(ping);
+        let (successor_pings, predecessor_ping) =
+            futures::join!(join_all(successor_pings), join_all(predecessor_ping));
 
-                let successors = self.store().successor_list();
-                self.store().set_successor_list(successors[1..].to_vec());
+        let mut successors = Vec::with_capacity(successor_pings.len());
+        for (successor, reachable) in successor_pings {
+            if reachable {
+                successors.push(successor);
+            } else {
+                log::info!("Dropping unreachable successor {}", successor);
             }
         }
+
+        let db = self.store();
+        let store = db.write_async().await;
+        if let Some((predecessor, true)) = predecessor_ping.into_iter().next() {
+            store.set_predecessor(predecessor);
+        }
+
+        if successors.is_empty() {
+            return false;
+        }
+
+        log::info!("Restored {} successors", successors.len());
+        store.set_successor_list(successors);
+        true
     }
 
-    /// Check predecessor
+    /// Fix fingers
     ///
-    /// This method is used to check if the predecessor is still alive. If not, the predecessor is
-    /// set to `None`.
+    /// This method is used to fix the fingers. It re-requests the successors of all the fingers'
+    /// ids concurrently, at most [`FIX_FINGERS_CONCURRENCY`](Self::FIX_FINGERS_CONCURRENCY) at a
+    /// time. Then sets the successor of each finger to the retrieved node. Fingers whose lookup
+    /// failed are left unchanged.
     ///
     /// > **Note**
     /// >
     /// > This method should be called periodically.
-    pub async fn check_predecessor(&self) -> Result<(), error::ServiceError> {
-        if let Some(predecessor) = self.store().predecessor() {
-            let client: Arc<C> = self.client(&predecessor).await;
-            match client.ping().await {
-                Ok(_) => Ok(()),
-                Err(err) => {
-                    log::info!(
-                        "Predecessor {:?} is down, removing. Error: {:?}",
-                        predecessor.addr,
-                        err
-                    );
-                    self.store().unset_predecessor();
-                    Ok(())
-                }
+    #[tracing::instrument(skip_all, fields(node.id = %self.id, node.addr = %self.addr))]
+    pub async fn fix_fingers(&self) {
+        let permits = Semaphore::new(Self::FIX_FINGERS_CONCURRENCY);
+        let lookups = (0..Finger::FINGER_TABLE_SIZE).map(|i| {
+            let permits = &permits;
+            async move {
+                let _permit = permits.acquire().await;
+                (i, self.lookup_finger(i).await)
             }
-        } else {
-            Ok(())
+        });
+
+        for (i, result) in join_all(lookups).await {
+            self.update_finger(i, result);
         }
+        self.warm_up_fingers();
     }
 
-    /// Fix fingers
+    /// Fix the next finger
     ///
-    /// This method is used to fix the fingers. It iterates over all fingers and re-requests the
-    /// successor of the finger's id. Then sets the successor of the finger to the retrieved node.
+    /// Unlike [`fix_fingers`](Self::fix_fingers), only one finger is fixed per call, the fingers
+    /// are fixed in turn as in the Chord paper. The lookups of the whole table are spread over
+    /// [`FINGER_TABLE_SIZE`](Finger::FINGER_TABLE_SIZE) calls, so the table takes longer to
+    /// converge but the load on the ring is much lower.
     ///
     /// > **Note**
     /// >
     /// > This method should be called periodically.
-    pub async fn fix_fingers(&self) {
-        for i in 0..Finger::FINGER_TABLE_SIZE {
-            let finger_id = Finger::finger_id(self.id.0, (i + 1) as u8);
-            let result = { self.find_successor(NodeId(finger_id)).await };
-            if let Ok(successor) = result {
-                self.store().update_finger(i.into(), successor)
-            } else {
-                log::error!("Failed to fix finger: {:?}", result.unwrap_err());
+    #[tracing::instrument(skip_all, fields(node.id = %self.id, node.addr = %self.addr))]
+    pub async fn fix_next_finger(&self) {
+        let next = self.next_finger.fetch_add(1, Ordering::Relaxed);
+        let i = (next % Finger::FINGER_TABLE_SIZE as usize) as u8;
+        let result = self.lookup_finger(i).await;
+        self.update_finger(i, result);
+        self.warm_up_fingers();
+    }
+
+    /// Look up the successor of the finger's id
+    async fn lookup_finger(&self, i: u8) -> Result<Node, error::ServiceError> {
+        let _permit = self.outbound.acquire().await;
+        let finger_id = Finger::finger_id(self.id.0, i + 1);
+        let span = tracing::debug_span!("fix_finger", finger = i, target = finger_id);
+        self.lookup(NodeId(finger_id)).instrument(span).await
+    }
+
+    /// Set the finger to the looked up successor, the finger is left unchanged if the lookup failed
+    fn update_finger(&self, i: u8, result: Result<Node, error::ServiceError>) {
+        match result {
+            Ok(successor) => self.store().update_finger(i.into(), successor),
+            Err(err) => {
+                metrics().task_failure("fix_fingers");
+                tracing::error!(finger = i, "Failed to fix finger: {:?}", err)
             }
         }
     }
 
+    /// Set up the clients of the fingers in the background, if enabled
+    fn warm_up_fingers(&self) {
+        if !self.warm_up_fingers {
+            return;
+        }
+        let state = self.store().state();
+        let fingers = state
+            .finger_table()
+            .iter()
+            .map(|finger| finger.node.clone())
+            .filter(|node| node.id != self.id);
+        self.clients.warm_up(fingers);
+    }
+
+    /// Update the metrics describing the state of the node
+    pub(crate) fn record_state(&self) {
+        let state = self.store().state();
+        metrics()
+            .successor_list_length
+            .set(state.successor_list().len() as i64);
+        metrics()
+            .predecessor_present
+            .set(state.predecessor().is_some() as i64);
+    }
+
     /// Get finger table
     ///
     /// This method is used to get the finger table of the node.
@@ -295,37 +2009,105 @@ impl<C: Client + Clone + Sync + Send + 'static> NodeService<C> {
     ///
     /// # Returns
     ///
-    /// The closest preceding node
-    fn closest_preceding_node(&self, id: NodeId) -> Node {
+    /// The closest preceding node, or the current node if no finger precedes the id
+    pub fn closest_preceding_node(&self, id: NodeId) -> Node {
         self.store()
             .closest_preceding_node(self.id.0, id.0)
             .unwrap_or(Node::with_id(self.id, self.addr))
     }
 
+    /// Subscribe to the changes of the node's place in the ring
+    ///
+    /// The events are broadcast without waiting for the subscribers, so a subscriber which
+    /// doesn't keep up misses the oldest events and gets a
+    /// [`Lagged`](tokio::sync::broadcast::error::RecvError::Lagged) error instead.
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<NodeEvent> {
+        self.store().subscribe()
+    }
+
+    /// Get the round-trip times of the calls made to the other nodes
+    pub fn peer_latencies(&self) -> HashMap<NodeId, LatencyStats> {
+        self.clients.latencies()
+    }
+
     async fn client(&self, node: &Node) -> Arc<C> {
         self.clients.get_or_init(node).await
     }
 }
 
 pub mod error {
+    use error_stack::Result;
     use thiserror::Error;
 
-    use crate::client;
+    use crate::{client, NodeId};
 
-    #[derive(Debug, Error)]
+    #[derive(Debug, Clone, PartialEq, Error)]
     pub enum ServiceError {
-        #[error("Unexpected error")]
-        Unexpected,
-        #[error("Client disconnected")]
-        ClientDisconnected,
+        /// A node failed to respond
+        #[error("Node {0} is unreachable")]
+        PeerUnreachable(NodeId),
+        /// The node doesn't know any other node of the ring
+        #[error("The node is not part of a ring")]
+        NotJoined,
+        /// The deadline of the request passed, or a node didn't respond in time
+        #[error("Deadline exceeded")]
+        Timeout,
+        #[error("Failed to join the ring through any of the seeds")]
+        JoinFailed,
+        /// The request is malformed, e.g. a batch over the limit
+        #[error("Invalid argument: {0}")]
+        InvalidArgument(String),
+        #[error("Internal error: {0}")]
+        Internal(String),
     }
 
-    impl From<client::ClientError> for ServiceError {
-        fn from(err: client::ClientError) -> Self {
+    impl ServiceError {
+        /// Whether the request may succeed if it's sent again, e.g. once the ring routes around
+        /// an unreachable node
+        pub fn is_transient(&self) -> bool {
+            matches!(self, Self::PeerUnreachable(_) | Self::Timeout)
+        }
+    }
+
+    /// Map the error of a call to another node, given the id of the called node
+    impl From<(NodeId, &client::ClientError)> for ServiceError {
+        fn from((peer, err): (NodeId, &client::ClientError)) -> Self {
             match err {
-                client::ClientError::ConnectionFailed(_) => Self::ClientDisconnected,
-                _ => Self::Unexpected,
+                client::ClientError::ConnectionFailed(_) => Self::PeerUnreachable(peer),
+                client::ClientError::DeadlineExceeded => Self::Timeout,
+                client::ClientError::PeerUnreachable(node) => Self::PeerUnreachable(*node),
+                err => Self::Internal(err.to_string()),
             }
         }
     }
+
+    /// Change the context of the result of a call to another node to a [`ServiceError`]
+    pub(crate) trait ClientResultExt<T> {
+        /// # Arguments
+        ///
+        /// * `peer` - The id of the called node
+        fn peer_context(self, peer: NodeId) -> Result<T, ServiceError>;
+    }
+
+    impl<T> ClientResultExt<T> for Result<T, client::ClientError> {
+        fn peer_context(self, peer: NodeId) -> Result<T, ServiceError> {
+            self.map_err(|report| {
+                let context = ServiceError::from((peer, report.current_context()));
+                report.change_context(context)
+            })
+        }
+    }
+
+    #[cfg(feature = "persistence")]
+    #[derive(Debug, Error)]
+    pub enum PersistenceError {
+        #[error("Failed to open the store in {0:?}")]
+        Open(std::path::PathBuf),
+        #[error("Failed to read from the store")]
+        Read,
+        #[error("Failed to write to the store")]
+        Write,
+        #[error("The persisted {0} is corrupted")]
+        Corrupted(String),
+    }
 }
@@ -0,0 +1,110 @@
+use crate::client::{ClientError, MockClient};
+use crate::service::tests::{self, ExpectationExt};
+use crate::service::tests::{get_lock, MTX};
+use crate::{Anomaly, NodeService};
+use std::net::SocketAddr;
+
+#[test]
+fn consistent_state_has_no_anomalies() {
+    let service = NodeService::test_service(8);
+    service
+        .store
+        .db()
+        .set_successor_list(vec![tests::node(16), tests::node(32), tests::node(4)]);
+    service.store.db().set_predecessor(tests::node(4));
+
+    assert_eq!(service.audit(), vec![]);
+}
+
+#[test]
+fn single_node_ring_has_no_anomalies() {
+    let service = NodeService::test_service(8);
+    service.store.db().set_successor(tests::node(8));
+    service.store.db().set_predecessor(tests::node(8));
+
+    assert_eq!(service.audit(), vec![]);
+}
+
+#[test]
+fn empty_successor_list_is_reported() {
+    let service = NodeService::test_service(8);
+    service.store.db().set_successor_list(vec![]);
+
+    assert_eq!(service.audit(), vec![Anomaly::EmptySuccessorList]);
+}
+
+#[test]
+fn successors_out_of_ring_order_are_reported() {
+    let service = NodeService::test_service(8);
+    service
+        .store
+        .db()
+        .set_successor_list(vec![tests::node(32), tests::node(16), tests::node(64)]);
+
+    assert_eq!(
+        service.audit(),
+        vec![Anomaly::MisorderedSuccessor {
+            index: 1,
+            node: tests::node(16)
+        }]
+    );
+}
+
+#[test]
+fn predecessor_between_the_node_and_its_successor_is_reported() {
+    let service = NodeService::test_service(8);
+    service
+        .store
+        .db()
+        .set_successor_list(vec![tests::node(32), tests::node(64)]);
+    service.store.db().set_predecessor(tests::node(16));
+
+    assert_eq!(
+        service.audit(),
+        vec![Anomaly::MisplacedPredecessor {
+            predecessor: tests::node(16),
+            successor: tests::node(32),
+        }]
+    );
+}
+
+#[tokio::test]
+async fn successor_which_failed_to_respond_is_reported() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        MockClient::mock(addr, 16, |mut client| {
+            client
+                .expect_ping()
+                .times(1)
+                .returning_error(ClientError::ConnectionFailed("Error".to_string()));
+            client
+        })
+    });
+
+    let service = NodeService::test_service(8);
+    service
+        .store
+        .db()
+        .set_successor_list(vec![tests::node(16), tests::node(32)]);
+    service.check_successor().await.unwrap();
+    assert_eq!(service.audit(), vec![]);
+
+    // A stale successor list brings the dead successor back
+    service
+        .store
+        .db()
+        .set_successor_list(vec![tests::node(16), tests::node(32)]);
+    assert_eq!(
+        service.audit(),
+        vec![Anomaly::DeadSuccessor {
+            index: 0,
+            node: tests::node(16)
+        }]
+    );
+
+    // The node is alive again once it notifies
+    service.notify(tests::node(16)).await;
+    assert_eq!(service.audit(), vec![]);
+}
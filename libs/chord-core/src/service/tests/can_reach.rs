@@ -0,0 +1,42 @@
+use crate::client::{ClientError, MockClient};
+use crate::service::tests::{self, ExpectationExt};
+use crate::service::tests::{get_lock, MTX};
+use crate::{NodeId, NodeService};
+use std::net::SocketAddr;
+
+#[tokio::test]
+async fn when_target_responds_to_ping_it_should_be_reachable() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        MockClient::mock(addr, 12, |mut client| {
+            client.expect_ping().times(1).returning(|| Ok(NodeId(12)));
+            client
+        })
+    });
+
+    let service = NodeService::test_service(8);
+
+    assert!(service.can_reach(tests::node(12)).await.unwrap());
+}
+
+#[tokio::test]
+async fn when_target_fails_to_respond_to_ping_it_should_not_be_reachable() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        MockClient::mock(addr, 12, |mut client| {
+            client
+                .expect_ping()
+                .times(1)
+                .returning_error(ClientError::ConnectionFailed("Error".to_string()));
+            client
+        })
+    });
+
+    let service = NodeService::test_service(8);
+
+    assert!(!service.can_reach(tests::node(12)).await.unwrap());
+}
@@ -12,13 +12,13 @@ async fn when_predecessor_is_up_it_should_not_be_removed() {
     ctx.expect().returning(|addr: SocketAddr| {
         let mut client = MockClient::new();
         if addr.port() == 42012 {
-            client.expect_ping().times(1).returning(|| Ok(()));
+            client.expect_ping().times(1).returning(|| Ok(NodeId(12)));
         }
         client
     });
 
     let service: NodeService<MockClient> =
-        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3);
+        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None);
     service.store.db().set_successor(tests::node(16));
     service.store.db().set_predecessor(tests::node(12));
 
@@ -28,6 +28,31 @@ async fn when_predecessor_is_up_it_should_not_be_removed() {
     assert_eq!(service.store.db().predecessor().unwrap().id, NodeId(12));
 }
 
+#[tokio::test]
+async fn when_another_node_responds_for_the_predecessor_it_should_be_removed() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    // The client is evicted, so the second check connects again
+    ctx.expect().times(2).returning(|_| {
+        let mut client = MockClient::new();
+        client.expect_ping().times(1).returning(|| Ok(NodeId(13)));
+        client
+    });
+
+    let service: NodeService<MockClient> =
+        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None);
+    service.store.db().set_successor(tests::node(16));
+    service.store.db().set_predecessor(tests::node(12));
+
+    service.check_predecessor().await.unwrap();
+    assert!(service.store.db().predecessor().is_none());
+
+    service.store.db().set_predecessor(tests::node(12));
+    service.check_predecessor().await.unwrap();
+    assert!(service.store.db().predecessor().is_none());
+}
+
 #[tokio::test]
 async fn when_predecessor_is_down_it_should_be_removed() {
     let _m = get_lock(&MTX);
@@ -47,7 +72,7 @@ async fn when_predecessor_is_down_it_should_be_removed() {
     });
 
     let service: NodeService<MockClient> =
-        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3);
+        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None);
     service.store.db().set_successor(tests::node(10));
     service.store.db().set_predecessor(tests::node(10));
 
@@ -55,3 +80,61 @@ async fn when_predecessor_is_down_it_should_be_removed() {
 
     assert!(service.store.db().predecessor().is_none());
 }
+
+#[tokio::test]
+async fn when_clients_come_from_a_factory_it_should_not_init_them() {
+    let service: NodeService<MockClient> =
+        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None)
+            .with_client_factory(|addr: SocketAddr| async move {
+                let mut client = MockClient::new();
+                if addr.port() == 42012 {
+                    client.expect_ping().times(1).returning(|| Ok(NodeId(12)));
+                }
+                client
+            });
+    service.store.db().set_successor(tests::node(16));
+    service.store.db().set_predecessor(tests::node(12));
+
+    service.check_predecessor().await.unwrap();
+
+    assert_eq!(service.store.db().predecessor().unwrap().id, NodeId(12));
+}
+
+#[tokio::test]
+async fn when_predecessor_is_down_it_should_be_replaced_from_the_predecessor_list() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        if addr.port() == 42006 {
+            client
+                .expect_ping()
+                .times(1)
+                .returning_error(ClientError::ConnectionFailed("Error".to_string()));
+        }
+        if addr.port() == 42004 {
+            client.expect_ping().times(1).returning(|| Ok(NodeId(4)));
+        }
+        client
+    });
+
+    let service: NodeService<MockClient> =
+        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None);
+    service.store.db().set_successor(tests::node(16));
+    service.store.db().set_predecessor(tests::node(6));
+    service
+        .store
+        .db()
+        .set_predecessor_list(vec![tests::node(4), tests::node(2)]);
+
+    service.check_predecessor().await.unwrap();
+    assert_eq!(
+        service.store.db().predecessor_list(),
+        vec![tests::node(4), tests::node(2)]
+    );
+
+    // The promoted predecessor is checked in turn
+    service.check_predecessor().await.unwrap();
+    assert_eq!(service.store.db().predecessor(), Some(tests::node(4)));
+}
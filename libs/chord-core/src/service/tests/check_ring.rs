@@ -0,0 +1,138 @@
+use crate::client::{ClientError, MockClient};
+use crate::service::tests::{self, get_lock, ExpectationExt, MTX};
+use crate::{NodeService, RingCheck};
+use std::net::SocketAddr;
+
+/// Mock clients whose successor is the next node of `ring`, the last node pointing to the first
+fn ring_of(ring: Vec<u64>) -> impl FnMut(SocketAddr) -> MockClient {
+    move |addr: SocketAddr| {
+        let mut client = MockClient::new();
+        if let Some(i) = ring.iter().position(|id| addr.port() == 42000 + *id as u16) {
+            let next = ring[(i + 1) % ring.len()];
+            client
+                .expect_successor()
+                .returning(move || Ok(tests::node(next)));
+        }
+
+        client
+    }
+}
+
+#[tokio::test]
+async fn when_successor_chain_loops_back_it_should_not_be_partitioned() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(ring_of(vec![8, 16, 32, 48]));
+
+    let service = NodeService::test_service(8);
+    service.store().set_successor(tests::node(16));
+
+    assert_eq!(service.check_ring(10).await.unwrap(), RingCheck::Closed);
+}
+
+#[tokio::test]
+async fn when_node_is_alone_it_should_not_be_partitioned() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().never();
+
+    let service = NodeService::test_service(8);
+
+    assert_eq!(service.check_ring(10).await.unwrap(), RingCheck::Closed);
+}
+
+#[tokio::test]
+async fn when_successor_chain_passes_over_node_it_should_be_partitioned() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    // The successors of 8 form a ring without it
+    ctx.expect().returning(ring_of(vec![16, 32, 48, 4]));
+
+    let service = NodeService::test_service(8);
+    service.store().set_successor(tests::node(16));
+
+    assert_eq!(
+        service.check_ring(10).await.unwrap(),
+        RingCheck::Partitioned
+    );
+}
+
+#[tokio::test]
+async fn when_successor_chain_is_too_long_it_should_be_inconclusive() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(ring_of(vec![8, 16, 32, 48]));
+
+    let service = NodeService::test_service(8);
+    service.store().set_successor(tests::node(16));
+
+    assert_eq!(
+        service.check_ring(2).await.unwrap(),
+        RingCheck::Inconclusive
+    );
+}
+
+#[tokio::test]
+async fn when_node_in_chain_fails_to_respond_it_should_return_error() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|addr: SocketAddr| {
+        MockClient::mock(addr, 16, |mut client| {
+            client
+                .expect_successor()
+                .returning_error(ClientError::ConnectionFailed("Error".to_string()));
+            client
+        })
+    });
+
+    let service = NodeService::test_service(8);
+    service.store().set_successor(tests::node(16));
+
+    assert!(service.check_ring(10).await.is_err());
+}
+
+#[tokio::test]
+async fn when_seed_knows_closer_successor_it_should_heal_partition() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        if addr.port() == 42040 {
+            client.mock_find_successor(8.into(), 12);
+        }
+        if addr.port() == 42012 {
+            client.expect_notify().times(1).returning(|_| Ok(()));
+        }
+
+        client
+    });
+
+    let service = NodeService::test_service(8);
+    service.store().set_successor(tests::node(16));
+    service.heal_partition(tests::node(40)).await.unwrap();
+
+    assert_eq!(service.store().successor(), tests::node(12));
+}
+
+#[tokio::test]
+async fn when_seed_knows_farther_successor_it_should_keep_successor() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        if addr.port() == 42040 {
+            client.mock_find_successor(8.into(), 20);
+        }
+        if addr.port() == 42020 {
+            client.expect_notify().times(1).returning(|_| Ok(()));
+        }
+
+        client
+    });
+
+    let service = NodeService::test_service(8);
+    service.store().set_successor(tests::node(16));
+    service.heal_partition(tests::node(40)).await.unwrap();
+
+    assert_eq!(service.store().successor(), tests::node(16));
+}
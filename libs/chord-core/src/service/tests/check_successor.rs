@@ -0,0 +1,153 @@
+use crate::client::{ClientError, MockClient};
+use crate::error::ServiceError;
+use crate::service::tests::{self, ExpectationExt};
+use crate::service::tests::{get_lock, MTX};
+use crate::{NodeId, NodeService};
+use std::net::SocketAddr;
+
+#[tokio::test]
+async fn when_successor_is_up_it_should_be_kept() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        MockClient::mock(addr, 16, |mut client| {
+            client.expect_ping().times(1).returning(|| Ok(NodeId(16)));
+            client
+        })
+    });
+
+    let service = NodeService::test_service(8);
+    service
+        .store
+        .db()
+        .set_successor_list(vec![tests::node(16), tests::node(32)]);
+
+    service.check_successor().await.unwrap();
+
+    let successor_list = service.store.db().successor_list();
+    assert_eq!(successor_list.len(), 2);
+    assert_eq!(successor_list[0].id, NodeId(16));
+    assert_eq!(successor_list[1].id, NodeId(32));
+}
+
+#[tokio::test]
+async fn when_another_node_responds_for_the_successor_the_next_successor_should_be_promoted() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        MockClient::mock(addr, 16, |mut client| {
+            client.expect_ping().times(1).returning(|| Ok(NodeId(17)));
+            client
+        })
+    });
+
+    let service = NodeService::test_service(8);
+    service
+        .store
+        .db()
+        .set_successor_list(vec![tests::node(16), tests::node(32)]);
+
+    service.check_successor().await.unwrap();
+
+    assert_eq!(service.store.db().successor().id, NodeId(32));
+    assert_eq!(service.store.db().successor_list().len(), 1);
+}
+
+#[tokio::test]
+async fn when_successor_is_down_the_next_successor_should_be_promoted() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        MockClient::mock(addr, 16, |mut client| {
+            client
+                .expect_ping()
+                .times(1)
+                .returning_error(ClientError::ConnectionFailed("Error".to_string()));
+            client
+        })
+    });
+
+    let service = NodeService::test_service(8);
+    service
+        .store
+        .db()
+        .set_successor_list(vec![tests::node(16), tests::node(32), tests::node(64)]);
+
+    service.check_successor().await.unwrap();
+
+    assert_eq!(service.store.db().successor().id, NodeId(32));
+    let successor_list = service.store.db().successor_list();
+    assert_eq!(successor_list.len(), 2);
+    assert_eq!(successor_list[1].id, NodeId(64));
+}
+
+#[tokio::test]
+async fn when_the_only_successor_is_down_it_should_be_kept() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        MockClient::mock(addr, 16, |mut client| {
+            client
+                .expect_ping()
+                .times(1)
+                .returning_error(ClientError::ConnectionFailed("Error".to_string()));
+            client
+        })
+    });
+
+    let service = NodeService::test_service(8);
+    service.store.db().set_successor(tests::node(16));
+
+    service.check_successor().await.unwrap();
+
+    assert_eq!(service.store.db().successor().id, NodeId(16));
+}
+
+#[tokio::test]
+async fn when_ping_fails_with_unexpected_error_the_successor_should_be_kept() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        MockClient::mock(addr, 16, |mut client| {
+            client
+                .expect_ping()
+                .times(1)
+                .returning_error(ClientError::Unexpected);
+            client
+        })
+    });
+
+    let service = NodeService::test_service(8);
+    service
+        .store
+        .db()
+        .set_successor_list(vec![tests::node(16), tests::node(32)]);
+
+    let result = service.check_successor().await;
+
+    assert!(result.is_err());
+    assert_eq!(service.store.db().successor().id, NodeId(16));
+}
+
+#[tokio::test(start_paused = true)]
+async fn when_successor_does_not_respond_in_time_it_should_be_kept() {
+    let service: NodeService<MockClient> = NodeService::test_service(8)
+        .with_client_factory(|_| async { std::future::pending::<MockClient>().await });
+    service
+        .store
+        .db()
+        .set_successor_list(vec![tests::node(16), tests::node(32)]);
+
+    let result = service.check_successor().await;
+
+    assert_eq!(
+        result.unwrap_err().current_context(),
+        &ServiceError::Timeout
+    );
+    assert_eq!(service.store.db().successor().id, NodeId(16));
+}
@@ -0,0 +1,86 @@
+use mockall::predicate;
+
+use crate::client::{ClientError, MockClient};
+use crate::service::tests::{self, ExpectationExt};
+use crate::service::tests::{get_lock, MTX};
+use crate::{NodeId, NodeService};
+use std::net::SocketAddr;
+
+#[tokio::test]
+async fn when_id_is_before_successor_it_should_return_current_node() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|_| MockClient::new());
+
+    let mut service = NodeService::test_service(8);
+    service.with_fingers_sized(6, vec![16, 32, 64]);
+    service.store.db().set_successor(tests::node(16));
+
+    assert_eq!(
+        service.find_predecessor(NodeId(10)).await.unwrap().id,
+        NodeId(8)
+    );
+    assert_eq!(
+        service.find_predecessor(NodeId(16)).await.unwrap().id,
+        NodeId(8)
+    );
+}
+
+#[tokio::test]
+async fn it_should_forward_lookup_to_closest_preceding_node() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        MockClient::mock(addr, 32, |mut client| {
+            client
+                .expect_find_predecessor()
+                .with(predicate::eq(NodeId(50)))
+                .times(1)
+                .returning(|_| Ok(tests::node(48)));
+            client
+        })
+    });
+
+    let mut service = NodeService::test_service(8);
+    service.with_fingers_sized(6, vec![16, 32, 64]);
+    service.store.db().set_successor(tests::node(16));
+
+    assert_eq!(
+        service.find_predecessor(NodeId(50)).await.unwrap().id,
+        NodeId(48)
+    );
+}
+
+#[tokio::test]
+async fn when_closest_preceding_node_fails_it_should_try_the_previous_one() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        if addr.port() == 42032 {
+            client
+                .expect_find_predecessor()
+                .times(1)
+                .returning_error(ClientError::ConnectionFailed("Error".to_string()));
+        }
+        if addr.port() == 42016 {
+            client
+                .expect_find_predecessor()
+                .with(predicate::eq(NodeId(50)))
+                .times(1)
+                .returning(|_| Ok(tests::node(48)));
+        }
+        client
+    });
+
+    let mut service = NodeService::test_service(8);
+    service.with_fingers_sized(6, vec![16, 32, 64]);
+    service.store.db().set_successor(tests::node(16));
+
+    assert_eq!(
+        service.find_predecessor(NodeId(50)).await.unwrap().id,
+        NodeId(48)
+    );
+}
@@ -1,16 +1,18 @@
 use mockall::predicate;
 
 use crate::client::MockClient;
+use crate::error::ServiceError;
 use crate::service::tests::{self, ExpectationExt};
 use crate::service::tests::{get_lock, MTX};
-use crate::{NodeId, NodeService};
+use crate::{deadline, NodeId, NodeService};
 use std::net::SocketAddr;
+use std::time::{Duration, SystemTime};
 
 #[tokio::test]
 async fn test_find_successor() {
     let _m = get_lock(&MTX);
     let service: NodeService<MockClient> =
-        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3);
+        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None);
     let result = service.find_successor(NodeId(10)).await;
     assert!(result.is_ok());
     let successor = result.unwrap();
@@ -33,7 +35,7 @@ async fn find_successor_with_2_nodes() {
     });
 
     let mut service: NodeService<MockClient> =
-        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3);
+        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None);
     service.with_fingers(vec![16]);
     service.store.db().set_successor(tests::node(16));
 
@@ -64,7 +66,7 @@ async fn find_successor_with_2_nodes_but_the_same_id() {
     });
 
     let service: NodeService<MockClient> =
-        NodeService::with_id(6, SocketAddr::from(([127, 0, 0, 1], 42001)), 3);
+        NodeService::with_id(6, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None);
     service.store.db().set_successor(tests::node(6));
 
     assert_eq!(
@@ -126,6 +128,30 @@ async fn check_closest_preceding_node() {
     assert_eq!(service.closest_preceding_node(NodeId(150)).id, NodeId(129));
 }
 
+#[tokio::test]
+async fn when_fingers_are_stale_it_should_forward_lookup_to_successor_list() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        MockClient::mock(addr, 32, |mut client| {
+            client.mock_find_successor(NodeId(40), 48);
+            client
+        })
+    });
+
+    // The fingers still point at the node itself, as right after joining the ring
+    let service = NodeService::test_service(8);
+    service
+        .store()
+        .set_successor_list(vec![tests::node(16), tests::node(32)]);
+
+    assert_eq!(
+        service.find_successor(NodeId(40)).await.unwrap().id,
+        NodeId(48)
+    );
+}
+
 #[tokio::test]
 async fn find_successor_using_finger_table() {
     let _m = get_lock(&MTX);
@@ -169,7 +195,7 @@ async fn find_successor_using_finger_table() {
 
     assert_eq!(
         service
-            .find_successor_using_finger_table(NodeId(150), None)
+            .find_successor_using_finger_table(NodeId(150), None, 1)
             .await
             .unwrap()
             .id,
@@ -211,10 +237,31 @@ async fn find_successor_using_finger_table_and_all_fingers_failing() {
     service.with_fingers(vec![10, 35]);
 
     let result = service
-        .find_successor_using_finger_table(NodeId(150), None)
+        .find_successor_using_finger_table(NodeId(150), None, 1)
         .await;
 
-    assert!(result.is_err());
+    assert!(matches!(
+        result.unwrap_err().current_context(),
+        ServiceError::PeerUnreachable(_)
+    ));
+}
+
+#[tokio::test]
+async fn find_successor_on_a_node_alone_in_its_ring_fails_with_not_joined() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|_| MockClient::new());
+
+    let service: NodeService<MockClient> = NodeService::default();
+
+    let result = service
+        .find_successor_using_finger_table(NodeId(150), None, 1)
+        .await;
+
+    assert_eq!(
+        result.unwrap_err().current_context(),
+        &ServiceError::NotJoined
+    );
 }
 
 #[tokio::test]
@@ -257,3 +304,54 @@ async fn find_successor_immediate_successor_list() {
         None
     );
 }
+
+#[tokio::test]
+async fn find_successor_with_an_expired_deadline_is_not_forwarded() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|_| {
+        let mut client = MockClient::new();
+        client.expect_find_successor().never();
+        client
+    });
+
+    let mut service: NodeService<MockClient> = NodeService::default();
+    service.with_fingers(vec![10, 35]);
+    service.store.db().set_successor(tests::node(10));
+
+    let expired = SystemTime::now() - Duration::from_secs(1);
+    let result = deadline::scope(Some(expired), service.find_successor(NodeId(30))).await;
+
+    assert!(matches!(
+        result.unwrap_err().current_context(),
+        ServiceError::Timeout
+    ));
+}
+
+#[tokio::test]
+async fn find_successor_returns_the_deadline_exceeded_by_the_next_node() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|_| {
+        let mut client = MockClient::new();
+        client
+            .expect_find_successor()
+            .times(1)
+            .returning_error(crate::client::ClientError::DeadlineExceeded);
+        client
+    });
+
+    let mut service: NodeService<MockClient> = NodeService::default();
+    service.with_fingers(vec![10, 35]);
+    service.store.db().set_successor(tests::node(10));
+
+    let future = SystemTime::now() + Duration::from_secs(60);
+    let result = deadline::scope(Some(future), service.find_successor(NodeId(30))).await;
+
+    assert!(matches!(
+        result.unwrap_err().current_context(),
+        ServiceError::Timeout
+    ));
+}
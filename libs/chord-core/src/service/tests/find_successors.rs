@@ -0,0 +1,89 @@
+use mockall::predicate;
+
+use crate::client::{ClientError, MockClient};
+use crate::error::ServiceError;
+use crate::service::tests::{self, ExpectationExt};
+use crate::service::tests::{get_lock, MTX};
+use crate::{NodeId, NodeService};
+use std::net::SocketAddr;
+
+#[tokio::test]
+async fn it_should_return_results_in_order_of_ids() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        MockClient::mock(addr, 32, |mut client| {
+            client
+                .expect_find_successor()
+                .with(predicate::eq(NodeId(40)))
+                .returning(|_| Ok(tests::node(48)));
+            client
+        })
+    });
+
+    let mut service = NodeService::test_service(8);
+    service.with_fingers_sized(6, vec![16, 32]);
+    service.store().set_successor(tests::node(16));
+
+    let successors = service
+        .find_successors(vec![NodeId(40), NodeId(10), NodeId(16)])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        successors,
+        vec![
+            Ok(tests::node(48)),
+            Ok(tests::node(16)),
+            Ok(tests::node(16))
+        ]
+    );
+}
+
+#[tokio::test]
+async fn when_a_lookup_fails_the_rest_of_the_batch_should_succeed() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        MockClient::mock(addr, 32, |mut client| {
+            client
+                .expect_find_successor()
+                .with(predicate::eq(NodeId(40)))
+                .returning_error(ClientError::Unexpected);
+            client
+        })
+    });
+
+    let mut service = NodeService::test_service(8);
+    service.with_fingers_sized(6, vec![16, 32]);
+    service.store().set_successor(tests::node(16));
+
+    let successors = service
+        .find_successors(vec![NodeId(10), NodeId(40), NodeId(12)])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        successors,
+        vec![
+            Ok(tests::node(16)),
+            Err(ServiceError::Internal("Unexpected error".to_string())),
+            Ok(tests::node(16))
+        ]
+    );
+}
+
+#[tokio::test]
+async fn when_the_batch_is_too_large_it_should_be_rejected() {
+    let service: NodeService<MockClient> = NodeService::test_service(8);
+    let ids = vec![NodeId(10); NodeService::<MockClient>::MAX_FIND_SUCCESSORS_BATCH + 1];
+
+    let err = service.find_successors(ids).await.unwrap_err();
+
+    assert!(matches!(
+        err.current_context(),
+        ServiceError::InvalidArgument(_)
+    ));
+}
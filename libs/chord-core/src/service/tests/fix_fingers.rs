@@ -1,7 +1,12 @@
-use crate::client::MockClient;
-use crate::service::tests::{get_lock, MTX};
-use crate::{NodeId, NodeService};
+use crate::client::{ClientError, MockClient};
+use crate::service::tests::{get_lock, node, MTX};
+use crate::{Finger, Node, NodeId, NodeService};
+use error_stack::Report;
 use std::net::SocketAddr;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 #[tokio::test]
 async fn fix_fingers_test() {
@@ -23,7 +28,7 @@ async fn fix_fingers_test() {
         client
     });
     let mut service: NodeService<MockClient> =
-        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42008)), 3);
+        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42008)), 3, None);
     service.with_fingers_sized(6, vec![1, 14, 21, 32, 38, 42, 48, 51]);
 
     let mut finger_ids = vec![14; 3];
@@ -40,3 +45,165 @@ async fn fix_fingers_test() {
     // );
     // assert_eq!(service.collect_finger_ids(), vec![9, 10, 12, 16, 24, 40]);
 }
+
+#[tokio::test]
+async fn fix_fingers_skips_failed_lookups() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        MockClient::mock(addr, 16, |mut client| {
+            client.expect_find_successor().returning(|id: NodeId| {
+                if id.0 < 1000 {
+                    Ok(node(id.0 + 1))
+                } else {
+                    Err(Report::new(ClientError::Unexpected))
+                }
+            });
+            client
+        })
+    });
+    let mut service: NodeService<MockClient> = NodeService::test_service(8);
+    service.store.db().set_successor(node(16));
+    service.with_fingers_sized(4, vec![16]);
+
+    service.fix_fingers().await;
+
+    let mut finger_ids = vec![16; 4];
+    finger_ids.append(&mut vec![25, 41, 73, 137, 265, 521]);
+    finger_ids.append(&mut vec![8; 54]);
+    assert_eq!(service.collect_finger_node_ids(), finger_ids);
+}
+
+#[tokio::test]
+async fn fix_fingers_wraps_around_the_end_of_the_ring() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        MockClient::mock(addr, 5, |mut client| {
+            // Only the fingers past the successor are forwarded, they start right after it
+            client
+                .expect_find_successor()
+                .withf(|id: &NodeId| id.0 >= 21 && id.0 < 1 << 63)
+                .returning(|_| Ok(node(100)));
+            client
+        })
+    });
+    let id = u64::MAX - 10;
+    let service: NodeService<MockClient> =
+        NodeService::with_id(id, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None);
+    service.store.db().set_successor(node(5));
+
+    service.fix_fingers().await;
+
+    // The fingers 1 to 5 are in (u64::MAX - 10, 5], which wraps around 0
+    let mut finger_ids = vec![5; 5];
+    finger_ids.append(&mut vec![100; 59]);
+    assert_eq!(service.collect_finger_node_ids(), finger_ids);
+}
+
+#[tokio::test]
+async fn fix_next_finger_fixes_the_fingers_in_turn() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    // The fixed fingers are used by the next lookups, so every node answers
+    ctx.expect().returning(|_| {
+        let mut client = MockClient::new();
+        client.expect_find_successor().returning(|id: NodeId| {
+            if id.0 < 1000 {
+                Ok(node(id.0 + 1))
+            } else {
+                Err(Report::new(ClientError::Unexpected))
+            }
+        });
+        client
+    });
+    let mut service: NodeService<MockClient> = NodeService::test_service(8);
+    service.store.db().set_successor(node(16));
+    service.with_fingers_sized(4, vec![16]);
+
+    for _ in 0..6 {
+        service.fix_next_finger().await;
+    }
+
+    let mut finger_ids = vec![16; 4];
+    finger_ids.append(&mut vec![25, 41]);
+    finger_ids.append(&mut vec![8; 58]);
+    assert_eq!(service.collect_finger_node_ids(), finger_ids);
+
+    // Back to the first finger once the whole table was fixed
+    for _ in 6..Finger::FINGER_TABLE_SIZE + 1 {
+        service.fix_next_finger().await;
+    }
+
+    let mut finger_ids = vec![16; 4];
+    finger_ids.append(&mut vec![25, 41, 73, 137, 265, 521]);
+    finger_ids.append(&mut vec![8; 54]);
+    assert_eq!(service.collect_finger_node_ids(), finger_ids);
+}
+
+#[tokio::test]
+async fn fix_fingers_stays_within_the_outbound_budget() {
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let max_in_flight = Arc::new(AtomicUsize::new(0));
+    let created = Arc::new(AtomicUsize::new(0));
+
+    let (counter, max, total) = (in_flight.clone(), max_in_flight.clone(), created.clone());
+    let mut service: NodeService<MockClient> =
+        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42008)), 3, None)
+            .with_outbound_limit(NonZeroUsize::new(2).unwrap())
+            .with_client_factory(move |addr: SocketAddr| {
+                let (counter, max, total) = (counter.clone(), max.clone(), total.clone());
+                async move {
+                    // Opening the connection takes a while, so the lookups overlap
+                    let current = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                    max.fetch_max(current, Ordering::SeqCst);
+                    total.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    counter.fetch_sub(1, Ordering::SeqCst);
+
+                    let mut client = MockClient::new();
+                    client
+                        .expect_find_successor()
+                        .returning(move |id: NodeId| Ok(Node::with_id(id, addr)));
+                    client
+                }
+            });
+    service.with_fingers(vec![10, 12, 16, 24, 40, 72, 136, 264, 520]);
+    service.store().set_successor(node(10));
+
+    service.fix_fingers().await;
+
+    assert!(created.load(Ordering::SeqCst) > 2);
+    assert!(max_in_flight.load(Ordering::SeqCst) <= 2);
+}
+
+#[tokio::test]
+async fn fix_fingers_warms_up_the_clients_of_the_new_fingers() {
+    for warm_up in [false, true] {
+        let created = Arc::new(std::sync::Mutex::new(vec![]));
+
+        let addrs = created.clone();
+        let mut service: NodeService<MockClient> =
+            NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42008)), 3, None)
+                .with_finger_warm_up(warm_up)
+                .with_client_factory(move |addr: SocketAddr| {
+                    addrs.lock().unwrap().push(addr.port());
+                    async move {
+                        let mut client = MockClient::new();
+                        client.expect_find_successor().returning(|_| Ok(node(20)));
+                        client
+                    }
+                });
+        service.with_fingers(vec![10, 12, 16]);
+        service.store().set_successor(node(10));
+
+        service.fix_fingers().await;
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        // Node 20 is only a finger, it isn't called during the lookups
+        assert_eq!(created.lock().unwrap().contains(&42020), warm_up);
+    }
+}
@@ -0,0 +1,73 @@
+use crate::client::{ClientError, MockClient};
+use crate::service::tests::{self, ExpectationExt};
+use crate::service::tests::{get_lock, MTX};
+use crate::{HealthStatus, NodeId, NodeService};
+use std::net::SocketAddr;
+
+#[tokio::test]
+async fn when_node_has_not_stabilized_it_should_not_be_joined() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().never();
+
+    let service = NodeService::test_service(8);
+
+    assert_eq!(
+        service.health().await.unwrap(),
+        HealthStatus {
+            joined: false,
+            has_predecessor: false,
+            successor_reachable: true,
+        }
+    );
+}
+
+#[tokio::test]
+async fn when_node_has_stabilized_it_should_be_joined() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        MockClient::mock(addr, 16, |mut client| {
+            client.expect_predecessor().returning(|| Ok(None));
+            client.expect_notify().returning(|_| Ok(()));
+            client.expect_ping().returning(|| Ok(NodeId(16)));
+            client
+        })
+    });
+
+    let service = NodeService::test_service(8);
+    service.store().set_successor(tests::node(16));
+    service.store().set_predecessor(tests::node(1));
+    service.stabilize().await.unwrap();
+
+    assert_eq!(
+        service.health().await.unwrap(),
+        HealthStatus {
+            joined: true,
+            has_predecessor: true,
+            successor_reachable: true,
+        }
+    );
+}
+
+#[tokio::test]
+async fn when_successor_fails_to_respond_it_should_not_be_reachable() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        MockClient::mock(addr, 16, |mut client| {
+            client
+                .expect_ping()
+                .times(1)
+                .returning_error(ClientError::ConnectionFailed("Error".to_string()));
+            client
+        })
+    });
+
+    let service = NodeService::test_service(8);
+    service.store().set_successor(tests::node(16));
+
+    assert!(!service.health().await.unwrap().successor_reachable);
+}
@@ -1,7 +1,8 @@
 use crate::client::{ClientError, MockClient};
+use crate::error::ServiceError;
 use crate::service::tests::{self, ExpectationExt};
 use crate::service::tests::{get_lock, MTX};
-use crate::{NodeId, NodeService};
+use crate::{Node, NodeId, NodeService};
 use mockall::predicate;
 use std::net::SocketAddr;
 
@@ -23,7 +24,7 @@ async fn join_test() {
         client
     });
     let service: NodeService<MockClient> =
-        NodeService::with_id(1, SocketAddr::from(([127, 0, 0, 1], 42001)), 3);
+        NodeService::with_id(1, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None);
 
     service.join(tests::node(115)).await.unwrap();
 
@@ -47,9 +48,58 @@ async fn join_error_test() {
         client
     });
     let service: NodeService<MockClient> =
-        NodeService::with_id(2, SocketAddr::from(([127, 0, 0, 1], 42001)), 3);
+        NodeService::with_id(2, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None);
 
     let result = service.join(tests::node(116)).await;
 
-    assert!(result.is_err());
+    assert!(matches!(
+        result.unwrap_err().current_context(),
+        ServiceError::Internal(_)
+    ));
+}
+
+#[tokio::test]
+async fn join_through_an_unreachable_seed_fails_with_the_seed_id() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|_| {
+        let mut client = MockClient::new();
+        client
+            .expect_find_successor()
+            .times(1)
+            .returning_error(ClientError::ConnectionFailed("Error".to_string()));
+        client
+    });
+    let service: NodeService<MockClient> =
+        NodeService::with_id(2, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None);
+
+    let result = service.join(tests::node(117)).await;
+
+    assert_eq!(
+        result.unwrap_err().current_context(),
+        &ServiceError::PeerUnreachable(NodeId(117))
+    );
+}
+
+#[tokio::test]
+async fn join_itself_test() {
+    // Any request would panic, as the clients have no expectation
+    let service: NodeService<MockClient> =
+        NodeService::with_id(3, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None)
+            .with_client_factory(|_| async { MockClient::new() });
+    service.store.db().set_successor(tests::node(115));
+
+    service
+        .join(Node::new(SocketAddr::from(([127, 0, 0, 1], 42001))))
+        .await
+        .unwrap();
+
+    assert_eq!(service.store.db().successor().id, NodeId(3));
+    assert_eq!(service.store.db().successor_list().len(), 1);
+    assert_eq!(
+        service.find_successor(NodeId(100)).await.unwrap().id,
+        NodeId(3)
+    );
+    assert!(service.health().await.unwrap().successor_reachable);
 }
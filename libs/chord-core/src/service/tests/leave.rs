@@ -0,0 +1,64 @@
+use crate::client::MockClient;
+use crate::service::tests;
+use crate::{NodeId, NodeService};
+use std::net::SocketAddr;
+
+#[test]
+fn when_the_predecessor_leaves_then_its_predecessor_should_be_adopted() {
+    let service: NodeService<MockClient> =
+        NodeService::with_id(30, SocketAddr::from(([127, 0, 0, 1], 42030)), 3, None);
+    service.store.db().set_successor(tests::node(10));
+    service.store.db().set_predecessor(tests::node(20));
+
+    service.handle_leave(tests::node(20), Some(tests::node(10)));
+
+    assert_eq!(service.store.db().predecessor().unwrap().id, NodeId(10));
+}
+
+#[test]
+fn when_the_predecessor_leaves_without_predecessor_then_the_predecessor_should_be_unset() {
+    let service: NodeService<MockClient> =
+        NodeService::with_id(30, SocketAddr::from(([127, 0, 0, 1], 42030)), 3, None);
+    service.store.db().set_successor(tests::node(10));
+    service.store.db().set_predecessor(tests::node(20));
+
+    service.handle_leave(tests::node(20), None);
+
+    assert!(service.store.db().predecessor().is_none());
+}
+
+#[test]
+fn when_another_node_leaves_then_the_predecessor_should_be_kept() {
+    let service: NodeService<MockClient> =
+        NodeService::with_id(30, SocketAddr::from(([127, 0, 0, 1], 42030)), 3, None);
+    service
+        .store
+        .db()
+        .set_successor_list(vec![tests::node(40), tests::node(50), tests::node(10)]);
+    service.store.db().set_predecessor(tests::node(20));
+
+    service.handle_leave(tests::node(40), Some(tests::node(30)));
+
+    assert_eq!(service.store.db().predecessor().unwrap().id, NodeId(20));
+    let successors: Vec<NodeId> = service
+        .store
+        .db()
+        .successor_list()
+        .iter()
+        .map(|node| node.id)
+        .collect();
+    assert_eq!(successors, vec![NodeId(50), NodeId(10)]);
+}
+
+#[test]
+fn when_the_only_successor_leaves_then_the_node_should_be_its_own_successor() {
+    let service: NodeService<MockClient> =
+        NodeService::with_id(30, SocketAddr::from(([127, 0, 0, 1], 42030)), 3, None);
+    service.store.db().set_successor(tests::node(20));
+    service.store.db().set_predecessor(tests::node(20));
+
+    service.handle_leave(tests::node(20), Some(tests::node(30)));
+
+    assert_eq!(service.store.db().successor().id, NodeId(30));
+    assert!(service.store.db().predecessor().is_none());
+}
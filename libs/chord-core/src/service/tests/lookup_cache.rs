@@ -0,0 +1,146 @@
+use crate::client::{ClientError, MockClient};
+use crate::service::tests::{self, ExpectationExt};
+use crate::service::tests::{get_lock, MTX};
+use crate::{NodeId, NodeService};
+use std::net::SocketAddr;
+use std::num::NonZeroUsize;
+use std::time::Duration;
+
+/// A node with the id 8 caching the lookups, whose successor is 16
+fn caching_service() -> NodeService<MockClient> {
+    let mut service: NodeService<MockClient> =
+        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None)
+            .with_lookup_cache(NonZeroUsize::new(16).unwrap(), Duration::from_secs(60));
+    service.with_fingers(vec![16, 32]);
+    service.store.db().set_successor(tests::node(16));
+
+    service
+}
+
+#[tokio::test]
+async fn repeated_lookup_is_served_from_the_cache() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        match addr.port() {
+            42016 => {
+                client
+                    .expect_find_successor()
+                    .times(1)
+                    .returning(|_| Ok(tests::node(32)));
+            }
+            42032 => {
+                client
+                    .expect_predecessor()
+                    .times(1)
+                    .returning(|| Ok(Some(tests::node(16))));
+            }
+            _ => {}
+        }
+        client
+    });
+    let service = caching_service();
+
+    assert_eq!(
+        service.find_successor(NodeId(20)).await.unwrap().id,
+        NodeId(32)
+    );
+    assert_eq!(
+        service.find_successor(NodeId(25)).await.unwrap().id,
+        NodeId(32)
+    );
+}
+
+#[tokio::test]
+async fn cached_successor_is_evicted_when_another_node_joined_in_front_of_it() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        match addr.port() {
+            42016 => {
+                client.mock_find_successor(NodeId(20), 32);
+                client.mock_find_successor(NodeId(22), 24);
+            }
+            42032 => {
+                client
+                    .expect_predecessor()
+                    .times(1)
+                    .returning(|| Ok(Some(tests::node(24))));
+            }
+            _ => {}
+        }
+        client
+    });
+    let service = caching_service();
+
+    assert_eq!(
+        service.find_successor(NodeId(20)).await.unwrap().id,
+        NodeId(32)
+    );
+    assert_eq!(
+        service.find_successor(NodeId(22)).await.unwrap().id,
+        NodeId(24)
+    );
+}
+
+#[tokio::test]
+async fn cached_successor_is_evicted_when_it_left_the_ring() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        match addr.port() {
+            42016 => {
+                client.mock_find_successor(NodeId(20), 32);
+                client.mock_find_successor(NodeId(25), 40);
+            }
+            42032 => {
+                client
+                    .expect_predecessor()
+                    .times(1)
+                    .returning_error(ClientError::ConnectionFailed("Error".to_string()));
+            }
+            _ => {}
+        }
+        client
+    });
+    let service = caching_service();
+
+    assert_eq!(
+        service.find_successor(NodeId(20)).await.unwrap().id,
+        NodeId(32)
+    );
+    assert_eq!(
+        service.find_successor(NodeId(25)).await.unwrap().id,
+        NodeId(40)
+    );
+}
+
+#[tokio::test]
+async fn change_of_the_routing_state_clears_the_cache() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        if addr.port() == 42016 {
+            client
+                .expect_find_successor()
+                .times(2)
+                .returning(|_| Ok(tests::node(32)));
+        }
+        client
+    });
+    let service = caching_service();
+
+    assert_eq!(
+        service.find_successor(NodeId(20)).await.unwrap().id,
+        NodeId(32)
+    );
+    service.store.db().set_predecessor(tests::node(4));
+    assert_eq!(
+        service.find_successor(NodeId(25)).await.unwrap().id,
+        NodeId(32)
+    );
+}
@@ -0,0 +1,175 @@
+use crate::client::{ClientError, MockClient};
+use crate::error::ServiceError;
+use crate::service::tests::{self, ExpectationExt};
+use crate::service::tests::{get_lock, MTX};
+use crate::{NodeId, NodeService, RetryPolicy};
+use std::net::SocketAddr;
+use std::time::Duration;
+
+/// A node with the id 8 retrying the lookups twice, whose successor is 16
+fn retrying_service() -> NodeService<MockClient> {
+    let mut service: NodeService<MockClient> =
+        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None)
+            .with_lookup_retry(RetryPolicy {
+                max_retries: 2,
+                base_delay: Duration::from_millis(100),
+                max_delay: Duration::from_secs(1),
+            });
+    service.with_fingers(vec![16, 32]);
+    service.store.db().set_successor(tests::node(16));
+
+    service
+}
+
+#[tokio::test(start_paused = true)]
+async fn transient_failure_is_retried() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        if addr.port() == 42016 {
+            client
+                .expect_find_successor()
+                .times(1)
+                .returning_error(ClientError::DeadlineExceeded);
+            client
+                .expect_find_successor()
+                .times(1)
+                .returning(|_| Ok(tests::node(24)));
+        }
+        client
+    });
+
+    let service = retrying_service();
+    let start = tokio::time::Instant::now();
+
+    assert_eq!(
+        service.find_successor(NodeId(20)).await.unwrap().id,
+        NodeId(24)
+    );
+    assert!(start.elapsed() >= Duration::from_millis(100));
+}
+
+#[tokio::test(start_paused = true)]
+async fn unreachable_peer_is_retried() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        if addr.port() == 42016 {
+            client
+                .expect_find_successor()
+                .times(1)
+                .returning_error(ClientError::ConnectionFailed("Unavailable".to_string()));
+            client
+                .expect_find_successor()
+                .times(1)
+                .returning(|_| Ok(tests::node(24)));
+        }
+        client
+    });
+
+    let service = retrying_service();
+
+    assert_eq!(
+        service.find_successor(NodeId(20)).await.unwrap().id,
+        NodeId(24)
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn lookup_fails_once_the_retries_are_exhausted() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        if addr.port() == 42016 {
+            client
+                .expect_find_successor()
+                .times(3)
+                .returning_error(ClientError::DeadlineExceeded);
+        }
+        client
+    });
+
+    let service = retrying_service();
+
+    assert_eq!(
+        service
+            .find_successor(NodeId(20))
+            .await
+            .unwrap_err()
+            .current_context(),
+        &ServiceError::Timeout
+    );
+}
+
+#[tokio::test]
+async fn permanent_failure_is_not_retried() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        if addr.port() == 42016 {
+            client
+                .expect_find_successor()
+                .times(1)
+                .returning_error(ClientError::InvalidRequest("Invalid id".to_string()));
+        }
+        client
+    });
+
+    let service = retrying_service();
+
+    assert!(matches!(
+        service
+            .find_successor(NodeId(20))
+            .await
+            .unwrap_err()
+            .current_context(),
+        ServiceError::Internal(_)
+    ));
+}
+
+#[test]
+fn delay_is_doubled_up_to_the_maximum() {
+    let policy = RetryPolicy {
+        max_retries: 5,
+        base_delay: Duration::from_millis(100),
+        max_delay: Duration::from_millis(300),
+    };
+
+    assert_eq!(policy.delay(1), Duration::from_millis(100));
+    assert_eq!(policy.delay(2), Duration::from_millis(200));
+    assert_eq!(policy.delay(3), Duration::from_millis(300));
+    assert_eq!(policy.delay(10), Duration::from_millis(300));
+}
+
+#[tokio::test(start_paused = true)]
+async fn transient_failure_of_a_batch_is_retried() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        if addr.port() == 42016 {
+            client
+                .expect_find_successor()
+                .times(1)
+                .returning_error(ClientError::DeadlineExceeded);
+            client
+                .expect_find_successor()
+                .times(1)
+                .returning(|_| Ok(tests::node(24)));
+        }
+        client
+    });
+
+    let service = retrying_service();
+
+    let successors = service
+        .find_successors(vec![NodeId(20), NodeId(10)])
+        .await
+        .unwrap();
+
+    assert_eq!(successors, vec![Ok(tests::node(24)), Ok(tests::node(16))]);
+}
@@ -1,17 +1,37 @@
 use crate::client::__mock_MockClient_Client::{
-    __find_successor, __ping, __predecessor, __successor_list,
+    __find_predecessor, __find_successor, __ping, __predecessor, __predecessor_list, __successor,
+    __successor_list,
 };
 use crate::client::{self, ClientsPool, MockClient};
 use crate::{Node, NodeId, NodeService};
 use std::net::SocketAddr;
+use std::num::NonZeroUsize;
 
+mod audit;
+mod can_reach;
 mod check_predecessor;
+mod check_ring;
+mod check_successor;
+mod find_predecessor;
 mod find_successor;
+mod find_successors;
 mod fix_fingers;
+mod health;
 mod join;
+mod leave;
+mod lookup_cache;
+mod lookup_retry;
 mod notify;
+mod owns;
+mod reconcile_predecessors;
 mod reconcile_successors;
+#[cfg(feature = "persistence")]
+mod restore;
+mod single_node;
+mod snapshot;
 mod stabilize;
+mod sweep_neighbours;
+mod trigger;
 
 use crate::node::store::NodeStore;
 use crate::node::Finger;
@@ -19,6 +39,7 @@ use error_stack::Report;
 use lazy_static::lazy_static;
 use mockall::predicate;
 use std::sync::{Mutex, MutexGuard};
+use tokio::sync::Semaphore;
 
 lazy_static! {
     pub(crate) static ref MTX: Mutex<()> = Mutex::new(());
@@ -36,7 +57,7 @@ pub(crate) fn get_lock(m: &'static Mutex<()>) -> MutexGuard<'static, ()> {
     }
 }
 
-fn node(id: u64) -> Node {
+pub(crate) fn node(id: u64) -> Node {
     let addr = SocketAddr::from(([127, 0, 0, 1], 42000 + id as u16));
     Node::with_id(id, addr)
 }
@@ -44,11 +65,19 @@ fn node(id: u64) -> Node {
 impl Default for NodeService<MockClient> {
     fn default() -> Self {
         let node = Node::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)));
-        let store = NodeStore::new(node.clone(), 3);
+        let store = NodeStore::new(node.clone(), 3, NonZeroUsize::new(3).unwrap());
         Self {
             id: node.id,
             addr: node.addr,
             store,
+            stabilized: Default::default(),
+            next_finger: Default::default(),
+            outbound: Semaphore::new(Semaphore::MAX_PERMITS),
+            verify_notify: false,
+            lookup_cache: None,
+            lookup_retry: Default::default(),
+            warm_up_fingers: false,
+            failed_nodes: Default::default(),
             clients: ClientsPool::default(),
         }
     }
@@ -57,11 +86,19 @@ impl Default for NodeService<MockClient> {
 impl NodeService<MockClient> {
     fn test_service(id: u64) -> Self {
         let node = Node::with_id(id, SocketAddr::from(([127, 0, 0, 1], 42000 + id as u16)));
-        let store = NodeStore::new(node.clone(), 3);
+        let store = NodeStore::new(node.clone(), 3, NonZeroUsize::new(3).unwrap());
         Self {
             id: node.id,
             addr: node.addr,
             store,
+            stabilized: Default::default(),
+            next_finger: Default::default(),
+            outbound: Semaphore::new(Semaphore::MAX_PERMITS),
+            verify_notify: false,
+            lookup_cache: None,
+            lookup_retry: Default::default(),
+            warm_up_fingers: false,
+            failed_nodes: Default::default(),
             clients: ClientsPool::default(),
         }
     }
@@ -107,7 +144,7 @@ impl NodeService<MockClient> {
     }
 
     // pub(crate) fn collect_finger_ids(&self) -> Vec<u64> {
-    //     self.store.db().finger_table().iter().map(|f| f._start).collect()
+    //     self.store.db().finger_table().iter().map(|f| f.start).collect()
     // }
 
     pub(crate) fn collect_finger_node_ids(&self) -> Vec<u64> {
@@ -170,12 +207,30 @@ impl ExpectationExt<client::ClientError> for __find_successor::Expectation {
     }
 }
 
+impl ExpectationExt<client::ClientError> for __find_predecessor::Expectation {
+    fn returning_error(&mut self, err: client::ClientError) -> &mut Self {
+        self.returning(move |_| Err(Report::new(err.to_owned())))
+    }
+}
+
 impl ExpectationExt<client::ClientError> for __predecessor::Expectation {
     fn returning_error(&mut self, err: client::ClientError) -> &mut Self {
         self.returning(move || Err(Report::new(err.to_owned())))
     }
 }
 
+impl ExpectationExt<client::ClientError> for __predecessor_list::Expectation {
+    fn returning_error(&mut self, err: client::ClientError) -> &mut Self {
+        self.returning(move || Err(Report::new(err.to_owned())))
+    }
+}
+
+impl ExpectationExt<client::ClientError> for __successor::Expectation {
+    fn returning_error(&mut self, err: client::ClientError) -> &mut Self {
+        self.returning(move || Err(Report::new(err.to_owned())))
+    }
+}
+
 impl ExpectationExt<client::ClientError> for __successor_list::Expectation {
     fn returning_error(&mut self, err: client::ClientError) -> &mut Self {
         self.returning(move || Err(Report::new(err.to_owned())))
@@ -207,16 +262,16 @@ mod tests {
         let nodes = vec![1, 16, 32, 64];
         service.with_fingers(nodes.clone());
 
-        assert_eq!(9, service.store.db().finger_table()[0]._start);
+        assert_eq!(9, service.store.db().finger_table()[0].start);
         assert_eq!(16, service.store.db().finger_table()[0].node.id.0);
-        assert_eq!(10, service.store.db().finger_table()[1]._start);
+        assert_eq!(10, service.store.db().finger_table()[1].start);
         assert_eq!(16, service.store.db().finger_table()[1].node.id.0);
-        assert_eq!(12, service.store.db().finger_table()[2]._start);
+        assert_eq!(12, service.store.db().finger_table()[2].start);
         assert_eq!(16, service.store.db().finger_table()[2].node.id.0);
-        assert_eq!(16, service.store.db().finger_table()[3]._start);
+        assert_eq!(16, service.store.db().finger_table()[3].start);
         assert_eq!(16, service.store.db().finger_table()[3].node.id.0);
 
-        assert_eq!(264, service.store.db().finger_table()[8]._start);
+        assert_eq!(264, service.store.db().finger_table()[8].start);
         assert_eq!(1, service.store.db().finger_table()[8].node.id.0);
 
         service.id = NodeId(2);
@@ -240,9 +295,9 @@ mod tests {
 
         assert_eq!(1, service.store.db().finger_table()[0].node.id.0);
         assert_eq!(1, service.store.db().finger_table()[1].node.id.0);
-        assert_eq!(12, service.store.db().finger_table()[2]._start);
+        assert_eq!(12, service.store.db().finger_table()[2].start);
         assert_eq!(16, service.store.db().finger_table()[2].node.id.0);
-        assert_eq!(24, service.store.db().finger_table()[4]._start);
+        assert_eq!(24, service.store.db().finger_table()[4].start);
         assert_eq!(16, service.store.db().finger_table()[4].node.id.0);
 
         // service.id = NodeId(1);
@@ -251,9 +306,9 @@ mod tests {
 
         // assert_eq!(16, service.store.db().finger_table()[0].node.id.0);
         // assert_eq!(16, service.store.db().finger_table()[1].node.id.0);
-        // assert_eq!(5, service.store.db().finger_table()[2]._start);
+        // assert_eq!(5, service.store.db().finger_table()[2].start);
         // assert_eq!(16, service.store.db().finger_table()[2].node.id.0);
-        // assert_eq!(17, service.store.db().finger_table()[4]._start);
+        // assert_eq!(17, service.store.db().finger_table()[4].start);
         // assert_eq!(32, service.store.db().finger_table()[4].node.id.0);
     }
 
@@ -1,44 +1,133 @@
-use crate::client::MockClient;
-use crate::service::tests;
+use crate::client::{ClientError, MockClient};
+use crate::service::tests::{self, ExpectationExt};
+use crate::service::tests::{get_lock, MTX};
 use crate::{NodeId, NodeService};
 use std::net::SocketAddr;
 
-#[test]
-fn when_calling_notify_and_predecessor_is_none_then_the_predecessor_should_be_set() {
+#[tokio::test]
+async fn when_calling_notify_and_predecessor_is_none_then_the_predecessor_should_be_set() {
     let service: NodeService<MockClient> =
-        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3);
+        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None);
     service.store.db().set_successor(tests::node(16));
 
     assert!(service.store.db().predecessor().is_none());
-    service.notify(tests::node(8));
+    service.notify(tests::node(6)).await;
 
-    assert_eq!(service.store.db().predecessor().unwrap().id, NodeId(8));
+    assert_eq!(service.store.db().predecessor().unwrap().id, NodeId(6));
 }
 
-#[test]
-fn when_calling_notify_and_predecessor_set_and_request_node_is_in_range_then_the_predecessor_should_be_set(
+#[tokio::test]
+async fn when_calling_notify_and_predecessor_set_and_request_node_is_in_range_then_the_predecessor_should_be_set(
 ) {
     let service: NodeService<MockClient> =
-        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3);
+        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None);
     service.store.db().set_successor(tests::node(16));
     service.store.db().set_predecessor(tests::node(4));
 
     assert!(service.store.db().predecessor().is_some());
-    service.notify(tests::node(8));
+    service.notify(tests::node(6)).await;
 
-    assert_eq!(service.store.db().predecessor().unwrap().id, NodeId(8));
+    assert_eq!(service.store.db().predecessor().unwrap().id, NodeId(6));
 }
 
-#[test]
-fn when_calling_notify_and_predecessor_set_and_request_node_is_not_in_range_then_the_predecessor_should_not_be_set(
+#[tokio::test]
+async fn when_calling_notify_and_predecessor_set_and_request_node_is_not_in_range_then_the_predecessor_should_not_be_set(
 ) {
     let service: NodeService<MockClient> =
-        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3);
+        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None);
     service.store.db().set_successor(tests::node(16));
     service.store.db().set_predecessor(tests::node(4));
 
     assert!(service.store.db().predecessor().is_some());
-    service.notify(tests::node(16));
+    service.notify(tests::node(16)).await;
 
     assert_eq!(service.store.db().predecessor().unwrap().id, NodeId(4));
 }
+
+#[tokio::test]
+async fn when_the_node_notifies_itself_then_the_predecessor_should_not_be_set() {
+    let service: NodeService<MockClient> =
+        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None);
+    service.store.db().set_successor(tests::node(16));
+
+    service.notify(tests::node(8)).await;
+    assert!(service.store.db().predecessor().is_none());
+
+    service.store.db().set_predecessor(tests::node(4));
+    service.notify(tests::node(8)).await;
+    assert_eq!(service.store.db().predecessor().unwrap().id, NodeId(4));
+}
+
+#[tokio::test]
+async fn when_the_predecessor_notifies_again_then_the_predecessor_should_be_kept() {
+    let service: NodeService<MockClient> =
+        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None);
+    service.store.db().set_successor(tests::node(16));
+
+    service.notify(tests::node(6)).await;
+    service.notify(tests::node(6)).await;
+
+    assert_eq!(service.store.db().predecessor(), Some(tests::node(6)));
+}
+
+#[tokio::test]
+async fn when_verifying_notify_and_the_node_is_not_reachable_then_the_predecessor_should_not_be_set(
+) {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|_| {
+        let mut client = MockClient::new();
+        client
+            .expect_ping()
+            .returning_error(ClientError::ConnectionFailed("Error".to_string()));
+        client
+    });
+
+    let service: NodeService<MockClient> =
+        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None)
+            .with_notify_verification(true);
+    service.store.db().set_predecessor(tests::node(4));
+
+    service.notify(tests::node(6)).await;
+
+    assert_eq!(service.store.db().predecessor().unwrap().id, NodeId(4));
+}
+
+#[tokio::test]
+async fn when_verifying_notify_and_another_node_responds_then_the_predecessor_should_not_be_set() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|_| {
+        let mut client = MockClient::new();
+        client.expect_ping().returning(|| Ok(NodeId(5)));
+        client
+    });
+
+    let service: NodeService<MockClient> =
+        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None)
+            .with_notify_verification(true);
+
+    service.notify(tests::node(6)).await;
+
+    assert!(service.store.db().predecessor().is_none());
+}
+
+#[tokio::test]
+async fn when_verifying_notify_and_the_node_is_reachable_then_the_predecessor_should_be_set() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|_| {
+        let mut client = MockClient::new();
+        client.expect_ping().returning(|| Ok(NodeId(6)));
+        client
+    });
+
+    let service: NodeService<MockClient> =
+        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None)
+            .with_notify_verification(true);
+    service.store.db().set_predecessor(tests::node(4));
+
+    service.notify(tests::node(6)).await;
+
+    assert_eq!(service.store.db().predecessor().unwrap().id, NodeId(6));
+}
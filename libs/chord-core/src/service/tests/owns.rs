@@ -0,0 +1,44 @@
+use crate::client::MockClient;
+use crate::service::tests;
+use crate::{Node, NodeId, NodeService};
+use std::net::SocketAddr;
+
+#[test]
+fn node_owns_the_ids_after_its_predecessor() {
+    let service: NodeService<MockClient> = NodeService::test_service(8);
+    service.store().set_successor(tests::node(16));
+    service.store().set_predecessor(tests::node(2));
+
+    assert!(service.owns(NodeId(3)));
+    assert!(service.owns(NodeId(8)));
+
+    assert!(!service.owns(NodeId(2)));
+    assert!(!service.owns(NodeId(9)));
+    assert!(!service.owns(NodeId(u64::MAX)));
+}
+
+#[test]
+fn node_owns_the_ids_wrapping_around_zero() {
+    let service: NodeService<MockClient> = NodeService::test_service(8);
+    service.store().set_successor(tests::node(16));
+    let predecessor = Node::with_id(u64::MAX - 10, SocketAddr::from(([127, 0, 0, 1], 42002)));
+    service.store().set_predecessor(predecessor);
+
+    assert!(service.owns(NodeId(u64::MAX)));
+    assert!(service.owns(NodeId(0)));
+    assert!(service.owns(NodeId(8)));
+
+    assert!(!service.owns(NodeId(u64::MAX - 10)));
+    assert!(!service.owns(NodeId(16)));
+}
+
+#[test]
+fn node_without_predecessor_owns_the_ids_only_when_alone() {
+    let service: NodeService<MockClient> = NodeService::test_service(8);
+    assert!(service.owns(NodeId(3)));
+    assert!(service.owns(NodeId(100)));
+
+    service.store().set_successor(tests::node(16));
+    assert!(!service.owns(NodeId(3)));
+    assert!(!service.owns(NodeId(8)));
+}
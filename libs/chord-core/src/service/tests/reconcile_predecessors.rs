@@ -0,0 +1,91 @@
+use crate::client::{ClientError, MockClient};
+use crate::service::tests::{self, get_lock, ExpectationExt, MTX};
+use crate::NodeService;
+
+#[tokio::test]
+async fn test_updating_predecessor_list_from_predecessor() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|addr| {
+        MockClient::mock(addr, 6, |mut client| {
+            client
+                .expect_predecessor_list()
+                .times(1)
+                .returning(|| Ok(vec![tests::node(4), tests::node(60), tests::node(50)]));
+            client
+        })
+    });
+
+    let service = NodeService::test_service(8);
+    service.store().set_predecessor(tests::node(6));
+
+    service.reconcile_predecessors().await;
+
+    assert_eq!(
+        service.store().predecessor_list(),
+        vec![tests::node(6), tests::node(4), tests::node(60)]
+    );
+}
+
+#[tokio::test]
+async fn test_predecessor_list_drops_the_nodes_not_preceding_the_predecessor() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|addr| {
+        MockClient::mock(addr, 6, |mut client| {
+            // A stale list, still holding the node itself and a node between them
+            client
+                .expect_predecessor_list()
+                .returning(|| Ok(vec![tests::node(7), tests::node(8), tests::node(4)]));
+            client
+        })
+    });
+
+    let service = NodeService::test_service(8);
+    service.store().set_predecessor(tests::node(6));
+
+    service.reconcile_predecessors().await;
+
+    assert_eq!(
+        service.store().predecessor_list(),
+        vec![tests::node(6), tests::node(4)]
+    );
+}
+
+#[tokio::test]
+async fn test_predecessor_failing_to_respond_keeps_the_predecessor_list() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|addr| {
+        MockClient::mock(addr, 6, |mut client| {
+            client
+                .expect_predecessor_list()
+                .returning_error(ClientError::ConnectionFailed("Error".to_string()));
+            client
+        })
+    });
+
+    let service = NodeService::test_service(8);
+    service.store().set_predecessor(tests::node(6));
+    service.store().set_predecessor_list(vec![tests::node(4)]);
+
+    service.reconcile_predecessors().await;
+
+    assert_eq!(
+        service.store().predecessor_list(),
+        vec![tests::node(6), tests::node(4)]
+    );
+}
+
+#[tokio::test]
+async fn test_without_predecessor_nothing_is_requested() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().never();
+
+    let service = NodeService::test_service(8);
+
+    service.reconcile_predecessors().await;
+
+    assert!(service.store().predecessor_list().is_empty());
+}
@@ -3,6 +3,7 @@ use crate::service::tests::{self, ExpectationExt};
 use crate::service::tests::{get_lock, MTX};
 use crate::{NodeId, NodeService};
 use std::net::SocketAddr;
+use std::num::NonZeroUsize;
 
 #[tokio::test]
 async fn test_updating_successor_list_from_successor() {
@@ -198,3 +199,209 @@ async fn test_updating_successor_list_with_failing_node_as_successor() {
     assert_eq!(successor_list[0].id, NodeId(32));
     assert_eq!(successor_list[1].id, NodeId(64));
 }
+
+#[tokio::test]
+async fn test_successor_returning_fewer_nodes_than_replication_factor_keeps_known_successors() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        if addr.port() == 42016 {
+            client
+                .expect_successor_list()
+                .returning(|| Ok(vec![tests::node(32)]));
+        }
+        client
+    });
+
+    let service = NodeService::test_service(8);
+    service
+        .store
+        .db()
+        .set_successor_list(vec![tests::node(16), tests::node(32), tests::node(64)]);
+
+    service.reconcile_successors().await;
+
+    let successor_list = service.store.db().successor_list();
+    assert_eq!(successor_list.len(), 3);
+    assert_eq!(successor_list[0].id, NodeId(16));
+    assert_eq!(successor_list[1].id, NodeId(32));
+    assert_eq!(successor_list[2].id, NodeId(64));
+}
+
+#[tokio::test]
+async fn test_successor_returning_empty_list_keeps_known_successors() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        if addr.port() == 42016 {
+            client.expect_successor_list().returning(|| Ok(vec![]));
+        }
+        client
+    });
+
+    let service = NodeService::test_service(8);
+    service
+        .store
+        .db()
+        .set_successor_list(vec![tests::node(16), tests::node(32)]);
+
+    service.reconcile_successors().await;
+
+    let successor_list = service.store.db().successor_list();
+    assert_eq!(successor_list.len(), 2);
+    assert_eq!(successor_list[0].id, NodeId(16));
+    assert_eq!(successor_list[1].id, NodeId(32));
+}
+
+#[tokio::test]
+async fn test_merged_successor_list_is_kept_in_ring_order() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        if addr.port() == 42016 {
+            client
+                .expect_successor_list()
+                .returning(|| Ok(vec![tests::node(48), tests::node(16), tests::node(4)]));
+        }
+        client
+    });
+
+    let service = NodeService::test_service(8);
+    service
+        .store
+        .db()
+        .set_successor_list(vec![tests::node(16), tests::node(32)]);
+
+    service.reconcile_successors().await;
+
+    let successor_list = service.store.db().successor_list();
+    assert_eq!(successor_list.len(), 3);
+    assert_eq!(successor_list[0].id, NodeId(16));
+    assert_eq!(successor_list[1].id, NodeId(32));
+    assert_eq!(successor_list[2].id, NodeId(48));
+}
+
+#[tokio::test]
+async fn test_only_successor_failing_to_respond_is_kept() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        if addr.port() == 42016 {
+            client
+                .expect_successor_list()
+                .returning_error(ClientError::ConnectionFailed("Error".to_string()));
+        }
+        client
+    });
+
+    let service = NodeService::test_service(8);
+    service.store.db().set_successor(tests::node(16));
+
+    service.reconcile_successors().await;
+
+    let successor_list = service.store.db().successor_list();
+    assert_eq!(successor_list.len(), 1);
+    assert_eq!(successor_list[0].id, NodeId(16));
+}
+
+#[test]
+fn test_successor_list_size_defaults_to_a_non_zero_replication_factor() {
+    let capacity = NodeService::<MockClient>::successor_list_capacity;
+
+    assert_eq!(capacity(3, None).get(), 3);
+    assert_eq!(capacity(0, NonZeroUsize::new(2)).get(), 2);
+    assert!(std::panic::catch_unwind(|| capacity(0, None)).is_err());
+}
+
+#[tokio::test]
+async fn test_successor_list_size_is_independent_of_replication_factor() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        if addr.port() == 42016 {
+            client.expect_successor_list().returning(|| {
+                Ok(vec![
+                    tests::node(32),
+                    tests::node(64),
+                    tests::node(128),
+                    tests::node(256),
+                ])
+            });
+        }
+        client
+    });
+
+    let service: NodeService<MockClient> = NodeService::with_id(
+        8,
+        SocketAddr::from(([127, 0, 0, 1], 42008)),
+        2,
+        NonZeroUsize::new(4),
+    );
+    assert_eq!(service.replication_factor(), 2);
+    assert_eq!(service.successor_list_size(), 4);
+    service.store.db().set_successor(tests::node(16));
+
+    service.reconcile_successors().await;
+
+    let successor_list: Vec<NodeId> = service
+        .store
+        .db()
+        .successor_list()
+        .iter()
+        .map(|node| node.id)
+        .collect();
+    assert_eq!(
+        successor_list,
+        vec![NodeId(16), NodeId(32), NodeId(64), NodeId(128)]
+    );
+}
+
+#[tokio::test]
+async fn test_successor_list_wrapping_around_to_the_node_excludes_it() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        if addr.port() == 42016 {
+            // A ring of three nodes, the successors of 16 wrap around back to 8
+            client
+                .expect_successor_list()
+                .returning(|| Ok(vec![tests::node(32), tests::node(8), tests::node(16)]));
+        }
+        client
+    });
+
+    let service = NodeService::test_service(8);
+    service.store.db().set_successor(tests::node(16));
+
+    service.reconcile_successors().await;
+
+    let successor_list = service.store.db().successor_list();
+    assert_eq!(successor_list, vec![tests::node(16), tests::node(32)]);
+    assert!(successor_list.iter().all(|node| node.id != service.id()));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_successor_which_does_not_respond_in_time_is_removed() {
+    let service: NodeService<MockClient> = NodeService::test_service(8)
+        .with_client_factory(|_| async { std::future::pending::<MockClient>().await });
+    service
+        .store
+        .db()
+        .set_successor_list(vec![tests::node(16), tests::node(32)]);
+
+    service.reconcile_successors().await;
+
+    assert_eq!(service.store.db().successor_list(), vec![tests::node(32)]);
+}
@@ -0,0 +1,90 @@
+use crate::client::{ClientError, ClientsPool, MockClient};
+use crate::node::persistence::tests::TempDir;
+use crate::node::persistence::Persistence;
+use crate::node::store::NodeStore;
+use crate::service::tests::{self, ExpectationExt};
+use crate::service::tests::{get_lock, MTX};
+use crate::{Node, NodeId, NodeService};
+use std::net::SocketAddr;
+use std::num::NonZeroUsize;
+use tokio::sync::Semaphore;
+
+fn self_node() -> Node {
+    Node::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)))
+}
+
+/// A service sharing the given store on disk, as if the node was restarted
+fn restarted_service(persistence: &Persistence) -> NodeService<MockClient> {
+    let node = self_node();
+    NodeService {
+        id: node.id,
+        addr: node.addr,
+        store: NodeStore::with_persistence(
+            node,
+            3,
+            NonZeroUsize::new(3).unwrap(),
+            persistence.clone(),
+        ),
+        stabilized: Default::default(),
+        next_finger: Default::default(),
+        outbound: Semaphore::new(Semaphore::MAX_PERMITS),
+        verify_notify: false,
+        lookup_cache: None,
+        lookup_retry: Default::default(),
+        warm_up_fingers: false,
+        failed_nodes: Default::default(),
+        clients: ClientsPool::default(),
+    }
+}
+
+#[tokio::test]
+async fn it_should_restore_reachable_neighbours() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        if addr.port() == 42020 {
+            client
+                .expect_ping()
+                .times(1)
+                .returning_error(ClientError::ConnectionFailed("Error".to_string()));
+        } else {
+            client
+                .expect_ping()
+                .times(1)
+                .returning(move || Ok(NodeId(u64::from(addr.port() - 42000))));
+        }
+        client
+    });
+
+    let dir = TempDir::new("restore_reachable");
+    let persistence = Persistence::open(&dir.0, self_node()).unwrap();
+
+    let service = restarted_service(&persistence);
+    service
+        .store()
+        .set_successor_list(vec![tests::node(10), tests::node(20)]);
+    service.store().set_predecessor(tests::node(30));
+
+    let service = restarted_service(&persistence);
+    assert!(service.restore().await);
+
+    assert_eq!(service.store().successor_list(), vec![tests::node(10)]);
+    assert_eq!(service.store().predecessor(), Some(tests::node(30)));
+}
+
+#[tokio::test]
+async fn when_nothing_is_persisted_it_should_not_restore() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|_| MockClient::new());
+
+    let dir = TempDir::new("restore_nothing");
+    let addr = SocketAddr::from(([127, 0, 0, 1], 42001));
+
+    let service: NodeService<MockClient> =
+        NodeService::with_persistence(8, addr, 3, None, &dir.0).unwrap();
+    assert!(!service.restore().await);
+    assert_eq!(service.store().successor().addr, addr);
+}
@@ -0,0 +1,84 @@
+use crate::client::MockClient;
+use crate::server::{background_tasks, BackgroundConfig, FixFingers};
+use crate::service::tests::{self, get_lock, MTX};
+use crate::{Node, NodeId, NodeService, RingCheck};
+use mockall::predicate;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn maintenance_of_a_single_node_ring_does_not_call_any_node() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().never();
+
+    let service = NodeService::test_service(8);
+
+    service.check_successor().await.unwrap();
+    service.stabilize().await.unwrap();
+    service.check_predecessor().await.unwrap();
+    service.reconcile_predecessors().await;
+    service.reconcile_successors().await;
+    assert_eq!(service.check_ring(8).await.unwrap(), RingCheck::Closed);
+    service.fix_fingers().await;
+    service.fix_next_finger().await;
+
+    assert_eq!(service.store().successor_list(), vec![tests::node(8)]);
+    assert_eq!(service.store().predecessor(), None);
+    assert!(service.audit().is_empty());
+}
+
+#[tokio::test]
+async fn alone_node_adopts_its_predecessor_as_successor_without_asking_itself() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect()
+        .with(predicate::eq(tests::node(16).addr))
+        .times(1)
+        .returning(|_| {
+            let mut client = MockClient::new();
+            client
+                .expect_notify()
+                .with(predicate::function(|n: &Node| n.id == NodeId(8)))
+                .times(1)
+                .returning(|_| Ok(()));
+            client
+        });
+
+    let service = NodeService::test_service(8);
+    service.notify(tests::node(16)).await;
+
+    service.stabilize().await.unwrap();
+
+    assert_eq!(service.store().successor(), tests::node(16));
+}
+
+#[tokio::test(start_paused = true)]
+async fn background_tasks_of_a_single_node_ring_do_not_call_any_node() {
+    static CONNECTIONS: AtomicUsize = AtomicUsize::new(0);
+
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|_: SocketAddr| {
+        CONNECTIONS.fetch_add(1, Ordering::Relaxed);
+        MockClient::new()
+    });
+
+    let service = Arc::new(NodeService::test_service(8));
+    background_tasks(
+        service.clone(),
+        BackgroundConfig {
+            check_ring_interval: Some(Duration::from_secs(1)),
+            audit_interval: Some(Duration::from_secs(1)),
+            sweep_interval: Some(Duration::from_secs(1)),
+            fix_fingers: FixFingers::One,
+            ..Default::default()
+        },
+    );
+    tokio::time::sleep(Duration::from_secs(10)).await;
+
+    assert_eq!(CONNECTIONS.load(Ordering::Relaxed), 0);
+    assert_eq!(service.store().successor_list(), vec![tests::node(8)]);
+}
@@ -0,0 +1,82 @@
+use crate::client::{ClientError, MockClient};
+use crate::service::tests::{self, ExpectationExt};
+use crate::service::tests::{get_lock, MTX};
+use crate::{NodeId, NodeService};
+use std::net::SocketAddr;
+
+/// A node with fingers and neighbours, with the id 8
+fn service_in_ring() -> NodeService<MockClient> {
+    let mut service: NodeService<MockClient> = NodeService::test_service(8);
+    service.with_fingers_sized(6, vec![1, 14, 21, 32]);
+    service
+        .store()
+        .set_successor_list(vec![tests::node(14), tests::node(21)]);
+    service.store().set_predecessor(tests::node(1));
+
+    service
+}
+
+#[test]
+fn snapshot_contains_the_neighbours_and_the_fingers() {
+    let service = service_in_ring();
+
+    let snapshot = service.snapshot();
+
+    assert_eq!(snapshot.node, tests::node(8));
+    assert_eq!(snapshot.predecessor, Some(tests::node(1)));
+    assert_eq!(
+        snapshot.successor_list,
+        vec![tests::node(14), tests::node(21)]
+    );
+    let fingers: Vec<u64> = snapshot
+        .finger_table
+        .iter()
+        .map(|finger| finger.node.id.0)
+        .collect();
+    assert_eq!(fingers, service.collect_finger_node_ids());
+}
+
+#[tokio::test]
+async fn restore_snapshot_keeps_the_reachable_nodes() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        if addr.port() == 42021 {
+            client
+                .expect_ping()
+                .returning_error(ClientError::ConnectionFailed("Error".to_string()));
+        } else {
+            client
+                .expect_ping()
+                .returning(move || Ok(NodeId(u64::from(addr.port() - 42000))));
+        }
+        client
+    });
+
+    let snapshot = service_in_ring().snapshot();
+    let service: NodeService<MockClient> = NodeService::test_service(8);
+    assert!(service.restore_snapshot(snapshot).await);
+
+    assert_eq!(service.store().successor_list(), vec![tests::node(14)]);
+    assert_eq!(service.store().predecessor(), Some(tests::node(1)));
+    let mut fingers = vec![14; 3];
+    fingers.extend([8, 32, 1]);
+    fingers.extend([8; 58]);
+    assert_eq!(service.collect_finger_node_ids(), fingers);
+}
+
+#[tokio::test]
+async fn snapshot_of_another_node_is_not_restored() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|_| MockClient::new());
+
+    let snapshot = service_in_ring().snapshot();
+    let service: NodeService<MockClient> = NodeService::test_service(9);
+
+    assert!(!service.restore_snapshot(snapshot).await);
+    assert_eq!(service.store().successor(), tests::node(9));
+    assert_eq!(service.store().predecessor(), None);
+}
@@ -1,9 +1,11 @@
 use crate::client::{ClientError, MockClient};
 use crate::service::tests::{self, ExpectationExt};
 use crate::service::tests::{get_lock, MTX};
-use crate::{Node, NodeId, NodeService};
+use crate::{Node, NodeEvent, NodeId, NodeService};
 use mockall::predicate;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 #[tokio::test]
 async fn stabilize_when_predecessor_is_between_node_and_successor_then_set_set_the_it_as_new_successor(
@@ -31,7 +33,7 @@ async fn stabilize_when_predecessor_is_between_node_and_successor_then_set_set_t
     });
 
     let service: NodeService<MockClient> =
-        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3);
+        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None);
     service.store.db().set_successor(tests::node(16));
 
     assert_eq!(service.store.db().successor().id, NodeId(16));
@@ -61,7 +63,7 @@ async fn when_predecessor_is_not_between_node_and_successor_then_the_old_one_sho
     });
 
     let service: NodeService<MockClient> =
-        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3);
+        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None);
     service.store.db().set_successor(tests::node(16));
 
     assert_eq!(service.store.db().successor().id, NodeId(16));
@@ -89,7 +91,7 @@ fn when_getting_predecessor_fails_then_nothing_should_be_updated() {
     });
 
     let service: NodeService<MockClient> =
-        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3);
+        NodeService::with_id(8, SocketAddr::from(([127, 0, 0, 1], 42001)), 3, None);
     service.store.db().set_successor(tests::node(16));
 
     assert_eq!(service.store.db().successor().id, NodeId(16));
@@ -97,3 +99,81 @@ fn when_getting_predecessor_fails_then_nothing_should_be_updated() {
 
     assert_eq!(service.store.db().successor().id, NodeId(16));
 }
+
+#[tokio::test]
+async fn when_node_stabilizes_for_the_first_time_it_should_emit_joined() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        if addr.port() == 42016 {
+            client
+                .expect_predecessor()
+                .returning(|| Ok(Some(tests::node(12))));
+        }
+        if addr.port() == 42012 {
+            client
+                .expect_predecessor()
+                .returning(|| Ok(Some(tests::node(8))));
+        }
+        client.expect_notify().returning(|_| Ok(()));
+        client
+    });
+
+    let service = NodeService::test_service(8);
+    service.store().set_successor(tests::node(16));
+    let mut events = service.subscribe();
+
+    service.stabilize().await.unwrap();
+    service.stabilize().await.unwrap();
+
+    assert_eq!(
+        events.try_recv(),
+        Ok(NodeEvent::SuccessorChanged(tests::node(12)))
+    );
+    assert_eq!(events.try_recv(), Ok(NodeEvent::Joined));
+    assert!(events.try_recv().is_err());
+}
+
+#[tokio::test(start_paused = true)]
+async fn stabilize_waits_for_the_other_async_writers() {
+    let service: NodeService<MockClient> = NodeService::test_service(8);
+    let mut events = service.subscribe();
+    let db = service.store();
+    let writer = db.write_async().await;
+
+    let waiting =
+        tokio::time::timeout(std::time::Duration::from_secs(1), service.stabilize()).await;
+    assert!(waiting.is_err());
+    assert!(events.try_recv().is_err());
+
+    drop(writer);
+    service.stabilize().await.unwrap();
+    assert_eq!(events.try_recv(), Ok(NodeEvent::Joined));
+}
+
+#[tokio::test(start_paused = true)]
+async fn stabilize_keeps_the_successor_which_does_not_respond_in_time() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let service: NodeService<MockClient> =
+        NodeService::test_service(8).with_client_factory(move |_| {
+            let first = calls.fetch_add(1, Ordering::SeqCst) == 0;
+            async move {
+                // The predecessor is never read, the notification goes through
+                if first {
+                    std::future::pending::<()>().await;
+                }
+                let mut client = MockClient::new();
+                client.expect_notify().times(1).returning(|_| Ok(()));
+                client
+            }
+        });
+    service.store().set_successor(tests::node(16));
+    let mut events = service.subscribe();
+
+    service.stabilize().await.unwrap();
+
+    assert_eq!(service.store().successor(), tests::node(16));
+    assert_eq!(events.try_recv(), Ok(NodeEvent::Joined));
+}
@@ -0,0 +1,131 @@
+use crate::client::{ClientError, MockClient};
+use crate::service::tests::{self, get_lock, ExpectationExt, MTX};
+use crate::{NodeId, NodeService};
+use std::net::SocketAddr;
+
+#[tokio::test]
+async fn dead_successors_are_dropped_and_the_first_live_one_is_promoted() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        match addr.port() {
+            42016 => {
+                client
+                    .expect_ping()
+                    .times(1)
+                    .returning_error(ClientError::ConnectionFailed("Error".to_string()));
+            }
+            42024 => {
+                client.expect_ping().times(1).returning(|| Ok(NodeId(24)));
+            }
+            // Another node took over the address
+            42032 => {
+                client.expect_ping().times(1).returning(|| Ok(NodeId(33)));
+            }
+            42004 => {
+                client.expect_ping().times(1).returning(|| Ok(NodeId(4)));
+            }
+            _ => {}
+        }
+        client
+    });
+
+    let service = NodeService::test_service(8);
+    service
+        .store()
+        .set_successor_list(vec![tests::node(16), tests::node(24), tests::node(32)]);
+    service.store().set_predecessor(tests::node(4));
+
+    let dead = service.sweep_neighbours().await;
+
+    assert_eq!(dead, vec![tests::node(16), tests::node(32)]);
+    assert_eq!(service.store().successor_list(), vec![tests::node(24)]);
+    assert_eq!(service.store().predecessor(), Some(tests::node(4)));
+    assert!(service.audit().is_empty());
+}
+
+#[tokio::test]
+async fn successor_list_is_kept_when_no_successor_responds() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        if addr.port() == 42004 {
+            client.expect_ping().times(1).returning(|| Ok(NodeId(4)));
+        } else {
+            client
+                .expect_ping()
+                .times(1)
+                .returning_error(ClientError::ConnectionFailed("Error".to_string()));
+        }
+        client
+    });
+
+    let service = NodeService::test_service(8);
+    service
+        .store()
+        .set_successor_list(vec![tests::node(16), tests::node(24)]);
+    service.store().set_predecessor(tests::node(4));
+
+    let dead = service.sweep_neighbours().await;
+
+    assert_eq!(dead.len(), 2);
+    assert_eq!(
+        service.store().successor_list(),
+        vec![tests::node(16), tests::node(24)]
+    );
+    assert_eq!(service.store().predecessor(), Some(tests::node(4)));
+}
+
+#[tokio::test]
+async fn dead_predecessor_is_replaced_from_the_predecessor_list() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        match addr.port() {
+            42006 => {
+                client
+                    .expect_ping()
+                    .times(1)
+                    .returning_error(ClientError::ConnectionFailed("Error".to_string()));
+            }
+            42016 => {
+                client.expect_ping().times(1).returning(|| Ok(NodeId(16)));
+            }
+            _ => {}
+        }
+        client
+    });
+
+    let service = NodeService::test_service(8);
+    service.store().set_successor(tests::node(16));
+    service.store().set_predecessor(tests::node(6));
+    service.store().set_predecessor_list(vec![tests::node(4)]);
+
+    let dead = service.sweep_neighbours().await;
+
+    assert_eq!(dead, vec![tests::node(6)]);
+    assert_eq!(service.store().successor_list(), vec![tests::node(16)]);
+    assert_eq!(service.store().predecessor(), Some(tests::node(4)));
+}
+
+#[tokio::test]
+async fn a_node_both_successor_and_predecessor_is_pinged_once() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+    ctx.expect().times(1).returning(|addr: SocketAddr| {
+        MockClient::mock(addr, 16, |mut client| {
+            client.expect_ping().times(1).returning(|| Ok(NodeId(16)));
+            client
+        })
+    });
+
+    let service = NodeService::test_service(8);
+    service.store().set_successor(tests::node(16));
+    service.store().set_predecessor(tests::node(16));
+
+    assert!(service.sweep_neighbours().await.is_empty());
+    assert_eq!(service.store().predecessor(), Some(tests::node(16)));
+}
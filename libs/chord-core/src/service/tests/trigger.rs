@@ -0,0 +1,81 @@
+use crate::client::{ClientError, MockClient};
+use crate::service::tests;
+use crate::service::tests::{get_lock, MTX};
+use crate::{MaintenanceTask, NodeId, NodeService};
+use error_stack::Report;
+use std::net::SocketAddr;
+
+#[tokio::test]
+async fn triggered_stabilize_adopts_the_new_successor() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|addr: SocketAddr| {
+        let mut client = MockClient::new();
+        if addr.port() == 42016 {
+            client
+                .expect_predecessor()
+                .returning(|| Ok(Some(tests::node(12))));
+        }
+        client.expect_notify().returning(|_| Ok(()));
+        client
+    });
+
+    let service: NodeService<MockClient> = NodeService::test_service(8);
+    service.store().set_successor(tests::node(16));
+
+    let result = service.trigger(MaintenanceTask::Stabilize).await;
+
+    assert!(result.is_ok());
+    assert_eq!(service.store().successor().id, NodeId(12));
+}
+
+#[tokio::test]
+async fn triggered_stabilize_reports_the_failure() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|_| {
+        let mut client = MockClient::new();
+        client.expect_predecessor().returning(|| Ok(None));
+        client.expect_notify().returning(|_| {
+            Err(Report::new(ClientError::ConnectionFailed(
+                "Error".to_string(),
+            )))
+        });
+        client
+    });
+
+    let service: NodeService<MockClient> = NodeService::test_service(8);
+    service.store().set_successor(tests::node(16));
+
+    assert!(service.trigger(MaintenanceTask::Stabilize).await.is_err());
+}
+
+#[tokio::test]
+async fn triggered_reconcile_updates_the_successor_list() {
+    let _m = get_lock(&MTX);
+    let ctx = MockClient::init_context();
+
+    ctx.expect().returning(|_| {
+        let mut client = MockClient::new();
+        client
+            .expect_successor_list()
+            .returning(|| Ok(vec![tests::node(32), tests::node(64)]));
+        client
+    });
+
+    let service: NodeService<MockClient> = NodeService::test_service(8);
+    service.store().set_successor(tests::node(16));
+
+    let result = service.trigger(MaintenanceTask::ReconcileSuccessors).await;
+
+    assert!(result.is_ok());
+    let successors: Vec<u64> = service
+        .store()
+        .successor_list()
+        .iter()
+        .map(|node| node.id.0)
+        .collect();
+    assert_eq!(successors, vec![16, 32, 64]);
+}
@@ -0,0 +1,358 @@
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use error_stack::Result;
+
+use crate::client::InMemoryClient;
+use crate::error::ServiceError;
+use crate::{Node, NodeId, NodeService};
+
+/// A periodic maintenance task of a node, see [`background_tasks`](crate::server::background_tasks)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Task {
+    CheckSuccessor,
+    Stabilize,
+    CheckPredecessor,
+    ReconcilePredecessors,
+    ReconcileSuccessors,
+    FixFingers,
+}
+
+impl Task {
+    /// The tasks run by every node in a round, in order
+    pub const ROUND: [Task; 6] = [
+        Task::CheckSuccessor,
+        Task::Stabilize,
+        Task::CheckPredecessor,
+        Task::ReconcilePredecessors,
+        Task::ReconcileSuccessors,
+        Task::FixFingers,
+    ];
+}
+
+/// Deterministic simulation of a ring in a single process
+///
+/// The nodes talk through the [`InMemoryClient`] and no background task runs on its own: the
+/// maintenance tasks only run when the simulator is stepped, either one [`Task`] of one node at a
+/// time with [`run_task`](Self::run_task), or a whole round with [`step`](Self::step). A round
+/// runs every task of [`Task::ROUND`] on every live node, in the order of their ids, and advances
+/// the simulated clock by one.
+///
+/// > **Note**
+/// >
+/// > The nodes are registered in the process-wide registry of the [`InMemoryClient`], on
+/// > consecutive ports starting at the base address, so simulators running in parallel should
+/// > use distinct base addresses. The nodes are unregistered when the simulator is dropped.
+#[derive(Debug)]
+pub struct Simulator {
+    base_addr: SocketAddr,
+    replication_factor: usize,
+    next_port: u16,
+    nodes: BTreeMap<NodeId, Arc<NodeService<InMemoryClient>>>,
+    round: u64,
+}
+
+impl Simulator {
+    /// Create a simulator without any node
+    ///
+    /// # Arguments
+    ///
+    /// * `base_addr` - The address of the first node, the next ones use the following ports
+    /// * `replication_factor` - The number of successors every node keeps track of
+    pub fn new(base_addr: SocketAddr, replication_factor: usize) -> Self {
+        Self {
+            base_addr,
+            replication_factor,
+            next_port: base_addr.port(),
+            nodes: BTreeMap::new(),
+            round: 0,
+        }
+    }
+
+    /// Start a node with the given id and join it to the ring
+    ///
+    /// The node joins through the live node with the lowest id. The first node forms a ring
+    /// on its own. The node only learns about the others through the following rounds.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the node
+    pub async fn add_node(
+        &mut self,
+        id: impl Into<NodeId>,
+    ) -> Result<Arc<NodeService<InMemoryClient>>, ServiceError> {
+        let addr = SocketAddr::new(self.base_addr.ip(), self.next_port);
+        self.next_port += 1;
+
+        let node = Arc::new(NodeService::with_id(
+            id,
+            addr,
+            self.replication_factor,
+            None,
+        ));
+        InMemoryClient::register(node.clone());
+        if let Some(seed) = self.nodes.values().next() {
+            node.join(Node::with_id(seed.id(), seed.addr())).await?;
+        }
+        self.nodes.insert(node.id(), node.clone());
+
+        Ok(node)
+    }
+
+    /// Make the node with the given id fail
+    ///
+    /// The node stops responding and doesn't run any task anymore. Returns `false` if there
+    /// is no live node with the given id.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the node
+    pub fn fail_node(&mut self, id: impl Into<NodeId>) -> bool {
+        match self.nodes.remove(&id.into()) {
+            Some(node) => {
+                InMemoryClient::unregister(node.addr());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Make the node with the given id leave the ring gracefully
+    ///
+    /// The node tells its successor it's leaving, then stops responding like a failed node.
+    /// Returns `false` if there is no live node with the given id.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the node
+    pub async fn leave_node(&mut self, id: impl Into<NodeId>) -> Result<bool, ServiceError> {
+        let Some(node) = self.nodes.remove(&id.into()) else {
+            return Ok(false);
+        };
+        let result = node.leave().await;
+        InMemoryClient::unregister(node.addr());
+        result?;
+
+        Ok(true)
+    }
+
+    /// Get the live node with the given id
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the node
+    pub fn node(&self, id: impl Into<NodeId>) -> Option<Arc<NodeService<InMemoryClient>>> {
+        self.nodes.get(&id.into()).cloned()
+    }
+
+    /// Get the live nodes, in the order of their ids
+    pub fn nodes(&self) -> impl Iterator<Item = &Arc<NodeService<InMemoryClient>>> {
+        self.nodes.values()
+    }
+
+    /// Get the number of rounds run so far
+    pub fn round(&self) -> u64 {
+        self.round
+    }
+
+    /// Run a single task of the node with the given id
+    ///
+    /// Errors of the task are ignored, the same way the background tasks only log them.
+    /// Returns `false` if there is no live node with the given id.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the node
+    /// * `task` - The task to run
+    pub async fn run_task(&self, id: impl Into<NodeId>, task: Task) -> bool {
+        let Some(node) = self.node(id) else {
+            return false;
+        };
+
+        match task {
+            Task::CheckSuccessor => {
+                let _ = node.check_successor().await;
+            }
+            Task::Stabilize => {
+                let _ = node.stabilize().await;
+            }
+            Task::CheckPredecessor => {
+                let _ = node.check_predecessor().await;
+            }
+            Task::ReconcilePredecessors => node.reconcile_predecessors().await,
+            Task::ReconcileSuccessors => node.reconcile_successors().await,
+            Task::FixFingers => node.fix_fingers().await,
+        }
+
+        true
+    }
+
+    /// Run a round: every task of [`Task::ROUND`] on every live node, in the order of their ids
+    pub async fn step(&mut self) {
+        let ids: Vec<NodeId> = self.nodes.keys().copied().collect();
+        for id in ids {
+            for task in Task::ROUND {
+                self.run_task(id, task).await;
+            }
+        }
+        self.round += 1;
+    }
+
+    /// Run the given number of rounds
+    ///
+    /// # Arguments
+    ///
+    /// * `rounds` - The number of rounds to run
+    pub async fn run(&mut self, rounds: u64) {
+        for _ in 0..rounds {
+            self.step().await;
+        }
+    }
+
+    /// Run rounds until every successor pointer is correct
+    ///
+    /// Returns the number of rounds it took, or `None` if the successors are still not correct
+    /// after `max_rounds`.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_rounds` - The maximum number of rounds to run
+    pub async fn run_until_stable(&mut self, max_rounds: u64) -> Option<u64> {
+        for rounds in 0..=max_rounds {
+            if self.successors_are_correct() {
+                return Some(rounds);
+            }
+            if rounds < max_rounds {
+                self.step().await;
+            }
+        }
+
+        None
+    }
+
+    /// Get the live nodes whose successor is not the next live node on the ring
+    pub fn incorrect_successors(&self) -> Vec<NodeId> {
+        let ids: Vec<NodeId> = self.nodes.keys().copied().collect();
+        ids.iter()
+            .enumerate()
+            .filter(|(position, id)| {
+                let expected = ids[(position + 1) % ids.len()];
+                self.nodes[id].store().successor().id() != expected
+            })
+            .map(|(_, id)| *id)
+            .collect()
+    }
+
+    /// Check that the successor of every live node is the next live node on the ring
+    pub fn successors_are_correct(&self) -> bool {
+        self.incorrect_successors().is_empty()
+    }
+}
+
+impl Drop for Simulator {
+    fn drop(&mut self) {
+        for node in self.nodes.values() {
+            InMemoryClient::unregister(node.addr());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::NodeEvent;
+
+    /// Spread the ids over the whole ring, in a shuffled but fixed order
+    fn id(i: u64) -> u64 {
+        i.wrapping_mul(0x9E37_79B9_7F4A_7C15)
+    }
+
+    #[tokio::test]
+    async fn hundred_nodes_converge() {
+        let mut simulator = Simulator::new(SocketAddr::from(([127, 0, 1, 1], 44000)), 3);
+        for i in 0..100 {
+            simulator.add_node(id(i)).await.unwrap();
+        }
+        assert!(!simulator.successors_are_correct());
+
+        // All the nodes joined through the same seed, so it takes about one round per node
+        let rounds = simulator.run_until_stable(150).await;
+        assert!(rounds.is_some(), "{:?}", simulator.incorrect_successors());
+        assert_eq!(simulator.round(), rounds.unwrap());
+
+        // Let the successor lists catch up with the successors
+        simulator.run(3).await;
+        assert!(simulator.successors_are_correct());
+
+        let ids: Vec<NodeId> = simulator.nodes().map(|node| node.id()).collect();
+        for node in simulator.nodes() {
+            for id in &ids {
+                assert_eq!(node.find_successor(*id).await.unwrap().id(), *id);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn ring_recovers_from_failed_nodes() {
+        let mut simulator = Simulator::new(SocketAddr::from(([127, 0, 1, 2], 44000)), 3);
+        for i in 0..20 {
+            simulator.add_node(id(i)).await.unwrap();
+        }
+        simulator.run_until_stable(20).await.unwrap();
+
+        assert!(simulator.fail_node(id(3)));
+        assert!(simulator.fail_node(id(7)));
+        assert!(!simulator.fail_node(id(7)));
+        assert!(simulator.node(id(7)).is_none());
+
+        assert!(simulator.run_until_stable(20).await.is_some());
+        assert_eq!(simulator.nodes().count(), 18);
+    }
+
+    #[tokio::test]
+    async fn tasks_run_on_demand() {
+        let mut simulator = Simulator::new(SocketAddr::from(([127, 0, 1, 3], 44000)), 3);
+        simulator.add_node(10).await.unwrap();
+        simulator.add_node(20).await.unwrap();
+
+        // 20 joined through 10, but 10 doesn't know about 20 until 20 notifies it
+        assert_eq!(simulator.incorrect_successors(), vec![NodeId(10)]);
+
+        assert!(simulator.run_task(20, Task::Stabilize).await);
+        assert!(simulator.run_task(10, Task::Stabilize).await);
+        assert!(simulator.successors_are_correct());
+        assert_eq!(simulator.round(), 0);
+
+        assert!(!simulator.run_task(30, Task::Stabilize).await);
+    }
+
+    #[tokio::test]
+    async fn node_leaves_the_middle_of_the_ring() {
+        let mut simulator = Simulator::new(SocketAddr::from(([127, 0, 1, 4], 44000)), 3);
+        for id in [10, 20, 30] {
+            simulator.add_node(id).await.unwrap();
+        }
+        simulator.run_until_stable(10).await.unwrap();
+        simulator.run(2).await;
+
+        let mut events = simulator.node(20).unwrap().subscribe();
+        assert!(simulator.leave_node(20).await.unwrap());
+        assert!(!simulator.leave_node(20).await.unwrap());
+        assert_eq!(events.recv().await.unwrap(), NodeEvent::Left);
+
+        // The successor adopted the predecessor of the leaving node without any round
+        let successor = simulator.node(30).unwrap();
+        assert_eq!(successor.store().predecessor().unwrap().id(), NodeId(10));
+        assert!(successor
+            .store()
+            .successor_list()
+            .iter()
+            .all(|node| node.id() != NodeId(20)));
+        assert_eq!(simulator.incorrect_successors(), vec![NodeId(10)]);
+
+        // The predecessor promotes its next successor as soon as it checks the leaving one
+        assert!(simulator.run_task(10, Task::CheckSuccessor).await);
+        assert!(simulator.successors_are_correct());
+    }
+}
@@ -0,0 +1,211 @@
+//! Virtual nodes, the positions on the ring hosted by the same process
+//!
+//! A process hosting more virtual nodes owns a larger share of the keyspace, so the nodes
+//! running on larger hardware can be given more of them. Every virtual node is a node of its own,
+//! with its own routing and stabilization, listening on its own port.
+
+use std::net::SocketAddr;
+use std::num::NonZeroUsize;
+
+use error_stack::{Report, Result};
+use thiserror::Error;
+
+use crate::{Hasher, Node, NodeId, Sha256Hasher};
+
+#[derive(Debug, Error)]
+pub enum VirtualNodesError {
+    #[error("The ports of the {0} virtual nodes listening on {1} go past 65535")]
+    PortOverflow(usize, SocketAddr),
+}
+
+/// Registry of the virtual nodes hosted by the process
+///
+/// The virtual node `i` listens on the port of the process plus `i`, and its id is derived from
+/// the address of the process and `i`. The first virtual node keeps the address and the id of
+/// the process, so a process with a single virtual node is the same as a plain node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VirtualNodes {
+    nodes: Vec<Node>,
+}
+
+impl VirtualNodes {
+    /// Lay out the virtual nodes of the process
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the process, which is the address of the first virtual node
+    /// * `id` - The id of the first virtual node, if not set it's derived from the address
+    /// * `count` - The number of virtual nodes
+    pub fn new(
+        addr: SocketAddr,
+        id: Option<NodeId>,
+        count: NonZeroUsize,
+    ) -> Result<Self, VirtualNodesError> {
+        Self::with_hasher(addr, id, count, &Sha256Hasher)
+    }
+
+    /// Lay out the virtual nodes of the process, deriving their ids with the given hasher
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the process, which is the address of the first virtual node
+    /// * `id` - The id of the first virtual node, if not set it's derived from the address
+    /// * `count` - The number of virtual nodes
+    /// * `hasher` - The hasher used to derive the ids, see [`id_with_hasher`](Self::id_with_hasher)
+    pub fn with_hasher(
+        addr: SocketAddr,
+        id: Option<NodeId>,
+        count: NonZeroUsize,
+        hasher: &impl Hasher,
+    ) -> Result<Self, VirtualNodesError> {
+        let mut nodes = Vec::with_capacity(count.get());
+        for index in 0..count.get() {
+            let port = u16::try_from(index)
+                .ok()
+                .and_then(|index| addr.port().checked_add(index))
+                .ok_or_else(|| Report::new(VirtualNodesError::PortOverflow(count.get(), addr)))?;
+            let id = match (index, id) {
+                (0, Some(id)) => id,
+                _ => Self::id_with_hasher(addr, index, hasher),
+            };
+
+            nodes.push(Node::with_id(id, SocketAddr::new(addr.ip(), port)));
+        }
+
+        Ok(Self { nodes })
+    }
+
+    /// Get the id of the virtual node of the process
+    ///
+    /// The first virtual node gets the id derived from the address of the process, the others
+    /// the hash of the address followed by their index, e.g. `127.0.0.1:42000#1`.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the process
+    /// * `index` - The index of the virtual node
+    pub fn id(addr: SocketAddr, index: usize) -> NodeId {
+        Self::id_with_hasher(addr, index, &Sha256Hasher)
+    }
+
+    /// Get the id of the virtual node of the process like [`id`](Self::id), hashing with the
+    /// given hasher
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the process
+    /// * `index` - The index of the virtual node
+    /// * `hasher` - The hasher used to derive the id
+    pub fn id_with_hasher(addr: SocketAddr, index: usize, hasher: &impl Hasher) -> NodeId {
+        match index {
+            0 => hasher.hash(addr.to_string().as_bytes()),
+            index => hasher.hash(format!("{}#{}", addr, index).as_bytes()),
+        }
+    }
+
+    /// The virtual nodes, starting with the first one
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// Check if the address is one of the virtual nodes of the process
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address to check
+    pub fn contains(&self, addr: &SocketAddr) -> bool {
+        self.nodes.iter().any(|node| node.addr() == *addr)
+    }
+
+    /// Get the seeds which are not hosted by the process
+    ///
+    /// # Arguments
+    ///
+    /// * `seeds` - Addresses of nodes in the ring
+    pub fn external_seeds(&self, seeds: &[SocketAddr]) -> Vec<SocketAddr> {
+        seeds
+            .iter()
+            .copied()
+            .filter(|seed| !self.contains(seed))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::SeaHasher;
+
+    fn count(count: usize) -> NonZeroUsize {
+        NonZeroUsize::new(count).unwrap()
+    }
+
+    #[test]
+    fn virtual_nodes_listen_on_consecutive_ports() {
+        let addr: SocketAddr = "127.0.0.1:42000".parse().unwrap();
+        let vnodes = VirtualNodes::new(addr, None, count(3)).unwrap();
+
+        let addrs: Vec<SocketAddr> = vnodes.nodes().iter().map(Node::addr).collect();
+        assert_eq!(
+            addrs,
+            vec![
+                "127.0.0.1:42000".parse().unwrap(),
+                "127.0.0.1:42001".parse().unwrap(),
+                "127.0.0.1:42002".parse().unwrap(),
+            ]
+        );
+
+        let ids: HashSet<NodeId> = vnodes.nodes().iter().map(Node::id).collect();
+        assert_eq!(ids.len(), 3);
+        assert_eq!(vnodes.nodes()[0].id(), NodeId::from(addr));
+        assert_eq!(
+            vnodes.nodes()[2].id(),
+            NodeId::from("127.0.0.1:42000#2".to_string())
+        );
+    }
+
+    #[test]
+    fn first_virtual_node_keeps_the_pinned_id() {
+        let addr: SocketAddr = "127.0.0.1:42000".parse().unwrap();
+        let vnodes = VirtualNodes::new(addr, Some(NodeId(7)), count(2)).unwrap();
+
+        assert_eq!(vnodes.nodes()[0].id(), NodeId(7));
+        assert_eq!(vnodes.nodes()[1].id(), VirtualNodes::id(addr, 1));
+    }
+
+    #[test]
+    fn ids_are_derived_with_the_hasher() {
+        let addr: SocketAddr = "127.0.0.1:42000".parse().unwrap();
+        let vnodes = VirtualNodes::with_hasher(addr, None, count(2), &SeaHasher).unwrap();
+
+        assert_eq!(vnodes.nodes()[0].id(), SeaHasher.hash(b"127.0.0.1:42000"));
+        assert_eq!(vnodes.nodes()[1].id(), SeaHasher.hash(b"127.0.0.1:42000#1"));
+    }
+
+    #[test]
+    fn ports_past_the_last_one_are_rejected() {
+        let addr: SocketAddr = "127.0.0.1:65534".parse().unwrap();
+
+        assert!(VirtualNodes::new(addr, None, count(2)).is_ok());
+        assert!(matches!(
+            VirtualNodes::new(addr, None, count(3))
+                .unwrap_err()
+                .current_context(),
+            VirtualNodesError::PortOverflow(3, _)
+        ));
+    }
+
+    #[test]
+    fn external_seeds_skip_the_virtual_nodes() {
+        let addr: SocketAddr = "127.0.0.1:42000".parse().unwrap();
+        let vnodes = VirtualNodes::new(addr, None, count(2)).unwrap();
+        let other: SocketAddr = "127.0.0.1:43000".parse().unwrap();
+
+        assert_eq!(
+            vnodes.external_seeds(&[addr, "127.0.0.1:42001".parse().unwrap(), other]),
+            vec![other]
+        );
+    }
+}
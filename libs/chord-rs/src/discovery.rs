@@ -0,0 +1,167 @@
+use std::net::SocketAddr;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use chord_rs_core::NodeId;
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+
+/// Type of the mDNS service advertised by the nodes
+const SERVICE_TYPE: &str = "_chord._tcp.local.";
+
+/// The mDNS daemon of the process, `None` if it failed to start
+///
+/// The daemon runs on its own thread for the whole life of the process, so the node stays
+/// advertised until it stops.
+static DAEMON: OnceLock<Option<ServiceDaemon>> = OnceLock::new();
+
+fn daemon() -> Option<&'static ServiceDaemon> {
+    DAEMON
+        .get_or_init(|| match ServiceDaemon::new() {
+            Ok(daemon) => Some(daemon),
+            Err(err) => {
+                log::error!("Failed to start the mDNS daemon: {}", err);
+                None
+            }
+        })
+        .as_ref()
+}
+
+/// Advertise the node on the local network, so the nodes started later can join through it
+///
+/// # Arguments
+///
+/// * `addr` - The address the node listens on
+/// * `id` - The id of the node
+pub(crate) fn advertise(addr: SocketAddr, id: NodeId) {
+    let Some(daemon) = daemon() else {
+        return;
+    };
+
+    let name = format!("chord-{}", id);
+    let host = format!("{}.local.", name);
+    let properties = [("addr", addr.to_string())];
+    let info = if addr.ip().is_unspecified() {
+        // Listening on all the interfaces, advertise all their addresses
+        ServiceInfo::new(SERVICE_TYPE, &name, &host, (), addr.port(), &properties[..])
+            .map(ServiceInfo::enable_addr_auto)
+    } else {
+        ServiceInfo::new(
+            SERVICE_TYPE,
+            &name,
+            &host,
+            addr.ip(),
+            addr.port(),
+            &properties[..],
+        )
+    };
+
+    match info.and_then(|info| daemon.register(info)) {
+        Ok(()) => log::info!("Advertising the node on the local network as {}", name),
+        Err(err) => log::error!("Failed to advertise the node: {}", err),
+    }
+}
+
+/// Look for the other nodes advertised on the local network
+///
+/// Returns as soon as a node is found, or with no node once the timeout is reached.
+///
+/// # Arguments
+///
+/// * `addr` - The address of the current node, which is not returned
+/// * `timeout` - How long to look for the nodes
+pub(crate) async fn discover(addr: SocketAddr, timeout: Duration) -> Vec<SocketAddr> {
+    let Some(daemon) = daemon() else {
+        return vec![];
+    };
+    let events = match daemon.browse(SERVICE_TYPE) {
+        Ok(events) => events,
+        Err(err) => {
+            log::error!("Failed to browse the local network: {}", err);
+            return vec![];
+        }
+    };
+
+    let deadline = Instant::now() + timeout;
+    let found = tokio::task::spawn_blocking(move || {
+        while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+            let Ok(event) = events.recv_timeout(left) else {
+                break;
+            };
+            if let ServiceEvent::ServiceResolved(info) = event {
+                match peer_addr(&info) {
+                    Some(peer) if peer != addr => return vec![peer],
+                    _ => {}
+                }
+            }
+        }
+
+        vec![]
+    })
+    .await
+    .unwrap_or_default();
+
+    if let Err(err) = daemon.stop_browse(SERVICE_TYPE) {
+        log::warn!("Failed to stop browsing the local network: {}", err);
+    }
+    if found.is_empty() {
+        log::info!("No node found on the local network within {:?}", timeout);
+    } else {
+        log::info!("Found nodes on the local network: {:?}", found);
+    }
+
+    found
+}
+
+/// Get the address a discovered node listens on
+///
+/// The address advertised by the node itself is preferred, unless the node listens on all its
+/// interfaces, in which case one of the resolved addresses is used.
+fn peer_addr(info: &ServiceInfo) -> Option<SocketAddr> {
+    let advertised = info
+        .get_property_val_str("addr")
+        .and_then(|addr| addr.parse::<SocketAddr>().ok());
+
+    match advertised {
+        Some(addr) if !addr.ip().is_unspecified() => Some(addr),
+        _ => info
+            .get_addresses()
+            .iter()
+            .min()
+            .map(|ip| SocketAddr::new(*ip, info.get_port())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use super::*;
+
+    fn info(ip: IpAddr, advertised: &str) -> ServiceInfo {
+        let properties = [("addr", advertised)];
+        ServiceInfo::new(
+            SERVICE_TYPE,
+            "chord-1",
+            "chord-1.local.",
+            ip,
+            42000,
+            &properties[..],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn peer_addr_prefers_the_advertised_address() {
+        let resolved = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 10));
+
+        let peer = info(resolved, "10.0.0.1:42001");
+        assert_eq!(peer_addr(&peer), "10.0.0.1:42001".parse().ok());
+
+        // The node listens on all its interfaces
+        let peer = info(resolved, "0.0.0.0:42000");
+        assert_eq!(peer_addr(&peer), Some(SocketAddr::new(resolved, 42000)));
+
+        let peer = info(resolved, "not an address");
+        assert_eq!(peer_addr(&peer), Some(SocketAddr::new(resolved, 42000)));
+    }
+}
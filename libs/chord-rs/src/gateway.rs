@@ -0,0 +1,100 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chord_rs_core::{Client, Finger, HealthStatus, Node, NodeId, NodeService};
+use serde::Serialize;
+
+/// State of the node, as returned by `GET /status`
+#[derive(Debug, Serialize)]
+struct Status {
+    id: NodeId,
+    addr: SocketAddr,
+    predecessor: Option<Node>,
+    successor_list: Vec<Node>,
+    /// The fingers, each with the `[start, end)` interval of ids it's responsible for
+    finger_table: Vec<Finger>,
+    health: HealthStatus,
+}
+
+/// Serve the HTTP/JSON gateway of the node on `http://<addr>`
+///
+/// The gateway is served in the background, on a separate port than the node itself, and uses
+/// the same node service as the RPC transport. It exposes:
+///
+/// * `GET /status` - The id, the neighbours, the finger table and the health of the node
+/// * `GET /lookup/:key` - The node responsible for the key
+///
+/// # Arguments
+///
+/// * `addr` - The address to listen on
+/// * `node` - The service of the node
+pub(crate) fn serve<C>(addr: SocketAddr, node: Arc<NodeService<C>>)
+where
+    C: Client + Clone + Sync + Send + 'static,
+{
+    let router = Router::new()
+        .route("/status", get(status::<C>))
+        .route("/lookup/:key", get(lookup::<C>))
+        .with_state(node);
+
+    tokio::spawn(async move {
+        let server = match axum::Server::try_bind(&addr) {
+            Ok(server) => server,
+            Err(err) => {
+                log::error!("Failed to bind HTTP gateway to {}: {}", addr, err);
+                return;
+            }
+        };
+        log::info!("Serving HTTP gateway on http://{}", addr);
+
+        if let Err(err) = server.serve(router.into_make_service()).await {
+            log::error!("HTTP gateway error: {}", err);
+        }
+    });
+}
+
+async fn status<C>(State(node): State<Arc<NodeService<C>>>) -> Result<Json<Status>, Response>
+where
+    C: Client + Clone + Sync + Send + 'static,
+{
+    let predecessor = node.get_predecessor().await.map_err(internal_error)?;
+    let successor_list = node.get_successor_list().await.map_err(internal_error)?;
+    let health = node.health().await.map_err(internal_error)?;
+
+    Ok(Json(Status {
+        id: node.id(),
+        addr: node.addr(),
+        predecessor,
+        successor_list,
+        finger_table: node.finger_table(),
+        health,
+    }))
+}
+
+/// Find the node responsible for the key, the key is hashed like any other key of the ring
+async fn lookup<C>(
+    State(node): State<Arc<NodeService<C>>>,
+    Path(key): Path<String>,
+) -> Result<Json<Node>, Response>
+where
+    C: Client + Clone + Sync + Send + 'static,
+{
+    let owner = node
+        .find_successor(NodeService::<C>::key_id(key))
+        .await
+        .map_err(|err| {
+            log::debug!("Lookup failed: {:?}", err);
+            (StatusCode::BAD_GATEWAY, err.to_string()).into_response()
+        })?;
+
+    Ok(Json(owner))
+}
+
+fn internal_error(err: impl std::fmt::Display) -> Response {
+    (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response()
+}
@@ -1,47 +1,508 @@
 
-#[cfg(all(feature = "capnp", feature = "grpc"))]
-compile_error!("feature \"capnp\" and feature \"grpc\" cannot be enabled at the same time");
-
 use std::net::SocketAddr;
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::path::PathBuf;
+use std::time::Duration;
 
-#[cfg(feature = "grpc")]
-pub use grpc::Server;
+#[cfg(all(feature = "discovery", any(feature = "capnp", feature = "grpc")))]
+mod discovery;
+#[cfg(all(feature = "http-gateway", any(feature = "capnp", feature = "grpc")))]
+mod gateway;
+mod metrics;
+#[cfg(all(feature = "srv-discovery", any(feature = "capnp", feature = "grpc")))]
+mod srv;
+mod transport;
 
-#[cfg(feature = "capnp")]
-pub use capnp::Server;
+/// Token used to stop a running [`Server`]
+pub use tokio_util::sync::CancellationToken;
+
+#[cfg(feature = "chaos")]
+pub use chord_rs_core::chaos::{Chaos, ChaosConfig};
+pub use chord_rs_core::client::ClientError;
+pub use chord_rs_core::crawl::{RingDump, RingEnd, RingLink};
+pub use chord_rs_core::server::{
+    BackgroundConfig, FixFingers, JoinConfig, LookupCacheConfig, ServerConfig,
+};
+pub use chord_rs_core::vnode::VirtualNodes;
+pub use chord_rs_core::{
+    key_to_id, HashFunction, IntoNodeId, Node, NodeId, RetryPolicy, RingParams,
+};
+
+pub use transport::Transport;
+#[cfg(any(feature = "capnp", feature = "grpc"))]
+pub use transport::{dump_ring, find_owner, Server};
 
 pub struct Config {
     pub addr: SocketAddr,
-    pub ring: Option<SocketAddr>,
+    /// RPC transport the node communicates with the ring over
+    pub transport: Transport,
+    /// Id of the node, if not set it's derived from the address
+    pub node_id: Option<NodeId>,
+    /// Hash function deriving the ids of the virtual nodes from the address
+    pub hasher: HashFunction,
+    /// Addresses of nodes in the ring to join, tried in order
+    pub ring: Vec<SocketAddr>,
+    /// Whether to advertise the node on the local network and look for the nodes to join there,
+    /// before the ones in `ring`. Requires the `discovery` feature.
+    pub discover: bool,
+    /// How long to look for the nodes on the local network
+    pub discover_timeout: Duration,
+    /// Name whose DNS SRV records point to the nodes to join, looked up again before every
+    /// attempt to join the ring. The nodes in `ring` are tried after the ones found, or alone
+    /// if none is found. Requires the `srv-discovery` feature.
+    pub discover_srv: Option<String>,
+    /// Number of nodes the data is replicated on
+    pub replication_factor: usize,
+    /// Number of successors every node keeps track of, defaults to the replication factor
+    pub successor_list_size: Option<NonZeroUsize>,
+    /// Number of virtual nodes hosted by the process, see [`VirtualNodes`]. The limits of the
+    /// connections and of the requests apply to every virtual node
+    pub vnodes: NonZeroUsize,
+
+    /// Maximum number of concurrent connections of the Cap'n Proto transport, and of concurrent
+    /// requests of the gRPC transport, see `chord_grpc::server::concurrency_limit`
+    pub max_connections: NonZeroUsize,
+
+    /// Maximum number of requests per second accepted from every peer address,
+    /// if not set the requests are not limited. The nodes running on the same host, including
+    /// this node's own calls to itself, share the same limit
+    pub rate_limit: Option<NonZeroU32>,
+
+    /// Maximum number of requests the maintenance tasks of every virtual node send to other
+    /// nodes at the same time, if not set the requests are not limited
+    pub max_outbound_requests: Option<NonZeroUsize>,
+
+    /// Whether every virtual node pings the nodes which notify it before adopting them as its
+    /// predecessor
+    pub verify_notify: bool,
+
+    /// Cache of the lookups of every virtual node, if not set the lookups are not cached
+    pub lookup_cache: Option<LookupCacheConfig>,
+
+    /// Retries of the lookups of every virtual node which fail transiently
+    pub lookup_retry: RetryPolicy,
+
+    /// Whether every virtual node connects to its fingers in the background once they're fixed
+    pub warm_up_fingers: bool,
+
+    /// Codec compressing the messages sent to the other nodes, if not set they are not
+    /// compressed. Supported by the gRPC transport only, the Cap'n Proto server panics if set
+    pub compression: Option<Compression>,
+
+    /// TLS configuration, if set the node communicates with the ring over TLS
+    pub tls: Option<TlsConfig>,
+
+    /// Cluster secret, if set the node rejects requests from nodes without the same secret
+    pub secret: Option<String>,
+
+    /// Address to serve the metrics on, if not set the metrics are not exposed
+    pub metrics: Option<SocketAddr>,
+
+    /// Address to serve the HTTP/JSON gateway on, if not set the gateway is not exposed.
+    /// Requires the `http-gateway` feature.
+    pub http_gateway: Option<SocketAddr>,
+
+    /// Origins allowed to call the gRPC transport from a browser over gRPC-Web, if empty every
+    /// origin is allowed. gRPC-Web is served with the `grpc-web` feature only.
+    pub grpc_web_origins: Vec<String>,
+
+    /// Intervals of the periodic maintenance tasks
+    pub background: BackgroundConfig,
+
+    /// Retry policy used to join the ring, and whether the node may create a new ring when it
+    /// has no other node to join
+    pub join: JoinConfig,
+
+    /// Directory to keep the node's neighbours in, if not set the state is kept in memory only.
+    /// Requires the `persistence` feature.
+    pub data_dir: Option<PathBuf>,
+
+    /// Faults injected into the requests of the ring, they can be changed while the node runs
+    /// through [`Server::chaos`]
+    #[cfg(feature = "chaos")]
+    pub chaos: ChaosConfig,
+}
+
+/// Codec compressing the messages sent to the other nodes
+///
+/// The nodes accept compressed messages whatever their own setting, so a ring can mix the nodes
+/// compressing their messages and the ones which don't.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+}
+
+/// Paths to the PEM encoded files needed to communicate over TLS
+#[derive(Clone, Debug)]
+pub struct TlsConfig {
+    pub cert: PathBuf,
+    pub key: PathBuf,
+    pub ca: PathBuf,
+
+    /// Domain name used to verify the peers' certificates
+    pub domain: Option<String>,
+}
+
+/// Get the nodes to join, looking for them on the local network first if discovery is enabled
+///
+/// The nodes in `config.ring` are tried after the discovered ones, or alone if none is found.
+#[cfg(any(feature = "capnp", feature = "grpc"))]
+#[allow(unused_variables)]
+async fn seeds(addr: SocketAddr, config: &Config) -> Vec<SocketAddr> {
+    if !config.discover {
+        return config.ring.clone();
+    }
+
+    #[cfg(feature = "discovery")]
+    {
+        let mut seeds = discovery::discover(addr, config.discover_timeout).await;
+        for seed in &config.ring {
+            if !seeds.contains(seed) {
+                seeds.push(*seed);
+            }
+        }
+
+        seeds
+    }
+
+    #[cfg(not(feature = "discovery"))]
+    {
+        log::error!("The local discovery is not enabled, cannot look for the nodes to join");
+        panic!("The `discovery` feature is required to discover the nodes on the local network");
+    }
+}
+
+/// Get the resolver of the nodes to join from the DNS SRV records, if their name is set
+#[cfg(any(feature = "capnp", feature = "grpc"))]
+fn seed_resolver(config: &Config) -> Option<chord_rs_core::server::SeedResolver> {
+    let name = config.discover_srv.clone()?;
+
+    #[cfg(feature = "srv-discovery")]
+    {
+        Some(srv::seed_resolver(name))
+    }
+
+    #[cfg(not(feature = "srv-discovery"))]
+    {
+        log::error!("The SRV discovery is not enabled, cannot look up {}", name);
+        panic!("The `srv-discovery` feature is required to discover the nodes over DNS SRV");
+    }
+}
+
+/// Advertise the node on the local network, if discovery is enabled
+#[cfg(any(feature = "capnp", feature = "grpc"))]
+#[allow(unused_variables)]
+fn advertise<C>(discover: bool, node: &chord_rs_core::NodeService<C>)
+where
+    C: chord_rs_core::Client + Clone + Sync + Send + 'static,
+{
+    #[cfg(feature = "discovery")]
+    if discover {
+        discovery::advertise(node.addr(), node.id());
+    }
+}
+
+/// Serve the HTTP/JSON gateway of the node, if its address is set
+#[cfg(any(feature = "capnp", feature = "grpc"))]
+#[allow(unused_variables)]
+fn serve_gateway<C>(addr: Option<SocketAddr>, node: std::sync::Arc<chord_rs_core::NodeService<C>>)
+where
+    C: chord_rs_core::Client + Clone + Sync + Send + 'static,
+{
+    let Some(addr) = addr else {
+        return;
+    };
+
+    #[cfg(feature = "http-gateway")]
+    gateway::serve(addr, node);
+
+    #[cfg(not(feature = "http-gateway"))]
+    {
+        log::error!(
+            "The HTTP gateway is not enabled, cannot serve it on {}",
+            addr
+        );
+        panic!("The `http-gateway` feature is required to serve the HTTP gateway");
+    }
+}
+
+/// Create the injector of the faults shared by the virtual nodes
+#[cfg(all(feature = "chaos", any(feature = "capnp", feature = "grpc")))]
+fn chaos(config: &Config) -> std::sync::Arc<Chaos> {
+    log::warn!("Injecting faults into the requests: {:?}", config.chaos);
+    match Chaos::new(config.chaos) {
+        Ok(chaos) => std::sync::Arc::new(chaos),
+        Err(err) => {
+            log::error!("{:?}", err);
+            panic!("Invalid chaos configuration: {}", err);
+        }
+    }
+}
+
+/// Lay out the virtual nodes of the process
+#[cfg(any(feature = "capnp", feature = "grpc"))]
+fn virtual_nodes(addr: SocketAddr, config: &Config) -> VirtualNodes {
+    match VirtualNodes::with_hasher(addr, config.node_id, config.vnodes, &config.hasher) {
+        Ok(vnodes) => vnodes,
+        Err(err) => {
+            log::error!("{:?}", err);
+            panic!("Invalid number of virtual nodes: {}", err);
+        }
+    }
+}
+
+/// Get the nodes the virtual node joins the ring through
+///
+/// The virtual nodes join through the seeds which are not hosted by the process. If there is
+/// none, the first virtual node joins through the seeds as a plain node would, and the others get
+/// no seed, they join the first one once it's served, see [`join_first_vnode`].
+///
+/// # Arguments
+///
+/// * `vnodes` - The virtual nodes of the process
+/// * `index` - The index of the virtual node
+/// * `seeds` - Addresses of nodes in the ring
+#[cfg(any(feature = "capnp", feature = "grpc"))]
+fn vnode_seeds(vnodes: &VirtualNodes, index: usize, seeds: &[SocketAddr]) -> Vec<SocketAddr> {
+    let external = vnodes.external_seeds(seeds);
+    if !external.is_empty() {
+        external
+    } else if index == 0 {
+        seeds.to_vec()
+    } else {
+        vec![]
+    }
+}
+
+/// Get the directory the virtual node keeps its neighbours in
+///
+/// The first virtual node uses the directory itself, the others a subdirectory each.
+#[cfg(any(feature = "capnp", feature = "grpc"))]
+fn vnode_data_dir(data_dir: Option<&PathBuf>, index: usize) -> Option<PathBuf> {
+    match index {
+        0 => data_dir.cloned(),
+        index => data_dir.map(|dir| dir.join(format!("vnode-{}", index))),
+    }
+}
+
+/// Get the parameters the transport starts the virtual node with
+///
+/// # Arguments
+///
+/// * `config` - The configuration of the process
+/// * `vnode` - The virtual node
+/// * `index` - The index of the virtual node
+/// * `ring` - Addresses of nodes the virtual node joins the ring through, see [`vnode_seeds`]
+///
+/// Only the first virtual node looks up the SRV records, the others join the ring through it
+/// unless they're given seeds hosted by other processes.
+#[cfg(any(feature = "capnp", feature = "grpc"))]
+fn vnode_config(
+    config: &Config,
+    vnode: &chord_rs_core::Node,
+    index: usize,
+    ring: Vec<SocketAddr>,
+) -> ServerConfig {
+    // The virtual nodes without a seed start alone, until they join the first one
+    let join = JoinConfig {
+        bootstrap: config.join.bootstrap || (index > 0 && ring.is_empty()),
+        ..config.join
+    };
+    ServerConfig {
+        node_id: Some(vnode.id()),
+        ring,
+        seed_resolver: if index == 0 {
+            seed_resolver(config)
+        } else {
+            None
+        },
+        replication_factor: config.replication_factor,
+        successor_list_size: config.successor_list_size,
+        max_outbound_requests: config.max_outbound_requests,
+        verify_notify: config.verify_notify,
+        lookup_cache: config.lookup_cache,
+        lookup_retry: config.lookup_retry,
+        warm_up_fingers: config.warm_up_fingers,
+        background: config.background,
+        join,
+        data_dir: vnode_data_dir(config.data_dir.as_ref(), index),
+    }
+}
+
+/// Join the virtual nodes created without a seed to the ring of the first virtual node
+///
+/// The first virtual node is expected to be served by the time the join is retried.
+///
+/// # Arguments
+///
+/// * `nodes` - The virtual nodes to join
+/// * `first` - The address of the first virtual node
+/// * `join` - The retry policy
+#[cfg(any(feature = "capnp", feature = "grpc"))]
+fn join_first_vnode<C>(
+    nodes: Vec<std::sync::Arc<chord_rs_core::NodeService<C>>>,
+    first: SocketAddr,
+    join: JoinConfig,
+) where
+    C: chord_rs_core::Client + Clone + Sync + Send + 'static,
+{
+    if nodes.is_empty() {
+        return;
+    }
 
-    pub max_connections: usize,
+    tokio::spawn(async move {
+        for node in nodes {
+            if let Err(err) = chord_rs_core::server::join_ring(node.clone(), &[first], join).await {
+                log::error!(
+                    "Virtual node {} failed to join the ring: {:?}",
+                    node.id(),
+                    err
+                );
+            }
+        }
+    });
 }
 
 #[cfg(feature = "capnp")]
 mod capnp {
     use std::net::SocketAddr;
+    use std::sync::Arc;
 
-    use crate::Config;
+    use crate::{CancellationToken, ClientError, Config, Node, NodeId, RingDump};
+    use chord_capnp::client::ChordCapnpClient;
     use chord_capnp::Server as CapnpServer;
+    use chord_rs_core::crawl::{crawl_ring_with, find_owner_with};
+    use chord_rs_core::NodeService;
+    use futures::future::join_all;
 
     pub struct Server {
-        server: CapnpServer,
+        servers: Vec<CapnpServer>,
+        /// Virtual nodes to join to the first one once it's served
+        pending: Vec<Arc<NodeService<ChordCapnpClient>>>,
         config: Config,
+        #[cfg(feature = "chaos")]
+        chaos: Arc<crate::Chaos>,
     }
 
     impl Server {
         pub async fn new(addr: SocketAddr, config: impl Into<Config>) -> Server {
             let config: Config = config.into();
-            let chord = CapnpServer::new(addr, config.ring).await;
+            if let Some(metrics) = config.metrics {
+                crate::metrics::serve(metrics);
+            }
+            if config.tls.is_some() {
+                log::error!("TLS is not supported by the Cap'n Proto transport");
+                panic!("TLS is not supported by the Cap'n Proto transport");
+            }
+            if config.compression.is_some() {
+                log::error!("Compression is not supported by the Cap'n Proto transport");
+                panic!("Compression is not supported by the Cap'n Proto transport");
+            }
+            let seeds = crate::seeds(addr, &config).await;
+            let vnodes = crate::virtual_nodes(addr, &config);
+            #[cfg(feature = "chaos")]
+            let chaos = crate::chaos(&config);
+            let mut servers = vec![];
+            let mut pending = vec![];
+            for (index, vnode) in vnodes.nodes().iter().enumerate() {
+                let ring = crate::vnode_seeds(&vnodes, index, &seeds);
+                let joins_later = index > 0 && ring.is_empty();
+                let chord = CapnpServer::new(
+                    vnode.addr(),
+                    crate::vnode_config(&config, vnode, index, ring),
+                    config.secret.clone(),
+                    config.rate_limit,
+                )
+                .await;
+                #[cfg(feature = "chaos")]
+                let chord = chord.with_chaos(chaos.clone());
+                if joins_later {
+                    pending.push(chord.node());
+                }
+                servers.push(chord);
+            }
+            crate::advertise(config.discover, &servers[0].node());
+            crate::serve_gateway(config.http_gateway, servers[0].node());
 
             Server {
-                server: chord,
-                config
+                servers,
+                pending,
+                config,
+                #[cfg(feature = "chaos")]
+                chaos,
             }
         }
 
-        pub async fn run(self) {
-            self.server.run(self.config.max_connections).await;
+        /// Get the injector of the faults shared by the virtual nodes
+        #[cfg(feature = "chaos")]
+        pub fn chaos(&self) -> Arc<crate::Chaos> {
+            self.chaos.clone()
+        }
+
+        pub async fn run(self, shutdown: CancellationToken) {
+            crate::join_first_vnode(
+                self.pending,
+                self.servers[0].node().addr(),
+                self.config.join,
+            );
+            let max_connections = self.config.max_connections.get();
+            join_all(
+                self.servers
+                    .iter()
+                    .map(|server| server.run(max_connections, shutdown.clone())),
+            )
+            .await;
+        }
+    }
+
+    /// Crawl the ring starting at the given node, see [`crawl_ring_with`]
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The address of the first node
+    /// * `max_hops` - The maximum number of nodes to visit
+    /// * `tls` - Not supported by this transport, must be `None`
+    /// * `secret` - The cluster secret, if the ring requires one
+    pub async fn dump_ring(
+        start: SocketAddr,
+        max_hops: usize,
+        tls: Option<crate::TlsConfig>,
+        secret: Option<String>,
+    ) -> RingDump {
+        no_tls(tls);
+        crawl_ring_with(start, max_hops, |addr| {
+            let client = ChordCapnpClient::with_secret(addr, secret.clone());
+            async move { client }
+        })
+        .await
+    }
+
+    /// Find the node responsible for the given id, see [`find_owner_with`]
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The address of a node of the ring
+    /// * `id` - The id to find the owner of
+    /// * `tls` - Not supported by this transport, must be `None`
+    /// * `secret` - The cluster secret, if the ring requires one
+    pub async fn find_owner(
+        seed: SocketAddr,
+        id: NodeId,
+        tls: Option<crate::TlsConfig>,
+        secret: Option<String>,
+    ) -> Result<Node, ClientError> {
+        no_tls(tls);
+        let client = ChordCapnpClient::with_secret(seed, secret);
+        find_owner_with(seed, id, |_| async move { client })
+            .await
+            .map_err(|err| err.current_context().clone())
+    }
+
+    /// Check that the tools talking to the ring don't expect TLS
+    fn no_tls(tls: Option<crate::TlsConfig>) {
+        if tls.is_some() {
+            log::error!("TLS is not supported by the Cap'n Proto transport");
+            panic!("TLS is not supported by the Cap'n Proto transport");
         }
     }
 }
@@ -49,36 +510,277 @@ mod capnp {
 #[cfg(feature = "grpc")]
 mod grpc {
     use std::net::SocketAddr;
-    use chord_grpc::server::ChordNodeServer;
+    use std::sync::Arc;
+
+    #[cfg(feature = "chaos")]
+    use chord_grpc::chaos::ChaosLayer;
+    use chord_grpc::client::{ChordGrpcClient, ClientConfig};
+    use chord_grpc::health::health_service;
     use chord_grpc::server::Server as GrpcServer;
-    use chord_grpc::server::ChordService;
+    use chord_grpc::server::{
+        authenticate, chord_node_server, concurrency_limit, rate_limit, ChordService,
+        CompressionEncoding,
+    };
+    use chord_grpc::tls::TlsConfig;
+    use chord_rs_core::crawl::{crawl_ring_with, find_owner_with};
+    use chord_rs_core::rate_limit::RateLimiter;
+    use chord_rs_core::NodeService;
+    use futures::future::join_all;
+    use tonic::service::interceptor::InterceptedService;
+    use tonic::transport::ClientTlsConfig;
+    use tower::layer::util::{Identity, Stack};
+    use tower::limit::GlobalConcurrencyLimitLayer;
+    #[cfg(feature = "chaos")]
+    use tower::Layer;
+
+    use crate::{CancellationToken, ClientError, Config, JoinConfig, Node, NodeId, RingDump};
+
+    /// Layers of the server of a virtual node
+    #[cfg(not(feature = "grpc-web"))]
+    type Layers = Stack<GlobalConcurrencyLimitLayer, Identity>;
+    #[cfg(feature = "grpc-web")]
+    type Layers =
+        Stack<GlobalConcurrencyLimitLayer, Stack<chord_grpc::web::GrpcWebLayer, Identity>>;
+
+    impl From<crate::Compression> for CompressionEncoding {
+        fn from(compression: crate::Compression) -> Self {
+            match compression {
+                crate::Compression::Gzip => CompressionEncoding::Gzip,
+            }
+        }
+    }
 
-    use crate::Config;
+    impl From<crate::TlsConfig> for TlsConfig {
+        fn from(config: crate::TlsConfig) -> Self {
+            TlsConfig {
+                cert: config.cert,
+                key: config.key,
+                ca: config.ca,
+                domain: config.domain,
+            }
+        }
+    }
 
     pub struct Server {
+        vnodes: Vec<VirtualNode>,
+        /// Virtual nodes to join to the first one once it's served
+        pending: Vec<Arc<NodeService<ChordGrpcClient>>>,
+        join: JoinConfig,
+        #[cfg(feature = "chaos")]
+        chaos: Arc<crate::Chaos>,
+    }
+
+    /// Router of a virtual node, served on its own address
+    struct VirtualNode {
         addr: SocketAddr,
-        router: tonic::transport::server::Router,
+        router: tonic::transport::server::Router<Layers>,
+        node: Arc<NodeService<ChordGrpcClient>>,
     }
 
     impl Server {
         pub async fn new(addr: SocketAddr, config: impl Into<Config>) -> Server {
             let config: Config = config.into();
-            let chord = ChordService::new(addr, config.ring).await;
-    
-            let router = GrpcServer::builder()
-                .add_service(ChordNodeServer::new(chord));
-    
+            if let Some(metrics) = config.metrics {
+                crate::metrics::serve(metrics);
+            }
+            let seeds = crate::seeds(addr, &config).await;
+            let vnodes = crate::virtual_nodes(addr, &config);
+            let mut server_tls = None;
+            let mut client_tls = None;
+            if let Some(tls) = config.tls.clone() {
+                let tls: TlsConfig = tls.into();
+                let client_config = tls
+                    .client_config()
+                    .expect("Failed to load TLS configuration");
+                let server_config = tls
+                    .server_config()
+                    .expect("Failed to load TLS configuration");
+
+                client_tls = Some(client_config);
+                server_tls = Some(server_config);
+            }
+            let compression = config.compression.map(CompressionEncoding::from);
+            let mut clients = client_config(client_tls, config.secret.as_deref());
+            if let Some(encoding) = compression {
+                clients = clients.with_compression(encoding);
+            }
+            let limiter = config
+                .rate_limit
+                .map(|rate| Arc::new(RateLimiter::new(rate)));
+            let authenticate = authenticate(config.secret.clone());
+            let rate_limit = rate_limit(limiter);
+            #[cfg(feature = "grpc-web")]
+            let grpc_web = match chord_grpc::web::grpc_web_layer(&config.grpc_web_origins) {
+                Ok(layer) => layer,
+                Err(err) => {
+                    log::error!("Invalid gRPC-Web origin: {}", err);
+                    panic!("Invalid gRPC-Web origin in {:?}", config.grpc_web_origins);
+                }
+            };
+            #[cfg(feature = "chaos")]
+            let chaos = crate::chaos(&config);
+
+            let mut servers = vec![];
+            let mut pending = vec![];
+            for (index, vnode) in vnodes.nodes().iter().enumerate() {
+                let ring = crate::vnode_seeds(&vnodes, index, &seeds);
+                let joins_later = index > 0 && ring.is_empty();
+                let chord = ChordService::with_clients(
+                    vnode.addr(),
+                    crate::vnode_config(&config, vnode, index, ring),
+                    &clients,
+                )
+                .await;
+                let node = chord.node();
+                if joins_later {
+                    pending.push(node.clone());
+                }
+
+                let mut builder = GrpcServer::builder();
+                if let Some(server_tls) = &server_tls {
+                    builder = builder
+                        .tls_config(server_tls.clone())
+                        .expect("Failed to configure TLS");
+                }
+                let health = health_service(chord.node());
+                let mut authenticate = authenticate.clone();
+                let mut rate_limit = rate_limit.clone();
+                let chord = chord_node_server(chord, compression);
+                let chord = InterceptedService::new(chord, move |request| {
+                    authenticate(rate_limit(request)?)
+                });
+                // The health checks are left out, like the ones of a load balancer would be
+                #[cfg(feature = "chaos")]
+                let chord = ChaosLayer::new(chaos.clone()).layer(chord);
+                #[cfg(feature = "grpc-web")]
+                let builder = builder.accept_http1(true).layer(grpc_web.clone());
+                let router = builder
+                    .layer(concurrency_limit(config.max_connections.get()))
+                    .add_service(health)
+                    .add_service(chord);
+                #[cfg(feature = "grpc-reflection")]
+                let router = router.add_service(chord_grpc::reflection::reflection_service());
+
+                servers.push(VirtualNode {
+                    addr: vnode.addr(),
+                    router,
+                    node,
+                });
+            }
+            crate::advertise(config.discover, &servers[0].node);
+            crate::serve_gateway(config.http_gateway, servers[0].node.clone());
+
             Server {
-                addr,
-                router
+                vnodes: servers,
+                pending,
+                join: config.join,
+                #[cfg(feature = "chaos")]
+                chaos,
             }
         }
-    
-        pub async fn run(self) {
-            match self.router.serve(self.addr).await {
-                Ok(_) => log::info!("Server stopped"),
+
+        /// Get the injector of the faults shared by the virtual nodes
+        #[cfg(feature = "chaos")]
+        pub fn chaos(&self) -> Arc<crate::Chaos> {
+            self.chaos.clone()
+        }
+
+        pub async fn run(self, shutdown: CancellationToken) {
+            crate::join_first_vnode(self.pending, self.vnodes[0].addr, self.join);
+            join_all(
+                self.vnodes
+                    .into_iter()
+                    .map(|vnode| vnode.run(shutdown.clone())),
+            )
+            .await;
+        }
+    }
+
+    impl VirtualNode {
+        async fn run(self, shutdown: CancellationToken) {
+            match self
+                .router
+                .serve_with_shutdown(self.addr, async {
+                    shutdown.cancelled().await;
+                    if let Err(err) = self.node.leave().await {
+                        log::warn!("Failed to leave the ring: {:?}", err);
+                    }
+                })
+                .await
+            {
+                Ok(_) => log::info!("Server of {} stopped", self.addr),
                 Err(e) => log::error!("Server error: {}", e),
             }
-        }    
+        }
+    }
+
+    /// Crawl the ring starting at the given node, see [`crawl_ring_with`]
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The address of the first node
+    /// * `max_hops` - The maximum number of nodes to visit
+    /// * `tls` - The TLS configuration, if the ring communicates over TLS
+    /// * `secret` - The cluster secret, if the ring requires one
+    pub async fn dump_ring(
+        start: SocketAddr,
+        max_hops: usize,
+        tls: Option<crate::TlsConfig>,
+        secret: Option<String>,
+    ) -> RingDump {
+        let clients = &tool_client_config(tls, secret.as_deref());
+        crawl_ring_with(start, max_hops, |addr| {
+            ChordGrpcClient::connect(addr, clients)
+        })
+        .await
+    }
+
+    /// Find the node responsible for the given id, see [`find_owner_with`]
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The address of a node of the ring
+    /// * `id` - The id to find the owner of
+    /// * `tls` - The TLS configuration, if the ring communicates over TLS
+    /// * `secret` - The cluster secret, if the ring requires one
+    pub async fn find_owner(
+        seed: SocketAddr,
+        id: NodeId,
+        tls: Option<crate::TlsConfig>,
+        secret: Option<String>,
+    ) -> Result<Node, ClientError> {
+        let clients = &tool_client_config(tls, secret.as_deref());
+        find_owner_with(seed, id, |addr| ChordGrpcClient::connect(addr, clients))
+            .await
+            .map_err(|err| err.current_context().clone())
+    }
+
+    /// Get the settings of the clients of a node
+    ///
+    /// # Arguments
+    ///
+    /// * `tls` - The client TLS configuration, if the ring communicates over TLS
+    /// * `secret` - The cluster secret, if the ring requires one
+    fn client_config(tls: Option<ClientTlsConfig>, secret: Option<&str>) -> ClientConfig {
+        let mut clients = ClientConfig::default();
+        if let Some(tls) = tls {
+            clients = clients.with_tls(tls);
+        }
+        match secret {
+            Some(secret) => clients
+                .with_secret(secret)
+                .expect("The cluster secret is not a valid metadata value"),
+            None => clients,
+        }
+    }
+
+    /// Get the settings of the clients of the tools talking to the ring
+    fn tool_client_config(tls: Option<crate::TlsConfig>, secret: Option<&str>) -> ClientConfig {
+        let tls = tls.map(|tls| {
+            let tls: TlsConfig = tls.into();
+            tls.client_config()
+                .expect("Failed to load TLS configuration")
+        });
+        client_config(tls, secret)
     }
 }
@@ -0,0 +1,58 @@
+use std::net::SocketAddr;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Serve the node's metrics in the Prometheus text format on `http://<addr>/metrics`
+///
+/// The endpoint is served in the background, on a separate port than the node itself.
+///
+/// # Arguments
+///
+/// * `addr` - The address to listen on
+pub(crate) fn serve(addr: SocketAddr) {
+    tokio::spawn(async move {
+        let listener = match TcpListener::bind(addr).await {
+            Ok(listener) => listener,
+            Err(err) => {
+                log::error!("Failed to bind metrics endpoint to {}: {}", addr, err);
+                return;
+            }
+        };
+        log::info!("Serving metrics on http://{}/metrics", addr);
+
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(handle(stream));
+                }
+                Err(err) => log::warn!("Failed to accept metrics connection: {}", err),
+            }
+        }
+    });
+}
+
+async fn handle(mut stream: TcpStream) {
+    let mut buffer = [0; 1024];
+    let read = match stream.read(&mut buffer).await {
+        Ok(read) => read,
+        Err(_) => return,
+    };
+
+    let request = String::from_utf8_lossy(&buffer[..read]);
+    let response = match request.lines().next() {
+        Some(line) if line.starts_with("GET /metrics ") => {
+            let body = chord_rs_core::metrics::gather();
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        }
+        _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+    };
+
+    if let Err(err) = stream.write_all(response.as_bytes()).await {
+        log::debug!("Failed to write metrics response: {}", err);
+    }
+}
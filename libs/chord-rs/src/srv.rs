@@ -0,0 +1,107 @@
+use std::cmp::Reverse;
+use std::net::SocketAddr;
+
+use chord_rs_core::server::SeedResolver;
+use hickory_resolver::proto::rr::rdata::SRV;
+use hickory_resolver::TokioAsyncResolver;
+
+/// Create the resolver of the seeds the SRV records of the name point to
+///
+/// The records are looked up again before every attempt to join the ring, so the node joins
+/// through the nodes currently behind the name, e.g. the pods of a headless service.
+///
+/// # Arguments
+///
+/// * `name` - The name of the SRV records, e.g. `_chord._tcp.chord.default.svc.cluster.local`
+pub(crate) fn seed_resolver(name: String) -> SeedResolver {
+    SeedResolver::new(move || {
+        let name = name.clone();
+        async move { lookup(&name).await }
+    })
+}
+
+/// Look up the addresses of the nodes the SRV records of the name point to
+///
+/// The targets are tried in the order of their records, see [`order`]. Returns no address if
+/// the lookup fails or no record is found.
+///
+/// # Arguments
+///
+/// * `name` - The name of the SRV records
+pub(crate) async fn lookup(name: &str) -> Vec<SocketAddr> {
+    let resolver = match TokioAsyncResolver::tokio_from_system_conf() {
+        Ok(resolver) => resolver,
+        Err(err) => {
+            log::error!("Failed to read the DNS configuration: {}", err);
+            return vec![];
+        }
+    };
+    let records = match resolver.srv_lookup(name).await {
+        Ok(records) => records,
+        Err(err) => {
+            log::warn!("No SRV record found for {}: {}", name, err);
+            return vec![];
+        }
+    };
+
+    let mut seeds = vec![];
+    for record in order(records.iter()) {
+        match resolver.lookup_ip(record.target().clone()).await {
+            Ok(ips) => {
+                for ip in ips.iter() {
+                    let seed = SocketAddr::new(ip, record.port());
+                    if !seeds.contains(&seed) {
+                        seeds.push(seed);
+                    }
+                }
+            }
+            Err(err) => log::warn!("Failed to resolve {}: {}", record.target(), err),
+        }
+    }
+    log::info!("Found nodes in the SRV records of {}: {:?}", name, seeds);
+
+    seeds
+}
+
+/// Order the SRV records by priority, the lowest first, then by weight, the heaviest first
+fn order<'a>(records: impl Iterator<Item = &'a SRV>) -> Vec<&'a SRV> {
+    let mut records: Vec<&SRV> = records.collect();
+    records.sort_by_key(|record| (record.priority(), Reverse(record.weight())));
+
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use hickory_resolver::Name;
+
+    use super::*;
+
+    fn record(priority: u16, weight: u16, target: &str) -> SRV {
+        SRV::new(priority, weight, 42000, Name::from_str(target).unwrap())
+    }
+
+    #[test]
+    fn records_are_ordered_by_priority_then_weight() {
+        let records = [
+            record(20, 10, "backup.chord.local."),
+            record(10, 5, "light.chord.local."),
+            record(10, 50, "heavy.chord.local."),
+        ];
+
+        let targets: Vec<String> = order(records.iter())
+            .into_iter()
+            .map(|record| record.target().to_string())
+            .collect();
+        assert_eq!(
+            targets,
+            vec![
+                "heavy.chord.local.",
+                "light.chord.local.",
+                "backup.chord.local."
+            ]
+        );
+    }
+}
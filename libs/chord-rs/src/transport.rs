@@ -0,0 +1,204 @@
+//! Selection of the RPC transport at runtime
+//!
+//! Every transport enabled by its feature is compiled in, the [`Config`] picks the one the node
+//! runs with.
+
+use std::fmt::Display;
+
+#[cfg(any(feature = "capnp", feature = "grpc"))]
+use std::net::SocketAddr;
+#[cfg(all(feature = "chaos", any(feature = "capnp", feature = "grpc")))]
+use std::sync::Arc;
+
+#[cfg(any(feature = "capnp", feature = "grpc"))]
+use futures::future::LocalBoxFuture;
+
+#[cfg(all(feature = "chaos", any(feature = "capnp", feature = "grpc")))]
+use crate::Chaos;
+#[cfg(any(feature = "capnp", feature = "grpc"))]
+use crate::{CancellationToken, ClientError, Config, Node, NodeId, RingDump, TlsConfig};
+
+/// The RPC transport the node communicates with the ring over
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transport {
+    /// Cap'n Proto RPC, requires the `capnp` feature
+    Capnp,
+    /// gRPC, requires the `grpc` feature
+    Grpc,
+}
+
+/// The Cap'n Proto transport if it's enabled, the gRPC one otherwise
+impl Default for Transport {
+    fn default() -> Self {
+        if cfg!(feature = "capnp") || !cfg!(feature = "grpc") {
+            Transport::Capnp
+        } else {
+            Transport::Grpc
+        }
+    }
+}
+
+impl Display for Transport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Transport::Capnp => write!(f, "capnp"),
+            Transport::Grpc => write!(f, "grpc"),
+        }
+    }
+}
+
+impl Transport {
+    /// Panic if the transport is not compiled in
+    #[cfg(any(feature = "capnp", feature = "grpc"))]
+    fn unsupported(self) -> ! {
+        log::error!("The {} transport is not enabled", self);
+        panic!(
+            "The `{}` feature is required to use the {} transport",
+            self, self
+        );
+    }
+}
+
+/// Server of one of the transports, see [`Server`]
+#[cfg(any(feature = "capnp", feature = "grpc"))]
+trait TransportServer {
+    /// Run the server until the shutdown token is cancelled
+    fn run(self: Box<Self>, shutdown: CancellationToken) -> LocalBoxFuture<'static, ()>;
+
+    /// Get the injector of the faults shared by the virtual nodes
+    #[cfg(feature = "chaos")]
+    fn chaos(&self) -> Arc<Chaos>;
+}
+
+#[cfg(feature = "capnp")]
+impl TransportServer for crate::capnp::Server {
+    fn run(self: Box<Self>, shutdown: CancellationToken) -> LocalBoxFuture<'static, ()> {
+        Box::pin((*self).run(shutdown))
+    }
+
+    #[cfg(feature = "chaos")]
+    fn chaos(&self) -> Arc<Chaos> {
+        self.chaos()
+    }
+}
+
+#[cfg(feature = "grpc")]
+impl TransportServer for crate::grpc::Server {
+    fn run(self: Box<Self>, shutdown: CancellationToken) -> LocalBoxFuture<'static, ()> {
+        Box::pin((*self).run(shutdown))
+    }
+
+    #[cfg(feature = "chaos")]
+    fn chaos(&self) -> Arc<Chaos> {
+        self.chaos()
+    }
+}
+
+/// Server of the node, over the transport selected by [`Config::transport`]
+#[cfg(any(feature = "capnp", feature = "grpc"))]
+pub struct Server {
+    server: Box<dyn TransportServer>,
+}
+
+#[cfg(any(feature = "capnp", feature = "grpc"))]
+impl Server {
+    /// Start the node and create the server of its transport
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address to listen on
+    /// * `config` - The configuration of the node
+    ///
+    /// # Panics
+    ///
+    /// If the selected transport is not enabled.
+    pub async fn new(addr: SocketAddr, config: impl Into<Config>) -> Server {
+        let config: Config = config.into();
+        let server: Box<dyn TransportServer> = match config.transport {
+            #[cfg(feature = "capnp")]
+            Transport::Capnp => Box::new(crate::capnp::Server::new(addr, config).await),
+            #[cfg(feature = "grpc")]
+            Transport::Grpc => Box::new(crate::grpc::Server::new(addr, config).await),
+            #[allow(unreachable_patterns)]
+            transport => transport.unsupported(),
+        };
+
+        Server { server }
+    }
+
+    /// Run the server until the shutdown token is cancelled
+    pub async fn run(self, shutdown: CancellationToken) {
+        self.server.run(shutdown).await;
+    }
+
+    /// Get the injector of the faults, to change them while the node runs
+    #[cfg(feature = "chaos")]
+    pub fn chaos(&self) -> Arc<Chaos> {
+        self.server.chaos()
+    }
+}
+
+/// Crawl the ring starting at the given node, over the given transport
+///
+/// # Arguments
+///
+/// * `transport` - The transport the ring communicates over
+/// * `start` - The address of the first node
+/// * `max_hops` - The maximum number of nodes to visit
+/// * `tls` - The TLS configuration, if the ring communicates over TLS
+/// * `secret` - The cluster secret, if the ring requires one
+///
+/// # Panics
+///
+/// If the transport is not enabled.
+#[cfg(any(feature = "capnp", feature = "grpc"))]
+pub async fn dump_ring(
+    transport: Transport,
+    start: SocketAddr,
+    max_hops: usize,
+    tls: Option<TlsConfig>,
+    secret: Option<String>,
+) -> RingDump {
+    match transport {
+        #[cfg(feature = "capnp")]
+        Transport::Capnp => crate::capnp::dump_ring(start, max_hops, tls, secret).await,
+        #[cfg(feature = "grpc")]
+        Transport::Grpc => crate::grpc::dump_ring(start, max_hops, tls, secret).await,
+        #[allow(unreachable_patterns)]
+        transport => transport.unsupported(),
+    }
+}
+
+/// Find the node responsible for the given id, over the given transport
+///
+/// Any node of the ring can be asked, the lookup is routed to the owner of the id. Map a key
+/// onto the ring with [`IntoNodeId`](crate::IntoNodeId) to find the node storing it.
+///
+/// # Arguments
+///
+/// * `transport` - The transport the ring communicates over
+/// * `seed` - The address of a node of the ring
+/// * `id` - The id to find the owner of
+/// * `tls` - The TLS configuration, if the ring communicates over TLS
+/// * `secret` - The cluster secret, if the ring requires one
+///
+/// # Panics
+///
+/// If the transport is not enabled.
+#[cfg(any(feature = "capnp", feature = "grpc"))]
+pub async fn find_owner(
+    transport: Transport,
+    seed: SocketAddr,
+    id: NodeId,
+    tls: Option<TlsConfig>,
+    secret: Option<String>,
+) -> Result<Node, ClientError> {
+    match transport {
+        #[cfg(feature = "capnp")]
+        Transport::Capnp => crate::capnp::find_owner(seed, id, tls, secret).await,
+        #[cfg(feature = "grpc")]
+        Transport::Grpc => crate::grpc::find_owner(seed, id, tls, secret).await,
+        #[allow(unreachable_patterns)]
+        transport => transport.unsupported(),
+    }
+}
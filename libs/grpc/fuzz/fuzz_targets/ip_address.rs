@@ -0,0 +1,15 @@
+//! Decode arbitrary bytes as an IP address sent by a peer, the conversion may only fail with an error
+
+#![no_main]
+
+use std::net::IpAddr;
+
+use chord_grpc::server::chord_proto;
+use libfuzzer_sys::fuzz_target;
+use prost::Message;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(ip) = chord_proto::IpAddress::decode(data) {
+        let _ = IpAddr::try_from(ip);
+    }
+});
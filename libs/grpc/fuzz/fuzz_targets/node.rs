@@ -0,0 +1,14 @@
+//! Decode arbitrary bytes as a node sent by a peer, the conversion may only fail with an error
+
+#![no_main]
+
+use chord_grpc::server::chord_proto;
+use chord_rs_core::Node;
+use libfuzzer_sys::fuzz_target;
+use prost::Message;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(node) = chord_proto::Node::decode(data) {
+        let _ = Node::try_from(node);
+    }
+});
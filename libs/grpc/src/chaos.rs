@@ -0,0 +1,141 @@
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use chord_rs_core::chaos::Chaos;
+use tonic::body::BoxBody;
+use tonic::codegen::http::{Request, Response};
+use tonic::codegen::BoxFuture;
+use tonic::server::NamedService;
+use tonic::Status;
+use tower::{Layer, Service};
+
+/// Layer injecting the faults of [`Chaos`] into the requests of a service
+///
+/// The requests are delayed before being handled, and the dropped ones are answered with an
+/// `Unavailable` status, like the requests of an unreachable node. The service keeps its name,
+/// so it can be wrapped before being added to the server, e.g. to leave the health service out.
+#[derive(Debug, Clone)]
+pub struct ChaosLayer {
+    chaos: Arc<Chaos>,
+}
+
+impl ChaosLayer {
+    /// Create the layer
+    ///
+    /// # Arguments
+    ///
+    /// * `chaos` - The faults to inject, shared with the other servers of the process
+    pub fn new(chaos: Arc<Chaos>) -> Self {
+        Self { chaos }
+    }
+}
+
+impl<S> Layer<S> for ChaosLayer {
+    type Service = ChaosService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ChaosService {
+            inner,
+            chaos: self.chaos.clone(),
+        }
+    }
+}
+
+/// Service injecting faults into the requests of the inner service, see [`ChaosLayer`]
+#[derive(Debug, Clone)]
+pub struct ChaosService<S> {
+    inner: S,
+    chaos: Arc<Chaos>,
+}
+
+impl<S, B> Service<Request<B>> for ChaosService<S>
+where
+    S: Service<Request<B>, Response = Response<BoxBody>, Error = Infallible>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        // The inner service is ready, not its clone, so the clone is the one left behind
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let chaos = self.chaos.clone();
+
+        Box::pin(async move {
+            if !chaos.inject().await {
+                return Ok(Status::unavailable("Request dropped to inject a fault").to_http());
+            }
+
+            inner.call(request).await
+        })
+    }
+}
+
+impl<S: NamedService> NamedService for ChaosService<S> {
+    const NAME: &'static str = S::NAME;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{SocketAddr, TcpListener};
+    use std::time::Duration;
+
+    use chord_rs_core::chaos::ChaosConfig;
+    use tonic::transport::{Channel, Server};
+    use tonic_health::pb::health_client::HealthClient;
+    use tonic_health::pb::HealthCheckRequest;
+
+    use super::*;
+
+    async fn serve(chaos: Arc<Chaos>) -> HealthClient<Channel> {
+        let (_, health) = tonic_health::server::health_reporter();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr: SocketAddr = listener.local_addr().unwrap();
+        drop(listener);
+        tokio::spawn(
+            Server::builder()
+                .add_service(ChaosLayer::new(chaos).layer(health))
+                .serve(addr),
+        );
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let channel = Channel::from_shared(format!("http://{}", addr))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        HealthClient::new(channel)
+    }
+
+    #[tokio::test]
+    async fn dropped_requests_are_unavailable() {
+        let chaos = Arc::new(
+            Chaos::new(ChaosConfig {
+                drop_probability: 1.0,
+                ..Default::default()
+            })
+            .unwrap(),
+        );
+        let mut client = serve(chaos.clone()).await;
+
+        let status = client
+            .check(HealthCheckRequest::default())
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unavailable);
+
+        chaos.set_config(ChaosConfig::default()).unwrap();
+        assert!(client.check(HealthCheckRequest::default()).await.is_ok());
+    }
+}
@@ -1,134 +1,461 @@
+use std::future::Future;
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 use std::sync::{Arc, Mutex};
 
 use crate::server::chord_proto::chord_node_client::ChordNodeClient;
 use crate::server::chord_proto::{
-    self, FindSuccessorRequest, GetPredecessorRequest, NotifyRequest,
+    self, find_successors_result, CanReachRequest, FindPredecessorRequest, FindSuccessorRequest,
+    FindSuccessorsRequest, GetNodeInfoRequest, GetPredecessorRequest, HealthRequest, LeaveRequest,
+    NotifyRequest, TriggerRequest,
+};
+use crate::server::{DEADLINE_METADATA_KEY, SECRET_METADATA_KEY, UNREACHABLE_METADATA_KEY};
+use chord_rs_core::client::{ClientError, ClientFactory, Latency, LatencyStats};
+use chord_rs_core::{
+    deadline, Client, HealthStatus, MaintenanceTask, Node, NodeId, NodeInfo, RingParams,
 };
-use chord_rs_core::client::ClientError;
-use chord_rs_core::{Client, Node, NodeId};
 use error_stack::{IntoReport, Report, Result, ResultExt};
 use tonic::async_trait;
-use tonic::transport::{Channel, Endpoint};
+use tonic::codec::CompressionEncoding;
+use tonic::metadata::errors::InvalidMetadataValue;
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
+use tonic::{Code, Status};
+
+/// Settings of the clients connecting to the other nodes
+///
+/// Every node keeps its own settings, passed to its clients through their factory, see
+/// [`factory`](Self::factory), so the nodes of a process can belong to rings with other
+/// settings.
+#[derive(Debug, Clone, Default)]
+pub struct ClientConfig {
+    /// TLS configuration, if the nodes are served over TLS
+    tls: Option<ClientTlsConfig>,
+    /// Cluster secret attached to all the requests, if the ring requires one
+    secret: Option<MetadataValue<Ascii>>,
+    /// Codec compressing the requests, if set
+    compression: Option<CompressionEncoding>,
+}
+
+impl ClientConfig {
+    /// Connect to the nodes using TLS
+    ///
+    /// # Arguments
+    ///
+    /// * `tls` - The client TLS configuration, see
+    ///   [`TlsConfig::client_config`](crate::tls::TlsConfig::client_config)
+    pub fn with_tls(mut self, tls: ClientTlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Attach the cluster secret to all the requests
+    ///
+    /// Returns an error if the secret is not a valid metadata value.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret` - The cluster secret
+    pub fn with_secret(mut self, secret: &str) -> std::result::Result<Self, InvalidMetadataValue> {
+        self.secret = Some(secret.parse()?);
+        Ok(self)
+    }
+
+    /// Compress the requests
+    ///
+    /// The nodes accept the compressed requests whatever their own setting, see
+    /// [`chord_node_server`](crate::server::chord_node_server). The clients accept compressed
+    /// responses in any case.
+    ///
+    /// # Arguments
+    ///
+    /// * `encoding` - The codec compressing the requests
+    pub fn with_compression(mut self, encoding: CompressionEncoding) -> Self {
+        self.compression = Some(encoding);
+        self
+    }
+
+    /// Get the factory creating the clients with these settings, see
+    /// [`NodeService::with_client_factory`](chord_rs_core::NodeService::with_client_factory)
+    pub fn factory(&self) -> ClientFactory<ChordGrpcClient> {
+        let config = self.clone();
+        Arc::new(move |addr| {
+            let config = config.clone();
+            Box::pin(async move { ChordGrpcClient::connect(addr, &config).await })
+        })
+    }
+}
 
 #[derive(Debug)]
 pub struct ChordGrpcClient {
     // pub(crate) endpoint: Endpoint,
     pub(crate) client: ClientGuard,
+    /// Round-trip times of the calls, shared between the clones
+    pub(crate) latency: Arc<Latency>,
+    /// Cluster secret attached to all the requests, if set
+    pub(crate) secret: Option<MetadataValue<Ascii>>,
 }
 
+/// Connection to a node, reopened on demand
+///
+/// The connection is empty until a connect succeeds, and it's emptied when a call fails because
+/// the node is unavailable, so the next call connects again.
 #[derive(Debug, Clone)]
 pub(crate) struct ClientGuard {
+    endpoint: Endpoint,
+    /// Codec compressing the requests, if set
+    compression: Option<CompressionEncoding>,
     client: Arc<Mutex<Option<ChordNodeClient<Channel>>>>,
 }
 
 impl ClientGuard {
-    fn new() -> Self {
+    fn new(endpoint: Endpoint, compression: Option<CompressionEncoding>) -> Self {
         Self {
+            endpoint,
+            compression,
             client: Arc::new(Mutex::new(None)),
         }
     }
-}
 
-#[async_trait]
-impl Client for ChordGrpcClient {
-    async fn init(addr: SocketAddr) -> Self {
-        log::debug!("Initializing client for {}", addr);
-        let endpoint = Endpoint::from_shared(format!("http://{}", addr)).unwrap();
-        let client_guard = ClientGuard::new();
-        let client_guard_clone = client_guard.clone();
+    /// Get the open connection, or try to connect if there is none
+    async fn get(&self) -> Result<ChordNodeClient<Channel>, ClientError> {
+        if let Some(client) = self.client.lock().unwrap().clone() {
+            return Ok(client);
+        }
 
-        let client = ChordNodeClient::connect(endpoint.clone()).await;
-        if let Err(err) = &client {
-            log::error!("Failed to initialize client: {:?}", err);
-        } else {
-            log::debug!("Client initialized");
-            client_guard_clone
-                .client
-                .lock()
-                .unwrap()
-                .replace(client.unwrap());
+        let client = ChordNodeClient::connect(self.endpoint.clone())
+            .await
+            .into_report()
+            .change_context_lazy(|| {
+                ClientError::ConnectionFailed(self.endpoint.uri().to_string())
+            })?;
+        let mut client = client.accept_compressed(CompressionEncoding::Gzip);
+        if let Some(encoding) = self.compression {
+            client = client.send_compressed(encoding);
         }
+        log::debug!("Connected to {}", self.endpoint.uri());
+        self.client.lock().unwrap().replace(client.clone());
 
-        ChordGrpcClient {
-            client: client_guard,
+        Ok(client)
+    }
+
+    /// Drop the connection, so the next call connects again
+    fn reset(&self) {
+        if self.client.lock().unwrap().take().is_some() {
+            log::debug!("Dropped the connection to {}", self.endpoint.uri());
         }
     }
+}
+
+#[async_trait]
+impl Client for ChordGrpcClient {
+    async fn init(addr: SocketAddr) -> Self {
+        Self::connect(addr, &ClientConfig::default()).await
+    }
 
     async fn find_successor(&self, id: NodeId) -> Result<Node, ClientError> {
-        let mut client = self.client()?;
+        let mut client = self.client().await?;
 
-        let request = tonic::Request::new(FindSuccessorRequest { id: id.into() });
-        let response = client
-            .find_successor(request)
+        let request = self.request(FindSuccessorRequest {
+            id: id.into(),
+            trace: false,
+        });
+        let response = self
+            .measure(client.find_successor(request))
             .await
-            .into_report()
-            .change_context(ClientError::Unexpected)?;
+            .map_err(|status| self.lookup_error(status, ClientError::Unexpected))?;
         // if let Err(err) = response {
         //     log::warn!("Failed to find successor: {:?}", err);
         //     return Err(ClientError::Unexpected(err.to_string()));
         // }
         let response = response.into_inner();
 
-        let node = response.node.unwrap();
-        let node: Node = node.try_into().unwrap();
+        Self::response_node(response.node, ClientError::FindSuccessorFailed)
+    }
+
+    async fn find_successor_traced(&self, id: NodeId) -> Result<(Node, Vec<NodeId>), ClientError> {
+        let mut client = self.client().await?;
 
-        Ok(node)
+        let request = self.request(FindSuccessorRequest {
+            id: id.into(),
+            trace: true,
+        });
+        let response = self
+            .measure(client.find_successor(request))
+            .await
+            .map_err(|status| self.lookup_error(status, ClientError::FindSuccessorFailed))?
+            .into_inner();
+
+        let node = Self::response_node(response.node, ClientError::FindSuccessorFailed)?;
+        let path = response.path.into_iter().map(NodeId::from).collect();
+
+        Ok((node, path))
+    }
+
+    async fn find_successors(
+        &self,
+        ids: Vec<NodeId>,
+    ) -> Result<Vec<std::result::Result<Node, ClientError>>, ClientError> {
+        let mut client = self.client().await?;
+
+        let request = self.request(FindSuccessorsRequest {
+            ids: ids.into_iter().map(Into::into).collect(),
+        });
+        let response = self
+            .measure(client.find_successors(request))
+            .await
+            .into_report()
+            .change_context(ClientError::FindSuccessorFailed)?
+            .into_inner();
+
+        let results = response
+            .results
+            .into_iter()
+            .map(|result| match result.result {
+                Some(find_successors_result::Result::Node(node)) => Node::try_from(node)
+                    .into_report()
+                    .change_context(ClientError::FindSuccessorFailed)
+                    .map(Ok),
+                Some(find_successors_result::Result::Error(error)) => {
+                    let status = Status::new(Code::from(error.code), error.message);
+                    let error = self.lookup_error(status, ClientError::FindSuccessorFailed);
+                    Ok(Err(error.current_context().clone()))
+                }
+                None => Err(Report::new(ClientError::FindSuccessorFailed))
+                    .attach_printable("Missing result"),
+            });
+
+        results.collect()
+    }
+
+    async fn find_predecessor(&self, id: NodeId) -> Result<Node, ClientError> {
+        let mut client = self.client().await?;
+
+        let request = self.request(FindPredecessorRequest { id: id.into() });
+        let response = self
+            .measure(client.find_predecessor(request))
+            .await
+            .map_err(|status| self.lookup_error(status, ClientError::FindPredecessorFailed))?
+            .into_inner();
+
+        Self::response_node(response.node, ClientError::FindPredecessorFailed)
     }
 
     async fn successor(&self) -> Result<Node, ClientError> {
-        let mut client = self.client()?;
+        let mut client = self.client().await?;
 
-        let request = tonic::Request::new(chord_proto::GetSuccessorRequest {});
+        let request = self.request(chord_proto::GetSuccessorRequest {});
 
-        let response = client.get_successor(request).await.unwrap().into_inner();
+        let response = self
+            .measure(client.get_successor(request))
+            .await
+            .into_report()
+            .change_context(ClientError::GetSuccessorFailed)?
+            .into_inner();
 
-        if let Some(node) = response.node {
-            let node: Node = node.try_into().unwrap();
-            Ok(node)
-        } else {
-            Err(Report::new(ClientError::Unexpected).attach_printable("No successor found"))
-        }
+        Self::response_node(response.node, ClientError::GetSuccessorFailed)
     }
 
     async fn successor_list(&self) -> Result<Vec<Node>, ClientError> {
-        todo!("successor_list")
+        let mut client = self.client().await?;
+
+        let request = self.request(chord_proto::GetSuccessorListRequest {});
+
+        let response = self
+            .measure(client.get_successor_list(request))
+            .await
+            .into_report()
+            .change_context(ClientError::GetSuccessorListFailed)?
+            .into_inner();
+
+        response
+            .nodes
+            .into_iter()
+            .map(|node| {
+                Node::try_from(node)
+                    .into_report()
+                    .change_context(ClientError::GetSuccessorListFailed)
+            })
+            .collect()
     }
 
     async fn predecessor(&self) -> Result<Option<Node>, ClientError> {
-        let mut client = self.client()?;
+        let mut client = self.client().await?;
 
-        let request = tonic::Request::new(GetPredecessorRequest {});
+        let request = self.request(GetPredecessorRequest {});
 
-        let response = client.get_predecessor(request).await.unwrap().into_inner();
+        let response = self
+            .measure(client.get_predecessor(request))
+            .await
+            .into_report()
+            .change_context(ClientError::GetPredecessorFailed)?
+            .into_inner();
 
-        if let Some(node) = response.node {
-            let node: Node = node.try_into().unwrap();
-            return Ok(Some(node));
-        }
+        response
+            .node
+            .map(Node::try_from)
+            .transpose()
+            .into_report()
+            .change_context(ClientError::GetPredecessorFailed)
+    }
+
+    async fn predecessor_list(&self) -> Result<Vec<Node>, ClientError> {
+        let mut client = self.client().await?;
 
-        Ok(None)
+        let request = self.request(chord_proto::GetPredecessorListRequest {});
+
+        let response = self
+            .measure(client.get_predecessor_list(request))
+            .await
+            .into_report()
+            .change_context(ClientError::GetPredecessorListFailed)?
+            .into_inner();
+
+        response
+            .nodes
+            .into_iter()
+            .map(|node| {
+                Node::try_from(node)
+                    .into_report()
+                    .change_context(ClientError::GetPredecessorListFailed)
+            })
+            .collect()
+    }
+
+    async fn node_info(&self) -> Result<NodeInfo, ClientError> {
+        let mut client = self.client().await?;
+
+        let request = self.request(GetNodeInfoRequest {});
+
+        let response = self
+            .measure(client.get_node_info(request))
+            .await
+            .into_report()
+            .change_context(ClientError::NodeInfoFailed)?
+            .into_inner();
+
+        let parse = |node: chord_proto::Node| {
+            Node::try_from(node)
+                .into_report()
+                .change_context(ClientError::NodeInfoFailed)
+        };
+        let node = response
+            .node
+            .ok_or(Report::new(ClientError::NodeInfoFailed))
+            .and_then(parse)?;
+
+        Ok(NodeInfo {
+            id: node.id(),
+            addr: node.addr(),
+            predecessor: response.predecessor.map(parse).transpose()?,
+            successor_list: response
+                .successors
+                .into_iter()
+                .map(parse)
+                .collect::<Result<_, _>>()?,
+            params: response.params.map(Into::into),
+        })
     }
 
     async fn notify(&self, predecessor: Node) -> Result<(), ClientError> {
-        let mut client = self.client()?;
+        let mut client = self.client().await?;
 
-        let request = tonic::Request::new(NotifyRequest {
+        let request = self.request(NotifyRequest {
             node: Some(predecessor.into()),
         });
-        client.notify(request).await.unwrap();
+        self.measure(client.notify(request))
+            .await
+            .into_report()
+            .change_context(ClientError::NotifyFailed)?;
+
+        Ok(())
+    }
+
+    async fn leave(&self, node: Node, predecessor: Option<Node>) -> Result<(), ClientError> {
+        let mut client = self.client().await?;
+
+        let request = self.request(LeaveRequest {
+            node: Some(node.into()),
+            predecessor: predecessor.map(Into::into),
+        });
+        self.measure(client.leave(request))
+            .await
+            .into_report()
+            .change_context(ClientError::LeaveFailed)?;
 
         Ok(())
     }
 
-    async fn ping(&self) -> Result<(), ClientError> {
-        let mut client = self.client()?;
+    async fn ping(&self) -> Result<NodeId, ClientError> {
+        let mut client = self.client().await?;
+
+        let request = self.request(chord_proto::PingRequest {});
+        let response = self
+            .measure(client.ping(request))
+            .await
+            .into_report()
+            .change_context(ClientError::PingFailed)?;
+
+        Ok(response.into_inner().id.into())
+    }
+
+    async fn can_reach(&self, target: Node) -> Result<bool, ClientError> {
+        let mut client = self.client().await?;
 
-        let request = tonic::Request::new(chord_proto::PingRequest {});
-        client.ping(request).await.unwrap();
+        let request = self.request(CanReachRequest {
+            node: Some(target.into()),
+        });
+        let response = self
+            .measure(client.can_reach(request))
+            .await
+            .into_report()
+            .change_context(ClientError::CanReachFailed)?;
+
+        Ok(response.into_inner().reachable)
+    }
+
+    async fn health(&self) -> Result<HealthStatus, ClientError> {
+        let mut client = self.client().await?;
+
+        let request = self.request(HealthRequest {});
+        let response = self
+            .measure(client.health(request))
+            .await
+            .into_report()
+            .change_context(ClientError::HealthFailed)?
+            .into_inner();
+
+        Ok(HealthStatus {
+            joined: response.joined,
+            has_predecessor: response.has_predecessor,
+            successor_reachable: response.successor_reachable,
+        })
+    }
+
+    async fn trigger(&self, task: MaintenanceTask) -> Result<(), ClientError> {
+        let mut client = self.client().await?;
+
+        let request = self.request(TriggerRequest {});
+        let response = match task {
+            MaintenanceTask::Stabilize => self.measure(client.trigger_stabilize(request)).await,
+            MaintenanceTask::FixFingers => self.measure(client.trigger_fix_fingers(request)).await,
+            MaintenanceTask::ReconcileSuccessors => {
+                self.measure(client.trigger_reconcile(request)).await
+            }
+        };
+        response
+            .into_report()
+            .change_context(ClientError::TriggerFailed)?;
 
         Ok(())
     }
+
+    fn latency(&self) -> Option<LatencyStats> {
+        self.latency.stats()
+    }
+
+    async fn close(&self) {
+        self.client.reset();
+    }
 }
 
 impl ChordGrpcClient {
@@ -136,13 +463,150 @@ impl ChordGrpcClient {
         Self::init(addr).await
     }
 
-    pub fn client(&self) -> Result<ChordNodeClient<Channel>, ClientError> {
-        if let Some(client) = self.client.client.lock().unwrap().clone() {
-            Ok(client)
+    /// Create the client of the node at the given address, with the given settings, and
+    /// connect to the node
+    ///
+    /// The client is returned even if the connection fails, it connects again on its next call.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the node
+    /// * `config` - The settings of the client
+    pub async fn connect(addr: SocketAddr, config: &ClientConfig) -> Self {
+        log::debug!("Initializing client for {}", addr);
+        let client = Self::with_endpoint(Self::endpoint(addr, config.tls.as_ref()), config);
+        if let Err(err) = client.client.get().await {
+            log::error!("Failed to initialize client: {:?}", err);
         } else {
-            Err(Report::new(ClientError::NotInitialized))
+            log::debug!("Client initialized");
+        }
+
+        client
+    }
+
+    /// Get the endpoint of the node at the given address, over TLS if it's configured
+    ///
+    /// IPv6 addresses are bracketed in the URI, e.g. `http://[::1]:42000`, the zone of a
+    /// link-local address is kept after a `%`, e.g. `http://[fe80::1%2]:42000`.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the node
+    /// * `tls` - The client TLS configuration, if the node is served over TLS
+    fn endpoint(addr: SocketAddr, tls: Option<&ClientTlsConfig>) -> Endpoint {
+        match tls {
+            Some(tls) => Endpoint::from_shared(format!("https://{}", addr))
+                .and_then(|endpoint| endpoint.tls_config(tls.clone()))
+                .unwrap(),
+            None => Endpoint::from_shared(format!("http://{}", addr)).unwrap(),
+        }
+    }
+
+    /// Create a client of the node at the given endpoint, without connecting yet
+    fn with_endpoint(endpoint: Endpoint, config: &ClientConfig) -> Self {
+        ChordGrpcClient {
+            client: ClientGuard::new(endpoint, config.compression),
+            latency: Arc::new(Latency::default()),
+            secret: config.secret.clone(),
         }
     }
+
+    /// Measure the round-trip time of the call, and drop the connection if the node is
+    /// unavailable, so the next call reconnects
+    async fn measure<T>(
+        &self,
+        call: impl Future<Output = std::result::Result<T, Status>>,
+    ) -> std::result::Result<T, Status> {
+        let result = self.latency.measure(call).await;
+        if let Err(status) = &result {
+            if status.code() == Code::Unavailable {
+                self.client.reset();
+            }
+        }
+
+        result
+    }
+
+    /// Get the error of a failed lookup from its status
+    ///
+    /// An `UNAVAILABLE` node is reported as a failed connection, so the lookup is routed around
+    /// it, and a `DEADLINE_EXCEEDED` one as a deadline exceeded, so the lookup is retried. An
+    /// `ABORTED` status naming the node the request couldn't be forwarded to is reported as that
+    /// peer being unreachable, this node answered so its connection is kept. The other statuses
+    /// are reported with the given context.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The status of the call
+    /// * `context` - The error of the other statuses
+    fn lookup_error(&self, status: Status, context: ClientError) -> Report<ClientError> {
+        let context = match status.code() {
+            Code::Unavailable => {
+                ClientError::ConnectionFailed(self.client.endpoint.uri().to_string())
+            }
+            Code::DeadlineExceeded => ClientError::DeadlineExceeded,
+            Code::Aborted => match Self::unreachable_node(&status) {
+                Some(node) => ClientError::PeerUnreachable(node),
+                None => context,
+            },
+            _ => context,
+        };
+
+        Report::new(status).change_context(context)
+    }
+
+    /// Get the node of a response, a missing or malformed node is reported with the given context
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node field of the response
+    /// * `context` - The error of the call
+    fn response_node(
+        node: Option<chord_proto::Node>,
+        context: ClientError,
+    ) -> Result<Node, ClientError> {
+        let node = node
+            .ok_or_else(|| Report::new(context.clone()))
+            .attach_printable("Missing node")?;
+
+        Node::try_from(node).into_report().change_context(context)
+    }
+
+    /// Get the node a request couldn't be forwarded to from the metadata of the status
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The status of the call
+    fn unreachable_node(status: &Status) -> Option<NodeId> {
+        let id = status.metadata().get(UNREACHABLE_METADATA_KEY)?;
+        let id: u64 = id.to_str().ok()?.parse().ok()?;
+
+        Some(NodeId::from(id))
+    }
+
+    /// Wrap the message into a request, attaching the cluster secret if set, and the deadline
+    /// of the request being handled if any
+    fn request<T>(&self, message: T) -> tonic::Request<T> {
+        let mut request = tonic::Request::new(message);
+        if let Some(secret) = &self.secret {
+            request
+                .metadata_mut()
+                .insert(SECRET_METADATA_KEY, secret.clone());
+        }
+        if let Some(deadline) = deadline::current() {
+            request.metadata_mut().insert(
+                DEADLINE_METADATA_KEY,
+                MetadataValue::from(deadline::to_millis(deadline)),
+            );
+        }
+
+        request
+    }
+
+    /// Get the connection to the node, connecting again if there is none
+    pub async fn client(&self) -> Result<ChordNodeClient<Channel>, ClientError> {
+        self.client.get().await
+    }
 }
 
 #[derive(Debug)]
@@ -151,7 +615,7 @@ pub struct IpParseError {
 }
 
 impl IpParseError {
-    fn new(msg: &str) -> Self {
+    pub(crate) fn new(msg: &str) -> Self {
         IpParseError {
             msg: msg.to_string(),
         }
@@ -164,32 +628,39 @@ impl std::fmt::Display for IpParseError {
     }
 }
 
+impl std::error::Error for IpParseError {}
+
+impl From<chord_proto::RingParams> for RingParams {
+    fn from(params: chord_proto::RingParams) -> Self {
+        RingParams {
+            replication_factor: params.replication_factor as usize,
+            successor_list_size: params.successor_list_size as usize,
+            // Out of range on a misconfigured node, it's reported as a mismatch anyway
+            key_bits: u8::try_from(params.key_bits).unwrap_or(u8::MAX),
+        }
+    }
+}
+
 impl TryFrom<chord_proto::IpAddress> for IpAddr {
     type Error = IpParseError;
 
     fn try_from(ip: chord_proto::IpAddress) -> std::result::Result<Self, Self::Error> {
-        fn ipv4(addr: Vec<u8>) -> [u8; 4] {
-            let mut array = [0; 4];
-            array.copy_from_slice(&addr);
-            return array;
+        fn ipv4(addr: &[u8]) -> std::result::Result<[u8; 4], IpParseError> {
+            addr.try_into()
+                .map_err(|_| IpParseError::new("Invalid IPv4 address"))
         }
 
-        fn ipv6(addr: Vec<u8>) -> [u8; 16] {
-            let mut array = [0; 16];
-            array.copy_from_slice(&addr);
-            return array;
+        fn ipv6(addr: &[u8]) -> std::result::Result<[u8; 16], IpParseError> {
+            addr.try_into()
+                .map_err(|_| IpParseError::new("Invalid IPv6 address"))
         }
 
-        if ip.is_v4() && ip.address.len() != 4 {
-            return Err(IpParseError::new("Invalid IPv4 address"));
-        } else if ip.is_v6() && ip.address.len() != 16 {
-            return Err(IpParseError::new("Invalid IPv6 address"));
-        } else if ip.is_v4() {
-            return Ok(IpAddr::V4(Ipv4Addr::from(ipv4(ip.address))));
+        if ip.is_v4() {
+            Ok(IpAddr::V4(Ipv4Addr::from(ipv4(&ip.address)?)))
         } else if ip.is_v6() {
-            return Ok(IpAddr::V6(Ipv6Addr::from(ipv6(ip.address))));
+            Ok(IpAddr::V6(Ipv6Addr::from(ipv6(&ip.address)?)))
         } else {
-            return Err(IpParseError::new("Invalid IP address"));
+            Err(IpParseError::new("Invalid IP address"))
         }
     }
 }
@@ -206,7 +677,43 @@ impl chord_proto::IpAddress {
 
 #[cfg(test)]
 mod tests {
+    use std::net::{SocketAddrV6, TcpListener};
+    use std::time::Duration;
+
+    use tonic::transport::Server;
+
+    use tonic::service::interceptor::InterceptedService;
+
     use super::*;
+    use crate::server::{authenticate, ChordNodeServer, ChordService};
+    use chord_rs_core::error::ServiceError;
+    use chord_rs_core::server::{JoinConfig, ServerConfig};
+    use chord_rs_core::NodeService;
+
+    fn free_addr() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap()
+    }
+
+    /// Create an interceptor answering all the requests with the status
+    #[allow(clippy::result_large_err)]
+    fn reject(
+        status: Status,
+    ) -> impl FnMut(tonic::Request<()>) -> std::result::Result<tonic::Request<()>, Status> + Clone
+    {
+        move |_| Err(status.clone())
+    }
+
+    /// Config of a node creating a new ring
+    fn bootstrap() -> ServerConfig {
+        ServerConfig {
+            join: JoinConfig {
+                bootstrap: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
 
     #[test]
     fn parse_ipv4() {
@@ -256,4 +763,246 @@ mod tests {
         assert!(invalid_ip.is_err());
         assert_eq!("Invalid IPv6 address", invalid_ip.err().unwrap().msg);
     }
+
+    #[test]
+    fn parse_ip_with_the_length_of_the_other_version() {
+        let ipv4_of_16_bytes = chord_proto::IpAddress {
+            version: chord_proto::IpVersion::Ipv4 as i32,
+            address: vec![0; 16],
+        };
+        let ipv6_of_4_bytes = chord_proto::IpAddress {
+            version: chord_proto::IpVersion::Ipv6 as i32,
+            address: vec![127, 0, 0, 1],
+        };
+
+        let invalid_ip = IpAddr::try_from(ipv4_of_16_bytes);
+        assert_eq!("Invalid IPv4 address", invalid_ip.err().unwrap().msg);
+        let invalid_ip = IpAddr::try_from(ipv6_of_4_bytes);
+        assert_eq!("Invalid IPv6 address", invalid_ip.err().unwrap().msg);
+    }
+
+    #[test]
+    fn parse_ip_of_unknown_version() {
+        let ip = chord_proto::IpAddress {
+            version: 7,
+            address: vec![127, 0, 0, 1],
+        };
+
+        let invalid_ip = IpAddr::try_from(ip);
+        assert_eq!("Invalid IP address", invalid_ip.err().unwrap().msg);
+    }
+
+    #[test]
+    fn missing_or_malformed_response_node_is_an_error() {
+        let missing = ChordGrpcClient::response_node(None, ClientError::GetSuccessorFailed);
+        let malformed = ChordGrpcClient::response_node(
+            Some(chord_proto::Node {
+                id: 1,
+                ip: None,
+                port: 42001,
+            }),
+            ClientError::GetSuccessorFailed,
+        );
+
+        assert_eq!(
+            missing.unwrap_err().current_context(),
+            &ClientError::GetSuccessorFailed
+        );
+        assert_eq!(
+            malformed.unwrap_err().current_context(),
+            &ClientError::GetSuccessorFailed
+        );
+    }
+
+    #[test]
+    fn ipv6_endpoint() {
+        let addr: SocketAddr = "[::1]:42000".parse().unwrap();
+        let endpoint = ChordGrpcClient::endpoint(addr, None);
+        assert_eq!(endpoint.uri().host(), Some("[::1]"));
+        assert_eq!(endpoint.uri().port_u16(), Some(42000));
+
+        let addr = SocketAddr::V6(SocketAddrV6::new("fe80::1".parse().unwrap(), 42000, 0, 2));
+        let endpoint = ChordGrpcClient::endpoint(addr, None);
+        assert_eq!(endpoint.uri().host(), Some("[fe80::1%2]"));
+        assert_eq!(endpoint.uri().port_u16(), Some(42000));
+    }
+
+    #[tokio::test]
+    async fn reconnect_once_the_node_is_up() {
+        let addr = free_addr();
+        let client = ChordGrpcClient::with_endpoint(
+            Endpoint::from_shared(format!("http://{}", addr)).unwrap(),
+            &ClientConfig::default(),
+        );
+
+        let err = client.ping().await.unwrap_err();
+        assert!(matches!(
+            err.current_context(),
+            ClientError::ConnectionFailed(_)
+        ));
+
+        let service = ChordService::new(addr, bootstrap()).await;
+        let id = service.node().id();
+        tokio::spawn(
+            Server::builder()
+                .add_service(ChordNodeServer::new(service))
+                .serve(addr),
+        );
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert_eq!(client.ping().await.unwrap(), id);
+    }
+
+    #[tokio::test]
+    async fn clients_send_their_own_secret() {
+        let mut nodes = vec![];
+        for secret in ["first", "second"] {
+            let addr = free_addr();
+            let clients = ClientConfig::default().with_secret(secret).unwrap();
+            let service = ChordService::with_clients(addr, bootstrap(), &clients).await;
+            let chord = InterceptedService::new(
+                ChordNodeServer::new(service),
+                authenticate(Some(secret.to_string())),
+            );
+            tokio::spawn(Server::builder().add_service(chord).serve(addr));
+            nodes.push((addr, clients));
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let (first, first_clients) = &nodes[0];
+        let (second, second_clients) = &nodes[1];
+        assert!(ChordGrpcClient::connect(*first, first_clients)
+            .await
+            .ping()
+            .await
+            .is_ok());
+        assert!(ChordGrpcClient::connect(*second, second_clients)
+            .await
+            .ping()
+            .await
+            .is_ok());
+        assert!(ChordGrpcClient::connect(*first, second_clients)
+            .await
+            .ping()
+            .await
+            .is_err());
+        assert!(ChordGrpcClient::init(*second).await.ping().await.is_err());
+        assert!(ClientConfig::default().with_secret("not\nvalid").is_err());
+    }
+
+    #[tokio::test]
+    async fn unavailable_peer_fails_the_lookup_as_a_failed_connection() {
+        let addr = free_addr();
+        let service = ChordService::new(addr, bootstrap()).await;
+        let chord = InterceptedService::new(
+            ChordNodeServer::new(service),
+            reject(Status::unavailable("Overloaded")),
+        );
+        tokio::spawn(Server::builder().add_service(chord).serve(addr));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let client = ChordGrpcClient::init(addr).await;
+        let err = client.find_successor(NodeId::from(1)).await.unwrap_err();
+
+        assert!(matches!(
+            err.current_context(),
+            ClientError::ConnectionFailed(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn unreachable_peer_keeps_the_connection_to_the_forwarding_node() {
+        let addr = free_addr();
+        let service = ChordService::new(addr, bootstrap()).await;
+        let unreachable = Report::new(ServiceError::PeerUnreachable(NodeId::from(7)));
+        let chord = InterceptedService::new(
+            ChordNodeServer::new(service),
+            reject(ChordService::map_error(unreachable)),
+        );
+        tokio::spawn(Server::builder().add_service(chord).serve(addr));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let client = ChordGrpcClient::init(addr).await;
+        let err = client.find_successor(NodeId::from(1)).await.unwrap_err();
+
+        assert_eq!(
+            err.current_context(),
+            &ClientError::PeerUnreachable(NodeId::from(7))
+        );
+        assert!(client.client.client.lock().unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn batch_lookups_are_limited() {
+        let addr = free_addr();
+        let service = ChordService::new(addr, bootstrap()).await;
+        let node = Node::with_id(service.node().id(), addr);
+        tokio::spawn(
+            Server::builder()
+                .add_service(ChordNodeServer::new(service))
+                .serve(addr),
+        );
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let client = ChordGrpcClient::init(addr).await;
+        let successors = client
+            .find_successors(vec![NodeId::from(1), NodeId::from(2)])
+            .await
+            .unwrap();
+        let limit = NodeService::<ChordGrpcClient>::MAX_FIND_SUCCESSORS_BATCH;
+        let err = client
+            .find_successors(vec![NodeId::from(1); limit + 1])
+            .await
+            .unwrap_err();
+
+        assert_eq!(successors, vec![Ok(node.clone()), Ok(node)]);
+        let status = err.downcast_ref::<Status>().unwrap();
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn node_info_of_a_new_ring() {
+        let addr = free_addr();
+        let service = ChordService::new(addr, bootstrap()).await;
+        let node = Node::with_id(service.node().id(), addr);
+        let params = service.node().params();
+        tokio::spawn(
+            Server::builder()
+                .add_service(ChordNodeServer::new(service))
+                .serve(addr),
+        );
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let client = ChordGrpcClient::init(addr).await;
+        let info = client.node_info().await.unwrap();
+
+        assert_eq!(info.id, node.id());
+        assert_eq!(info.addr, addr);
+        assert_eq!(info.predecessor, None);
+        assert_eq!(info.successor_list, vec![node]);
+        assert_eq!(info.params, Some(params));
+    }
+
+    #[tokio::test]
+    async fn predecessor_list_starts_at_the_predecessor() {
+        let addr = free_addr();
+        let config = ServerConfig {
+            node_id: Some(NodeId::from(100)),
+            ..bootstrap()
+        };
+        let service = ChordService::new(addr, config).await;
+        let predecessor = Node::with_id(50, free_addr());
+        service.node().notify(predecessor.clone()).await;
+        tokio::spawn(
+            Server::builder()
+                .add_service(ChordNodeServer::new(service))
+                .serve(addr),
+        );
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let client = ChordGrpcClient::init(addr).await;
+        let predecessors = client.predecessor_list().await.unwrap();
+
+        assert_eq!(predecessors, vec![predecessor]);
+    }
 }
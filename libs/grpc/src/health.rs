@@ -0,0 +1,143 @@
+use std::sync::Arc;
+
+use chord_rs_core::{Client, NodeEvent, NodeService};
+use tokio::sync::broadcast::error::RecvError;
+use tonic::server::NamedService;
+use tonic_health::pb::health_server::{Health, HealthServer};
+use tonic_health::server::{health_reporter, HealthReporter};
+use tonic_health::ServingStatus;
+
+use crate::server::{ChordNodeServer, ChordService};
+
+/// Create the standard `grpc.health.v1.Health` service of the node
+///
+/// Both the whole server (the empty service name) and the `chord.ChordNode` service are reported
+/// as `NOT_SERVING` until the node stabilizes for the first time, so probes only pass once the
+/// node is part of the ring. They go back to `NOT_SERVING` when the node leaves the ring.
+///
+/// # Arguments
+///
+/// * `node` - The service of the node
+pub fn health_service<C: Client + Clone + Sync + Send + 'static>(
+    node: Arc<NodeService<C>>,
+) -> HealthServer<impl Health> {
+    let (reporter, service) = health_reporter();
+    tokio::spawn(report_health(node, reporter));
+
+    service
+}
+
+/// Keep the health statuses in sync with the events of the node
+async fn report_health<C: Client + Clone + Sync + Send + 'static>(
+    node: Arc<NodeService<C>>,
+    mut reporter: HealthReporter,
+) {
+    // Subscribe before reading the state, so an event in between is not missed
+    let mut events = node.subscribe();
+    let joined = match node.health().await {
+        Ok(health) => health.joined,
+        Err(_) => false,
+    };
+    set_status(&mut reporter, joined).await;
+
+    loop {
+        match events.recv().await {
+            Ok(NodeEvent::Joined) => set_status(&mut reporter, true).await,
+            Ok(NodeEvent::Left) => set_status(&mut reporter, false).await,
+            Ok(_) => {}
+            Err(RecvError::Lagged(skipped)) => {
+                log::debug!("Health reporter skipped {} node events", skipped);
+            }
+            Err(RecvError::Closed) => return,
+        }
+    }
+}
+
+async fn set_status(reporter: &mut HealthReporter, serving: bool) {
+    let status = if serving {
+        ServingStatus::Serving
+    } else {
+        ServingStatus::NotServing
+    };
+
+    reporter.set_service_status("", status).await;
+    reporter
+        .set_service_status(
+            <ChordNodeServer<ChordService> as NamedService>::NAME,
+            status,
+        )
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{SocketAddr, TcpListener};
+    use std::time::Duration;
+
+    use chord_rs_core::client::InMemoryClient;
+    use tonic::transport::{Channel, Server};
+    use tonic_health::pb::health_check_response::ServingStatus;
+    use tonic_health::pb::health_client::HealthClient;
+    use tonic_health::pb::HealthCheckRequest;
+
+    use super::*;
+
+    fn free_addr() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap()
+    }
+
+    async fn status(client: &mut HealthClient<Channel>, service: &str) -> i32 {
+        client
+            .check(HealthCheckRequest {
+                service: service.to_string(),
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .status
+    }
+
+    #[tokio::test]
+    async fn serving_only_after_node_joined() {
+        let node = Arc::new(NodeService::<InMemoryClient>::with_id(
+            1,
+            SocketAddr::from(([127, 0, 0, 1], 43001)),
+            3,
+            None,
+        ));
+        InMemoryClient::register(node.clone());
+
+        let addr = free_addr();
+        tokio::spawn(
+            Server::builder()
+                .add_service(health_service(node.clone()))
+                .serve(addr),
+        );
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let channel = Channel::from_shared(format!("http://{}", addr))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let mut client = HealthClient::new(channel);
+        assert_eq!(
+            status(&mut client, "").await,
+            ServingStatus::NotServing as i32
+        );
+        assert_eq!(
+            status(&mut client, "chord.ChordNode").await,
+            ServingStatus::NotServing as i32
+        );
+
+        node.stabilize().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert_eq!(status(&mut client, "").await, ServingStatus::Serving as i32);
+        assert_eq!(
+            status(&mut client, "chord.ChordNode").await,
+            ServingStatus::Serving as i32
+        );
+    }
+}
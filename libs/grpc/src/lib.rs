@@ -1,18 +1,30 @@
 use std::net::SocketAddr;
 
+use client::IpParseError;
 use server::chord_proto;
 
+#[cfg(feature = "chaos")]
+pub mod chaos;
 pub mod client;
+pub mod health;
+#[cfg(feature = "reflection")]
+pub mod reflection;
 pub mod server;
+pub mod tls;
+#[cfg(feature = "web")]
+pub mod web;
 
 impl TryFrom<chord_proto::Node> for chord_rs_core::Node {
-    type Error = std::net::AddrParseError;
+    type Error = IpParseError;
 
     fn try_from(node: chord_proto::Node) -> Result<Self, Self::Error> {
         let id = node.id;
-        let ip = node.ip.unwrap();
-        let ip = ip.try_into().unwrap();
-        let port = node.port as u16;
+        let ip = node
+            .ip
+            .ok_or_else(|| IpParseError::new("Missing IP address"))?;
+        let ip = ip.try_into()?;
+        let port = u16::try_from(node.port)
+            .map_err(|_| IpParseError::new(&format!("Invalid port {}", node.port)))?;
 
         let addr = SocketAddr::new(ip, port);
 
@@ -33,4 +45,22 @@ mod tests {
         let result = add(2, 2);
         assert_eq!(result, 4);
     }
+
+    fn proto_node(ip: Option<chord_proto::IpAddress>, port: i32) -> chord_proto::Node {
+        chord_proto::Node { id: 1, ip, port }
+    }
+
+    #[test]
+    fn node_without_ip_is_rejected() {
+        assert!(chord_rs_core::Node::try_from(proto_node(None, 42000)).is_err());
+    }
+
+    #[test]
+    fn node_with_port_out_of_range_is_rejected() {
+        let ip = chord_proto::IpAddress::from(std::net::IpAddr::from([127, 0, 0, 1]));
+
+        assert!(chord_rs_core::Node::try_from(proto_node(Some(ip.clone()), 42000)).is_ok());
+        assert!(chord_rs_core::Node::try_from(proto_node(Some(ip.clone()), 65536)).is_err());
+        assert!(chord_rs_core::Node::try_from(proto_node(Some(ip), -1)).is_err());
+    }
 }
@@ -0,0 +1,15 @@
+use tonic_reflection::server::{Builder, ServerReflection, ServerReflectionServer};
+
+use crate::server::chord_proto;
+
+/// Create the gRPC server reflection service
+///
+/// It describes the `chord.ChordNode` and the `grpc.health.v1.Health` services, so tools like
+/// `grpcurl` can call the node without the proto files.
+pub fn reflection_service() -> ServerReflectionServer<impl ServerReflection> {
+    Builder::configure()
+        .register_encoded_file_descriptor_set(chord_proto::FILE_DESCRIPTOR_SET)
+        .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+        .build()
+        .expect("Failed to build the reflection service")
+}
@@ -1,21 +1,35 @@
 use std::{
     net::{IpAddr, SocketAddr},
     sync::Arc,
+    time::SystemTime,
 };
 
 use chord_proto::chord_node_server::ChordNode;
 pub use chord_proto::chord_node_server::ChordNodeServer;
 use chord_proto::{PingRequest, PingResponse};
-use chord_rs_core::{Node, NodeService};
+use chord_rs_core::auth::secret_matches;
+use chord_rs_core::deadline;
+use chord_rs_core::error::ServiceError;
+use chord_rs_core::rate_limit::RateLimiter;
+use chord_rs_core::server::{launch_node, launch_node_with, ServerConfig};
+use chord_rs_core::{HealthStatus, MaintenanceTask, Node, NodeId, NodeService, RingParams};
 use error_stack::Report;
+pub use tonic::codec::CompressionEncoding;
+use tonic::metadata::MetadataValue;
 pub use tonic::transport::Server;
 use tonic::{Request, Response, Status};
+use tower::limit::GlobalConcurrencyLimitLayer;
 
-use crate::client::ChordGrpcClient;
+use crate::client::{ChordGrpcClient, ClientConfig};
 
 use self::chord_proto::{
-    FindSuccessorRequest, FindSuccessorResponse, GetPredecessorRequest, GetPredecessorResponse,
-    GetSuccessorResponse, NotifyRequest, NotifyResponse,
+    find_successors_result, CanReachRequest, CanReachResponse, FindPredecessorRequest,
+    FindPredecessorResponse, FindSuccessorRequest, FindSuccessorResponse, FindSuccessorsRequest,
+    FindSuccessorsResponse, FindSuccessorsResult, GetNodeInfoRequest, GetNodeInfoResponse,
+    GetPredecessorListRequest, GetPredecessorListResponse, GetPredecessorRequest,
+    GetPredecessorResponse, GetSuccessorListRequest, GetSuccessorListResponse,
+    GetSuccessorResponse, HealthRequest, HealthResponse, LeaveRequest, LeaveResponse, LookupError,
+    NotifyRequest, NotifyResponse, TriggerRequest, TriggerResponse,
 };
 
 pub mod chord_proto {
@@ -23,10 +37,16 @@ pub mod chord_proto {
 
     include!(concat!(env!("OUT_DIR"), "/chord.rs"));
 
+    /// Encoded descriptors of the chord protocol, served by the reflection service
+    #[cfg(feature = "reflection")]
+    pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("chord_descriptor");
+
     impl Clone for ChordGrpcClient {
         fn clone(&self) -> Self {
             Self {
                 client: self.client.clone(),
+                latency: self.latency.clone(),
+                secret: self.secret.clone(),
             }
         }
     }
@@ -41,25 +61,231 @@ pub struct ChordService {
 }
 
 impl ChordService {
-    pub async fn new(addr: SocketAddr, ring: Option<SocketAddr>) -> Self {
-        const REPLICATION_FACTOR: usize = 3; // TODO: make this configurable
-        let node_service = Arc::new(NodeService::new(addr, REPLICATION_FACTOR));
+    /// Start the node and create the service serving it, see [`launch_node`]
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the node
+    /// * `config` - The parameters of the node
+    pub async fn new(addr: SocketAddr, config: ServerConfig) -> Self {
+        Self {
+            node: launch_node(addr, &config).await,
+        }
+    }
 
-        if let Some(ring) = ring {
-            const MAX_RETRIES: u32 = 5;
-            chord_rs_core::server::join_ring(node_service.clone(), ring, MAX_RETRIES).await;
+    /// Start the node like [`new`](Self::new), its clients connecting to the other nodes with
+    /// the given settings
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the node
+    /// * `config` - The parameters of the node
+    /// * `clients` - The settings of the clients of the node
+    pub async fn with_clients(
+        addr: SocketAddr,
+        config: ServerConfig,
+        clients: &ClientConfig,
+    ) -> Self {
+        Self {
+            node: launch_node_with(addr, &config, Some(clients.factory())).await,
         }
-        chord_rs_core::server::background_tasks(node_service.clone());
+    }
 
-        Self { node: node_service }
+    /// Get the service of the node, shared with the RPC handlers
+    pub fn node(&self) -> Arc<NodeService<ChordGrpcClient>> {
+        self.node.clone()
     }
 
-    fn map_error(error: Report<chord_rs_core::error::ServiceError>) -> Status {
+    /// Get the status of an error of the node service
+    ///
+    /// The status codes follow the gRPC semantics, so the clients can tell which requests are
+    /// worth retrying: a node which is not part of a ring yet is `FAILED_PRECONDITION` and a
+    /// timeout is `DEADLINE_EXCEEDED`. A node unreachable from this one is `ABORTED`, with its
+    /// id in the [`UNREACHABLE_METADATA_KEY`] metadata. Unlike `UNAVAILABLE`, it doesn't make
+    /// the clients drop their connection to this node, which is alive.
+    ///
+    /// > **Note**
+    /// >
+    /// > This is not a `From` implementation, as both the error and the status are foreign to
+    /// > this crate.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The error returned by the node service
+    pub(crate) fn map_error(error: Report<ServiceError>) -> Status {
         let message = error.to_string();
         match error.current_context() {
-            chord_rs_core::error::ServiceError::Unexpected => Status::internal(message),
-            chord_rs_core::error::ServiceError::ClientDisconnected => todo!(),
+            ServiceError::PeerUnreachable(node) => {
+                let id: u64 = (*node).into();
+                let mut status = Status::aborted(message);
+                status
+                    .metadata_mut()
+                    .insert(UNREACHABLE_METADATA_KEY, MetadataValue::from(id));
+                status
+            }
+            ServiceError::NotJoined => Status::failed_precondition(message),
+            ServiceError::InvalidArgument(_) => Status::invalid_argument(message),
+            ServiceError::Timeout => Status::deadline_exceeded(message),
+            ServiceError::JoinFailed | ServiceError::Internal(_) => Status::internal(message),
+        }
+    }
+
+    /// Parse a node sent with a request, a missing or malformed node is `INVALID_ARGUMENT`
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node field of the request
+    // The handlers return the `Status` as is, boxing it would only move the allocation there
+    #[allow(clippy::result_large_err)]
+    fn parse_node(node: Option<chord_proto::Node>) -> Result<Node, Status> {
+        let node = node.ok_or_else(|| Status::invalid_argument("Missing node"))?;
+
+        Node::try_from(node).map_err(|err| Status::invalid_argument(err.to_string()))
+    }
+
+    /// Run a maintenance task on the request of an administrator, see [`NodeService::trigger`]
+    ///
+    /// Without the `admin` feature the request is `PERMISSION_DENIED`, so a peer can't make the
+    /// node run its tasks.
+    ///
+    /// # Arguments
+    ///
+    /// * `task` - The task to run
+    async fn trigger(&self, task: MaintenanceTask) -> Result<Response<TriggerResponse>, Status> {
+        if !cfg!(feature = "admin") {
+            return Err(Status::permission_denied(format!(
+                "Triggering {} requires the admin feature",
+                task
+            )));
         }
+
+        self.node.trigger(task).await.map_err(Self::map_error)?;
+
+        Ok(Response::new(TriggerResponse {}))
+    }
+}
+
+/// Metadata key carrying the cluster secret
+pub const SECRET_METADATA_KEY: &str = "x-chord-secret";
+
+/// Metadata key carrying the deadline of the request, in milliseconds since the Unix epoch
+pub const DEADLINE_METADATA_KEY: &str = "x-chord-deadline";
+
+/// Metadata key carrying the id of the node a request couldn't be forwarded to
+pub const UNREACHABLE_METADATA_KEY: &str = "x-chord-unreachable";
+
+/// Get the deadline sent with the request, if any
+///
+/// A malformed deadline is ignored, as if the request had none.
+///
+/// # Arguments
+///
+/// * `request` - The received request
+fn request_deadline<T>(request: &Request<T>) -> Option<SystemTime> {
+    request
+        .metadata()
+        .get(DEADLINE_METADATA_KEY)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+        .map(deadline::from_millis)
+}
+
+/// Create an interceptor which rejects requests without the cluster secret.
+///
+/// If no secret is set, all requests are accepted.
+///
+/// # Arguments
+///
+/// * `secret` - The cluster secret
+#[allow(clippy::result_large_err)]
+pub fn authenticate(
+    secret: Option<String>,
+) -> impl FnMut(Request<()>) -> Result<Request<()>, Status> + Clone {
+    move |request: Request<()>| {
+        let expected = match &secret {
+            Some(secret) => secret,
+            None => return Ok(request),
+        };
+
+        let provided = request
+            .metadata()
+            .get(SECRET_METADATA_KEY)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+
+        if secret_matches(expected, provided) {
+            Ok(request)
+        } else {
+            log::warn!("Rejected request with an invalid cluster secret");
+            Err(Status::unauthenticated("Invalid cluster secret"))
+        }
+    }
+}
+
+/// Create an interceptor which rejects the requests of the peers exceeding their rate.
+///
+/// If no rate limiter is set, all requests are accepted. The requests whose peer address is
+/// unknown are accepted too.
+///
+/// # Arguments
+///
+/// * `limiter` - The rate limiter shared by all the requests
+#[allow(clippy::result_large_err)]
+pub fn rate_limit(
+    limiter: Option<Arc<RateLimiter>>,
+) -> impl FnMut(Request<()>) -> Result<Request<()>, Status> + Clone {
+    move |request: Request<()>| {
+        let (Some(limiter), Some(peer)) = (&limiter, request.remote_addr()) else {
+            return Ok(request);
+        };
+
+        if limiter.check(peer.ip()) {
+            Ok(request)
+        } else {
+            log::debug!("Rejected request from {}: rate limit exceeded", peer);
+            Err(Status::resource_exhausted("Rate limit exceeded"))
+        }
+    }
+}
+
+/// Create a layer which limits the number of requests served at the same time.
+///
+/// The limit is shared by all the connections of the server, the requests over the limit wait
+/// until one of the requests being served completes, so a busy node slows its peers down instead
+/// of buffering an unbounded number of requests.
+///
+/// > **Note**
+/// > gRPC multiplexes the requests over a few HTTP/2 connections, so unlike the Cap'n Proto
+/// > transport, which limits the open connections, the gRPC transport limits the requests. A
+/// > per connection limit, like [`Server::concurrency_limit_per_connection`], doesn't bound the
+/// > memory of the node, since every new connection gets its own budget.
+///
+/// # Arguments
+///
+/// * `max_requests` - The maximum number of requests served at the same time
+pub fn concurrency_limit(max_requests: usize) -> GlobalConcurrencyLimitLayer {
+    GlobalConcurrencyLimitLayer::new(max_requests)
+}
+
+/// Create the server of the node, accepting compressed requests
+///
+/// The requests compressed with any codec are accepted whatever the node's own setting, so the
+/// nodes compressing their messages and the ones which don't can be part of the same ring. The
+/// responses are compressed only for the clients which accept the codec, the other ones get
+/// uncompressed responses.
+///
+/// # Arguments
+///
+/// * `service` - The service of the node
+/// * `compression` - The codec compressing the responses, if not set they are not compressed
+pub fn chord_node_server(
+    service: ChordService,
+    compression: Option<CompressionEncoding>,
+) -> ChordNodeServer<ChordService> {
+    let server = ChordNodeServer::new(service).accept_compressed(CompressionEncoding::Gzip);
+    match compression {
+        Some(encoding) => server.send_compressed(encoding),
+        None => server,
     }
 }
 
@@ -68,19 +294,17 @@ pub enum JoinRingError {
     ServiceError,
 }
 
-impl From<chord_rs_core::error::ServiceError> for JoinRingError {
-    fn from(error: chord_rs_core::error::ServiceError) -> Self {
-        match error {
-            chord_rs_core::error::ServiceError::Unexpected => Self::ServiceError,
-            chord_rs_core::error::ServiceError::ClientDisconnected => todo!(),
-        }
+impl From<ServiceError> for JoinRingError {
+    fn from(_: ServiceError) -> Self {
+        Self::ServiceError
     }
 }
 
 #[tonic::async_trait]
 impl ChordNode for ChordService {
     async fn ping(&self, _request: Request<PingRequest>) -> Result<Response<PingResponse>, Status> {
-        let reply = chord_proto::PingResponse {};
+        let id = self.node.ping();
+        let reply = chord_proto::PingResponse { id: id.into() };
 
         Ok(Response::new(reply))
     }
@@ -89,15 +313,81 @@ impl ChordNode for ChordService {
         &self,
         request: Request<FindSuccessorRequest>,
     ) -> Result<Response<FindSuccessorResponse>, Status> {
-        let result = self
-            .node
-            .find_successor(request.get_ref().id.into())
+        let deadline = request_deadline(&request);
+        let request = request.get_ref();
+        if request.trace {
+            let (node, path) = self
+                .node
+                .find_successor_traced(request.id.into())
+                .await
+                .map_err(Self::map_error)?;
+
+            return Ok(Response::new(FindSuccessorResponse {
+                node: Some(node.into()),
+                path: path.into_iter().map(|id| id.into()).collect(),
+            }));
+        }
+
+        let result = deadline::scope(deadline, self.node.find_successor(request.id.into()))
             .await
             .map_err(Self::map_error)?;
 
         Ok(Response::new(result.into()))
     }
 
+    async fn find_successors(
+        &self,
+        request: Request<FindSuccessorsRequest>,
+    ) -> Result<Response<FindSuccessorsResponse>, Status> {
+        let ids = request
+            .into_inner()
+            .ids
+            .into_iter()
+            .map(NodeId::from)
+            .collect();
+        let successors = self
+            .node
+            .find_successors(ids)
+            .await
+            .map_err(Self::map_error)?;
+
+        Ok(Response::new(FindSuccessorsResponse {
+            results: successors
+                .into_iter()
+                .map(|result| {
+                    let result = match result {
+                        Ok(node) => find_successors_result::Result::Node(node.into()),
+                        Err(err) => {
+                            let status = Self::map_error(Report::new(err));
+                            find_successors_result::Result::Error(LookupError {
+                                code: status.code() as i32,
+                                message: status.message().to_string(),
+                            })
+                        }
+                    };
+                    FindSuccessorsResult {
+                        result: Some(result),
+                    }
+                })
+                .collect(),
+        }))
+    }
+
+    async fn find_predecessor(
+        &self,
+        request: Request<FindPredecessorRequest>,
+    ) -> Result<Response<FindPredecessorResponse>, Status> {
+        let node = self
+            .node
+            .find_predecessor(request.get_ref().id.into())
+            .await
+            .map_err(Self::map_error)?;
+
+        Ok(Response::new(FindPredecessorResponse {
+            node: Some(node.into()),
+        }))
+    }
+
     async fn get_successor(
         &self,
         _request: Request<chord_proto::GetSuccessorRequest>,
@@ -107,6 +397,21 @@ impl ChordNode for ChordService {
         Ok(Response::new(result.into()))
     }
 
+    async fn get_successor_list(
+        &self,
+        _request: Request<GetSuccessorListRequest>,
+    ) -> Result<Response<GetSuccessorListResponse>, Status> {
+        let nodes = self
+            .node
+            .get_successor_list()
+            .await
+            .map_err(Self::map_error)?;
+
+        Ok(Response::new(GetSuccessorListResponse {
+            nodes: nodes.into_iter().map(Into::into).collect(),
+        }))
+    }
+
     async fn get_predecessor(
         &self,
         _request: Request<GetPredecessorRequest>,
@@ -116,23 +421,120 @@ impl ChordNode for ChordService {
         Ok(Response::new(result.into()))
     }
 
+    async fn get_predecessor_list(
+        &self,
+        _request: Request<GetPredecessorListRequest>,
+    ) -> Result<Response<GetPredecessorListResponse>, Status> {
+        let nodes = self
+            .node
+            .get_predecessor_list()
+            .await
+            .map_err(Self::map_error)?;
+
+        Ok(Response::new(GetPredecessorListResponse {
+            nodes: nodes.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn get_node_info(
+        &self,
+        _request: Request<GetNodeInfoRequest>,
+    ) -> Result<Response<GetNodeInfoResponse>, Status> {
+        let info = self.node.node_info().await.map_err(Self::map_error)?;
+
+        Ok(Response::new(GetNodeInfoResponse {
+            node: Some(Node::with_id(info.id, info.addr).into()),
+            predecessor: info.predecessor.map(Into::into),
+            successors: info.successor_list.into_iter().map(Into::into).collect(),
+            params: info.params.map(Into::into),
+        }))
+    }
+
     async fn notify(
         &self,
         request: Request<NotifyRequest>,
     ) -> Result<Response<NotifyResponse>, Status> {
-        let node = request.get_ref().node.clone();
-        let node = Node::try_from(node.unwrap()).unwrap();
+        let node = Self::parse_node(request.into_inner().node)?;
 
-        self.node.notify(node);
+        self.node.notify(node).await;
 
         Ok(Response::new(NotifyResponse {}))
     }
+
+    async fn leave(
+        &self,
+        request: Request<LeaveRequest>,
+    ) -> Result<Response<LeaveResponse>, Status> {
+        let request = request.into_inner();
+        let node = Self::parse_node(request.node)?;
+        let predecessor = request
+            .predecessor
+            .map(Node::try_from)
+            .transpose()
+            .map_err(|err| Status::invalid_argument(err.to_string()))?;
+
+        self.node.handle_leave(node, predecessor);
+
+        Ok(Response::new(LeaveResponse {}))
+    }
+
+    async fn can_reach(
+        &self,
+        request: Request<CanReachRequest>,
+    ) -> Result<Response<CanReachResponse>, Status> {
+        let node = Self::parse_node(request.into_inner().node)?;
+
+        let reachable = self.node.can_reach(node).await.map_err(Self::map_error)?;
+
+        Ok(Response::new(CanReachResponse { reachable }))
+    }
+
+    async fn health(
+        &self,
+        _request: Request<HealthRequest>,
+    ) -> Result<Response<HealthResponse>, Status> {
+        let health = self.node.health().await.map_err(Self::map_error)?;
+
+        Ok(Response::new(health.into()))
+    }
+
+    async fn trigger_stabilize(
+        &self,
+        _request: Request<TriggerRequest>,
+    ) -> Result<Response<TriggerResponse>, Status> {
+        self.trigger(MaintenanceTask::Stabilize).await
+    }
+
+    async fn trigger_fix_fingers(
+        &self,
+        _request: Request<TriggerRequest>,
+    ) -> Result<Response<TriggerResponse>, Status> {
+        self.trigger(MaintenanceTask::FixFingers).await
+    }
+
+    async fn trigger_reconcile(
+        &self,
+        _request: Request<TriggerRequest>,
+    ) -> Result<Response<TriggerResponse>, Status> {
+        self.trigger(MaintenanceTask::ReconcileSuccessors).await
+    }
 }
 
 impl From<chord_rs_core::Node> for FindSuccessorResponse {
     fn from(node: chord_rs_core::Node) -> Self {
         FindSuccessorResponse {
             node: Some(node.into()),
+            path: vec![],
+        }
+    }
+}
+
+impl From<HealthStatus> for HealthResponse {
+    fn from(health: HealthStatus) -> Self {
+        HealthResponse {
+            joined: health.joined,
+            has_predecessor: health.has_predecessor,
+            successor_reachable: health.successor_reachable,
         }
     }
 }
@@ -153,6 +555,16 @@ impl From<Option<chord_rs_core::Node>> for GetPredecessorResponse {
     }
 }
 
+impl From<RingParams> for chord_proto::RingParams {
+    fn from(params: RingParams) -> Self {
+        chord_proto::RingParams {
+            replication_factor: params.replication_factor as u64,
+            successor_list_size: params.successor_list_size as u64,
+            key_bits: params.key_bits.into(),
+        }
+    }
+}
+
 impl From<chord_rs_core::Node> for chord_proto::Node {
     fn from(node: chord_rs_core::Node) -> Self {
         chord_proto::Node {
@@ -176,3 +588,145 @@ impl From<IpAddr> for chord_proto::IpAddress {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+    use std::num::NonZeroU32;
+    use std::time::Duration;
+
+    use tonic::transport::Channel;
+    use tonic_health::pb::health_client::HealthClient;
+    use tonic_health::pb::HealthCheckRequest;
+
+    use super::*;
+
+    #[test]
+    fn service_errors_are_mapped_to_status_codes() {
+        let code = |error| ChordService::map_error(Report::new(error)).code();
+
+        assert_eq!(
+            code(ServiceError::PeerUnreachable(NodeId::from(1))),
+            tonic::Code::Aborted
+        );
+        assert_eq!(
+            code(ServiceError::NotJoined),
+            tonic::Code::FailedPrecondition
+        );
+        assert_eq!(code(ServiceError::Timeout), tonic::Code::DeadlineExceeded);
+        assert_eq!(
+            code(ServiceError::InvalidArgument("Error".to_string())),
+            tonic::Code::InvalidArgument
+        );
+        assert_eq!(
+            code(ServiceError::Internal("Error".to_string())),
+            tonic::Code::Internal
+        );
+    }
+
+    #[test]
+    fn missing_node_is_an_invalid_argument() {
+        let status = ChordService::parse_node(None).unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test]
+    fn authenticate_without_secret_accepts_all_requests() {
+        let mut interceptor = authenticate(None);
+
+        assert!(interceptor(Request::new(())).is_ok());
+    }
+
+    #[test]
+    fn authenticate_with_secret() {
+        let mut interceptor = authenticate(Some("secret".to_string()));
+
+        let status = interceptor(Request::new(())).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+        let mut request = Request::new(());
+        request
+            .metadata_mut()
+            .insert(SECRET_METADATA_KEY, "invalid".parse().unwrap());
+        let status = interceptor(request).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+        let mut request = Request::new(());
+        request
+            .metadata_mut()
+            .insert(SECRET_METADATA_KEY, "secret".parse().unwrap());
+        assert!(interceptor(request).is_ok());
+    }
+
+    #[tokio::test]
+    async fn rate_limit_per_peer() {
+        let limiter = RateLimiter::new(NonZeroU32::new(2).unwrap());
+        let (_, health) = tonic_health::server::health_reporter();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        tokio::spawn(
+            Server::builder()
+                .layer(tonic::service::interceptor(rate_limit(Some(Arc::new(
+                    limiter,
+                )))))
+                .add_service(health)
+                .serve(addr),
+        );
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let channel = Channel::from_shared(format!("http://{}", addr))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let mut client = HealthClient::new(channel);
+        let request = HealthCheckRequest::default;
+
+        assert!(client.check(request()).await.is_ok());
+        assert!(client.check(request()).await.is_ok());
+        let status = client.check(request()).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+    }
+
+    #[tokio::test]
+    async fn requests_over_the_concurrency_limit_wait() {
+        let (_, health) = tonic_health::server::health_reporter();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        tokio::spawn(
+            Server::builder()
+                .layer(concurrency_limit(1))
+                .add_service(health)
+                .serve(addr),
+        );
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let channel = Channel::from_shared(format!("http://{}", addr))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let requests: Vec<_> = (0..8)
+            .map(|_| {
+                let mut client = HealthClient::new(channel.clone());
+                tokio::spawn(async move { client.check(HealthCheckRequest::default()).await })
+            })
+            .collect();
+
+        for request in requests {
+            assert!(request.await.unwrap().is_ok());
+        }
+    }
+
+    #[test]
+    fn rate_limit_without_limiter_accepts_all_requests() {
+        let mut interceptor = rate_limit(None);
+
+        for _ in 0..100 {
+            assert!(interceptor(Request::new(())).is_ok());
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use std::path::PathBuf;
+
+use tonic::transport::{Certificate, ClientTlsConfig, Identity, ServerTlsConfig};
+
+/// TLS configuration of a node
+///
+/// The node uses the same certificate to serve requests and to authenticate itself when it
+/// connects to other nodes. Peers are verified against the given certificate authority, so a
+/// node configured for TLS refuses plaintext peers as well as peers with untrusted certificates.
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    /// Path to the PEM encoded certificate of the node
+    pub cert: PathBuf,
+    /// Path to the PEM encoded private key of the node
+    pub key: PathBuf,
+    /// Path to the PEM encoded certificate of the authority which signs the nodes' certificates
+    pub ca: PathBuf,
+    /// Domain name used to verify the peers' certificates.
+    /// If not set, the peer's address is used.
+    pub domain: Option<String>,
+}
+
+impl TlsConfig {
+    /// Build the TLS configuration for the server side of the node
+    pub fn server_config(&self) -> std::io::Result<ServerTlsConfig> {
+        let config = ServerTlsConfig::new()
+            .identity(self.identity()?)
+            .client_ca_root(self.ca_certificate()?);
+
+        Ok(config)
+    }
+
+    /// Build the TLS configuration for the clients connecting to other nodes
+    pub fn client_config(&self) -> std::io::Result<ClientTlsConfig> {
+        let mut config = ClientTlsConfig::new()
+            .identity(self.identity()?)
+            .ca_certificate(self.ca_certificate()?);
+
+        if let Some(domain) = &self.domain {
+            config = config.domain_name(domain);
+        }
+
+        Ok(config)
+    }
+
+    fn identity(&self) -> std::io::Result<Identity> {
+        let cert = std::fs::read(&self.cert)?;
+        let key = std::fs::read(&self.key)?;
+
+        Ok(Identity::from_pem(cert, key))
+    }
+
+    fn ca_certificate(&self) -> std::io::Result<Certificate> {
+        let ca = std::fs::read(&self.ca)?;
+
+        Ok(Certificate::from_pem(ca))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{SocketAddr, TcpListener};
+    use std::path::Path;
+    use std::time::Duration;
+
+    use chord_rs_core::server::{JoinConfig, ServerConfig};
+    use chord_rs_core::Client;
+    use rcgen::{BasicConstraints, Certificate as RcgenCertificate, CertificateParams, IsCa};
+
+    use super::*;
+    use crate::client::{ChordGrpcClient, ClientConfig};
+    use crate::server::chord_proto::chord_node_client::ChordNodeClient;
+    use crate::server::chord_proto::PingRequest;
+    use crate::server::{ChordNodeServer, ChordService, Server};
+
+    const DOMAIN: &str = "chord.test";
+
+    fn write_certificates(dir: &Path) -> TlsConfig {
+        let mut ca_params = CertificateParams::new(vec![]);
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca = RcgenCertificate::from_params(ca_params).unwrap();
+
+        let node =
+            RcgenCertificate::from_params(CertificateParams::new(vec![DOMAIN.into()])).unwrap();
+
+        std::fs::create_dir_all(dir).unwrap();
+        let config = TlsConfig {
+            cert: dir.join("node.pem"),
+            key: dir.join("node.key"),
+            ca: dir.join("ca.pem"),
+            domain: Some(DOMAIN.to_string()),
+        };
+        std::fs::write(&config.ca, ca.serialize_pem().unwrap()).unwrap();
+        std::fs::write(&config.cert, node.serialize_pem_with_signer(&ca).unwrap()).unwrap();
+        std::fs::write(&config.key, node.serialize_private_key_pem()).unwrap();
+
+        config
+    }
+
+    fn free_addr() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap()
+    }
+
+    #[tokio::test]
+    async fn tls_client_should_connect_to_tls_server() {
+        let dir = std::env::temp_dir().join(format!("chord-tls-{}", std::process::id()));
+        let config = write_certificates(&dir);
+        let addr = free_addr();
+
+        let node_config = ServerConfig {
+            join: JoinConfig {
+                bootstrap: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let service = ChordService::new(addr, node_config).await;
+        let server = Server::builder()
+            .tls_config(config.server_config().unwrap())
+            .unwrap()
+            .add_service(ChordNodeServer::new(service))
+            .serve(addr);
+        tokio::spawn(server);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let clients = ClientConfig::default().with_tls(config.client_config().unwrap());
+        let client = ChordGrpcClient::connect(addr, &clients).await;
+        assert!(client.ping().await.is_ok());
+        // The TLS settings are not shared with the other clients
+        assert!(ChordGrpcClient::init(addr).await.ping().await.is_err());
+
+        let plaintext = ChordNodeClient::connect(format!("http://{}", addr)).await;
+        if let Ok(mut plaintext) = plaintext {
+            assert!(plaintext.ping(PingRequest {}).await.is_err());
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
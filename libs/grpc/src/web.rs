@@ -0,0 +1,158 @@
+use std::time::Duration;
+
+use tonic::codegen::http::header::{HeaderName, HeaderValue, InvalidHeaderValue};
+use tonic::codegen::http::Method;
+use tower::layer::util::Stack;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::server::{DEADLINE_METADATA_KEY, SECRET_METADATA_KEY};
+
+/// Layer serving gRPC-Web requests, see [`grpc_web_layer`]
+pub type GrpcWebLayer = Stack<tonic_web::GrpcWebLayer, CorsLayer>;
+
+/// How long the browsers cache the response to a preflight request
+const MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Headers the browsers are allowed to send, the chord metadata included
+const ALLOWED_HEADERS: [&str; 6] = [
+    "x-grpc-web",
+    "x-user-agent",
+    "content-type",
+    "grpc-timeout",
+    SECRET_METADATA_KEY,
+    DEADLINE_METADATA_KEY,
+];
+
+/// Headers the browsers let the clients read, carrying the status of the call
+const EXPOSED_HEADERS: [&str; 3] = ["grpc-status", "grpc-message", "grpc-status-details-bin"];
+
+/// Create the layer translating gRPC-Web requests, sent by browsers over HTTP/1.1, to gRPC
+///
+/// The gRPC requests go through unchanged. The browsers send the requests to another origin only
+/// if the CORS headers allow it, so the layer answers the preflight requests as well.
+///
+/// > **Note**
+/// >
+/// > The server must accept HTTP/1.1 connections, see
+/// > [`Server::accept_http1`](tonic::transport::Server::accept_http1).
+///
+/// # Arguments
+///
+/// * `allowed_origins` - The origins allowed to call the server, e.g. `https://admin.example.com`.
+///   If empty, every origin is allowed
+pub fn grpc_web_layer(allowed_origins: &[String]) -> Result<GrpcWebLayer, InvalidHeaderValue> {
+    let allow_origin = if allowed_origins.is_empty() {
+        AllowOrigin::mirror_request()
+    } else {
+        let origins = allowed_origins
+            .iter()
+            .map(|origin| HeaderValue::from_str(origin))
+            .collect::<Result<Vec<_>, _>>()?;
+        AllowOrigin::list(origins)
+    };
+    let cors = CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods([Method::POST])
+        .allow_headers(ALLOWED_HEADERS.map(HeaderName::from_static))
+        .expose_headers(EXPOSED_HEADERS.map(HeaderName::from_static))
+        .max_age(MAX_AGE);
+
+    Ok(Stack::new(tonic_web::GrpcWebLayer::new(), cors))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{SocketAddr, TcpListener};
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    use super::*;
+    use crate::server::Server;
+
+    /// Serve the health service with the gRPC-Web layer
+    async fn serve(allowed_origins: &[String]) -> SocketAddr {
+        let (_, health) = tonic_health::server::health_reporter();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        tokio::spawn(
+            Server::builder()
+                .accept_http1(true)
+                .layer(grpc_web_layer(allowed_origins).unwrap())
+                .add_service(health)
+                .serve(addr),
+        );
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        addr
+    }
+
+    /// Send a raw HTTP/1.1 request and read the whole response
+    async fn send(addr: SocketAddr, request: &[u8]) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(request).await.unwrap();
+        let mut response = vec![];
+        stream.read_to_end(&mut response).await.unwrap();
+
+        String::from_utf8_lossy(&response).to_lowercase()
+    }
+
+    fn preflight(origin: &str) -> Vec<u8> {
+        format!(
+            "OPTIONS /grpc.health.v1.Health/Check HTTP/1.1\r\n\
+             Host: localhost\r\n\
+             Origin: {}\r\n\
+             Access-Control-Request-Method: POST\r\n\
+             Access-Control-Request-Headers: content-type,x-grpc-web,x-chord-secret\r\n\
+             Connection: close\r\n\r\n",
+            origin
+        )
+        .into_bytes()
+    }
+
+    #[tokio::test]
+    async fn preflight_allows_the_chord_metadata() {
+        let addr = serve(&[]).await;
+
+        let response = send(addr, &preflight("https://admin.example.com")).await;
+
+        assert!(response.starts_with("http/1.1 200"));
+        assert!(response.contains("access-control-allow-origin: https://admin.example.com"));
+        assert!(response.contains(SECRET_METADATA_KEY));
+        assert!(response.contains(DEADLINE_METADATA_KEY));
+    }
+
+    #[tokio::test]
+    async fn preflight_from_another_origin_is_not_allowed() {
+        let addr = serve(&["https://admin.example.com".to_string()]).await;
+
+        let response = send(addr, &preflight("https://other.example.com")).await;
+
+        assert!(!response.contains("access-control-allow-origin"));
+    }
+
+    #[tokio::test]
+    async fn grpc_web_request_is_served_over_http1() {
+        let addr = serve(&[]).await;
+        // An empty `HealthCheckRequest`, in a frame without compression
+        let body = [0u8; 5];
+        let mut request = format!(
+            "POST /grpc.health.v1.Health/Check HTTP/1.1\r\n\
+             Host: localhost\r\n\
+             Content-Type: application/grpc-web+proto\r\n\
+             X-Grpc-Web: 1\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\r\n",
+            body.len()
+        )
+        .into_bytes();
+        request.extend(body);
+
+        let response = send(addr, &request).await;
+
+        assert!(response.starts_with("http/1.1 200"));
+        assert!(response.contains("content-type: application/grpc-web+proto"));
+        assert!(response.contains("grpc-status:0"));
+    }
+}
@@ -0,0 +1,57 @@
+//! Maintenance tasks run on the request of an administrator
+
+use std::net::{SocketAddr, TcpListener};
+use std::time::Duration;
+
+use chord_grpc::client::ChordGrpcClient;
+use chord_grpc::server::{ChordNodeServer, ChordService, Server};
+use chord_rs_core::client::ClientError;
+use chord_rs_core::server::{JoinConfig, ServerConfig};
+use chord_rs_core::{Client, MaintenanceTask};
+
+fn free_addr() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap()
+}
+
+async fn start_node(addr: SocketAddr) -> ChordService {
+    let config = ServerConfig {
+        join: JoinConfig {
+            bootstrap: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let service = ChordService::new(addr, config).await;
+    tokio::spawn(
+        Server::builder()
+            .add_service(ChordNodeServer::new(service.clone()))
+            .serve(addr),
+    );
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    service
+}
+
+#[tokio::test]
+async fn tasks_are_triggered_only_with_the_admin_feature() {
+    let addr = free_addr();
+    start_node(addr).await;
+    let client = ChordGrpcClient::init(addr).await;
+
+    for task in [
+        MaintenanceTask::Stabilize,
+        MaintenanceTask::FixFingers,
+        MaintenanceTask::ReconcileSuccessors,
+    ] {
+        let result = client.trigger(task).await;
+        if cfg!(feature = "admin") {
+            assert!(result.is_ok(), "{} failed: {:?}", task, result);
+        } else {
+            assert_eq!(
+                result.unwrap_err().current_context(),
+                &ClientError::TriggerFailed
+            );
+        }
+    }
+}
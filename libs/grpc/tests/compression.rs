@@ -0,0 +1,86 @@
+//! A ring mixing the nodes compressing their messages and the ones which don't
+
+use std::net::{SocketAddr, TcpListener};
+use std::time::Duration;
+
+use chord_grpc::client::ClientConfig;
+use chord_grpc::server::chord_proto::chord_node_client::ChordNodeClient;
+use chord_grpc::server::chord_proto::GetNodeInfoRequest;
+use chord_grpc::server::{chord_node_server, ChordService, CompressionEncoding, Server};
+use chord_rs_core::server::{JoinConfig, ServerConfig};
+
+fn free_addr() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap()
+}
+
+async fn start_node(
+    addr: SocketAddr,
+    ring: Vec<SocketAddr>,
+    compression: Option<CompressionEncoding>,
+) -> ChordService {
+    let mut clients = ClientConfig::default();
+    if let Some(encoding) = compression {
+        clients = clients.with_compression(encoding);
+    }
+    let service = ChordService::with_clients(
+        addr,
+        ServerConfig {
+            join: JoinConfig {
+                bootstrap: ring.is_empty(),
+                ..Default::default()
+            },
+            ring,
+            ..Default::default()
+        },
+        &clients,
+    )
+    .await;
+    tokio::spawn(
+        Server::builder()
+            .add_service(chord_node_server(service.clone(), compression))
+            .serve(addr),
+    );
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    service
+}
+
+#[tokio::test]
+async fn compressing_and_plain_nodes_share_a_ring() {
+    let plain_addr = free_addr();
+    let plain = start_node(plain_addr, vec![], None).await;
+
+    let compressing_addr = free_addr();
+    let compressing = start_node(
+        compressing_addr,
+        vec![plain_addr],
+        Some(CompressionEncoding::Gzip),
+    )
+    .await;
+    assert_eq!(
+        compressing.node().get_successor().await.unwrap().addr(),
+        plain_addr
+    );
+
+    compressing.node().stabilize().await.unwrap();
+    plain.node().stabilize().await.unwrap();
+    assert_eq!(
+        plain.node().get_successor().await.unwrap().addr(),
+        compressing_addr
+    );
+
+    // A client which doesn't accept compressed responses gets them uncompressed
+    let mut client = ChordNodeClient::connect(format!("http://{}", compressing_addr))
+        .await
+        .unwrap();
+    let response = client
+        .get_node_info(GetNodeInfoRequest {})
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(
+        response.successors.first().map(|node| node.port),
+        Some(plain_addr.port() as i32)
+    );
+}
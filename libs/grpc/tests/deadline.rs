@@ -0,0 +1,52 @@
+//! Deadlines sent along with the lookups
+
+use std::net::{SocketAddr, TcpListener};
+use std::time::{Duration, SystemTime};
+
+use chord_grpc::client::ChordGrpcClient;
+use chord_grpc::server::{ChordNodeServer, ChordService, Server};
+use chord_rs_core::client::ClientError;
+use chord_rs_core::server::{JoinConfig, ServerConfig};
+use chord_rs_core::{deadline, Client, NodeId};
+
+fn free_addr() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap()
+}
+
+async fn start_node(addr: SocketAddr) -> ChordService {
+    let config = ServerConfig {
+        join: JoinConfig {
+            bootstrap: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let service = ChordService::new(addr, config).await;
+    tokio::spawn(
+        Server::builder()
+            .add_service(ChordNodeServer::new(service.clone()))
+            .serve(addr),
+    );
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    service
+}
+
+#[tokio::test]
+async fn lookup_with_an_expired_deadline_is_rejected() {
+    let addr = free_addr();
+    start_node(addr).await;
+    let client = ChordGrpcClient::init(addr).await;
+
+    let expired = SystemTime::now() - Duration::from_secs(1);
+    let result = deadline::scope(Some(expired), client.find_successor(NodeId::from(1))).await;
+    assert_eq!(
+        result.unwrap_err().current_context(),
+        &ClientError::DeadlineExceeded
+    );
+
+    let future = SystemTime::now() + Duration::from_secs(60);
+    let result = deadline::scope(Some(future), client.find_successor(NodeId::from(1))).await;
+    assert_eq!(result.unwrap().addr(), addr);
+}
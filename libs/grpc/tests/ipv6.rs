@@ -0,0 +1,65 @@
+//! A ring of nodes listening on IPv6 addresses only
+
+use std::net::{SocketAddr, TcpListener};
+use std::time::Duration;
+
+use chord_grpc::server::{ChordNodeServer, ChordService, Server};
+use chord_rs_core::server::{JoinConfig, ServerConfig};
+
+fn free_addr() -> SocketAddr {
+    let listener = TcpListener::bind("[::1]:0").unwrap();
+    listener.local_addr().unwrap()
+}
+
+async fn start_node(addr: SocketAddr, ring: Vec<SocketAddr>) -> ChordService {
+    let service = ChordService::new(
+        addr,
+        ServerConfig {
+            join: JoinConfig {
+                bootstrap: ring.is_empty(),
+                ..Default::default()
+            },
+            ring,
+            ..Default::default()
+        },
+    )
+    .await;
+    tokio::spawn(
+        Server::builder()
+            .add_service(ChordNodeServer::new(service.clone()))
+            .serve(addr),
+    );
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    service
+}
+
+#[tokio::test]
+async fn node_joins_a_ring_over_ipv6() {
+    let first_addr = free_addr();
+    let first = start_node(first_addr, vec![]).await;
+
+    let second_addr = free_addr();
+    let second = start_node(second_addr, vec![first_addr]).await;
+    assert_eq!(
+        second.node().get_successor().await.unwrap().addr(),
+        first_addr
+    );
+
+    second.node().stabilize().await.unwrap();
+    first.node().stabilize().await.unwrap();
+    assert_eq!(
+        first.node().get_successor().await.unwrap().addr(),
+        second_addr
+    );
+    assert_eq!(
+        first
+            .node()
+            .get_predecessor()
+            .await
+            .unwrap()
+            .unwrap()
+            .addr(),
+        second_addr
+    );
+}
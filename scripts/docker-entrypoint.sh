@@ -6,7 +6,7 @@ ARGS=()
 # If LEADER_HOST is not set, then we are the leader
 if [ -z "$LEADER_HOST" ]; then
     echo "Starting leader"
-    # RING=""
+    ARGS+=("--bootstrap")
 else
     echo "Starting follower"
     LEADER_IP=$(getent hosts $LEADER_HOST | awk '{ print $1 }')
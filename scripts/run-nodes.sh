@@ -43,7 +43,7 @@ echo "Start port: $START_PORT"
 
 if [ -z "$LEADER" ]; then
     LEADER="$LISTEN_IP:$START_PORT"
-    ARGS+=("--listen" "$LEADER")
+    ARGS+=("--listen" "$LEADER" "--bootstrap")
     START_PORT=$((START_PORT + 1))
     NUM_NODES=$((NUM_NODES - 1))
 
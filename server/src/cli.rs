@@ -1,30 +1,351 @@
 use std::net::SocketAddr;
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
 
-use chord_rs::Config;
-use clap::{arg, command, Parser, ValueEnum};
+use chord_rs::{
+    BackgroundConfig, Config, JoinConfig, LookupCacheConfig, NodeId, RetryPolicy, TlsConfig,
+};
+use clap::{arg, command, Args, Parser, Subcommand, ValueEnum};
+use serde::Deserialize;
+use tracing::level_filters::LevelFilter;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
+#[command(
+    after_help = "Durations are given as a number followed by a unit, one of `ms`, `s`, `m` or `h`, e.g. `500ms` or `2s`."
+)]
 pub(crate) struct Cli {
-    /// Sets a socket address to listen on
+    /// Run a tool instead of the node
+    #[command(subcommand)]
+    pub(crate) command: Option<Command>,
+
+    /// Read the options from a TOML file, whose keys are the long options, e.g.
+    /// `listen = "127.0.0.1:42000"`. The options given on the command line take precedence
+    /// over the file
+    #[arg(short, long, value_name = "PATH")]
+    pub(crate) config: Option<PathBuf>,
+
+    /// Sets a socket address to listen on,
+    /// IPv6 addresses are given in brackets, e.g. `[::1]:42000`
     #[arg(short, long, value_name = "[ADDRESS[:PORT]]", default_value_t = SocketAddr::from(([127, 0, 0, 1], 42000)))]
     pub(crate) listen: SocketAddr,
 
-    /// Address of a node in the ring to join
-    #[arg(short, long, value_name = "[ADDRESS[:PORT]]")]
-    pub(crate) ring: Option<SocketAddr>,
+    /// RPC transport to communicate with the ring over, must be enabled by its feature
+    /// (default: capnp if it's enabled, grpc otherwise)
+    #[arg(long, value_name = "TRANSPORT", value_enum, default_value_t = Transport::default())]
+    pub(crate) transport: Transport,
+
+    /// Pin the id of the node, so it keeps its position on the ring when restarted on another address.
+    /// Given as a decimal number or as a hex number prefixed with `0x`,
+    /// if not set the id is derived from the listen address
+    #[arg(long, value_name = "ID", value_parser = NodeId::from_str)]
+    pub(crate) node_id: Option<NodeId>,
+
+    /// Hash function deriving the id of the node from the listen address. The ids used to be
+    /// derived with seahash, the nodes joining a ring created by an earlier release use
+    /// `seahash` until all of its nodes are upgraded
+    #[arg(long, value_name = "HASH", value_enum, default_value_t = Hasher::Sha256)]
+    pub(crate) hasher: Hasher,
+
+    /// Addresses of nodes in the ring to join, tried in order until one responds.
+    /// Can be repeated or given as a comma-separated list
+    #[arg(short, long, value_name = "[ADDRESS[:PORT]]", value_delimiter = ',')]
+    pub(crate) ring: Vec<SocketAddr>,
+
+    /// Advertise the node on the local network over mDNS, and join the nodes found there before
+    /// the ones given with `--ring` (requires the `discovery` feature). The loopback interface
+    /// is not used, so the nodes listening on `127.0.0.1` can't find each other
+    #[arg(long)]
+    pub(crate) discover: bool,
+
+    /// How long to look for the nodes on the local network before falling back to `--ring`,
+    /// or creating a new ring with `--bootstrap`
+    #[arg(long, value_name = "DURATION", default_value = "3s", value_parser = humantime::parse_duration)]
+    pub(crate) discover_timeout: Duration,
+
+    /// Join the nodes the DNS SRV records of the name point to, e.g. the pods of a headless
+    /// Kubernetes service, before the ones given with `--ring` (requires the `srv-discovery`
+    /// feature). The records are looked up again before every attempt to join the ring
+    #[arg(long, value_name = "NAME")]
+    pub(crate) discover_srv: Option<String>,
+
+    /// Create a new ring when there is no other node to join, i.e. no seed besides the node
+    /// itself was given or discovered. Without it, the node exits instead of forming a ring of
+    /// its own, e.g. when `--ring` was forgotten or the discovery found nobody
+    #[arg(long)]
+    pub(crate) bootstrap: bool,
+
+    /// Number of nodes the data is replicated on
+    #[arg(long, value_name = "NODES", default_value = "3")]
+    pub(crate) replication_factor: NonZeroUsize,
+
+    /// Number of successors every node keeps track of, the more of them the more successors can
+    /// fail at once without breaking the ring (default: the replication factor)
+    #[arg(long, value_name = "NODES")]
+    pub(crate) successor_list_size: Option<NonZeroUsize>,
+
+    /// Number of virtual nodes hosted by the process, the more of them the larger the share of
+    /// the keyspace it owns. The virtual node `i` listens on the port of `--listen` plus `i`
+    #[arg(long, value_name = "COUNT", default_value = "1")]
+    pub(crate) vnodes: NonZeroUsize,
+
+    /// How many times to try the whole list of ring nodes before giving up on joining
+    #[arg(long, value_name = "RETRIES", default_value = "5")]
+    pub(crate) join_retries: u32,
+
+    /// Delay before the first retry to join the ring, doubled on every following retry
+    /// (up to 30s) and randomized so nodes started together don't retry in lockstep
+    #[arg(long, value_name = "DURATION", default_value = "1s", value_parser = humantime::parse_duration)]
+    pub(crate) join_backoff: Duration,
 
     /// Set the log level
     #[arg(short('L'), long, value_name = "LEVEL", value_enum, default_value_t = LogLevel::Info)]
     pub(crate) log_level: LogLevel,
 
-    /// Set the maximum number of concurrent connections
+    /// Set the format of the logs, `json` writes an object per line for the log aggregators
+    #[arg(long, value_name = "FORMAT", value_enum, default_value_t = LogFormat::Text)]
+    pub(crate) log_format: LogFormat,
+
+    /// Also write the logs to the file, at the level and in the format of the terminal ones.
+    /// The file is rotated once it reaches `--log-max-size`, the previous ones being renamed
+    /// `<PATH>.1`, `<PATH>.2`, etc.
+    #[arg(long, value_name = "PATH")]
+    pub(crate) log_file: Option<PathBuf>,
+
+    /// Write the logs to `--log-file` only, not to the terminal
+    #[arg(long, requires = "log_file")]
+    pub(crate) log_file_only: bool,
+
+    /// Size the log file is rotated at, in bytes or followed by `K`, `M` or `G`
+    /// (powers of 1024), e.g. `512K`
+    #[arg(long, value_name = "SIZE", default_value = "10M", value_parser = byte_size)]
+    pub(crate) log_max_size: u64,
+
+    /// Number of rotated log files kept next to the current one, the oldest is deleted past it
+    #[arg(long, value_name = "FILES", default_value = "5")]
+    pub(crate) log_max_files: usize,
+
+    /// Set the maximum number of concurrent connections, must be greater than zero.
+    /// Connections over the limit wait until an open one is closed. The gRPC transport
+    /// multiplexes the requests over the connections, so it limits the concurrent requests instead
     /// (default: 1024)
     #[arg(long, value_name = "CONNECTIONS", default_value = "1024")]
-    pub(crate) max_connections: usize,
+    pub(crate) max_connections: NonZeroUsize,
+
+    /// Maximum number of requests per second accepted from every peer address, must be greater
+    /// than zero. Requests over the limit are rejected, if not set the requests are not limited
+    #[arg(long, value_name = "REQUESTS")]
+    pub(crate) rate_limit: Option<NonZeroU32>,
+
+    /// Maximum number of requests the maintenance tasks of every virtual node send to other
+    /// nodes at the same time, must be greater than zero. It bounds the connections the node
+    /// opens however large the ring is, if not set the requests are not limited
+    #[arg(long, value_name = "REQUESTS")]
+    pub(crate) max_outbound_requests: Option<NonZeroUsize>,
+
+    /// Ping the nodes which notify this node before adopting them as its predecessor, so a buggy
+    /// or unreachable peer can't become the predecessor
+    #[arg(long)]
+    pub(crate) verify_notify: bool,
+
+    /// Number of successors found by the lookups to cache, so the repeated lookups don't go
+    /// around the ring again. A cached successor is checked with a single request before it's
+    /// used, if not set the lookups are not cached
+    #[arg(long, value_name = "ENTRIES")]
+    pub(crate) lookup_cache_size: Option<NonZeroUsize>,
+
+    /// How long a successor found by a lookup is cached for
+    #[arg(long, value_name = "DURATION", default_value = "30s", value_parser = humantime::parse_duration)]
+    pub(crate) lookup_cache_ttl: Duration,
+
+    /// How many times a lookup is retried when a node on its route is unreachable or doesn't
+    /// respond in time. The route is resolved again on every retry, so a lookup can go around a
+    /// node which left the ring in the meantime
+    #[arg(long, value_name = "RETRIES", default_value = "0")]
+    pub(crate) lookup_retries: u32,
+
+    /// Delay before the first retry of a lookup, doubled on every following retry (up to 1s)
+    #[arg(long, value_name = "DURATION", default_value = "50ms", value_parser = humantime::parse_duration)]
+    pub(crate) lookup_retry_backoff: Duration,
+
+    /// Connect to the fingers in the background once they're fixed, so the first lookups routed
+    /// through them don't wait for the connection. A finger which doesn't respond is connected
+    /// to by its first lookup instead
+    #[arg(long)]
+    pub(crate) warm_up_fingers: bool,
+
+    /// Path to the PEM encoded certificate of the node,
+    /// enables TLS (supported only by the gRPC transport)
+    #[arg(long, value_name = "PATH", requires_all = ["tls_key", "tls_ca"])]
+    pub(crate) tls_cert: Option<PathBuf>,
+
+    /// Path to the PEM encoded private key of the node
+    #[arg(long, value_name = "PATH", requires = "tls_cert")]
+    pub(crate) tls_key: Option<PathBuf>,
+
+    /// Path to the PEM encoded certificate of the authority which signs the nodes' certificates
+    #[arg(long, value_name = "PATH", requires = "tls_cert")]
+    pub(crate) tls_ca: Option<PathBuf>,
+
+    /// Domain name used to verify the peers' certificates,
+    /// if not set the peer's address is used
+    #[arg(long, value_name = "DOMAIN", requires = "tls_cert")]
+    pub(crate) tls_domain: Option<String>,
+
+    /// Shared cluster secret, requests from nodes without the same secret are rejected
+    #[arg(
+        long,
+        value_name = "SECRET",
+        env = "CHORD_SECRET",
+        hide_env_values = true
+    )]
+    pub(crate) secret: Option<String>,
+
+    /// Compress the messages sent to the other nodes (supported only by the gRPC transport).
+    /// The nodes accept compressed messages whatever this option, so it can be enabled one node
+    /// at a time
+    #[arg(long, value_name = "CODEC", value_enum, default_value_t = Compression::None)]
+    pub(crate) compression: Compression,
+
+    /// Sets a socket address to serve the Prometheus metrics on (at `/metrics`),
+    /// if given without an address, the metrics are served on the port of `--listen` plus 1000.
+    /// If not set the metrics are not exposed
+    #[arg(long, visible_alias = "listen-metrics", value_name = "ADDRESS:PORT", num_args = 0..=1)]
+    pub(crate) metrics_listen: Option<Option<SocketAddr>>,
+
+    /// Sets a socket address to serve the HTTP/JSON gateway on (`GET /status`, `GET /lookup/<KEY>`),
+    /// if given without an address, the gateway is served on the port of `--listen` plus 2000.
+    /// If not set the gateway is not exposed (requires the `http-gateway` feature)
+    #[arg(long, visible_alias = "listen-gateway", value_name = "ADDRESS:PORT", num_args = 0..=1)]
+    pub(crate) http_listen: Option<Option<SocketAddr>>,
+
+    /// Origins allowed to call the gRPC transport from a browser over gRPC-Web, e.g.
+    /// `https://admin.example.com`. Can be repeated or given as a comma-separated list,
+    /// if not set every origin is allowed (requires the `grpc-web` feature)
+    #[arg(long, value_name = "ORIGIN", value_delimiter = ',')]
+    pub(crate) grpc_web_origins: Vec<String>,
+
+    /// Directory to keep the node's neighbours in, so they are restored after a restart
+    /// (requires the `persistence` feature)
+    #[arg(long, value_name = "PATH")]
+    pub(crate) data_dir: Option<PathBuf>,
+
+    /// Probability of dropping every request of the ring, between 0 and 1, to test the recovery
+    /// of the ring. Never use it in production
+    #[cfg(feature = "chaos")]
+    #[arg(long, value_name = "PROBABILITY", default_value = "0", value_parser = probability)]
+    pub(crate) chaos_drop: f64,
+
+    /// Latency added to every request of the ring, to test the recovery of the ring
+    #[cfg(feature = "chaos")]
+    #[arg(long, value_name = "DURATION", default_value = "0s", value_parser = humantime::parse_duration)]
+    pub(crate) chaos_latency: Duration,
+
+    /// Maximum random latency added to every request of the ring on top of `--chaos-latency`
+    #[cfg(feature = "chaos")]
+    #[arg(long, value_name = "DURATION", default_value = "0s", value_parser = humantime::parse_duration)]
+    pub(crate) chaos_jitter: Duration,
+
+    /// How often to check the successor and stabilize the node
+    #[arg(long, value_name = "DURATION", default_value = "1s", value_parser = humantime::parse_duration)]
+    pub(crate) stabilize_interval: Duration,
+
+    /// How often to refresh the finger table
+    #[arg(long, value_name = "DURATION", default_value = "1s", value_parser = humantime::parse_duration)]
+    pub(crate) fix_fingers_interval: Duration,
+
+    /// How many fingers to refresh every time: `all` of them, or `one`, the next one in turn.
+    /// Refreshing one finger puts a much lower load on large rings, but the finger table takes
+    /// longer to converge
+    #[arg(long, value_name = "FINGERS", value_enum, default_value_t = FixFingers::All)]
+    pub(crate) fix_fingers: FixFingers,
+
+    /// How often to check if the predecessor is alive
+    #[arg(long, value_name = "DURATION", default_value = "1s", value_parser = humantime::parse_duration)]
+    pub(crate) check_predecessor_interval: Duration,
+
+    /// How often to refresh the successor list
+    #[arg(long, value_name = "DURATION", default_value = "1s", value_parser = humantime::parse_duration)]
+    pub(crate) reconcile_successors_interval: Duration,
+
+    /// Periodically check that the successor chain loops back to the node, a partition warning
+    /// is logged if it doesn't. Every check walks the whole ring, one request per node
+    #[arg(long)]
+    pub(crate) check_ring: bool,
+
+    /// How often to check the ring
+    #[arg(long, value_name = "DURATION", default_value = "30s", value_parser = humantime::parse_duration)]
+    pub(crate) check_ring_interval: Duration,
+
+    /// How many successors to follow when checking the ring, the check is inconclusive on
+    /// rings with more nodes
+    #[arg(long, value_name = "HOPS", default_value_t = 64)]
+    pub(crate) check_ring_max_hops: usize,
+
+    /// Periodically check the invariants of the routing state of the node, e.g. that the
+    /// successor list is in ring order. Every broken invariant is logged as a warning and
+    /// counted in the metrics
+    #[arg(long)]
+    pub(crate) audit: bool,
+
+    /// How often to audit the routing state of the node
+    #[arg(long, value_name = "DURATION", default_value = "10s", value_parser = humantime::parse_duration)]
+    pub(crate) audit_interval: Duration,
+
+    /// Periodically ping the whole successor list and the predecessor at once, dropping the
+    /// nodes which don't respond. Dead successors further down the list are found before they
+    /// become the successor
+    #[arg(long)]
+    pub(crate) sweep: bool,
+
+    /// How often to sweep the neighbours
+    #[arg(long, value_name = "DURATION", default_value = "5s", value_parser = humantime::parse_duration)]
+    pub(crate) sweep_interval: Duration,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+#[derive(Subcommand)]
+pub(crate) enum Command {
+    /// Walk the successors around the ring, starting at the given node, and print every node
+    /// with the consistency of its link to the successor. The nodes configured with another
+    /// replication factor or successor list size than the rest of the ring are flagged. Uses
+    /// the `--transport`, `--tls-*` and `--secret` options to talk to the ring
+    RingDump(RingDumpArgs),
+    /// Print the node responsible for the given key, asking any node of the ring. The key is
+    /// not read, only its place on the ring is looked up. Uses the `--transport`, `--tls-*` and
+    /// `--secret` options to talk to the ring
+    Owner(OwnerArgs),
+}
+
+#[derive(Args)]
+pub(crate) struct RingDumpArgs {
+    /// Address of the node to start at
+    #[arg(value_name = "ADDRESS:PORT")]
+    pub(crate) addr: SocketAddr,
+
+    /// Maximum number of nodes to visit before giving up on getting back to the first one
+    #[arg(long, value_name = "HOPS", default_value_t = 1024)]
+    pub(crate) max_hops: usize,
+}
+
+#[derive(Args)]
+pub(crate) struct OwnerArgs {
+    /// Address of a node of the ring to ask
+    #[arg(value_name = "ADDRESS:PORT")]
+    pub(crate) addr: SocketAddr,
+
+    /// The key, hashed onto the ring like the keys of the stored data
+    pub(crate) key: String,
+
+    /// Take the key as an id on the ring, in decimal or in hex prefixed with `0x`, instead of
+    /// hashing it
+    #[arg(long)]
+    pub(crate) id: bool,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub(crate) enum LogLevel {
     Error,
     Warn,
@@ -33,12 +354,387 @@ pub(crate) enum LogLevel {
     Trace,
 }
 
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum LogFormat {
+    /// Human readable lines
+    Text,
+    /// A JSON object per line, with the level, the target, the message and the fields
+    Json,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Transport {
+    Capnp,
+    Grpc,
+}
+
+impl Default for Transport {
+    fn default() -> Self {
+        chord_rs::Transport::default().into()
+    }
+}
+
+impl From<chord_rs::Transport> for Transport {
+    fn from(transport: chord_rs::Transport) -> Self {
+        match transport {
+            chord_rs::Transport::Capnp => Transport::Capnp,
+            chord_rs::Transport::Grpc => Transport::Grpc,
+        }
+    }
+}
+
+impl From<Transport> for chord_rs::Transport {
+    fn from(transport: Transport) -> Self {
+        match transport {
+            Transport::Capnp => chord_rs::Transport::Capnp,
+            Transport::Grpc => chord_rs::Transport::Grpc,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Hasher {
+    Sha256,
+    Seahash,
+}
+
+impl From<Hasher> for chord_rs::HashFunction {
+    fn from(hasher: Hasher) -> Self {
+        match hasher {
+            Hasher::Sha256 => chord_rs::HashFunction::Sha256,
+            Hasher::Seahash => chord_rs::HashFunction::SeaHash,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum FixFingers {
+    All,
+    One,
+}
+
+impl From<FixFingers> for chord_rs::FixFingers {
+    fn from(fix_fingers: FixFingers) -> Self {
+        match fix_fingers {
+            FixFingers::All => chord_rs::FixFingers::All,
+            FixFingers::One => chord_rs::FixFingers::One,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Compression {
+    None,
+    Gzip,
+}
+
+impl From<Compression> for Option<chord_rs::Compression> {
+    fn from(compression: Compression) -> Self {
+        match compression {
+            Compression::None => None,
+            Compression::Gzip => Some(chord_rs::Compression::Gzip),
+        }
+    }
+}
+
+impl Cli {
+    /// Offset of the port of the metrics from the port of `--listen`, when `--metrics-listen` is
+    /// given without an address
+    pub(crate) const METRICS_PORT_OFFSET: u16 = 1000;
+
+    /// Offset of the port of the HTTP gateway from the port of `--listen`, when `--http-listen`
+    /// is given without an address
+    pub(crate) const GATEWAY_PORT_OFFSET: u16 = 2000;
+
+    /// Address to serve the metrics on, if they're exposed
+    pub(crate) fn metrics_addr(&self) -> Option<SocketAddr> {
+        self.endpoint(self.metrics_listen, Self::METRICS_PORT_OFFSET)
+    }
+
+    /// Address to serve the HTTP gateway on, if it's exposed
+    pub(crate) fn gateway_addr(&self) -> Option<SocketAddr> {
+        self.endpoint(self.http_listen, Self::GATEWAY_PORT_OFFSET)
+    }
+
+    /// Get the address of an endpoint served next to the transport
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The option of the endpoint, `Some(None)` if it's given without an address
+    /// * `offset` - The offset of its port from the port of `--listen`, if it's given without an
+    ///   address
+    fn endpoint(&self, addr: Option<Option<SocketAddr>>, offset: u16) -> Option<SocketAddr> {
+        match addr? {
+            Some(addr) => Some(addr),
+            None => {
+                let port = self.listen.port().checked_add(offset)?;
+                Some(SocketAddr::new(self.listen.ip(), port))
+            }
+        }
+    }
+
+    /// Check that the node has a way to get into a ring
+    ///
+    /// Without `--bootstrap`, the node needs seeds to join, found by a discovery or given with
+    /// `--ring`, or neighbours to restore from `--data-dir`. The subcommands don't start a node.
+    pub(crate) fn check_seeds(&self) -> Result<(), String> {
+        let has_seeds = !self.ring.is_empty() || self.discover || self.discover_srv.is_some();
+        if self.bootstrap || has_seeds || self.data_dir.is_some() || self.command.is_some() {
+            return Ok(());
+        }
+
+        Err(
+            "No ring to join: give `--ring`, `--discover` or `--discover-srv`, \
+            or `--bootstrap` to create a new ring"
+                .to_string(),
+        )
+    }
+
+    /// Check that the options given are supported by the transport
+    ///
+    /// The messages are compressed only by the gRPC transport.
+    pub(crate) fn check_transport(&self) -> Result<(), String> {
+        if self.transport == Transport::Capnp && self.compression != Compression::None {
+            return Err("`--compression` is supported only by the gRPC transport".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Check that the transport, the metrics and the HTTP gateway listen on different ports
+    ///
+    /// The transport listens on a port per virtual node, starting with the port of `--listen`.
+    pub(crate) fn check_listen_addrs(&self) -> Result<(), String> {
+        let endpoints = [
+            ("metrics", self.metrics_listen, self.metrics_addr()),
+            ("HTTP gateway", self.http_listen, self.gateway_addr()),
+        ];
+        for (name, option, addr) in endpoints {
+            if option.is_some() && addr.is_none() {
+                return Err(format!(
+                    "The port of the {} derived from `--listen` {} is past 65535, give its address",
+                    name, self.listen
+                ));
+            }
+        }
+        let endpoints = endpoints.map(|(name, _, addr)| (name, addr));
+
+        let transport = (0..self.vnodes.get())
+            .filter_map(|index| u16::try_from(index).ok())
+            .filter_map(|index| self.listen.port().checked_add(index))
+            .map(|port| SocketAddr::new(self.listen.ip(), port));
+        for port in transport {
+            for (name, addr) in endpoints {
+                if addr.is_some_and(|addr| collides(addr, port)) {
+                    return Err(format!(
+                        "The {} and the transport both listen on {}",
+                        name, port
+                    ));
+                }
+            }
+        }
+        if let [(_, Some(metrics)), (_, Some(gateway))] = endpoints {
+            if collides(metrics, gateway) {
+                return Err(format!(
+                    "The metrics and the HTTP gateway both listen on {}",
+                    gateway
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// TLS configuration of the node, if the certificate is set
+    pub(crate) fn tls(&self) -> Option<TlsConfig> {
+        self.tls_cert.clone().map(|cert| TlsConfig {
+            cert,
+            key: self.tls_key.clone().unwrap_or_default(),
+            ca: self.tls_ca.clone().unwrap_or_default(),
+            domain: self.tls_domain.clone(),
+        })
+    }
+}
+
+/// Check if two addresses can't be listened on at the same time
+///
+/// An unspecified address, e.g. `0.0.0.0`, listens on all the interfaces, so it collides with
+/// every address on the same port.
+fn collides(first: SocketAddr, second: SocketAddr) -> bool {
+    first.port() == second.port()
+        && (first.ip() == second.ip()
+            || first.ip().is_unspecified()
+            || second.ip().is_unspecified())
+}
+
 impl Into<Config> for Cli {
     fn into(self) -> Config {
+        let tls = self.tls();
+        let metrics = self.metrics_addr();
+        let http_gateway = self.gateway_addr();
         Config {
             addr: self.listen,
+            transport: self.transport.into(),
+            node_id: self.node_id,
+            hasher: self.hasher.into(),
             ring: self.ring,
+            discover: self.discover,
+            discover_timeout: self.discover_timeout,
+            discover_srv: self.discover_srv,
+            replication_factor: self.replication_factor.get(),
+            successor_list_size: self.successor_list_size,
+            vnodes: self.vnodes,
             max_connections: self.max_connections,
+            rate_limit: self.rate_limit,
+            max_outbound_requests: self.max_outbound_requests,
+            verify_notify: self.verify_notify,
+            lookup_cache: self.lookup_cache_size.map(|capacity| LookupCacheConfig {
+                capacity,
+                ttl: self.lookup_cache_ttl,
+            }),
+            lookup_retry: RetryPolicy {
+                max_retries: self.lookup_retries,
+                base_delay: self.lookup_retry_backoff,
+                ..Default::default()
+            },
+            warm_up_fingers: self.warm_up_fingers,
+            tls,
+            secret: self.secret,
+            compression: self.compression.into(),
+            metrics,
+            http_gateway,
+            grpc_web_origins: self.grpc_web_origins,
+            background: BackgroundConfig {
+                stabilize_interval: self.stabilize_interval,
+                fix_fingers_interval: self.fix_fingers_interval,
+                fix_fingers: self.fix_fingers.into(),
+                check_predecessor_interval: self.check_predecessor_interval,
+                reconcile_successors_interval: self.reconcile_successors_interval,
+                check_ring_interval: self.check_ring.then_some(self.check_ring_interval),
+                check_ring_max_hops: self.check_ring_max_hops,
+                audit_interval: self.audit.then_some(self.audit_interval),
+                sweep_interval: self.sweep.then_some(self.sweep_interval),
+            },
+            join: JoinConfig {
+                max_retries: self.join_retries,
+                base_delay: self.join_backoff,
+                bootstrap: self.bootstrap,
+                ..Default::default()
+            },
+            data_dir: self.data_dir,
+            #[cfg(feature = "chaos")]
+            chaos: chord_rs::ChaosConfig {
+                drop_probability: self.chaos_drop,
+                latency: self.chaos_latency,
+                jitter: self.chaos_jitter,
+            },
+        }
+    }
+}
+
+/// Parse a probability, between 0 and 1
+/// Parse a size in bytes, optionally followed by a `K`, `M` or `G` unit (powers of 1024)
+pub(crate) fn byte_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => value.split_at(index),
+        None => (value, ""),
+    };
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        unit => return Err(format!("Unknown size unit: {}", unit)),
+    };
+    let number: u64 = number
+        .parse()
+        .map_err(|err| format!("Invalid size: {}", err))?;
+
+    match number.checked_mul(multiplier) {
+        Some(0) => Err("The size must be greater than zero".to_string()),
+        Some(size) => Ok(size),
+        None => Err(format!("{} is too large", value)),
+    }
+}
+
+#[cfg(feature = "chaos")]
+fn probability(value: &str) -> Result<f64, String> {
+    let probability: f64 = value
+        .parse()
+        .map_err(|err| format!("Invalid probability: {}", err))?;
+    if (0.0..=1.0).contains(&probability) {
+        Ok(probability)
+    } else {
+        Err(format!("{} is not between 0 and 1", probability))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_level_maps_to_the_same_filter() {
+        let levels = [
+            (LogLevel::Error, LevelFilter::ERROR),
+            (LogLevel::Warn, LevelFilter::WARN),
+            (LogLevel::Info, LevelFilter::INFO),
+            (LogLevel::Debug, LevelFilter::DEBUG),
+            (LogLevel::Trace, LevelFilter::TRACE),
+        ];
+
+        for (level, filter) in levels {
+            assert_eq!(LevelFilter::from(level), filter);
         }
     }
+
+    #[test]
+    fn error_level_filters_out_the_debug_events() {
+        let filter = LevelFilter::from(LogLevel::Error);
+
+        assert!(tracing::Level::ERROR <= filter);
+        assert!(tracing::Level::DEBUG > filter);
+    }
+
+    #[test]
+    fn byte_size_accepts_the_units() {
+        assert_eq!(byte_size("512"), Ok(512));
+        assert_eq!(byte_size("512B"), Ok(512));
+        assert_eq!(byte_size("64K"), Ok(64 * 1024));
+        assert_eq!(byte_size("10M"), Ok(10 * 1024 * 1024));
+        assert_eq!(byte_size("10 MiB"), Ok(10 * 1024 * 1024));
+        assert_eq!(byte_size("1g"), Ok(1024 * 1024 * 1024));
+        assert!(byte_size("0").is_err());
+        assert!(byte_size("10T").is_err());
+        assert!(byte_size("M").is_err());
+        assert!(byte_size("99999999999G").is_err());
+    }
+
+    #[cfg(feature = "chaos")]
+    #[test]
+    fn probability_is_between_0_and_1() {
+        assert_eq!(probability("0.1"), Ok(0.1));
+        assert_eq!(probability("1"), Ok(1.0));
+        assert!(probability("1.5").is_err());
+        assert!(probability("-0.1").is_err());
+        assert!(probability("often").is_err());
+    }
 }
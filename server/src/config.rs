@@ -0,0 +1,526 @@
+use std::ffi::OsString;
+use std::net::SocketAddr;
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use chord_rs::NodeId;
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
+use serde::{Deserialize, Deserializer};
+
+use crate::cli::{Cli, Compression, FixFingers, Hasher, LogFormat, LogLevel, Transport};
+
+/// Options read from the `--config` file
+///
+/// The keys are the long options of the command line, e.g. `replication-factor = 5`, and the
+/// values are given the same way as on the command line, except the lists which are TOML
+/// arrays.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct ConfigFile {
+    listen: Option<SocketAddr>,
+    transport: Option<Transport>,
+    #[serde(default, deserialize_with = "node_id")]
+    node_id: Option<NodeId>,
+    hasher: Option<Hasher>,
+    ring: Option<Vec<SocketAddr>>,
+    discover: Option<bool>,
+    #[serde(default, deserialize_with = "duration")]
+    discover_timeout: Option<Duration>,
+    discover_srv: Option<String>,
+    bootstrap: Option<bool>,
+    replication_factor: Option<NonZeroUsize>,
+    successor_list_size: Option<NonZeroUsize>,
+    vnodes: Option<NonZeroUsize>,
+    join_retries: Option<u32>,
+    #[serde(default, deserialize_with = "duration")]
+    join_backoff: Option<Duration>,
+    log_level: Option<LogLevel>,
+    log_format: Option<LogFormat>,
+    log_file: Option<PathBuf>,
+    log_file_only: Option<bool>,
+    #[serde(default, deserialize_with = "byte_size")]
+    log_max_size: Option<u64>,
+    log_max_files: Option<usize>,
+    max_connections: Option<NonZeroUsize>,
+    rate_limit: Option<NonZeroU32>,
+    max_outbound_requests: Option<NonZeroUsize>,
+    verify_notify: Option<bool>,
+    lookup_cache_size: Option<NonZeroUsize>,
+    #[serde(default, deserialize_with = "duration")]
+    lookup_cache_ttl: Option<Duration>,
+    lookup_retries: Option<u32>,
+    #[serde(default, deserialize_with = "duration")]
+    lookup_retry_backoff: Option<Duration>,
+    warm_up_fingers: Option<bool>,
+    tls_cert: Option<PathBuf>,
+    tls_key: Option<PathBuf>,
+    tls_ca: Option<PathBuf>,
+    tls_domain: Option<String>,
+    secret: Option<String>,
+    compression: Option<Compression>,
+    metrics_listen: Option<Option<SocketAddr>>,
+    http_listen: Option<Option<SocketAddr>>,
+    grpc_web_origins: Option<Vec<String>>,
+    data_dir: Option<PathBuf>,
+    #[serde(default, deserialize_with = "duration")]
+    stabilize_interval: Option<Duration>,
+    #[serde(default, deserialize_with = "duration")]
+    fix_fingers_interval: Option<Duration>,
+    fix_fingers: Option<FixFingers>,
+    #[serde(default, deserialize_with = "duration")]
+    check_predecessor_interval: Option<Duration>,
+    #[serde(default, deserialize_with = "duration")]
+    reconcile_successors_interval: Option<Duration>,
+    check_ring: Option<bool>,
+    #[serde(default, deserialize_with = "duration")]
+    check_ring_interval: Option<Duration>,
+    check_ring_max_hops: Option<usize>,
+    audit: Option<bool>,
+    #[serde(default, deserialize_with = "duration")]
+    audit_interval: Option<Duration>,
+    sweep: Option<bool>,
+    #[serde(default, deserialize_with = "duration")]
+    sweep_interval: Option<Duration>,
+}
+
+impl ConfigFile {
+    /// Read the config file
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the TOML file
+    pub(crate) fn read(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read the config file {:?}: {}", path, err))?;
+
+        toml::from_str(&content).map_err(|err| format!("Invalid config file {:?}: {}", path, err))
+    }
+
+    /// Set the options of the command line which were left to their defaults
+    ///
+    /// An option is taken from the file unless it's given on the command line, or through its
+    /// environment variable.
+    ///
+    /// # Arguments
+    ///
+    /// * `cli` - The options parsed from the command line
+    /// * `matches` - The matches `cli` was parsed from, telling where each value comes from
+    fn apply(self, cli: &mut Cli, matches: &ArgMatches) {
+        let unset = |id: &str| {
+            !matches!(
+                matches.value_source(id),
+                Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+            )
+        };
+
+        macro_rules! apply {
+            ($($field:ident),* $(,)?) => {$(
+                if let Some(value) = self.$field {
+                    if unset(stringify!($field)) {
+                        cli.$field = value.into();
+                    }
+                }
+            )*};
+        }
+
+        apply!(
+            listen,
+            transport,
+            node_id,
+            hasher,
+            ring,
+            discover,
+            discover_timeout,
+            discover_srv,
+            bootstrap,
+            replication_factor,
+            successor_list_size,
+            vnodes,
+            join_retries,
+            join_backoff,
+            log_level,
+            log_format,
+            log_file,
+            log_file_only,
+            log_max_size,
+            log_max_files,
+            max_connections,
+            rate_limit,
+            max_outbound_requests,
+            verify_notify,
+            lookup_cache_size,
+            lookup_cache_ttl,
+            lookup_retries,
+            lookup_retry_backoff,
+            warm_up_fingers,
+            tls_cert,
+            tls_key,
+            tls_ca,
+            tls_domain,
+            secret,
+            compression,
+            metrics_listen,
+            http_listen,
+            grpc_web_origins,
+            data_dir,
+            stabilize_interval,
+            fix_fingers_interval,
+            fix_fingers,
+            check_predecessor_interval,
+            reconcile_successors_interval,
+            check_ring,
+            check_ring_interval,
+            check_ring_max_hops,
+            audit,
+            audit_interval,
+            sweep,
+            sweep_interval,
+        );
+    }
+}
+
+impl Cli {
+    /// Parse the command line, taking the options it doesn't set from the `--config` file
+    ///
+    /// Exits with the usage if the command line or the config file is invalid.
+    pub(crate) fn load() -> Self {
+        Self::try_load_from(std::env::args_os()).unwrap_or_else(|err| err.exit())
+    }
+
+    /// Parse the given command line, taking the options it doesn't set from the `--config` file
+    ///
+    /// Every option is taken from the command line first, then from the config file, and then
+    /// from its default.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - The command line, starting with the name of the binary
+    pub(crate) fn try_load_from<I, T>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let matches = Self::command().try_get_matches_from(args)?;
+        let mut cli = Self::from_arg_matches(&matches)?;
+        if let Some(path) = cli.config.clone() {
+            let file = ConfigFile::read(&path)
+                .map_err(|message| Self::command().error(ErrorKind::InvalidValue, message))?;
+            file.apply(&mut cli, &matches);
+
+            // The file bypasses the checks of the command line
+            if cli.tls_cert.is_some() && (cli.tls_key.is_none() || cli.tls_ca.is_none()) {
+                return Err(Self::command().error(
+                    ErrorKind::MissingRequiredArgument,
+                    "`tls-cert` requires `tls-key` and `tls-ca`",
+                ));
+            }
+        }
+
+        cli.check_transport()
+            .map_err(|message| Self::command().error(ErrorKind::ArgumentConflict, message))?;
+        cli.check_listen_addrs()
+            .map_err(|message| Self::command().error(ErrorKind::ArgumentConflict, message))?;
+        cli.check_seeds().map_err(|message| {
+            Self::command().error(ErrorKind::MissingRequiredArgument, message)
+        })?;
+
+        Ok(cli)
+    }
+}
+
+fn node_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<NodeId>, D::Error> {
+    // TOML integers are signed, so the ids above `i64::MAX` can only be given as strings
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        Number(u64),
+        String(String),
+    }
+
+    match Id::deserialize(deserializer)? {
+        Id::Number(id) => Ok(Some(NodeId::from(id))),
+        Id::String(id) => NodeId::from_str(&id)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+    }
+}
+
+fn byte_size<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    // A plain number of bytes, or a string with a unit like on the command line
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Size {
+        Number(u64),
+        String(String),
+    }
+
+    match Size::deserialize(deserializer)? {
+        Size::Number(size) => Ok(Some(size)),
+        Size::String(size) => crate::cli::byte_size(&size)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+    }
+}
+
+fn duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+    let duration = String::deserialize(deserializer)?;
+    humantime::parse_duration(&duration)
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write the config file to a temporary file, named after the test
+    fn config_file(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("chord-config-{}.toml", name));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    const SAMPLE: &str = r#"
+        listen = "127.0.0.1:42100"
+        replication-factor = 5
+        successor-list-size = 8
+        hasher = "seahash"
+        ring = ["127.0.0.1:42001", "127.0.0.1:42002"]
+        discover-srv = "_chord._tcp.chord.default.svc.cluster.local"
+        stabilize-interval = "250ms"
+        fix-fingers = "one"
+        max-outbound-requests = 16
+        verify-notify = true
+        lookup-cache-size = 128
+        lookup-retries = 3
+        warm-up-fingers = true
+        compression = "gzip"
+        audit = true
+        audit-interval = "1m"
+        sweep = true
+        sweep-interval = "2s"
+        check-ring = true
+        check-ring-max-hops = 128
+        log-format = "json"
+        log-file = "/var/log/chord/node.log"
+        log-max-size = "1M"
+    "#;
+
+    #[test]
+    fn file_overrides_defaults() {
+        let path = config_file("defaults", SAMPLE);
+
+        let cli = Cli::try_load_from(["server", "--config", path.to_str().unwrap()]).unwrap();
+
+        assert_eq!(cli.listen, SocketAddr::from(([127, 0, 0, 1], 42100)));
+        assert_eq!(cli.replication_factor.get(), 5);
+        assert_eq!(cli.successor_list_size, NonZeroUsize::new(8));
+        assert_eq!(cli.hasher, Hasher::Seahash);
+        assert_eq!(cli.ring.len(), 2);
+        assert_eq!(
+            cli.discover_srv.as_deref(),
+            Some("_chord._tcp.chord.default.svc.cluster.local")
+        );
+        assert_eq!(cli.stabilize_interval, Duration::from_millis(250));
+        assert_eq!(cli.fix_fingers, FixFingers::One);
+        assert_eq!(cli.max_outbound_requests, NonZeroUsize::new(16));
+        assert!(cli.verify_notify);
+        assert_eq!(cli.lookup_cache_size, NonZeroUsize::new(128));
+        assert_eq!(cli.lookup_retries, 3);
+        assert!(cli.warm_up_fingers);
+        assert_eq!(cli.compression, Compression::Gzip);
+        assert!(cli.audit);
+        assert_eq!(cli.audit_interval, Duration::from_secs(60));
+        assert!(cli.sweep);
+        assert_eq!(cli.sweep_interval, Duration::from_secs(2));
+        assert!(cli.check_ring);
+        assert_eq!(cli.check_ring_max_hops, 128);
+        assert_eq!(cli.log_format, LogFormat::Json);
+        assert_eq!(cli.log_file, Some(PathBuf::from("/var/log/chord/node.log")));
+        assert_eq!(cli.log_max_size, 1024 * 1024);
+        // Not in the file
+        assert_eq!(cli.fix_fingers_interval, Duration::from_secs(1));
+        assert_eq!(cli.max_connections.get(), 1024);
+    }
+
+    #[test]
+    fn flags_override_file() {
+        let path = config_file("flags", SAMPLE);
+
+        let cli = Cli::try_load_from([
+            "server",
+            "--config",
+            path.to_str().unwrap(),
+            "--listen",
+            "127.0.0.1:42200",
+            "--replication-factor",
+            "3",
+        ])
+        .unwrap();
+
+        assert_eq!(cli.listen, SocketAddr::from(([127, 0, 0, 1], 42200)));
+        assert_eq!(cli.replication_factor.get(), 3);
+        assert_eq!(cli.stabilize_interval, Duration::from_millis(250));
+    }
+
+    #[test]
+    fn node_id_is_given_as_number_or_string() {
+        let path = config_file("node-id-number", "node-id = 42\nbootstrap = true");
+        let cli = Cli::try_load_from(["server", "-c", path.to_str().unwrap()]).unwrap();
+        assert_eq!(cli.node_id, Some(NodeId::from(42)));
+
+        let path = config_file(
+            "node-id-string",
+            "node-id = \"0xFFFFFFFFFFFFFFFF\"\nbootstrap = true",
+        );
+        let cli = Cli::try_load_from(["server", "-c", path.to_str().unwrap()]).unwrap();
+        assert_eq!(cli.node_id, Some(NodeId::from(u64::MAX)));
+    }
+
+    #[test]
+    fn transport_is_read_from_file() {
+        let path = config_file("transport", r#"transport = "grpc""#);
+        let args = ["server", "--bootstrap", "-c", path.to_str().unwrap()];
+        let cli = Cli::try_load_from(args).unwrap();
+        assert_eq!(cli.transport, Transport::Grpc);
+
+        let path = config_file("transport-unknown", r#"transport = "http""#);
+        assert!(Cli::try_load_from(["server", "-c", path.to_str().unwrap()]).is_err());
+    }
+
+    #[test]
+    fn empty_successor_list_is_rejected() {
+        let args = ["server", "--bootstrap", "--successor-list-size", "0"];
+        assert!(Cli::try_load_from(args).is_err());
+
+        let path = config_file("successor-list-size", "successor-list-size = 0");
+        let args = ["server", "--bootstrap", "-c", path.to_str().unwrap()];
+        assert!(Cli::try_load_from(args).is_err());
+    }
+
+    #[test]
+    fn invalid_file_is_rejected() {
+        let path = config_file("unknown-key", "replication = 5");
+        assert!(Cli::try_load_from(["server", "-c", path.to_str().unwrap()]).is_err());
+
+        let path = config_file("tls", r#"tls-cert = "node.pem""#);
+        assert!(Cli::try_load_from(["server", "-c", path.to_str().unwrap()]).is_err());
+
+        assert!(Cli::try_load_from(["server", "-c", "/nonexistent/chord.toml"]).is_err());
+    }
+
+    #[test]
+    fn endpoints_without_address_listen_next_to_the_transport() {
+        let args = [
+            "server",
+            "--bootstrap",
+            "--metrics-listen",
+            "--listen-gateway",
+        ];
+        let cli = Cli::try_load_from(args).unwrap();
+
+        assert_eq!(
+            cli.metrics_addr(),
+            Some(SocketAddr::from(([127, 0, 0, 1], 43000)))
+        );
+        assert_eq!(
+            cli.gateway_addr(),
+            Some(SocketAddr::from(([127, 0, 0, 1], 44000)))
+        );
+
+        let cli = Cli::try_load_from(["server", "--bootstrap"]).unwrap();
+        assert_eq!(cli.metrics_addr(), None);
+        assert_eq!(cli.gateway_addr(), None);
+    }
+
+    #[test]
+    fn compression_with_the_capnp_transport_is_rejected() {
+        let args = [
+            "server",
+            "--bootstrap",
+            "--transport",
+            "capnp",
+            "--compression",
+            "gzip",
+        ];
+        let error = Cli::try_load_from(args).err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::ArgumentConflict);
+
+        let args = [
+            "server",
+            "--bootstrap",
+            "--transport",
+            "grpc",
+            "--compression",
+            "gzip",
+        ];
+        assert!(Cli::try_load_from(args).is_ok());
+    }
+
+    #[test]
+    fn endpoints_on_the_ports_of_the_transport_are_rejected() {
+        let args = ["server", "--http-listen", "0.0.0.0:42000"];
+        let error = Cli::try_load_from(args).err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::ArgumentConflict);
+
+        let args = [
+            "server",
+            "--vnodes",
+            "3",
+            "--metrics-listen",
+            "127.0.0.1:42002",
+        ];
+        assert!(Cli::try_load_from(args).is_err());
+        let args = [
+            "server",
+            "--bootstrap",
+            "--vnodes",
+            "3",
+            "--metrics-listen",
+            "127.0.0.1:42003",
+        ];
+        assert!(Cli::try_load_from(args).is_ok());
+
+        let path = config_file("listen-collision", r#"http-listen = "127.0.0.1:42000""#);
+        assert!(Cli::try_load_from(["server", "-c", path.to_str().unwrap()]).is_err());
+    }
+
+    #[test]
+    fn metrics_and_gateway_on_the_same_port_are_rejected() {
+        let args = [
+            "server",
+            "--metrics-listen",
+            "127.0.0.1:9000",
+            "--http-listen",
+            "127.0.0.1:9000",
+        ];
+        assert!(Cli::try_load_from(args).is_err());
+
+        let args = ["server", "--listen", "127.0.0.1:64000", "--http-listen"];
+        assert!(Cli::try_load_from(args).is_err());
+    }
+
+    #[test]
+    fn creating_a_new_ring_requires_bootstrap() {
+        let error = Cli::try_load_from(["server"]).err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::MissingRequiredArgument);
+
+        let cli = Cli::try_load_from(["server", "--bootstrap"]).unwrap();
+        assert!(cli.bootstrap);
+        assert!(Cli::try_load_from(["server", "--ring", "127.0.0.1:42001"]).is_ok());
+        assert!(Cli::try_load_from(["server", "--discover-srv", "_chord._tcp.local"]).is_ok());
+        // The neighbours may be restored instead
+        assert!(Cli::try_load_from(["server", "--data-dir", "/var/lib/chord"]).is_ok());
+        // The subcommands don't start a node
+        assert!(Cli::try_load_from(["server", "ring-dump", "127.0.0.1:42001"]).is_ok());
+        assert!(Cli::try_load_from(["server", "owner", "127.0.0.1:42001", "key"]).is_ok());
+
+        let path = config_file("bootstrap", "bootstrap = true");
+        let cli = Cli::try_load_from(["server", "-c", path.to_str().unwrap()]).unwrap();
+        assert!(cli.bootstrap);
+    }
+}
@@ -1,34 +1,128 @@
-use log::LevelFilter;
-use simplelog::{ColorChoice, CombinedLogger, Config, TermLogger, TerminalMode};
-// use chord_capnp::Server as CapnpServer;
-use chord_rs::Server;
+use chord_rs::{CancellationToken, Server};
+use rolling_file::{BasicRollingFileAppender, RollingConditionBasic};
+use tracing::level_filters::LevelFilter;
+use tracing::Subscriber;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
 
 mod cli;
-use clap::Parser;
-use cli::Cli;
+mod config;
+mod owner;
+mod ring_dump;
+use cli::{Cli, Command, LogFormat};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    setup_logging();
-    let cli = Cli::parse();
+    let cli = Cli::load();
+
+    if let Some(Command::RingDump(args)) = &cli.command {
+        let healthy =
+            ring_dump::ring_dump(args, cli.transport.into(), cli.tls(), cli.secret.clone()).await;
+        std::process::exit(if healthy { 0 } else { 1 });
+    }
+    if let Some(Command::Owner(args)) = &cli.command {
+        let found = owner::owner(args, cli.transport.into(), cli.tls(), cli.secret.clone()).await;
+        std::process::exit(if found { 0 } else { 1 });
+    }
+
+    // Flushes the logs still buffered for the log file when dropped
+    let _log_guard = setup_logging(&cli)?;
 
     let addr = cli.listen;
     println!("Listening on: {}", addr);
 
     let server = Server::new(addr, cli).await;
 
-    server.run().await;
+    let shutdown = CancellationToken::new();
+    tokio::spawn(shutdown_signal(shutdown.clone()));
+
+    server.run(shutdown).await;
     Ok(())
 }
 
-fn setup_logging() {
-    CombinedLogger::init(vec![TermLogger::new(
-        LevelFilter::Debug,
-        Config::default(),
-        TerminalMode::Mixed,
-        ColorChoice::Auto,
-    )])
-    .unwrap();
+/// Cancel the shutdown token on Ctrl-C or SIGTERM
+async fn shutdown_signal(shutdown: CancellationToken) {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to listen for Ctrl-C");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    tracing::info!("Shutting down");
+    shutdown.cancel();
+}
+
+/// Log to the terminal and/or to the rotating log file, see `--log-file`
+///
+/// Returns the guard of the writer of the log file, it must be kept alive until the end
+fn setup_logging(cli: &Cli) -> std::io::Result<Option<WorkerGuard>> {
+    let terminal = (!cli.log_file_only).then(|| fmt_layer(cli.log_format, std::io::stdout, true));
+
+    let mut guard = None;
+    let file = match &cli.log_file {
+        Some(path) => {
+            let condition = RollingConditionBasic::new().max_size(cli.log_max_size);
+            let appender = BasicRollingFileAppender::new(path, condition, cli.log_max_files)
+                .map_err(|err| {
+                    std::io::Error::new(
+                        err.kind(),
+                        format!("Failed to open the log file {}: {}", path.display(), err),
+                    )
+                })?;
+            let (writer, worker) = tracing_appender::non_blocking(appender);
+            guard = Some(worker);
+            Some(fmt_layer(cli.log_format, writer, false))
+        }
+        None => None,
+    };
+
+    // Records of the `log` crate, used by the libraries, are forwarded to the subscriber as well
+    tracing_subscriber::registry()
+        .with(terminal)
+        .with(file)
+        .with(LevelFilter::from(cli.log_level))
+        .init();
+
+    tracing::info!("Logging started");
+    Ok(guard)
+}
 
-    log::info!("Logging started");
+/// Create the layer formatting the logs in the format and writing them to the writer
+///
+/// # Arguments
+///
+/// * `format` - The format of the logs
+/// * `writer` - Where the logs are written
+/// * `ansi` - Whether the logs are colored, only for the terminal
+fn fmt_layer<S, W>(format: LogFormat, writer: W, ansi: bool) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_ansi(ansi);
+    match format {
+        LogFormat::Text => layer.boxed(),
+        LogFormat::Json => layer.json().flatten_event(true).boxed(),
+    }
 }
@@ -0,0 +1,73 @@
+use std::num::ParseIntError;
+
+use chord_rs::{IntoNodeId, NodeId, TlsConfig, Transport};
+
+use crate::cli::OwnerArgs;
+
+/// Look up the node responsible for the key and print it
+///
+/// Returns false if the key is not a valid id while `--id` is set, or if the lookup failed.
+pub(crate) async fn owner(
+    args: &OwnerArgs,
+    transport: Transport,
+    tls: Option<TlsConfig>,
+    secret: Option<String>,
+) -> bool {
+    let id = match key_id(args) {
+        Ok(id) => id,
+        Err(err) => {
+            eprintln!("Invalid id {:?}: {}", args.key, err);
+            return false;
+        }
+    };
+
+    match chord_rs::find_owner(transport, args.addr, id, tls, secret).await {
+        Ok(owner) => {
+            if !args.id {
+                println!("Key:   {:?}", args.key);
+            }
+            println!("Id:    {}", id);
+            println!("Owner: {} {}", owner.id(), owner.addr());
+            true
+        }
+        Err(err) => {
+            eprintln!("Failed to look up {} through {}: {}", id, args.addr, err);
+            false
+        }
+    }
+}
+
+/// Get the place of the key on the ring
+fn key_id(args: &OwnerArgs) -> Result<NodeId, ParseIntError> {
+    if args.id {
+        args.key.parse()
+    } else {
+        Ok(args.key.as_str().into_node_id())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{Cli, Command};
+
+    fn args(args: &[&str]) -> OwnerArgs {
+        let cli =
+            Cli::try_load_from(["server", "owner", "127.0.0.1:42001"].iter().chain(args)).unwrap();
+        match cli.command {
+            Some(Command::Owner(args)) => args,
+            _ => panic!("Not the owner command"),
+        }
+    }
+
+    #[test]
+    fn keys_are_hashed_unless_they_are_ids() {
+        assert_eq!(
+            key_id(&args(&["0x10"])).unwrap(),
+            chord_rs::key_to_id(b"0x10")
+        );
+        assert_eq!(key_id(&args(&["0x10", "--id"])).unwrap(), NodeId::from(16));
+        assert_eq!(key_id(&args(&["16", "--id"])).unwrap(), NodeId::from(16));
+        assert!(key_id(&args(&["key", "--id"])).is_err());
+    }
+}
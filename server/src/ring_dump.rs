@@ -0,0 +1,92 @@
+use chord_rs::{RingDump, RingEnd, TlsConfig, Transport};
+
+use crate::cli::RingDumpArgs;
+
+/// Crawl the ring and print every visited node
+///
+/// Returns false if the ring doesn't close, a link is inconsistent or a node is configured with
+/// other parameters than the rest of the ring.
+pub(crate) async fn ring_dump(
+    args: &RingDumpArgs,
+    transport: Transport,
+    tls: Option<TlsConfig>,
+    secret: Option<String>,
+) -> bool {
+    println!("Crawling the ring from {}", args.addr);
+    let dump = chord_rs::dump_ring(transport, args.addr, args.max_hops, tls, secret).await;
+    print_dump(&dump);
+
+    dump.is_healthy()
+}
+
+fn print_dump(dump: &RingDump) {
+    let common = dump.common_params();
+    for (i, link) in dump.links.iter().enumerate() {
+        // The id of a node is only known from the successor of the previous one
+        let id = match i {
+            0 if dump.end == RingEnd::Closed => dump.links.last().map(|last| last.successor.id()),
+            0 => None,
+            _ => Some(dump.links[i - 1].successor.id()),
+        };
+        let id = id.map_or("?".to_string(), |id| id.to_string());
+        let predecessor = link
+            .predecessor
+            .as_ref()
+            .map_or("none".to_string(), |node| node.addr().to_string());
+        let mut status = match link.consistent {
+            Some(true) => "ok".to_string(),
+            Some(false) => "INCONSISTENT: the successor has another predecessor".to_string(),
+            None => "not checked".to_string(),
+        };
+        if let Some(params) = link.params.filter(|params| Some(*params) != common) {
+            status.push_str(&format!(", MISMATCHED PARAMETERS: {}", params));
+        }
+
+        println!(
+            "{:>20} {:<21} predecessor: {:<21} successor: {} {:<21} {}",
+            id,
+            link.addr,
+            predecessor,
+            link.successor.id(),
+            link.successor.addr(),
+            status
+        );
+    }
+
+    let inconsistent = dump
+        .links
+        .iter()
+        .filter(|link| link.consistent == Some(false))
+        .count();
+    match &dump.end {
+        RingEnd::Closed if inconsistent == 0 => {
+            println!("The ring is closed: {} nodes", dump.links.len());
+        }
+        RingEnd::Closed => println!(
+            "The ring is closed: {} nodes, {} inconsistent links",
+            dump.links.len(),
+            inconsistent
+        ),
+        RingEnd::Loop(addr) => println!(
+            "The ring doesn't close: the successors loop back to {} without reaching the first node",
+            addr
+        ),
+        RingEnd::MaxHops => println!(
+            "The ring doesn't close within {} nodes, increase --max-hops if the ring is larger",
+            dump.links.len()
+        ),
+        RingEnd::Unreachable(addr, err) => {
+            println!("The ring doesn't close: {} failed to respond: {}", addr, err)
+        }
+    }
+
+    let mismatched = dump.mismatched_params().len();
+    match common {
+        Some(params) if mismatched == 0 => println!("Ring parameters: {}", params),
+        Some(params) => println!(
+            "Ring parameters: {}, {} nodes are configured differently",
+            params, mismatched
+        ),
+        None => println!("Ring parameters: not reported by the nodes"),
+    }
+}